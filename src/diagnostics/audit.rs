@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use lsp_types::{Location, Uri};
+use serde::Serialize;
+
+use crate::document_store::{index_documents, DocumentStore};
+use crate::parser::token_range_to_range;
+use crate::parser::tokens::TokenData;
+use crate::utils::uri_to_path;
+
+use super::check::get_relative_path;
+use super::get_class_call_tokens;
+
+/// A deprecated API that is used in the project, with the locations it is used at.
+#[derive(Debug, Serialize)]
+pub struct DeprecatedApi {
+    pub kind: &'static str,
+    pub name: String,
+    pub message: String,
+    pub usages: Vec<Location>,
+}
+
+/// Find the usages of deprecated APIs in the whole index, f.e as a checklist before upgrading.
+///
+/// For now only services are known to be deprecated, through the `deprecated` key of their
+/// definition.
+pub fn get_deprecated_apis(store: &DocumentStore) -> Vec<DeprecatedApi> {
+    let mut deprecated_services: HashMap<&str, DeprecatedApi> = store
+        .get_documents()
+        .values()
+        .flat_map(|document| document.tokens.iter())
+        .filter_map(|token| match &token.data {
+            TokenData::DrupalServiceDefinition(service) => {
+                let message = service.deprecated.as_ref()?;
+                Some((
+                    service.name.as_str(),
+                    DeprecatedApi {
+                        kind: "service",
+                        name: service.name.to_string(),
+                        message: message.replace("%service_id%", &service.name),
+                        usages: vec![],
+                    },
+                ))
+            }
+            _ => None,
+        })
+        .collect();
+    if deprecated_services.is_empty() {
+        return vec![];
+    }
+
+    for document in store.get_documents().values() {
+        let Ok(uri) = document.uri.parse::<Uri>() else {
+            continue;
+        };
+        let class_call_tokens = get_class_call_tokens(document);
+        for token in document.tokens.iter().chain(&class_call_tokens) {
+            // Service arguments are only parsed as references at a position, so the services
+            // depending on a deprecated service are reported at their definition.
+            let service_names: Vec<&str> = match &token.data {
+                TokenData::DrupalServiceReference(service_name) => vec![service_name],
+                TokenData::DrupalServiceDefinition(service) => service
+                    .arguments
+                    .iter()
+                    .filter_map(|argument| argument.strip_prefix('@'))
+                    .map(|argument| argument.trim_start_matches('?'))
+                    .collect(),
+                _ => continue,
+            };
+            for service_name in service_names {
+                if let Some(api) = deprecated_services.get_mut(service_name) {
+                    api.usages.push(Location {
+                        uri: uri.clone(),
+                        range: token_range_to_range(&token.range),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut apis: Vec<DeprecatedApi> = deprecated_services
+        .into_values()
+        .filter(|api| !api.usages.is_empty())
+        .collect();
+    apis.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+    for api in &mut apis {
+        api.usages
+            .sort_by(|a, b| (a.uri.as_str(), a.range.start).cmp(&(b.uri.as_str(), b.range.start)));
+    }
+    apis
+}
+
+/// Index a project, and report the usages of deprecated APIs.
+pub fn audit_project(root_dir: &str) -> String {
+    let mut store = DocumentStore::new();
+    store.add_documents(index_documents(root_dir));
+    get_report(Path::new(root_dir), &get_deprecated_apis(&store))
+}
+
+fn get_report(root_dir: &Path, apis: &[DeprecatedApi]) -> String {
+    let mut report = String::new();
+    for api in apis {
+        report.push_str(&format!("{} {}: {}\n", api.kind, api.name, api.message));
+        for usage in &api.usages {
+            let path = uri_to_path(usage.uri.as_str())
+                .map(|path| get_relative_path(root_dir, &path))
+                .unwrap_or(usage.uri.to_string());
+            report.push_str(&format!(
+                "  {}:{}:{}\n",
+                path,
+                usage.range.start.line + 1,
+                usage.range.start.character + 1
+            ));
+        }
+    }
+    report.push_str(&format!(
+        "Found {} usages of {} deprecated APIs\n",
+        apis.iter().map(|api| api.usages.len()).sum::<usize>(),
+        apis.len()
+    ));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn deprecated_services() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/core.services.yml",
+            r#"services:
+  path.alias_manager:
+    class: Drupal\path_alias\AliasManager
+    deprecated: The "%service_id%" service is deprecated. Use "path_alias.manager" instead.
+  unused.service:
+    class: Drupal\Unused
+    deprecated: The "%service_id%" service is deprecated.
+  foo.bar:
+    class: Drupal\foo\Bar
+    arguments: ['@path.alias_manager']
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/foo.module",
+            "<?php\n\n$manager = \\Drupal::service('path.alias_manager');\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/src/Foo.php",
+            r#"<?php
+
+namespace Drupal\foo;
+
+class Foo {
+
+  public function getAlias($path) {
+    return \Drupal::service('path.alias_manager')->getAliasByPath($path);
+  }
+
+}
+"#
+            .to_string(),
+        );
+
+        let apis = get_deprecated_apis(&store);
+        assert_eq!(1, apis.len());
+        assert_eq!("path.alias_manager", apis[0].name);
+        assert_eq!(
+            "The \"path.alias_manager\" service is deprecated. Use \"path_alias.manager\" instead.",
+            apis[0].message
+        );
+        assert_eq!(3, apis[0].usages.len());
+
+        let report = get_report(Path::new("/var/www"), &apis);
+        assert!(report.contains("\n  web/core/core.services.yml:8:"));
+        assert!(report.contains("\n  web/modules/custom/foo/foo.module:3:"));
+        assert!(report.contains("\n  web/modules/custom/foo/src/Foo.php:8:"));
+        assert!(report.ends_with("Found 3 usages of 1 deprecated APIs\n"));
+    }
+}
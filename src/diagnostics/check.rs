@@ -0,0 +1,459 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use serde::Serialize;
+
+use crate::document_store::document::Document;
+use crate::document_store::{index_documents, DocumentStore};
+use crate::utils::uri_to_path;
+
+use super::{
+    get_diagnostics, CONFIG_TYPE_MISMATCH, MISSING_LIBRARY_ASSET, MISSING_ROUTE_ACCESS,
+    MISSING_ROUTE_TITLE, MISSING_USE_STATEMENT, SERVICE_ARGUMENTS, TRANSLATION_PLACEHOLDERS,
+    UNDECLARED_CONFIG_KEY, UNKNOWN_HOOK, UNKNOWN_MENU_LINK, UNKNOWN_MODULE, UNKNOWN_PERMISSION,
+    UNKNOWN_SERVICE, UNTRANSLATED_STRING,
+};
+
+// The rules that diagnostics are reported under, by their diagnostic code.
+pub const RULES: &[(&str, &str)] = &[
+    (
+        MISSING_USE_STATEMENT,
+        "A class is used without a use statement",
+    ),
+    (
+        UNKNOWN_PERMISSION,
+        "A permission is referenced that isn't defined",
+    ),
+    (
+        UNTRANSLATED_STRING,
+        "A translatable string is missing translations",
+    ),
+    (
+        UNKNOWN_MENU_LINK,
+        "A menu link parent is referenced that isn't defined",
+    ),
+    (
+        UNKNOWN_SERVICE,
+        "A service is referenced that isn't defined",
+    ),
+    (
+        SERVICE_ARGUMENTS,
+        "The arguments of a service don't match the constructor of its class",
+    ),
+    (
+        MISSING_ROUTE_TITLE,
+        "A route has no _title or _title_callback",
+    ),
+    (
+        MISSING_ROUTE_ACCESS,
+        "A route has no access requirement, so access is always denied",
+    ),
+    (
+        TRANSLATION_PLACEHOLDERS,
+        "The placeholders of a translatable string don't match its arguments",
+    ),
+    (
+        UNKNOWN_HOOK,
+        "A function implements a hook that isn't defined or invoked",
+    ),
+    (
+        MISSING_LIBRARY_ASSET,
+        "A library references a CSS or JavaScript file that doesn't exist",
+    ),
+    (
+        UNKNOWN_MODULE,
+        "A module depends on a module that isn't in the project",
+    ),
+    (
+        UNDECLARED_CONFIG_KEY,
+        "Default config has a key that isn't declared in its schema",
+    ),
+    (
+        CONFIG_TYPE_MISMATCH,
+        "A value in default config doesn't match the type in its schema",
+    ),
+];
+
+// The directories of custom code, which are checked when no paths are given.
+const CUSTOM_DIRECTORIES: [&str; 3] = ["modules/custom", "themes/custom", "profiles/custom"];
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum CheckFormat {
+    /// One line per problem, f.e `web/modules/custom/foo/foo.module:3:5: warning ...`.
+    #[default]
+    Text,
+    /// A SARIF 2.1.0 log, which can be uploaded to GitHub code scanning or GitLab.
+    Sarif,
+}
+
+/// A problem found in a file of the project.
+struct Problem {
+    path: String,
+    diagnostic: Diagnostic,
+}
+
+impl Problem {
+    fn get_rule_id(&self) -> &str {
+        match &self.diagnostic.code {
+            Some(NumberOrString::String(code)) => code,
+            _ => "unknown",
+        }
+    }
+}
+
+/// Index a project, and report the diagnostics of the documents in the given paths, f.e to lint
+/// in CI. Without paths, the custom modules, themes and profiles are checked.
+///
+/// Returns the report, and whether any warnings or errors were found.
+pub fn check_project(root_dir: &str, paths: &[String], format: CheckFormat) -> (String, bool) {
+    let mut store = DocumentStore::new();
+    store.add_documents(index_documents(root_dir));
+
+    let root_dir = Path::new(root_dir);
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| get_relative_path(root_dir, Path::new(path)))
+        .collect();
+    let mut problems: Vec<Problem> = store
+        .get_documents()
+        .values()
+        .filter_map(|document| {
+            let path = uri_to_path(&document.uri)
+                .map(|path| get_relative_path(root_dir, &path))
+                .unwrap_or(document.uri.clone());
+            is_checked_path(&path, &paths).then_some((document, path))
+        })
+        .flat_map(|(document, path)| {
+            let diagnostics = match document.is_path_only() {
+                // The content of default config without tokens isn't indexed.
+                true => {
+                    let mut document =
+                        Document::new(&document.uri, document.get_content().into_owned());
+                    document.parse();
+                    get_diagnostics(&store, &document)
+                }
+                false => get_diagnostics(&store, document),
+            };
+            diagnostics.into_iter().map(move |diagnostic| Problem {
+                path: path.clone(),
+                diagnostic,
+            })
+        })
+        .collect();
+    problems.sort_by(|a, b| {
+        (&a.path, a.diagnostic.range.start).cmp(&(&b.path, b.diagnostic.range.start))
+    });
+
+    let failed = problems.iter().any(|problem| {
+        matches!(
+            problem.diagnostic.severity,
+            None | Some(DiagnosticSeverity::ERROR) | Some(DiagnosticSeverity::WARNING)
+        )
+    });
+    let report = match format {
+        CheckFormat::Text => get_text_report(&problems),
+        CheckFormat::Sarif => get_sarif_report(&problems),
+    };
+    (report, failed)
+}
+
+pub fn get_relative_path(root_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(root_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether a file is checked, given its path and the paths to check relative to the project.
+fn is_checked_path(path: &str, paths: &[String]) -> bool {
+    let is_in_directory = |directory: &str| {
+        let directory = directory.trim_start_matches("./").trim_end_matches('/');
+        directory.is_empty()
+            || directory == "."
+            || path == directory
+            || path.starts_with(&format!("{}/", directory))
+    };
+    if paths.is_empty() {
+        // Custom code can be in the docroot, f.e `web/modules/custom`.
+        let path = format!("/{}", path);
+        return CUSTOM_DIRECTORIES
+            .iter()
+            .any(|directory| path.contains(&format!("/{}/", directory)));
+    }
+    paths.iter().any(|directory| is_in_directory(directory))
+}
+
+fn get_severity_name(severity: Option<DiagnosticSeverity>) -> &'static str {
+    match severity {
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        Some(DiagnosticSeverity::HINT) => "hint",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        _ => "error",
+    }
+}
+
+fn get_text_report(problems: &[Problem]) -> String {
+    let mut report = String::new();
+    for problem in problems {
+        let start = problem.diagnostic.range.start;
+        report.push_str(&format!(
+            "{}:{}:{}: {} [{}] {}\n",
+            problem.path,
+            start.line + 1,
+            start.character + 1,
+            get_severity_name(problem.diagnostic.severity),
+            problem.get_rule_id(),
+            problem.diagnostic.message
+        ));
+    }
+    report.push_str(&format!("Found {} problems\n", problems.len()));
+    report
+}
+
+#[derive(Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRule {
+    id: &'static str,
+    short_description: SarifMessage,
+}
+
+#[derive(Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    region: SarifRegion,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+    end_column: u32,
+}
+
+fn get_sarif_report(problems: &[Problem]) -> String {
+    let results = problems
+        .iter()
+        .map(|problem| {
+            let range = problem.diagnostic.range;
+            SarifResult {
+                rule_id: problem.get_rule_id().to_string(),
+                rule_index: RULES
+                    .iter()
+                    .position(|(id, _)| *id == problem.get_rule_id()),
+                level: match problem.diagnostic.severity {
+                    Some(DiagnosticSeverity::INFORMATION) | Some(DiagnosticSeverity::HINT) => {
+                        "note"
+                    }
+                    Some(DiagnosticSeverity::WARNING) => "warning",
+                    _ => "error",
+                },
+                message: SarifMessage {
+                    text: problem.diagnostic.message.clone(),
+                },
+                locations: vec![SarifLocation {
+                    physical_location: SarifPhysicalLocation {
+                        artifact_location: SarifArtifactLocation {
+                            uri: problem.path.clone(),
+                            uri_base_id: "%SRCROOT%",
+                        },
+                        // SARIF lines and columns start at 1.
+                        region: SarifRegion {
+                            start_line: range.start.line + 1,
+                            start_column: range.start.character + 1,
+                            end_line: range.end.line + 1,
+                            end_column: range.end.character + 1,
+                        },
+                    },
+                }],
+            }
+        })
+        .collect();
+
+    let log = SarifLog {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "drupal_ls",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: "https://github.com/jdrupal-dev/drupal_ls",
+                    rules: RULES
+                        .iter()
+                        .map(|(id, description)| SarifRule {
+                            id,
+                            short_description: SarifMessage {
+                                text: description.to_string(),
+                            },
+                        })
+                        .collect(),
+                },
+            },
+            results,
+        }],
+    };
+    serde_json::to_string_pretty(&log).unwrap_or_default() + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use super::*;
+
+    fn problems() -> Vec<Problem> {
+        let diagnostic = |line, severity, code: &str, message: &str| Diagnostic {
+            range: Range::new(Position::new(line, 4), Position::new(line, 10)),
+            severity: Some(severity),
+            code: Some(NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Diagnostic::default()
+        };
+        vec![
+            Problem {
+                path: "web/modules/custom/foo/foo.routing.yml".to_string(),
+                diagnostic: diagnostic(
+                    2,
+                    DiagnosticSeverity::WARNING,
+                    UNKNOWN_PERMISSION,
+                    "Unknown permission 'foo'",
+                ),
+            },
+            Problem {
+                path: "web/modules/custom/foo/foo.module".to_string(),
+                diagnostic: diagnostic(
+                    0,
+                    DiagnosticSeverity::INFORMATION,
+                    UNTRANSLATED_STRING,
+                    "Missing translations: da",
+                ),
+            },
+        ]
+    }
+
+    #[test]
+    fn checked_paths() {
+        assert!(is_checked_path("web/modules/custom/foo/foo.module", &[]));
+        assert!(is_checked_path("themes/custom/bar/bar.theme", &[]));
+        assert!(!is_checked_path("web/core/modules/node/node.module", &[]));
+        assert!(!is_checked_path(
+            "web/modules/contrib/token/token.module",
+            &[]
+        ));
+
+        let paths = vec!["web/modules/contrib/token/".to_string()];
+        assert!(is_checked_path(
+            "web/modules/contrib/token/token.module",
+            &paths
+        ));
+        assert!(!is_checked_path(
+            "web/modules/contrib/token_extra/token_extra.module",
+            &paths
+        ));
+        assert!(!is_checked_path(
+            "web/modules/custom/foo/foo.module",
+            &paths
+        ));
+        assert!(is_checked_path(
+            "web/core/modules/node/node.module",
+            &[".".to_string()]
+        ));
+    }
+
+    #[test]
+    fn text_report() {
+        assert_eq!(
+            "web/modules/custom/foo/foo.routing.yml:3:5: warning [unknown-permission] Unknown permission 'foo'\n\
+             web/modules/custom/foo/foo.module:1:5: info [untranslated-string] Missing translations: da\n\
+             Found 2 problems\n",
+            get_text_report(&problems())
+        );
+    }
+
+    #[test]
+    fn sarif_report() {
+        let log: serde_json::Value = serde_json::from_str(&get_sarif_report(&problems())).unwrap();
+        assert_eq!("2.1.0", log["version"]);
+        let run = &log["runs"][0];
+        assert_eq!(
+            UNKNOWN_PERMISSION,
+            run["tool"]["driver"]["rules"][1]["id"].as_str().unwrap()
+        );
+
+        let result = &run["results"][0];
+        assert_eq!(UNKNOWN_PERMISSION, result["ruleId"].as_str().unwrap());
+        assert_eq!(1, result["ruleIndex"]);
+        assert_eq!("warning", result["level"]);
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(
+            "web/modules/custom/foo/foo.routing.yml",
+            location["artifactLocation"]["uri"]
+        );
+        assert_eq!(3, location["region"]["startLine"]);
+        assert_eq!(5, location["region"]["startColumn"]);
+        assert_eq!("note", run["results"][1]["level"]);
+    }
+}
@@ -0,0 +1,1445 @@
+pub mod audit;
+pub mod check;
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, TextEdit};
+use regex::Regex;
+
+use crate::document_store::document::{Document, FileType};
+use crate::document_store::DocumentStore;
+use crate::integrations::drush::get_drush_data;
+use crate::integrations::phpcs::get_phpcs_diagnostics;
+use crate::parser::php::{PhpParser, TranslationArguments};
+use crate::parser::token_range_to_range;
+use crate::parser::tokens::{DrupalConfigSchema, PhpClassName, Token, TokenData};
+use crate::parser::yaml::{ServiceArguments, YamlParser};
+use crate::settings::get_settings;
+use crate::utils::uri_to_path;
+
+pub const MISSING_USE_STATEMENT: &str = "missing-use-statement";
+pub const UNKNOWN_PERMISSION: &str = "unknown-permission";
+pub const UNTRANSLATED_STRING: &str = "untranslated-string";
+pub const UNKNOWN_MENU_LINK: &str = "unknown-menu-link";
+pub const UNKNOWN_SERVICE: &str = "unknown-service";
+pub const SERVICE_ARGUMENTS: &str = "service-arguments";
+pub const MISSING_ROUTE_TITLE: &str = "missing-route-title";
+pub const MISSING_ROUTE_ACCESS: &str = "missing-route-access";
+pub const TRANSLATION_PLACEHOLDERS: &str = "translation-placeholders";
+pub const UNKNOWN_HOOK: &str = "unknown-hook";
+pub const MISSING_LIBRARY_ASSET: &str = "missing-library-asset";
+pub const UNKNOWN_MODULE: &str = "unknown-module";
+pub const UNDECLARED_CONFIG_KEY: &str = "undeclared-config-key";
+pub const CONFIG_TYPE_MISMATCH: &str = "config-type-mismatch";
+
+// Matches the placeholders in a translatable string, f.e `@name` or `%count`, but not the `@` of
+// an email address or the `:` of a time.
+pub static TRANSLATION_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:^|\W)(?<placeholder>[@%:]\w+)"#).unwrap());
+
+// The maximum number of base types to follow when resolving a config schema type, which protects
+// against schema types that are based on themselves.
+const MAX_SCHEMA_TYPE_DEPTH: usize = 20;
+
+// Route requirements that aren't access checks. Other requirements starting with an underscore
+// are, including the ones of custom access checks, f.e `_access_theme`.
+const NON_ACCESS_REQUIREMENTS: &[&str] = &[
+    "_format",
+    "_content_type_format",
+    "_method",
+    "_module_dependencies",
+    "_entity_bundles",
+];
+
+// The services that the container registers itself, which aren't defined in `*.services.yml`
+// files.
+const CONTAINER_SERVICES: &[&str] = &["service_container", "kernel", "class_loader"];
+
+pub fn get_diagnostics(store: &DocumentStore, document: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    if document.file_type == FileType::Php {
+        diagnostics.extend(get_missing_use_statement_diagnostics(store, document));
+        diagnostics.extend(get_translation_placeholder_diagnostics(document));
+        diagnostics.extend(get_unknown_hook_diagnostics(store, document));
+    }
+    diagnostics.extend(get_unknown_permission_diagnostics(store, document));
+    if document.uri.ends_with(".links.menu.yml") {
+        diagnostics.extend(get_unknown_menu_link_diagnostics(store, document));
+    }
+    if document.uri.ends_with(".routing.yml") {
+        diagnostics.extend(get_route_diagnostics(document));
+    }
+    if document.uri.ends_with(".services.yml") {
+        diagnostics.extend(get_service_argument_diagnostics(store, document));
+    }
+    if document.uri.ends_with(".libraries.yml") {
+        diagnostics.extend(get_missing_library_asset_diagnostics(document));
+    }
+    if document.uri.ends_with(".info.yml") {
+        diagnostics.extend(get_unknown_module_diagnostics(store, document));
+    }
+    if document.file_type == FileType::Yaml {
+        diagnostics.extend(get_config_schema_diagnostics(store, document));
+    }
+    if get_settings().translations.require_complete {
+        diagnostics.extend(get_untranslated_string_diagnostics(store, document));
+    }
+    diagnostics.extend(get_phpcs_diagnostics(&document.uri));
+
+    diagnostics
+}
+
+/// Report short class names that don't resolve against the `use` statements of the file.
+///
+/// Only classes that can be found in the index are reported, since we don't know about global
+/// PHP classes and interfaces.
+fn get_missing_use_statement_diagnostics(
+    store: &DocumentStore,
+    document: &Document,
+) -> Vec<Diagnostic> {
+    let parser = PhpParser::new(&document.content);
+    let usages = parser.get_class_name_usages();
+    if usages.is_empty() {
+        return vec![];
+    }
+
+    let class_names_by_short_name = store.get_class_names_by_short_name();
+    let namespace = parser.get_namespace();
+    let imports = parser.get_imported_class_names();
+    let declared_class_names = parser.get_declared_class_names();
+
+    usages
+        .into_iter()
+        .filter(|(name, _)| !imports.contains_key(name) && !declared_class_names.contains(name))
+        .filter_map(|(name, range)| {
+            let candidates = class_names_by_short_name.get(name.as_str())?;
+
+            // Classes in the same namespace don't need to be imported.
+            if let Some(namespace) = &namespace {
+                let class_name = PhpClassName::from(format!("{}\\{}", namespace, name).as_str());
+                if candidates.contains(&class_name) {
+                    return None;
+                }
+            }
+
+            Some(Diagnostic {
+                range: token_range_to_range(&range),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(MISSING_USE_STATEMENT.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!("Missing use statement for class {}", name),
+                data: serde_json::to_value(
+                    candidates
+                        .iter()
+                        .map(|class_name| class_name.to_string())
+                        .collect::<Vec<String>>(),
+                )
+                .ok(),
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+fn get_unknown_permission_diagnostics(
+    store: &DocumentStore,
+    document: &Document,
+) -> Vec<Diagnostic> {
+    let permissions: Vec<&str> = store
+        .get_documents()
+        .values()
+        .flat_map(|document| document.tokens.iter())
+        .filter_map(|token| match &token.data {
+            TokenData::DrupalPermissionDefinition(permission) => Some(permission.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    // Don't report anything before the project has been indexed.
+    if permissions.is_empty() {
+        return vec![];
+    }
+
+    // Permissions provided by callbacks are unknown to us, so only report them as information.
+    let has_permission_callbacks = store.has_permission_callbacks();
+
+    let class_call_tokens = get_class_call_tokens(document);
+    document
+        .tokens
+        .iter()
+        .chain(&class_call_tokens)
+        .filter_map(|token| match &token.data {
+            TokenData::DrupalPermissionReference(permission) => Some((token, permission)),
+            _ => None,
+        })
+        .flat_map(|(token, permission)| {
+            // Routes can require multiple permissions, f.e `access content+administer nodes`.
+            permission
+                .split(['+', ','])
+                .map(|name| name.trim())
+                .filter(|name| !name.is_empty() && !permissions.contains(name))
+                .map(|name| Diagnostic {
+                    range: token_range_to_range(&token.range),
+                    severity: Some(match has_permission_callbacks {
+                        true => DiagnosticSeverity::INFORMATION,
+                        false => DiagnosticSeverity::WARNING,
+                    }),
+                    code: Some(NumberOrString::String(UNKNOWN_PERMISSION.to_string())),
+                    source: Some("drupal_ls".to_string()),
+                    message: match has_permission_callbacks {
+                        true => format!(
+                            "Unknown permission '{}', unless it is provided by a permission callback",
+                            name
+                        ),
+                        false => format!("Unknown permission '{}'", name),
+                    },
+                    data: Some(serde_json::Value::String(name.to_string())),
+                    ..Diagnostic::default()
+                })
+                .collect::<Vec<Diagnostic>>()
+        })
+        .collect()
+}
+
+/// Report menu link parents that aren't defined in any `*.links.menu.yml` file.
+///
+/// Parents with a derivative ID, f.e `menu_link_content:<uuid>`, are provided by derivers which
+/// are unknown to us, so they aren't reported.
+fn get_unknown_menu_link_diagnostics(
+    store: &DocumentStore,
+    document: &Document,
+) -> Vec<Diagnostic> {
+    let menu_links: HashSet<&str> = store
+        .get_documents()
+        .values()
+        .flat_map(|document| document.tokens.iter())
+        .filter_map(|token| match &token.data {
+            TokenData::DrupalMenuLinkDefinition(menu_link) => Some(menu_link.id.as_str()),
+            _ => None,
+        })
+        .collect();
+    // Don't report anything before the project has been indexed.
+    if menu_links.is_empty() {
+        return vec![];
+    }
+
+    YamlParser::new(&document.content, &document.uri)
+        .get_menu_link_parent_references()
+        .into_iter()
+        .filter_map(|token| {
+            let TokenData::DrupalMenuLinkReference(parent) = &token.data else {
+                return None;
+            };
+            if parent.is_empty() || parent.contains(':') || menu_links.contains(parent.as_str()) {
+                return None;
+            }
+
+            Some(Diagnostic {
+                range: token_range_to_range(&token.range),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(UNKNOWN_MENU_LINK.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!("Unknown menu link '{}'", parent),
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+/// Report dependencies in `*.info.yml` files on modules that aren't in the project, f.e because of
+/// a typo.
+fn get_unknown_module_diagnostics(store: &DocumentStore, document: &Document) -> Vec<Diagnostic> {
+    let mut modules = store.get_extension_names();
+    if let Some(drush_data) = get_drush_data() {
+        modules.extend(drush_data.modules.iter().map(|module| module.as_str()));
+    }
+    // Don't report anything before the project has been indexed, which includes this file.
+    if modules.len() <= 1 {
+        return vec![];
+    }
+
+    document
+        .tokens
+        .iter()
+        .filter_map(|token| match &token.data {
+            TokenData::DrupalModuleReference(module) if !modules.contains(module.as_str()) => {
+                Some(Diagnostic {
+                    range: token_range_to_range(&token.range),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    code: Some(NumberOrString::String(UNKNOWN_MODULE.to_string())),
+                    source: Some("drupal_ls".to_string()),
+                    message: format!("Unknown module '{}'", module),
+                    ..Diagnostic::default()
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Report CSS and JavaScript files of libraries that don't exist, since Drupal silently skips them.
+fn get_missing_library_asset_diagnostics(document: &Document) -> Vec<Diagnostic> {
+    let Some(directory) = uri_to_path(&document.uri)
+        .and_then(|path| path.parent().map(|directory| directory.to_path_buf()))
+    else {
+        return vec![];
+    };
+
+    YamlParser::new(&document.content, &document.uri)
+        .get_library_asset_paths()
+        .into_iter()
+        .filter_map(|(asset, range)| {
+            // Paths starting with a slash are relative to the Drupal root.
+            let path = match asset.strip_prefix('/') {
+                Some(relative_path) => directory
+                    .ancestors()
+                    .find(|ancestor| ancestor.join("core/core.libraries.yml").is_file())?
+                    .join(relative_path),
+                None => directory.join(&asset),
+            };
+            if path.exists() {
+                return None;
+            }
+
+            Some(Diagnostic {
+                range: token_range_to_range(&range),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(MISSING_LIBRARY_ASSET.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!("Library asset not found: {}", path.display()),
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+/// The schema of a config key, as far as it can be resolved from the indexed schema files.
+#[derive(Debug, PartialEq)]
+enum SchemaKey<'a> {
+    Declared(Option<&'a str>),
+    Undeclared,
+    Unknown,
+}
+
+/// Report keys of default config that aren't declared in its schema, and values that don't match
+/// the primitive type of their key, like the strict config schema checks of Drupal's tests do.
+fn get_config_schema_diagnostics(store: &DocumentStore, document: &Document) -> Vec<Diagnostic> {
+    let Some(schema) = document
+        .uri
+        .rsplit('/')
+        .next()
+        .and_then(|file_name| file_name.strip_suffix(".yml"))
+        .and_then(|config_name| store.get_config_schema(config_name))
+    else {
+        return vec![];
+    };
+
+    YamlParser::new(&document.content, &document.uri)
+        .get_config_values()
+        .into_iter()
+        .filter_map(|value| {
+            let keys: Vec<&str> = value.keys.iter().map(|key| key.as_str()).collect();
+            let type_name = match get_schema_key(store, schema, &keys) {
+                SchemaKey::Declared(type_name) => type_name,
+                SchemaKey::Undeclared => {
+                    return Some(Diagnostic {
+                        range: token_range_to_range(&value.range),
+                        severity: Some(DiagnosticSeverity::WARNING),
+                        code: Some(NumberOrString::String(UNDECLARED_CONFIG_KEY.to_string())),
+                        source: Some("drupal_ls".to_string()),
+                        message: format!(
+                            "Config key '{}' is not declared in the schema of '{}'",
+                            keys.join("."),
+                            schema.name
+                        ),
+                        ..Diagnostic::default()
+                    });
+                }
+                SchemaKey::Unknown => return None,
+            };
+
+            let value_type = value.value_type?;
+            let primitive_type = get_primitive_schema_type(store, type_name?)?;
+            // YAML has no distinct syntax for floats without a fraction, f.e `1`.
+            let is_valid = match primitive_type {
+                "float" => value_type == "float" || value_type == "integer",
+                "email" | "uri" => value_type == "string",
+                _ => value_type == primitive_type,
+            };
+            if is_valid {
+                return None;
+            }
+
+            Some(Diagnostic {
+                range: token_range_to_range(&value.value_range),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(CONFIG_TYPE_MISMATCH.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!(
+                    "Expected a value of type '{}' for config key '{}', found {}",
+                    primitive_type,
+                    keys.join("."),
+                    value_type
+                ),
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+/// Find the schema of a config key, following the types of the keys leading up to it.
+fn get_schema_key<'a>(
+    store: &'a DocumentStore,
+    schema: &'a DrupalConfigSchema,
+    keys: &[&str],
+) -> SchemaKey<'a> {
+    let mut type_name = schema.type_name.as_deref();
+    let mut declared_keys: Vec<(&str, Option<&str>)> = schema
+        .keys
+        .iter()
+        .map(|key| (key.key.as_str(), key.type_name.as_deref()))
+        .collect();
+
+    for (index, key) in keys.iter().enumerate() {
+        // The keys of the mapping the type is based on, unless they can't be known, f.e for
+        // sequences or dynamic types like `block.settings.[%parent.plugin]`.
+        let base_keys = type_name.and_then(|type_name| get_schema_type_keys(store, type_name));
+        let is_complete = base_keys.is_some();
+        declared_keys.extend(base_keys.unwrap_or_default());
+
+        let Some(key_type_name) = declared_keys
+            .iter()
+            .find(|(declared_key, _)| declared_key == key)
+            .map(|(_, key_type_name)| *key_type_name)
+        else {
+            return match is_complete {
+                true => SchemaKey::Undeclared,
+                false => SchemaKey::Unknown,
+            };
+        };
+        if index == keys.len() - 1 {
+            return SchemaKey::Declared(key_type_name);
+        }
+
+        let prefix = format!("{}.", key);
+        type_name = key_type_name;
+        declared_keys = declared_keys
+            .into_iter()
+            .filter_map(|(declared_key, declared_type_name)| {
+                Some((declared_key.strip_prefix(&prefix)?, declared_type_name))
+            })
+            .collect();
+    }
+    SchemaKey::Unknown
+}
+
+/// Get the keys of a schema type, including the keys of the types it is based on, or `None` when
+/// the type isn't a mapping or its keys can't be known.
+fn get_schema_type_keys<'a>(
+    store: &'a DocumentStore,
+    type_name: &str,
+) -> Option<Vec<(&'a str, Option<&'a str>)>> {
+    let mut keys = vec![];
+    let mut type_name = type_name;
+    for _ in 0..MAX_SCHEMA_TYPE_DEPTH {
+        if type_name == "mapping" {
+            return Some(keys);
+        }
+        if type_name.contains(['[', '%']) {
+            return None;
+        }
+        let schema = store.get_config_schema(type_name)?;
+        keys.extend(
+            schema
+                .keys
+                .iter()
+                .map(|key| (key.key.as_str(), key.type_name.as_deref())),
+        );
+        type_name = schema.type_name.as_deref()?;
+    }
+    None
+}
+
+/// Get the primitive type a schema type is based on, f.e `string` for `label`.
+fn get_primitive_schema_type<'a>(store: &'a DocumentStore, type_name: &'a str) -> Option<&'a str> {
+    let mut type_name = type_name;
+    for _ in 0..MAX_SCHEMA_TYPE_DEPTH {
+        if ["boolean", "integer", "float", "string", "email", "uri"].contains(&type_name) {
+            return Some(type_name);
+        }
+        if type_name.contains(['[', '%']) {
+            return None;
+        }
+        type_name = store.get_config_schema(type_name)?.type_name.as_deref()?;
+    }
+    None
+}
+
+/// Report routes without a title, and routes without any access requirement, since access to
+/// those is always denied.
+fn get_route_diagnostics(document: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for token in &document.tokens {
+        let TokenData::DrupalRouteDefinition(route) = &token.data else {
+            continue;
+        };
+        // Report at the route name, instead of the whole definition.
+        let range = Range {
+            start: token_range_to_range(&token.range).start,
+            end: Position {
+                line: token.range.start_point.row as u32,
+                character: (token.range.start_point.column + route.name.len()) as u32,
+            },
+        };
+        let diagnostic = |code: &str, message: String| Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(code.to_string())),
+            source: Some("drupal_ls".to_string()),
+            message,
+            ..Diagnostic::default()
+        };
+
+        // Only HTML pages need a title, not f.e POST endpoints or JSON responses.
+        let is_page = (route.methods.is_empty()
+            || route
+                .methods
+                .iter()
+                .any(|method| method.eq_ignore_ascii_case("GET")))
+            && route
+                .requirements
+                .iter()
+                .all(|(key, value)| key != "_format" || value == "html");
+        if is_page && route._defaults._title.is_none() && route._defaults._title_callback.is_none()
+        {
+            diagnostics.push(diagnostic(
+                MISSING_ROUTE_TITLE,
+                format!("Route '{}' has no _title or _title_callback", route.name),
+            ));
+        }
+
+        if !route.requirements.iter().any(|(key, _)| {
+            key.starts_with('_') && !NON_ACCESS_REQUIREMENTS.contains(&key.as_str())
+        }) {
+            diagnostics.push(diagnostic(
+                MISSING_ROUTE_ACCESS,
+                format!(
+                    "Route '{}' has no access requirement, f.e _permission or _access, so access is always denied",
+                    route.name
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Report service arguments that refer to services that don't exist, and arguments that don't
+/// match the constructor of the service class.
+fn get_service_argument_diagnostics(store: &DocumentStore, document: &Document) -> Vec<Diagnostic> {
+    let service_names: HashSet<&str> = store
+        .get_documents()
+        .values()
+        .flat_map(|document| document.tokens.iter())
+        .filter_map(|token| match &token.data {
+            TokenData::DrupalServiceDefinition(service) => Some(service.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    // Don't report anything before the project has been indexed.
+    if service_names.is_empty() {
+        return vec![];
+    }
+
+    let mut diagnostics = vec![];
+    for service in YamlParser::new(&document.content, &document.uri).get_service_arguments() {
+        for (argument, range) in &service.arguments {
+            // Optional services are prefixed with `@?`, and `@@` escapes strings starting with `@`.
+            // Services named after their class are often autowired, and not indexed.
+            let Some(service_name) = argument.strip_prefix('@') else {
+                continue;
+            };
+            if service_name.starts_with(['?', '@'])
+                || service_name.contains('\\')
+                || service_names.contains(service_name)
+                || CONTAINER_SERVICES.contains(&service_name)
+            {
+                continue;
+            }
+
+            diagnostics.push(Diagnostic {
+                range: token_range_to_range(range),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(UNKNOWN_SERVICE.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!("Unknown service '{}'", service_name),
+                data: Some(serde_json::Value::String(service_name.to_string())),
+                ..Diagnostic::default()
+            });
+        }
+        if service.is_complete {
+            diagnostics.extend(get_constructor_argument_diagnostics(store, &service));
+        }
+    }
+    diagnostics
+}
+
+/// Compare the number and order of the arguments of a service against the parameters of the
+/// constructor of its class.
+///
+/// Constructors inherited from a parent class are unknown to us, so those aren't checked.
+fn get_constructor_argument_diagnostics(
+    store: &DocumentStore,
+    service: &ServiceArguments,
+) -> Vec<Diagnostic> {
+    let Some(class_name) = store.get_service_class(&service.service_name) else {
+        return vec![];
+    };
+    let Some((_, token)) = store.get_class_definition(class_name) else {
+        return vec![];
+    };
+    let TokenData::PhpClassDefinition(class) = &token.data else {
+        return vec![];
+    };
+    let Some(TokenData::PhpMethodDefinition(constructor)) = class
+        .methods
+        .get("__construct")
+        .map(|constructor| &constructor.data)
+    else {
+        return vec![];
+    };
+
+    let diagnostic = |range: &tree_sitter::Range, message: String| Diagnostic {
+        range: token_range_to_range(range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(SERVICE_ARGUMENTS.to_string())),
+        source: Some("drupal_ls".to_string()),
+        message,
+        ..Diagnostic::default()
+    };
+
+    let parameters = &constructor.parameters;
+    let arguments = &service.arguments;
+    let mut diagnostics = vec![];
+    let missing_parameters: Vec<String> = parameters
+        .iter()
+        .skip(arguments.len())
+        .filter(|parameter| !parameter.is_optional && !parameter.is_variadic)
+        .map(|parameter| format!("${}", parameter.name))
+        .collect();
+    if !missing_parameters.is_empty() {
+        diagnostics.push(diagnostic(
+            &service.range,
+            format!(
+                "Missing arguments for {}::__construct(): {}",
+                class_name,
+                missing_parameters.join(", ")
+            ),
+        ));
+    } else if arguments.len() > parameters.len()
+        && !parameters.iter().any(|parameter| parameter.is_variadic)
+    {
+        diagnostics.push(diagnostic(
+            &arguments[parameters.len()].1,
+            format!(
+                "Too many arguments for {}::__construct(), expected at most {} but got {}",
+                class_name,
+                parameters.len(),
+                arguments.len()
+            ),
+        ));
+    }
+
+    // Only report services passed in the wrong order when they match another parameter, since
+    // the parent classes of the service classes are unknown to us.
+    let is_of_type = |service_class: &PhpClassName, type_name: &PhpClassName| {
+        service_class == type_name
+            || store
+                .get_class_definition(service_class)
+                .is_some_and(|(_, token)| match &token.data {
+                    TokenData::PhpClassDefinition(class) => class.interfaces.contains(type_name),
+                    _ => false,
+                })
+    };
+    for ((argument, range), parameter) in arguments.iter().zip(parameters) {
+        let (Some(service_name), Some(type_name)) =
+            (argument.strip_prefix('@'), &parameter.class_name)
+        else {
+            continue;
+        };
+        let Some(service_class) = store.get_service_class(service_name.trim_start_matches('?'))
+        else {
+            continue;
+        };
+        if is_of_type(service_class, type_name) {
+            continue;
+        }
+        if let Some((position, expected_parameter)) =
+            parameters.iter().enumerate().find(|(_, parameter)| {
+                parameter
+                    .class_name
+                    .as_ref()
+                    .is_some_and(|type_name| is_of_type(service_class, type_name))
+            })
+        {
+            diagnostics.push(diagnostic(
+                range,
+                format!(
+                    "Argument '{}' doesn't match ${}, but matches ${} at position {}",
+                    argument,
+                    parameter.name,
+                    expected_parameter.name,
+                    position + 1
+                ),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Report functions documented as implementing a hook that isn't defined in any `*.api.php` file
+/// nor invoked anywhere, which usually means it was renamed or removed.
+fn get_unknown_hook_diagnostics(store: &DocumentStore, document: &Document) -> Vec<Diagnostic> {
+    let known_hooks = store.get_known_hooks();
+    // Don't report anything before the project has been indexed.
+    if known_hooks.is_empty() {
+        return vec![];
+    }
+
+    document
+        .tokens
+        .iter()
+        .filter_map(|token| {
+            let TokenData::DrupalHookReference(hook_name) = &token.data else {
+                return None;
+            };
+            if known_hooks.contains(hook_name) {
+                return None;
+            }
+
+            Some(Diagnostic {
+                range: token_range_to_range(&token.range),
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                code: Some(NumberOrString::String(UNKNOWN_HOOK.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!(
+                    "Unknown hook '{}', it may have been renamed or removed",
+                    hook_name
+                ),
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+/// Report placeholders of translatable strings without an argument, and arguments without a
+/// placeholder. The diagnostics contain an edit that syncs the arguments with the placeholders.
+fn get_translation_placeholder_diagnostics(document: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for arguments in PhpParser::new(&document.content).get_translation_arguments() {
+        let mut placeholders: Vec<&str> = vec![];
+        for capture in TRANSLATION_PLACEHOLDER.captures_iter(&arguments.string) {
+            if let Some(placeholder) = capture.name("placeholder") {
+                if !placeholders.contains(&placeholder.as_str()) {
+                    placeholders.push(placeholder.as_str());
+                }
+            }
+        }
+        let is_missing = |placeholder: &str| {
+            !arguments
+                .elements
+                .iter()
+                .any(|(key, _, _)| key == placeholder)
+        };
+
+        let mut mismatches: Vec<(tree_sitter::Range, String)> = placeholders
+            .iter()
+            .filter(|placeholder| is_missing(placeholder))
+            .map(|placeholder| {
+                (
+                    arguments.string_range,
+                    format!("Placeholder '{}' has no argument", placeholder),
+                )
+            })
+            .collect();
+        mismatches.extend(
+            arguments
+                .elements
+                .iter()
+                .filter(|(key, _, _)| !placeholders.contains(&key.as_str()))
+                .map(|(key, range, _)| {
+                    (
+                        *range,
+                        format!("Argument '{}' isn't used in the string", key),
+                    )
+                }),
+        );
+        if mismatches.is_empty() {
+            continue;
+        }
+
+        let edit = TextEdit {
+            range: token_range_to_range(&arguments.range),
+            new_text: get_synced_translation_arguments(&arguments, &placeholders),
+        };
+        diagnostics.extend(mismatches.into_iter().map(|(range, message)| Diagnostic {
+            range: token_range_to_range(&range),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(TRANSLATION_PLACEHOLDERS.to_string())),
+            source: Some("drupal_ls".to_string()),
+            message,
+            data: serde_json::to_value(&edit).ok(),
+            ..Diagnostic::default()
+        }));
+    }
+    diagnostics
+}
+
+/// Get the arguments array with only the used arguments, and an empty argument for each missing
+/// placeholder.
+fn get_synced_translation_arguments(
+    arguments: &TranslationArguments,
+    placeholders: &[&str],
+) -> String {
+    let mut elements: Vec<String> = arguments
+        .elements
+        .iter()
+        .filter(|(key, _, _)| placeholders.contains(&key.as_str()))
+        .map(|(_, _, element)| element.to_string())
+        .collect();
+    elements.extend(
+        placeholders
+            .iter()
+            .filter(|placeholder| {
+                !arguments
+                    .elements
+                    .iter()
+                    .any(|(key, _, _)| key == *placeholder)
+            })
+            .map(|placeholder| format!("'{}' => ''", placeholder)),
+    );
+
+    if arguments.range.start_point.row == arguments.range.end_point.row {
+        return format!("[{}]", elements.join(", "));
+    }
+    format!(
+        "[\n{}{}]",
+        elements
+            .iter()
+            .map(|element| format!("{}  {},\n", arguments.indentation, element))
+            .collect::<String>(),
+        arguments.indentation
+    )
+}
+
+/// Report translatable strings that are missing a translation for any of the indexed languages.
+fn get_untranslated_string_diagnostics(
+    store: &DocumentStore,
+    document: &Document,
+) -> Vec<Diagnostic> {
+    let langcodes = store.get_translation_langcodes();
+    if langcodes.is_empty() {
+        return vec![];
+    }
+
+    let class_call_tokens = get_class_call_tokens(document);
+    document
+        .tokens
+        .iter()
+        .chain(&class_call_tokens)
+        .filter_map(|token| {
+            let TokenData::DrupalTranslationString(translation_string) = &token.data else {
+                return None;
+            };
+            let translations = store.get_translations(&translation_string.string);
+            let missing_langcodes: Vec<&str> = langcodes
+                .iter()
+                .filter(|langcode| {
+                    !translations
+                        .iter()
+                        .any(|(_, _, translation)| translation.langcode == **langcode)
+                })
+                .copied()
+                .collect();
+            if missing_langcodes.is_empty() {
+                return None;
+            }
+
+            Some(Diagnostic {
+                range: token_range_to_range(&token.range),
+                severity: Some(DiagnosticSeverity::INFORMATION),
+                code: Some(NumberOrString::String(UNTRANSLATED_STRING.to_string())),
+                source: Some("drupal_ls".to_string()),
+                message: format!("Missing translations: {}", missing_langcodes.join(", ")),
+                ..Diagnostic::default()
+            })
+        })
+        .collect()
+}
+
+/// Get the tokens of the calls inside the classes of a PHP file, which aren't part of its indexed
+/// tokens, f.e `$this->currentUser()->hasPermission('administer nodes')` in a method.
+fn get_class_call_tokens(document: &Document) -> Vec<Token> {
+    if document.file_type != FileType::Php {
+        return vec![];
+    }
+    // Chained calls start at the same byte, f.e `\Drupal::service('foo')->bar()`.
+    let indexed: HashSet<(usize, usize)> = document
+        .tokens
+        .iter()
+        .map(|token| (token.range.start_byte, token.range.end_byte))
+        .collect();
+    PhpParser::new(&document.content)
+        .get_call_tokens()
+        .into_iter()
+        .filter(|token| !indexed.contains(&(token.range.start_byte, token.range.end_byte)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::path_to_uri;
+
+    #[test]
+    fn unknown_permissions() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///mymodule/mymodule.permissions.yml",
+            "administer mymodule:\n  title: 'Administer mymodule'\n".to_string(),
+        );
+        let uri = "file:///mymodule/src/Controller/FooController.php";
+        store.add_document(
+            uri,
+            r#"<?php
+
+namespace Drupal\mymodule\Controller;
+
+class FooController {
+
+  public function access() {
+    return $this->currentUser()->hasPermission('administer mymodule')
+      || $this->currentUser()->hasPermission('administer mymodul');
+  }
+
+}
+"#
+            .to_string(),
+        );
+
+        let diagnostics =
+            get_unknown_permission_diagnostics(&store, store.get_document(uri).unwrap());
+        assert_eq!(
+            vec![(8, "Unknown permission 'administer mymodul'")],
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+                .collect::<Vec<(u32, &str)>>()
+        );
+    }
+
+    #[test]
+    fn untranslated_strings() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///mymodule/translations/nl.po",
+            "msgid \"Hello\"\nmsgstr \"Hallo\"\n".to_string(),
+        );
+        let uri = "file:///mymodule/src/Controller/FooController.php";
+        store.add_document(
+            uri,
+            r#"<?php
+
+namespace Drupal\mymodule\Controller;
+
+class FooController {
+
+  public function build() {
+    return [
+      '#title' => $this->t('Hello'),
+      '#markup' => $this->t('Goodbye'),
+    ];
+  }
+
+}
+"#
+            .to_string(),
+        );
+
+        let diagnostics =
+            get_untranslated_string_diagnostics(&store, store.get_document(uri).unwrap());
+        assert_eq!(
+            vec![(9, "Missing translations: nl")],
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+                .collect::<Vec<(u32, &str)>>()
+        );
+    }
+
+    #[test]
+    fn unknown_menu_links() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///system/system.links.menu.yml",
+            "system.admin_config:\n  title: Configuration\n  route_name: system.admin_config\n"
+                .to_string(),
+        );
+        let uri = "file:///mymodule/mymodule.links.menu.yml";
+        store.add_document(
+            uri,
+            r#"mymodule.admin:
+  title: 'My module'
+  parent: system.admin_config
+mymodule.settings:
+  title: Settings
+  parent: mymodule.missing
+mymodule.content:
+  title: Content
+  parent: 'menu_link_content:7a1f4e3b'
+"#
+            .to_string(),
+        );
+
+        let diagnostics =
+            get_unknown_menu_link_diagnostics(&store, store.get_document(uri).unwrap());
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            "Unknown menu link 'mymodule.missing'",
+            diagnostics[0].message
+        );
+        assert_eq!(5, diagnostics[0].range.start.line);
+    }
+
+    #[test]
+    fn unknown_modules() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/modules/node/node.info.yml",
+            "name: Node\ntype: module\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/contrib/pathauto/pathauto.info.yml",
+            "name: Pathauto\ntype: module\n".to_string(),
+        );
+        let uri = "file:///var/www/web/modules/custom/foo/foo.info.yml";
+        store.add_document(
+            uri,
+            "name: Foo\ntype: module\ndependencies:\n  - drupal:node\n  - pathauto:pathauto (>=1.12)\n  - drupal:pathuto\n"
+                .to_string(),
+        );
+
+        let diagnostics = get_unknown_module_diagnostics(&store, store.get_document(uri).unwrap());
+        assert_eq!(
+            vec![(5, "Unknown module 'pathuto'".to_string())],
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message))
+                .collect::<Vec<(u32, String)>>()
+        );
+    }
+
+    #[test]
+    fn missing_library_assets() {
+        let directory = std::env::temp_dir().join(format!(
+            "drupal_ls_missing_library_assets_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(directory.join("css")).unwrap();
+        std::fs::write(directory.join("css/style.css"), "").unwrap();
+
+        let mut store = DocumentStore::new();
+        let uri = path_to_uri(&directory.join("foo.libraries.yml")).unwrap();
+        store.add_document(
+            &uri,
+            "global-styling:\n  css:\n    theme:\n      css/style.css: {}\n      css/missing.css: {}\n  js:\n    https://cdn.example.com/lib.js: { type: external }\n"
+                .to_string(),
+        );
+
+        let diagnostics = get_missing_library_asset_diagnostics(store.get_document(&uri).unwrap());
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(4, diagnostics[0].range.start.line);
+        assert_eq!(
+            format!(
+                "Library asset not found: {}",
+                directory.join("css/missing.css").display()
+            ),
+            diagnostics[0].message
+        );
+    }
+
+    #[test]
+    fn config_schema() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/config/schema/core.data_types.schema.yml",
+            r#"boolean:
+  label: 'Boolean'
+integer:
+  label: 'Integer'
+string:
+  label: 'String'
+label:
+  type: string
+sequence:
+  label: 'Sequence'
+config_object:
+  type: mapping
+  mapping:
+    langcode:
+      type: string
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/config/schema/foo.schema.yml",
+            r#"foo.settings:
+  type: config_object
+  mapping:
+    name:
+      type: label
+    page:
+      type: mapping
+      mapping:
+        limit:
+          type: integer
+    roles:
+      type: sequence
+    block:
+      type: foo.block.[%parent.plugin]
+    page_size:
+      type: foo.page_size
+
+foo.page_size:
+  type: integer
+"#
+            .to_string(),
+        );
+        let uri = "file:///var/www/web/modules/custom/foo/config/install/foo.settings.yml";
+        store.add_document(
+            uri,
+            r#"langcode: en
+name: Foo
+page:
+  limit: 'ten'
+  offset: 5
+roles:
+  editor: true
+block:
+  id: foo
+page_size: true
+enabled: false
+"#
+            .to_string(),
+        );
+
+        let diagnostics = get_config_schema_diagnostics(&store, store.get_document(uri).unwrap());
+        assert_eq!(
+            vec![
+                (
+                    3,
+                    "Expected a value of type 'integer' for config key 'page.limit', found string"
+                        .to_string()
+                ),
+                (
+                    4,
+                    "Config key 'page.offset' is not declared in the schema of 'foo.settings'"
+                        .to_string()
+                ),
+                (
+                    9,
+                    "Expected a value of type 'integer' for config key 'page_size', found boolean"
+                        .to_string()
+                ),
+                (
+                    10,
+                    "Config key 'enabled' is not declared in the schema of 'foo.settings'"
+                        .to_string()
+                ),
+            ],
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message))
+                .collect::<Vec<(u32, String)>>()
+        );
+    }
+
+    #[test]
+    fn service_arguments() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///core/core.services.yml",
+            r#"services:
+  entity_type.manager:
+    class: Drupal\Core\Entity\EntityTypeManager
+  logger.factory:
+    class: Drupal\Core\Logger\LoggerChannelFactory
+  logger.factory.alias:
+    alias: logger.factory
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///core/lib/Drupal/Core/Entity/EntityTypeManager.php",
+            "<?php\n\nnamespace Drupal\\Core\\Entity;\n\nclass EntityTypeManager implements EntityTypeManagerInterface {}\n"
+                .to_string(),
+        );
+        store.add_document(
+            "file:///mymodule/src/Foo.php",
+            r#"<?php
+
+namespace Drupal\mymodule;
+
+use Drupal\Core\Entity\EntityTypeManagerInterface;
+use Drupal\Core\Logger\LoggerChannelFactory;
+
+class Foo {
+
+  public function __construct(
+    protected LoggerChannelFactory $loggerFactory,
+    protected EntityTypeManagerInterface $entityTypeManager,
+    protected int $limit = 10,
+  ) {}
+
+}
+"#
+            .to_string(),
+        );
+        let uri = "file:///mymodule/mymodule.services.yml";
+        store.add_document(
+            uri,
+            r#"services:
+  mymodule.swapped:
+    class: Drupal\mymodule\Foo
+    arguments: ['@entity_type.manager', '@logger.factory.alias']
+  mymodule.missing:
+    class: Drupal\mymodule\Foo
+    arguments:
+      - '@logger.factory'
+  mymodule.too_many:
+    class: Drupal\mymodule\Foo
+    arguments: ['@logger.factory', '@entity_type.manager', 5, '@?optional.service', '@unknown.service']
+  mymodule.child:
+    parent: mymodule.missing
+  logger.channel.mymodule:
+    parent: logger.channel_base
+  mymodule.synthetic:
+    synthetic: true
+  mymodule.container_aware:
+    class: Drupal\mymodule\Bar
+    arguments: ['@service_container', '@kernel', '@logger.channel.mymodule', '@mymodule.synthetic']
+"#
+            .to_string(),
+        );
+
+        let diagnostics =
+            get_service_argument_diagnostics(&store, store.get_document(uri).unwrap());
+        let messages: Vec<(u32, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    3,
+                    "Argument '@entity_type.manager' doesn't match $loggerFactory, but matches $entityTypeManager at position 2"
+                ),
+                (
+                    3,
+                    "Argument '@logger.factory.alias' doesn't match $entityTypeManager, but matches $loggerFactory at position 1"
+                ),
+                (
+                    4,
+                    "Missing arguments for Drupal\\mymodule\\Foo::__construct(): $entityTypeManager"
+                ),
+                (10, "Unknown service 'unknown.service'"),
+                (
+                    10,
+                    "Too many arguments for Drupal\\mymodule\\Foo::__construct(), expected at most 3 but got 5"
+                ),
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn route_requirements() {
+        let mut store = DocumentStore::new();
+        let uri = "file:///mymodule/mymodule.routing.yml";
+        store.add_document(
+            uri,
+            r#"mymodule.page:
+  path: '/mymodule'
+  defaults:
+    _controller: '\Drupal\mymodule\Controller\PageController::page'
+    _title: 'My page'
+  requirements:
+    _permission: 'access content'
+mymodule.untitled:
+  path: '/mymodule/{node}'
+  defaults:
+    _controller: '\Drupal\mymodule\Controller\PageController::node'
+  requirements:
+    node: \d+
+mymodule.callback:
+  path: '/mymodule/callback'
+  defaults:
+    _controller: '\Drupal\mymodule\Controller\PageController::callback'
+  methods: [POST]
+  requirements:
+    _custom_access: '\Drupal\mymodule\Controller\PageController::access'
+    _format: json
+"#
+            .to_string(),
+        );
+
+        let diagnostics = get_route_diagnostics(store.get_document(uri).unwrap());
+        let codes: Vec<(u32, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| match &diagnostic.code {
+                Some(NumberOrString::String(code)) => (diagnostic.range.start.line, code.as_str()),
+                _ => (diagnostic.range.start.line, ""),
+            })
+            .collect();
+        assert_eq!(
+            vec![(7, MISSING_ROUTE_TITLE), (7, MISSING_ROUTE_ACCESS)],
+            codes
+        );
+        assert_eq!(17, diagnostics[0].range.end.character);
+    }
+
+    #[test]
+    fn translation_placeholders() {
+        let mut store = DocumentStore::new();
+        let uri = "file:///mymodule/mymodule.module";
+        store.add_document(
+            uri,
+            r#"<?php
+
+function mymodule_message($name, $count) {
+  $message = t('Hello @name', ['@name' => $name]);
+  $message = t('Hello @name, you have %count messages', ['@name' => $name, ':url' => 'https://example.com']);
+  return t('@count items', [
+    '@total' => $count,
+  ]);
+}
+
+function mymodule_contact() {
+  return t('Mail info@example.com before 10:30, or 100%off');
+}
+"#
+            .to_string(),
+        );
+
+        let diagnostics = get_translation_placeholder_diagnostics(store.get_document(uri).unwrap());
+        let messages: Vec<(u32, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            vec![
+                (4, "Placeholder '%count' has no argument"),
+                (4, "Argument ':url' isn't used in the string"),
+                (5, "Placeholder '@count' has no argument"),
+                (6, "Argument '@total' isn't used in the string"),
+            ],
+            messages
+        );
+
+        let edit = |index: usize| {
+            serde_json::from_value::<TextEdit>(diagnostics[index].data.clone().unwrap())
+                .unwrap()
+                .new_text
+        };
+        assert_eq!("['@name' => $name, '%count' => '']", edit(0));
+        assert_eq!("[\n    '@count' => '',\n  ]", edit(2));
+    }
+
+    #[test]
+    fn unknown_hooks() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///core/modules/system/system.api.php",
+            r#"<?php
+
+function hook_cron() {
+}
+
+function hook_form_FORM_ID_alter(&$form, $form_state, $form_id) {
+}
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///mymodule/src/Mailer.php",
+            r#"<?php
+
+namespace Drupal\mymodule;
+
+class Mailer {
+
+  public function send() {
+    $this->moduleHandler->invokeAll('mymodule_mail_sent', [$this]);
+    \Drupal::moduleHandler()->alter(['mymodule_mail', 'mymodule_mail_html'], $message);
+  }
+
+}
+"#
+            .to_string(),
+        );
+        let uri = "file:///mymodule/mymodule.module";
+        store.add_document(
+            uri,
+            r#"<?php
+
+/**
+ * Implements hook_cron().
+ */
+function mymodule_cron() {
+}
+
+/**
+ * Implements hook_form_FORM_ID_alter().
+ */
+function mymodule_form_node_form_alter(&$form, $form_state, $form_id) {
+}
+
+/**
+ * Implements hook_form_user_login_form_alter().
+ */
+function mymodule_form_user_login_form_alter(&$form, $form_state, $form_id) {
+}
+
+/**
+ * Implements hook_mymodule_mail_html_alter().
+ */
+function mymodule_mymodule_mail_html_alter(&$message) {
+}
+
+/**
+ * Implements hook_field_widget_form_alter().
+ */
+function mymodule_field_widget_form_alter(&$element, $form_state, $context) {
+}
+"#
+            .to_string(),
+        );
+
+        let diagnostics = get_unknown_hook_diagnostics(&store, store.get_document(uri).unwrap());
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(
+            "Unknown hook 'hook_field_widget_form_alter', it may have been renamed or removed",
+            diagnostics[0].message
+        );
+        assert_eq!(26, diagnostics[0].range.start.line);
+
+        // The known hooks are built again when a hook is defined.
+        store.add_document(
+            "file:///core/modules/field/field.api.php",
+            "<?php\n\nfunction hook_field_widget_form_alter(&$element, $form_state, $context) {\n}\n"
+                .to_string(),
+        );
+        assert!(get_unknown_hook_diagnostics(&store, store.get_document(uri).unwrap()).is_empty());
+    }
+}
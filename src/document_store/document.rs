@@ -1,24 +1,36 @@
+use std::borrow::Cow;
+use std::fs;
 use std::str::FromStr;
 
 use lsp_types::{Position, Uri};
+use tree_sitter::Range;
 
+use crate::parser::gettext::GettextParser;
 use crate::parser::php::PhpParser;
-use crate::parser::tokens::Token;
+use crate::parser::tokens::{PhpClassName, Token, TokenData};
+use crate::parser::twig::TwigParser;
 use crate::parser::yaml::YamlParser;
+use crate::utils::uri_to_path;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum FileType {
     Php,
     Yaml,
+    Twig,
+    Gettext,
     Unknown,
 }
 
 #[derive(Debug)]
 pub struct Document {
     pub file_type: FileType,
+    /// Empty when only the path is indexed, see [`Document::get_content`].
     pub content: String,
     pub tokens: Vec<Token>,
-    uri: String,
+    /// The fully qualified classes referenced in PHP files, see
+    /// [`PhpParser::get_class_references`].
+    pub class_references: Vec<(PhpClassName, Range)>,
+    pub uri: String,
 }
 
 impl Document {
@@ -27,6 +39,7 @@ impl Document {
             file_type: uri_to_file_type(uri),
             uri: uri.to_string(),
             tokens: vec![],
+            class_references: vec![],
             content,
         }
     }
@@ -36,15 +49,35 @@ impl Document {
     }
 
     pub fn parse(&mut self) {
+        self.class_references = vec![];
         self.tokens = match self.file_type {
             FileType::Php => {
                 let parser = PhpParser::new(&self.content);
-                parser.get_tokens()
+                self.class_references = parser.get_class_references();
+                let mut tokens = parser.get_tokens();
+                if is_settings_file(&self.uri) {
+                    tokens.extend(parser.get_config_overrides());
+                }
+                if let Some(module) = get_hook_implementation_module(&self.uri) {
+                    tokens.extend(parser.get_hook_implementations(module));
+                }
+                if self.uri.contains("/tests/src/") {
+                    tokens.extend(parser.get_covered_classes());
+                }
+                tokens
             }
             FileType::Yaml => {
                 let parser = YamlParser::new(&self.content, &self.uri);
                 parser.get_tokens()
             }
+            FileType::Twig => {
+                let parser = TwigParser::new(&self.content);
+                parser.get_tokens()
+            }
+            FileType::Gettext => {
+                let parser = GettextParser::new(&self.content, &self.uri);
+                parser.get_tokens()
+            }
             FileType::Unknown => {
                 log::error!("Unable to parse documet {:?}", self);
                 vec![]
@@ -52,6 +85,37 @@ impl Document {
         };
     }
 
+    /// Get all translatable strings in the document, which may be more than its tokens include.
+    pub fn get_translation_strings(&self) -> Vec<Token> {
+        match self.file_type {
+            FileType::Php => PhpParser::new(&self.content).get_translation_strings(),
+            FileType::Twig => TwigParser::new(&self.content)
+                .get_tokens()
+                .into_iter()
+                .filter(|token| matches!(token.data, TokenData::DrupalTranslationString(_)))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Whether only the path of the document is indexed, which is the case for default config
+    /// without tokens, since it is only indexed to go to its file.
+    pub fn is_path_only(&self) -> bool {
+        self.tokens.is_empty() && is_default_config_file(&self.uri)
+    }
+
+    /// Get the content of the document, which is read from disk when only its path is indexed.
+    pub fn get_content(&self) -> Cow<'_, str> {
+        if self.is_path_only() && self.content.is_empty() {
+            if let Some(content) =
+                uri_to_path(&self.uri).and_then(|path| fs::read_to_string(path).ok())
+            {
+                return Cow::Owned(content);
+            }
+        }
+        Cow::Borrowed(&self.content)
+    }
+
     pub fn get_uri(&self) -> Option<Uri> {
         Uri::from_str(&self.uri).ok()
     }
@@ -66,11 +130,43 @@ impl Document {
                 let parser = YamlParser::new(&self.content, &self.uri);
                 parser.get_token_at_position(position)
             }
+            FileType::Twig => {
+                let parser = TwigParser::new(&self.content);
+                parser.get_token_at_position(position)
+            }
+            FileType::Gettext => {
+                let parser = GettextParser::new(&self.content, &self.uri);
+                parser.get_token_at_position(position)
+            }
             _ => None,
         }
     }
 }
 
+/// Get the module or theme of a file that can implement hooks, f.e `foo` for `foo.module` or
+/// `foo.views.inc`.
+fn get_hook_implementation_module(uri: &str) -> Option<&str> {
+    let file_name = uri.rsplit('/').next()?;
+    let (module, _) = file_name.split_once('.')?;
+    [".module", ".theme", ".install", ".inc"]
+        .iter()
+        .any(|suffix| file_name.ends_with(suffix))
+        .then_some(module)
+}
+
+/// Whether a file is default config, f.e `config/install/mymodule.settings.yml`.
+pub fn is_default_config_file(uri: &str) -> bool {
+    uri.contains("/config/install/") || uri.contains("/config/optional/")
+}
+
+/// Whether a file is a settings file that can override config, f.e `sites/default/settings.php`
+/// or `settings.local.php`.
+pub fn is_settings_file(uri: &str) -> bool {
+    uri.rsplit('/')
+        .next()
+        .is_some_and(|file_name| file_name.starts_with("settings") && file_name.ends_with(".php"))
+}
+
 fn uri_to_file_type(uri: &str) -> FileType {
     if uri.ends_with(".yml") || uri.ends_with(".yaml") {
         FileType::Yaml
@@ -78,8 +174,13 @@ fn uri_to_file_type(uri: &str) -> FileType {
         || uri.ends_with(".module")
         || uri.ends_with(".theme")
         || uri.ends_with(".install")
+        || uri.ends_with(".inc")
     {
         FileType::Php
+    } else if uri.ends_with(".twig") {
+        FileType::Twig
+    } else if uri.ends_with(".po") {
+        FileType::Gettext
     } else {
         FileType::Unknown
     }
@@ -94,16 +195,42 @@ mod tests {
         let document = Document::new(&String::from("file://test.php"), String::new());
         assert_eq!(FileType::Php, document.file_type);
 
+        let document = Document::new(&String::from("file://theme.inc"), String::new());
+        assert_eq!(FileType::Php, document.file_type);
+
         let document = Document::new(&String::from("file://test.yml"), String::new());
         assert_eq!(FileType::Yaml, document.file_type);
 
         let document = Document::new(&String::from("file://test.yaml"), String::new());
         assert_eq!(FileType::Yaml, document.file_type);
 
+        let document = Document::new(&String::from("file://test.html.twig"), String::new());
+        assert_eq!(FileType::Twig, document.file_type);
+
+        let document = Document::new(&String::from("file://nl.po"), String::new());
+        assert_eq!(FileType::Gettext, document.file_type);
+
         let document = Document::new(&String::from("file://test"), String::new());
         assert_eq!(FileType::Unknown, document.file_type);
 
         let document = Document::new(&String::from("file://test.php.txt"), String::new());
         assert_eq!(FileType::Unknown, document.file_type);
     }
+
+    #[test]
+    fn path_only() {
+        let mut document = Document::new(
+            &String::from("file:///mymodule/config/install/mymodule.settings.yml"),
+            String::from("enabled: true\n"),
+        );
+        document.parse();
+        assert!(document.is_path_only());
+
+        let mut document = Document::new(
+            &String::from("file:///mymodule/config/install/user.role.editor.yml"),
+            String::from("id: editor\nlabel: Editor\npermissions: {}\n"),
+        );
+        document.parse();
+        assert!(!document.is_path_only());
+    }
 }
@@ -1,49 +1,129 @@
 pub mod document;
+pub mod profile;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::sync::{Arc, LazyLock, Mutex};
-use std::time::SystemTime;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, LockResult, Mutex, MutexGuard, OnceLock};
+use std::time::{Instant, SystemTime};
 
-use ignore::overrides::OverrideBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
 use ignore::{WalkBuilder, WalkState};
 use lsp_types::TextDocumentContentChangeEvent;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use url::Url;
+use regex::Regex;
 
+use crate::parser::symbol::Symbol;
 use crate::parser::tokens::{
-    ClassAttribute, DrupalPluginReference, PhpClassName, PhpMethod, Token, TokenData,
+    get_hook_pattern, get_theme_name_from_template_file_name, get_theme_template_file_name,
+    ClassAttribute, DrupalConfigOverride, DrupalConfigSchema, DrupalEntityBundle, DrupalEntityType,
+    DrupalEntityTypeHint, DrupalExtension, DrupalFieldStorage, DrupalPluginReference,
+    DrupalPluginType, DrupalService, DrupalTranslation, PhpClassName, PhpMethod, Token, TokenData,
 };
+use crate::parser::yaml::ENTITY_BUNDLE_CONFIG;
 
-use self::document::{Document, FileType};
+use crate::server::metrics::record_lock_wait;
+use crate::utils::{normalize_uri, path_to_uri, uri_to_path};
 
-pub static DOCUMENT_STORE: LazyLock<Mutex<DocumentStore>> =
-    LazyLock::new(|| Mutex::new(DocumentStore::new()));
+use self::document::{is_settings_file, Document, FileType};
 
-pub fn initialize_document_store(root_dir: String) {
+pub static DOCUMENT_STORE: LazyLock<DocumentStoreMutex> =
+    LazyLock::new(|| DocumentStoreMutex(Mutex::new(DocumentStore::new())));
+
+// The globs of the files that are indexed, used to decide whether files that change on disk
+// should be indexed.
+static INDEX_OVERRIDES: OnceLock<Override> = OnceLock::new();
+
+/// How far indexing the project has come.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct IndexProgress {
+    pub files_parsed: usize,
+    pub files_total: usize,
+    /// The files that couldn't be read.
+    pub failures: usize,
+}
+
+/// Index the project, calling `on_progress` as files are parsed.
+pub fn initialize_document_store(
+    root_dir: String,
+    on_progress: impl Fn(IndexProgress) + Sync,
+) -> IndexProgress {
     log::info!("Starting project initialization...");
     let now = SystemTime::now();
 
-    let mut builder = WalkBuilder::new(&root_dir);
-    builder.standard_filters(false);
+    let _ = INDEX_OVERRIDES.set(get_index_overrides(&root_dir));
+    let (documents, progress) = index_documents_with_progress(&root_dir, on_progress);
 
-    let mut override_builder = OverrideBuilder::new(&root_dir);
-    override_builder.add("**/*.services.yml").unwrap();
-    override_builder.add("**/*.routing.yml").unwrap();
-    override_builder.add("**/*.permissions.yml").unwrap();
-    override_builder.add("**/*.menu.yml").unwrap();
-    override_builder.add("**/core/**/*.php").unwrap();
-    override_builder.add("**/modules/**/*.php").unwrap();
-    // For now we don't care about interfaces at all.
-    override_builder.add("!**/*Interface.php").unwrap();
-    override_builder.add("!**/tests/**/*.php").unwrap();
-    override_builder.add("!vendor").unwrap();
-    override_builder.add("!node_modules").unwrap();
-    override_builder.add("!libraries").unwrap();
-    builder.overrides(override_builder.build().unwrap());
+    log::info!(
+        "Parsed {} files in {} seconds",
+        documents.len(),
+        now.elapsed().unwrap().as_secs_f64()
+    );
+
+    DOCUMENT_STORE.lock().unwrap().add_documents(documents);
+    progress
+}
+
+/// The mutex guarding the document store, which records how long callers wait for the lock.
+pub struct DocumentStoreMutex(Mutex<DocumentStore>);
+
+impl DocumentStoreMutex {
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, DocumentStore>> {
+        let now = Instant::now();
+        let guard = self.0.lock();
+        record_lock_wait(now.elapsed());
+        guard
+    }
+}
+
+/// Parse all of the documents of a project that are part of the index in parallel.
+pub fn index_documents(root_dir: &str) -> HashMap<String, Document> {
+    index_documents_with_progress(root_dir, |_| {}).0
+}
+
+/// Parse all of the documents of a project that are part of the index in parallel, calling
+/// `on_progress` after each file.
+fn index_documents_with_progress(
+    root_dir: &str,
+    on_progress: impl Fn(IndexProgress) + Sync,
+) -> (HashMap<String, Document>, IndexProgress) {
+    let paths = get_document_paths(root_dir, get_index_overrides(root_dir));
+    let files_total = paths.len();
+    let files_parsed = AtomicUsize::new(0);
+    let failures = AtomicUsize::new(0);
+
+    let documents = paths
+        .into_par_iter()
+        .filter_map(|path| {
+            let document = parse_document(&path);
+            if document.is_none() {
+                failures.fetch_add(1, Ordering::Relaxed);
+            }
+            on_progress(IndexProgress {
+                files_parsed: files_parsed.fetch_add(1, Ordering::Relaxed) + 1,
+                files_total,
+                failures: failures.load(Ordering::Relaxed),
+            });
+            document
+        })
+        .collect();
+
+    let progress = IndexProgress {
+        files_parsed: files_parsed.into_inner(),
+        files_total,
+        failures: failures.into_inner(),
+    };
+    (documents, progress)
+}
+
+/// Find all of the documents that we are interested in parsing by walking the file tree in
+/// parallel.
+fn get_document_paths(root_dir: &str, overrides: Override) -> Vec<PathBuf> {
+    let mut builder = WalkBuilder::new(root_dir);
+    builder.standard_filters(false);
+    builder.overrides(overrides);
 
-    // Find all of the documents that we are interested in parsing by walking the file tree using a
-    // parallel iterator.
     let document_paths = Arc::new(Mutex::new(vec![]));
     builder.build_parallel().run(|| {
         let document_paths = document_paths.clone();
@@ -53,77 +133,255 @@ pub fn initialize_document_store(root_dir: String) {
                     document_paths
                         .lock()
                         .unwrap()
-                        .push(Url::from_file_path(dir_entry.path()));
+                        .push(dir_entry.path().to_path_buf());
                 }
             }
             WalkState::Continue
         })
     });
 
-    // Start parsing files in parallel.
-    let documents: HashMap<String, Document> = document_paths
-        .lock()
-        .unwrap()
-        .clone()
-        .into_par_iter()
-        .filter_map(|path| {
-            if let Ok(path) = path {
-                let path = path.to_file_path().unwrap().to_str().unwrap().to_string();
-                let uri = format!("file://{}", path).to_string();
-                let text = fs::read_to_string(&path).unwrap();
+    let document_paths = document_paths.lock().unwrap().clone();
+    document_paths
+}
 
-                let mut document = Document::new(&uri, text);
-                document.parse();
-                return Some((uri, document));
-            }
-            None
-        })
-        .collect();
+/// Read and parse a document, returning it together with its uri.
+fn parse_document(path: &Path) -> Option<(String, Document)> {
+    // The uri is normalized like the ones of the documents that are added by the client, f.e the
+    // drive letter is uppercased on Windows.
+    let uri = normalize_uri(&path_to_uri(path)?);
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            log::error!("Unable to read {}: {:?}", uri, err);
+            return None;
+        }
+    };
 
-    log::info!(
-        "Parsed {} files in {} seconds",
-        documents.len(),
-        now.elapsed().unwrap().as_secs_f64()
-    );
+    let mut document = Document::new(&uri, text);
+    document.parse();
+    if document.is_path_only() {
+        document.set_content(String::new());
+    }
+    Some((uri, document))
+}
 
-    DOCUMENT_STORE.lock().unwrap().add_documents(documents);
+/// Get the globs of the files in the project that are indexed.
+fn get_index_overrides(root_dir: &str) -> Override {
+    let mut override_builder = OverrideBuilder::new(root_dir);
+    override_builder.add("**/*.services.yml").unwrap();
+    override_builder.add("**/*.routing.yml").unwrap();
+    override_builder.add("**/*.permissions.yml").unwrap();
+    override_builder.add("**/*.menu.yml").unwrap();
+    override_builder.add("**/*.libraries.yml").unwrap();
+    override_builder.add("**/*.info.yml").unwrap();
+    override_builder
+        .add("**/config/schema/*.schema.yml")
+        .unwrap();
+    override_builder.add("**/field.storage.*.yml").unwrap();
+    override_builder.add("**/user.role.*.yml").unwrap();
+    for (config_prefix, _) in ENTITY_BUNDLE_CONFIG {
+        override_builder
+            .add(&format!("**/{}*.yml", config_prefix))
+            .unwrap();
+    }
+    override_builder.add("**/config/install/*.yml").unwrap();
+    override_builder.add("**/config/optional/*.yml").unwrap();
+    override_builder.add("**/*.html.twig").unwrap();
+    override_builder.add("**/translations/*.po").unwrap();
+    override_builder.add("**/core/**/*.php").unwrap();
+    override_builder.add("**/modules/**/*.php").unwrap();
+    override_builder.add("**/*.module").unwrap();
+    override_builder.add("**/*.theme").unwrap();
+    override_builder.add("**/*.install").unwrap();
+    // Hooks can be implemented in include files, and core theme hooks are defined in
+    // drupal_common_theme() in theme.inc.
+    override_builder.add("**/*.inc").unwrap();
+    // Config can be overridden in settings.php and settings.local.php.
+    override_builder.add("**/sites/*/settings*.php").unwrap();
+    // For now we don't care about interfaces at all.
+    override_builder.add("!**/*Interface.php").unwrap();
+    override_builder.add("!**/tests/**/*.php").unwrap();
+    // Except for the tests of custom code, to find the tests covering a class.
+    override_builder
+        .add("**/custom/**/tests/src/**/*.php")
+        .unwrap();
+    override_builder.add("!vendor").unwrap();
+    override_builder.add("!node_modules").unwrap();
+    override_builder.add("!libraries").unwrap();
+    override_builder.build().unwrap()
+}
+
+/// Whether a file is part of the index, i.e. matches the globs and isn't in an excluded directory.
+fn is_indexed_path(overrides: &Override, path: &Path) -> bool {
+    overrides.matched(path, false).is_whitelist()
+        && !path
+            .ancestors()
+            .skip(1)
+            .take_while(|directory| directory.starts_with(overrides.path()))
+            .any(|directory| overrides.matched(directory, true).is_ignore())
+}
+
+/// The hooks that are defined in `*.api.php` files or invoked anywhere.
+#[derive(Default)]
+pub struct KnownHooks {
+    names: HashSet<String>,
+    /// The patterns of hooks with placeholders, f.e `hook_form_FORM_ID_alter`.
+    patterns: Vec<Regex>,
+}
+
+impl KnownHooks {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn contains(&self, hook_name: &str) -> bool {
+        self.names.contains(hook_name)
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.is_match(hook_name))
+    }
 }
 
 pub struct DocumentStore {
     documents: HashMap<String, Document>,
+    /// The snippets generated from element usage examples, built on first use and reset when
+    /// the elements change.
+    element_snippets: OnceLock<Vec<(String, String)>>,
+    /// The hooks that are defined or invoked, built on first use and reset when they change.
+    known_hooks: OnceLock<KnownHooks>,
+    /// The names of the PHP classes keyed by their short name, built on first use and reset when
+    /// the class definitions change.
+    class_names: OnceLock<HashMap<String, Vec<PhpClassName>>>,
+    /// When the project was indexed.
+    indexed_at: Option<SystemTime>,
+    /// When a document was last added, changed or removed after that.
+    changed_at: Option<SystemTime>,
 }
 
 impl DocumentStore {
     pub fn new() -> Self {
         Self {
             documents: HashMap::new(),
+            element_snippets: OnceLock::new(),
+            known_hooks: OnceLock::new(),
+            class_names: OnceLock::new(),
+            indexed_at: None,
+            changed_at: None,
         }
     }
 
-    pub fn get_document(&mut self, uri: &String) -> Option<&Document> {
-        self.documents.get(uri)
+    // Documents are keyed by their normalized uri, since clients may encode the uris of indexed
+    // files differently.
+    pub fn get_document(&self, uri: &str) -> Option<&Document> {
+        self.documents.get(&normalize_uri(uri))
     }
 
-    fn get_document_mut(&mut self, uri: &String) -> Option<&mut Document> {
-        self.documents.get_mut(uri)
+    fn get_document_mut(&mut self, uri: &str) -> Option<&mut Document> {
+        self.documents.get_mut(&normalize_uri(uri))
     }
 
     pub fn get_documents(&self) -> &HashMap<String, Document> {
         &self.documents
     }
 
-    pub fn add_document(&mut self, uri: &String, text: String) {
-        self.documents
-            .insert(uri.to_string(), Document::new(uri, text));
-        let document = self.get_document_mut(uri).unwrap();
+    pub fn add_document(&mut self, uri: &str, text: String) {
+        let uri = &normalize_uri(uri);
+        let mut document = Document::new(uri, text);
         document.parse();
+        let has_changed_elements = has_element_snippet(&document)
+            || self.documents.get(uri).is_some_and(has_element_snippet);
+        let has_changed_hooks = get_known_hook_names(&document)
+            != self
+                .documents
+                .get(uri)
+                .map(get_known_hook_names)
+                .unwrap_or_default();
+        let has_changed_classes = get_class_names(&document)
+            != self
+                .documents
+                .get(uri)
+                .map(get_class_names)
+                .unwrap_or_default();
+        self.documents.insert(uri.to_string(), document);
+        if has_changed_elements {
+            self.element_snippets = OnceLock::new();
+        }
+        if has_changed_hooks {
+            self.known_hooks = OnceLock::new();
+        }
+        if has_changed_classes {
+            self.class_names = OnceLock::new();
+        }
+        self.changed_at = Some(SystemTime::now());
+    }
+
+    /// Parse a document again from disk, f.e after it was saved or changed outside of the editor.
+    ///
+    /// Files that aren't indexed are only reloaded when they were opened before.
+    pub fn reload_document(&mut self, uri: &str) {
+        let Some(path) = uri_to_path(uri) else {
+            return;
+        };
+        let is_indexed = INDEX_OVERRIDES
+            .get()
+            .is_some_and(|overrides| is_indexed_path(overrides, &path));
+        if self.get_document(uri).is_none() && !is_indexed {
+            return;
+        }
+
+        // Documents that are open keep their content, f.e when they are saved.
+        let is_indexed_only = self
+            .get_document(uri)
+            .is_none_or(|document| document.content.is_empty());
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                self.add_document(uri, text);
+                if let Some(document) = self
+                    .get_document_mut(uri)
+                    .filter(|document| is_indexed_only && document.is_path_only())
+                {
+                    document.set_content(String::new());
+                }
+            }
+            Err(err) => log::error!("Unable to read {}: {:?}", uri, err),
+        }
+    }
+
+    pub fn remove_document(&mut self, uri: &str) {
+        if let Some(document) = self.documents.remove(&normalize_uri(uri)) {
+            if has_element_snippet(&document) {
+                self.element_snippets = OnceLock::new();
+            }
+            if !get_known_hook_names(&document).is_empty() {
+                self.known_hooks = OnceLock::new();
+            }
+            if !get_class_names(&document).is_empty() {
+                self.class_names = OnceLock::new();
+            }
+            self.changed_at = Some(SystemTime::now());
+        }
     }
 
     pub fn add_documents(&mut self, documents: HashMap<String, Document>) {
         self.documents.extend(documents);
+        self.element_snippets = OnceLock::new();
+        self.known_hooks = OnceLock::new();
+        self.class_names = OnceLock::new();
+        self.indexed_at = Some(SystemTime::now());
+    }
+
+    /// Get when the project was indexed, or `None` while it is being indexed.
+    pub fn get_indexed_at(&self) -> Option<SystemTime> {
+        self.indexed_at
     }
 
-    pub fn change_document(&mut self, uri: &String, changes: Vec<TextDocumentContentChangeEvent>) {
+    /// Get when a document was last added, changed or removed.
+    pub fn get_changed_at(&self) -> Option<SystemTime> {
+        self.changed_at
+    }
+
+    pub fn change_document(&mut self, uri: &str, changes: Vec<TextDocumentContentChangeEvent>) {
         if changes.len() > 1 {
             log::error!(
                 "Only full text document sync is supported! Received {} content changes for {}",
@@ -135,17 +393,95 @@ impl DocumentStore {
 
         match self.get_document_mut(uri) {
             Some(document) => {
+                let had_element_snippet = has_element_snippet(document);
+                let old_hook_names: Vec<String> = get_known_hook_names(document)
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                let old_class_names: Vec<PhpClassName> =
+                    get_class_names(document).into_iter().cloned().collect();
                 for change in changes {
                     document.set_content(change.text);
                 }
                 document.parse();
+                let has_changed_elements = had_element_snippet || has_element_snippet(document);
+                let has_changed_hooks = get_known_hook_names(document) != old_hook_names;
+                let has_changed_classes = get_class_names(document)
+                    .into_iter()
+                    .ne(old_class_names.iter());
+                if has_changed_elements {
+                    self.element_snippets = OnceLock::new();
+                }
+                if has_changed_hooks {
+                    self.known_hooks = OnceLock::new();
+                }
+                if has_changed_classes {
+                    self.class_names = OnceLock::new();
+                }
+                self.changed_at = Some(SystemTime::now());
             }
             None => log::error!("Unable to apply changes to non-existing document: {}", uri),
         }
     }
 
+    /// Get the snippets for form and render elements with a usage example, keyed by f.e
+    /// `form-textfield`.
+    pub fn get_element_snippets(&self) -> &[(String, String)] {
+        self.element_snippets.get_or_init(|| {
+            self.documents
+                .values()
+                .flat_map(|document| document.tokens.iter())
+                .filter_map(get_element_snippet)
+                .collect()
+        })
+    }
+
+    /// Get the hooks that are defined in `*.api.php` files or invoked anywhere.
+    pub fn get_known_hooks(&self) -> &KnownHooks {
+        self.known_hooks.get_or_init(|| {
+            let mut known_hooks = KnownHooks::default();
+            for token in self
+                .get_documents_by_file_type(FileType::Php)
+                .into_iter()
+                .flat_map(|document| document.tokens.iter())
+            {
+                match &token.data {
+                    TokenData::DrupalHookDefinition(hook) => {
+                        known_hooks.patterns.extend(get_hook_pattern(&hook.name));
+                        known_hooks.names.insert(hook.name.clone());
+                    }
+                    TokenData::DrupalHookInvocation(hook_names) => {
+                        known_hooks.names.extend(hook_names.iter().cloned());
+                    }
+                    _ => {}
+                }
+            }
+            known_hooks
+        })
+    }
+
+    /// Get the names of the PHP classes that are defined, keyed by their short name, f.e `Node`
+    /// for `Drupal\node\Entity\Node`.
+    pub fn get_class_names_by_short_name(&self) -> &HashMap<String, Vec<PhpClassName>> {
+        self.class_names.get_or_init(|| {
+            let mut class_names: HashMap<String, Vec<PhpClassName>> = HashMap::new();
+            for class_name in self
+                .get_documents_by_file_type(FileType::Php)
+                .into_iter()
+                .flat_map(get_class_names)
+            {
+                class_names
+                    .entry(class_name.get_short_name().to_string())
+                    .or_default()
+                    .push(class_name.clone());
+            }
+            class_names
+        })
+    }
+
     // TODO: Consider moving this to a separate module.
     pub fn get_service_definition(&self, service_name: &str) -> Option<(&Document, &Token)> {
+        let service_name = Symbol::get(service_name)?;
         let files = self.get_documents_by_file_type(FileType::Yaml);
 
         files.iter().find_map(|&document| {
@@ -161,7 +497,136 @@ impl DocumentStore {
         })
     }
 
+    /// Get the class of a service, services without a class inherit it from their parent or the
+    /// service they are an alias for.
+    pub fn get_service_class(&self, service_name: &str) -> Option<&PhpClassName> {
+        let mut service_name = service_name;
+        // The depth is limited to guard against circular parents.
+        for _ in 0..4 {
+            let (_, token) = self.get_service_definition(service_name)?;
+            let TokenData::DrupalServiceDefinition(service) = &token.data else {
+                return None;
+            };
+            match (
+                &service.class,
+                service.parent.as_ref().or(service.alias.as_ref()),
+            ) {
+                (Some(class_name), _) => return Some(class_name),
+                (None, Some(parent)) => service_name = parent,
+                (None, None) => return None,
+            }
+        }
+        None
+    }
+
+    /// Get the places a class is referenced, both in PHP and in service and route definitions.
+    pub fn get_class_references(
+        &self,
+        class_name: &PhpClassName,
+    ) -> Vec<(&Document, tree_sitter::Range)> {
+        let mut references = vec![];
+        for document in self.documents.values() {
+            match document.file_type {
+                FileType::Php => references.extend(
+                    document
+                        .class_references
+                        .iter()
+                        .filter(|(name, _)| name == class_name)
+                        .map(|(_, range)| (document, *range)),
+                ),
+                FileType::Yaml => references.extend(
+                    document
+                        .tokens
+                        .iter()
+                        .filter(|token| match &token.data {
+                            TokenData::DrupalServiceDefinition(service) => {
+                                service.class.as_ref() == Some(class_name)
+                            }
+                            TokenData::DrupalRouteDefinition(route) => {
+                                route._defaults._form.as_ref() == Some(class_name)
+                                    || route._defaults._controller.as_ref().is_some_and(
+                                        |controller| {
+                                            controller.class_name.as_ref() == Some(class_name)
+                                        },
+                                    )
+                            }
+                            _ => false,
+                        })
+                        .map(|token| (document, token.range)),
+                ),
+                _ => {}
+            }
+        }
+        references
+    }
+
+    /// Find the service to inject for a class or interface type.
+    ///
+    /// Services of exactly the given class are preferred over services implementing the interface.
+    pub fn get_service_name_for_class(&self, class_name: &PhpClassName) -> Option<&str> {
+        let implementing_classes: Vec<&PhpClassName> = self
+            .get_documents_by_file_type(FileType::Php)
+            .iter()
+            .flat_map(|document| document.tokens.iter())
+            .filter_map(|token| match &token.data {
+                TokenData::PhpClassDefinition(class) if class.interfaces.contains(class_name) => {
+                    Some(&class.name)
+                }
+                _ => None,
+            })
+            .collect();
+
+        let mut services: Vec<(bool, &str)> = self
+            .get_documents_by_file_type(FileType::Yaml)
+            .iter()
+            .flat_map(|document| document.tokens.iter())
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalServiceDefinition(service)
+                    if service.class.as_ref() == Some(class_name) =>
+                {
+                    Some((false, service.name.as_str()))
+                }
+                TokenData::DrupalServiceDefinition(service)
+                    if service
+                        .class
+                        .as_ref()
+                        .is_some_and(|class| implementing_classes.contains(&class)) =>
+                {
+                    Some((true, service.name.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // Sort to get a stable result, as documents are stored in a hash map.
+        services.sort_by_key(|(is_implementation, name)| (*is_implementation, name.len(), *name));
+        services.first().map(|(_, name)| *name)
+    }
+
+    pub fn get_service_definitions_for_class(
+        &self,
+        class_name: &PhpClassName,
+    ) -> Vec<(&Document, &DrupalService)> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .flat_map(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .filter_map(move |token| match &token.data {
+                        TokenData::DrupalServiceDefinition(service)
+                            if service.class.as_ref() == Some(class_name) =>
+                        {
+                            Some((document, service))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
     pub fn get_route_definition(&self, route_name: &str) -> Option<(&Document, &Token)> {
+        let route_name = Symbol::get(route_name)?;
         let files = self.get_documents_by_file_type(FileType::Yaml);
 
         files.iter().find_map(|&document| {
@@ -220,14 +685,397 @@ impl DocumentStore {
     }
 
     pub fn get_permission_definition(&self, permission_name: &str) -> Option<(&Document, &Token)> {
+        self.get_permission_definitions(permission_name)
+            .into_iter()
+            .next()
+    }
+
+    /// Get all definitions of a permission, as multiple modules may define the same permission.
+    pub fn get_permission_definitions(&self, permission_name: &str) -> Vec<(&Document, &Token)> {
+        let Some(permission_name) = Symbol::get(permission_name) else {
+            return vec![];
+        };
+        let permission_name = &permission_name;
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .flat_map(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .filter(move |token| {
+                        matches!(&token.data, TokenData::DrupalPermissionDefinition(permission) if permission.name == *permission_name)
+                    })
+                    .map(move |token| (document, token))
+            })
+            .collect()
+    }
+
+    /// Get the implementations of a hook, based on their "Implements hook_NAME()." comment or else
+    /// on their function name.
+    pub fn get_hook_implementations(&self, hook_name: &str) -> Vec<(&Document, &Token)> {
+        self.get_hooks_implementations(&[hook_name])
+            .remove(hook_name)
+            .unwrap_or_default()
+    }
+
+    /// Get the implementations of multiple hooks at once, grouped by hook name.
+    pub fn get_hooks_implementations<'a>(
+        &self,
+        hook_names: &[&'a str],
+    ) -> HashMap<&'a str, Vec<(&Document, &Token)>> {
+        // Uppercase parts are placeholders, f.e `hook_form_FORM_ID_alter`.
+        let hooks: Vec<(&str, Option<Regex>)> = hook_names
+            .iter()
+            .map(|hook_name| (*hook_name, get_hook_pattern(hook_name)))
+            .collect();
+        let known_hooks = self.get_known_hooks();
+        let mut implementations: HashMap<&str, Vec<(&Document, &Token)>> = HashMap::new();
+        for document in self.get_documents_by_file_type(FileType::Php) {
+            for token in &document.tokens {
+                let TokenData::DrupalHookImplementation(implementation) = &token.data else {
+                    continue;
+                };
+                // Functions without a docblock only implement a hook when their name matches a
+                // known hook exactly, otherwise helpers like `mymodule_item_load()` would
+                // implement `hook_ENTITY_TYPE_load`.
+                if !implementation.documented
+                    && !known_hooks.names.contains(&implementation.hook_name)
+                {
+                    continue;
+                }
+                for (hook_name, hook_pattern) in &hooks {
+                    if implementation.implements(hook_name, hook_pattern.as_ref()) {
+                        implementations
+                            .entry(hook_name)
+                            .or_default()
+                            .push((document, token));
+                    }
+                }
+            }
+        }
+        implementations
+    }
+
+    pub fn get_role_definition(&self, role_id: &str) -> Option<(&Document, &Token)> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .find_map(|document| {
+                Some((
+                    document,
+                    document.tokens.iter().find(|token| {
+                        matches!(&token.data, TokenData::DrupalRoleDefinition(role) if role.id == role_id)
+                    })?,
+                ))
+            })
+    }
+
+    pub fn get_entity_bundle_definition(
+        &self,
+        entity_type_id: &str,
+        name: &str,
+    ) -> Option<(&Document, &Token)> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .find_map(|document| {
+                Some((
+                    document,
+                    document.tokens.iter().find(|token| {
+                        matches!(&token.data, TokenData::DrupalEntityBundleDefinition(bundle) if bundle.entity_type == entity_type_id && bundle.name == name)
+                    })?,
+                ))
+            })
+    }
+
+    pub fn get_menu_link_definition(&self, menu_link_id: &str) -> Option<(&Document, &Token)> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .find_map(|document| {
+                Some((
+                    document,
+                    document.tokens.iter().find(|token| {
+                        matches!(&token.data, TokenData::DrupalMenuLinkDefinition(menu_link) if menu_link.id == menu_link_id)
+                    })?,
+                ))
+            })
+    }
+
+    /// Get the overrides of a config object in settings files.
+    pub fn get_config_overrides(
+        &self,
+        config_name: &str,
+    ) -> Vec<(&Document, &DrupalConfigOverride)> {
+        self.get_documents_by_file_type(FileType::Php)
+            .into_iter()
+            .filter(|document| is_settings_file(&document.uri))
+            .flat_map(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .filter_map(move |token| match &token.data {
+                        TokenData::DrupalConfigOverride(config_override)
+                            if config_override.config_name == config_name =>
+                        {
+                            Some((document, config_override))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect()
+    }
+
+    /// Get the schema of a config object, preferring exact matches over wildcards.
+    pub fn get_config_schema(&self, config_name: &str) -> Option<&DrupalConfigSchema> {
+        match &self.get_config_schema_definition(config_name)?.1.data {
+            TokenData::DrupalConfigSchemaDefinition(schema) => Some(schema),
+            _ => None,
+        }
+    }
+
+    /// Get the schema definition of a config object, preferring exact matches over wildcards.
+    pub fn get_config_schema_definition(&self, config_name: &str) -> Option<(&Document, &Token)> {
+        let schemas: Vec<(&Document, &Token, &DrupalConfigSchema)> = self
+            .get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .filter(|document| document.uri.ends_with(".schema.yml"))
+            .flat_map(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .filter_map(move |token| match &token.data {
+                        TokenData::DrupalConfigSchemaDefinition(schema)
+                            if schema.matches(config_name) =>
+                        {
+                            Some((document, token, schema))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect();
+
+        schemas
+            .iter()
+            .find(|(_, _, schema)| schema.name == config_name)
+            .or(schemas.first())
+            .map(|(document, token, _)| (*document, *token))
+    }
+
+    /// Get the default config shipped by a module in `config/install` or `config/optional`.
+    pub fn get_default_config(&self, config_name: &str) -> Option<&Document> {
+        let file_name = format!("/{}.yml", config_name);
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .find(|document| {
+                document.uri.ends_with(&file_name)
+                    && (document.uri.contains("/config/install/")
+                        || document.uri.contains("/config/optional/"))
+            })
+    }
+
+    /// Check whether any module provides dynamic permissions through `permission_callbacks`.
+    pub fn has_permission_callbacks(&self) -> bool {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .iter()
+            .filter(|document| document.uri.ends_with(".permissions.yml"))
+            .any(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .any(|token| matches!(token.data, TokenData::PhpMethodReference(_)))
+            })
+    }
+
+    pub fn get_plugin_definition(
+        &self,
+        plugin_reference: &DrupalPluginReference,
+    ) -> Option<(&Document, &Token)> {
+        if plugin_reference.plugin_type == DrupalPluginType::EntityType {
+            // Views refer to entity types by their tables, f.e `base_table: node_field_data`.
+            return self
+                .get_entity_type_definition(&plugin_reference.plugin_id)
+                .or_else(|| {
+                    self.get_entity_types()
+                        .into_iter()
+                        .find(|(_, _, entity_type)| {
+                            [&entity_type.base_table, &entity_type.data_table]
+                                .into_iter()
+                                .flatten()
+                                .any(|table| *table == plugin_reference.plugin_id)
+                        })
+                        .map(|(document, token, _)| (document, token))
+                });
+        }
+
+        let files = self.get_documents_by_file_type(FileType::Php);
+
+        files.iter().find_map(|&document| {
+            Some((
+                document,
+                document.tokens.iter().find(|token| {
+                    if let TokenData::PhpClassDefinition(class) = &token.data {
+                        if let Some(ClassAttribute::Plugin(plugin)) = &class.attribute {
+                            return plugin_reference.plugin_type.includes(&plugin.plugin_type)
+                                && plugin.plugin_id == plugin_reference.plugin_id;
+                        }
+                    }
+                    false
+                })?,
+            ))
+        })
+    }
+
+    pub fn get_entity_type_definition(&self, entity_type_id: &str) -> Option<(&Document, &Token)> {
+        self.get_entity_types()
+            .into_iter()
+            .find(|(_, _, entity_type)| entity_type.id == entity_type_id)
+            .map(|(document, token, _)| (document, token))
+    }
+
+    /// Get all content and config entity types, sorted by id.
+    pub fn get_entity_types(&self) -> Vec<(&Document, &Token, &DrupalEntityType)> {
+        let mut entity_types: Vec<(&Document, &Token, &DrupalEntityType)> = self
+            .get_documents_by_file_type(FileType::Php)
+            .into_iter()
+            .flat_map(|document| {
+                document.tokens.iter().filter_map(move |token| {
+                    if let TokenData::PhpClassDefinition(class) = &token.data {
+                        if let Some(ClassAttribute::EntityType(entity_type)) = &class.attribute {
+                            return Some((document, token, entity_type));
+                        }
+                    }
+                    None
+                })
+            })
+            .collect();
+        entity_types.sort_by(|(_, _, a), (_, _, b)| a.id.cmp(&b.id));
+        entity_types
+    }
+
+    /// Get the entity type that a hint refers to.
+    ///
+    /// Interfaces that are shared by multiple entity types, f.e `ContentEntityInterface`, don't
+    /// resolve to an entity type.
+    pub fn get_entity_type(&self, hint: &DrupalEntityTypeHint) -> Option<&DrupalEntityType> {
+        let entity_types: Vec<&DrupalEntityType> = self
+            .get_entity_types()
+            .into_iter()
+            .filter(|(_, token, entity_type)| match hint {
+                DrupalEntityTypeHint::Id(id) => entity_type.id == *id,
+                DrupalEntityTypeHint::Class(class_name) => match &token.data {
+                    TokenData::PhpClassDefinition(class) => {
+                        class.name == *class_name || class.interfaces.contains(class_name)
+                    }
+                    _ => false,
+                },
+            })
+            .map(|(_, _, entity_type)| entity_type)
+            .collect();
+
+        match entity_types.as_slice() {
+            [entity_type] => Some(entity_type),
+            _ => None,
+        }
+    }
+
+    /// Get the configurable fields of an entity type.
+    pub fn get_field_storages(&self, entity_type_id: &str) -> Vec<&DrupalFieldStorage> {
+        let mut field_storages: Vec<&DrupalFieldStorage> = self
+            .get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .flat_map(|document| document.tokens.iter())
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalFieldStorageDefinition(field_storage)
+                    if field_storage.entity_type == entity_type_id =>
+                {
+                    Some(field_storage)
+                }
+                _ => None,
+            })
+            .collect();
+        // The same config is often both in a module and the sync directory.
+        field_storages.sort_by(|a, b| a.field.name.cmp(&b.field.name));
+        field_storages.dedup_by(|a, b| a.field.name == b.field.name);
+        field_storages
+    }
+
+    /// Get the bundles of an entity type, from their config.
+    pub fn get_entity_bundles(&self, entity_type_id: &str) -> Vec<&DrupalEntityBundle> {
+        let mut bundles: Vec<&DrupalEntityBundle> = self
+            .get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .flat_map(|document| document.tokens.iter())
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalEntityBundleDefinition(bundle)
+                    if bundle.entity_type == entity_type_id =>
+                {
+                    Some(bundle)
+                }
+                _ => None,
+            })
+            .collect();
+        // The same config is often both in a module and the sync directory.
+        bundles.sort_by(|a, b| a.name.cmp(&b.name));
+        bundles.dedup_by(|a, b| a.name == b.name);
+        bundles
+    }
+
+    /// Get the machine names of the modules, themes and profiles, from their `*.info.yml` files.
+    pub fn get_extension_names(&self) -> HashSet<&str> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .filter_map(|document| document.uri.rsplit('/').next()?.strip_suffix(".info.yml"))
+            .collect()
+    }
+
+    /// Get the tests covering a class, with the first `@covers` tag in each test file.
+    pub fn get_class_tests(&self, class_name: &PhpClassName) -> Vec<(&Document, &Token)> {
+        self.get_documents_by_file_type(FileType::Php)
+            .into_iter()
+            .filter_map(|document| {
+                Some((
+                    document,
+                    document.tokens.iter().find(|token| {
+                        matches!(&token.data, TokenData::PhpUnitCovers(covered_class) if covered_class == class_name)
+                    })?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the modules, themes and profiles of the project.
+    pub fn get_extensions(&self) -> Vec<&DrupalExtension> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .flat_map(|document| document.tokens.iter())
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalExtensionDefinition(extension) => Some(extension),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get the `*.info.yml` file of a module, theme or profile.
+    pub fn get_extension_definition(&self, name: &str) -> Option<(&Document, &Token)> {
+        self.get_documents_by_file_type(FileType::Yaml)
+            .into_iter()
+            .find_map(|document| {
+                Some((
+                    document,
+                    document.tokens.iter().find(|token| {
+                        matches!(&token.data, TokenData::DrupalExtensionDefinition(extension) if extension.name == name)
+                    })?,
+                ))
+            })
+    }
+
+    pub fn get_library_definition(&self, library_name: &str) -> Option<(&Document, &Token)> {
         let files = self.get_documents_by_file_type(FileType::Yaml);
 
         files.iter().find_map(|&document| {
             Some((
                 document,
                 document.tokens.iter().find(|token| {
-                    if let TokenData::DrupalPermissionDefinition(permission) = &token.data {
-                        return permission.name == permission_name;
+                    if let TokenData::DrupalLibraryDefinition(library) = &token.data {
+                        return library.name == library_name;
                     }
                     false
                 })?,
@@ -235,21 +1083,15 @@ impl DocumentStore {
         })
     }
 
-    pub fn get_plugin_definition(
-        &self,
-        plugin_reference: &DrupalPluginReference,
-    ) -> Option<(&Document, &Token)> {
+    pub fn get_theme_definition(&self, theme_name: &str) -> Option<(&Document, &Token)> {
         let files = self.get_documents_by_file_type(FileType::Php);
 
         files.iter().find_map(|&document| {
             Some((
                 document,
                 document.tokens.iter().find(|token| {
-                    if let TokenData::PhpClassDefinition(class) = &token.data {
-                        if let Some(ClassAttribute::Plugin(plugin)) = &class.attribute {
-                            return plugin.plugin_type == plugin_reference.plugin_type
-                                && plugin.plugin_id == plugin_reference.plugin_id;
-                        }
+                    if let TokenData::DrupalThemeDefinition(theme) = &token.data {
+                        return theme.name == theme_name;
                     }
                     false
                 })?,
@@ -257,6 +1099,188 @@ impl DocumentStore {
         })
     }
 
+    /// Find the template used to render a theme hook, including suggestions like `node__article`.
+    pub fn get_theme_template(&self, theme_name: &str) -> Option<&Document> {
+        let template = self
+            .get_theme_definition(theme_name)
+            .and_then(|(_, token)| match &token.data {
+                TokenData::DrupalThemeDefinition(theme) => theme.template.as_deref(),
+                _ => None,
+            });
+
+        if let Some(document) =
+            self.get_template_definition(&get_theme_template_file_name(theme_name, template))
+        {
+            return Some(document);
+        }
+
+        let (base_hook, _) = theme_name.rsplit_once("__")?;
+        self.get_theme_template(base_hook)
+    }
+
+    /// Get the theme hook rendered by a template, taking `template` overrides into account.
+    pub fn get_theme_name_for_template(&self, file_name: &str) -> String {
+        self.get_documents_by_file_type(FileType::Php)
+            .iter()
+            .flat_map(|document| document.tokens.iter())
+            .find_map(|token| match &token.data {
+                TokenData::DrupalThemeDefinition(theme)
+                    if theme.template.is_some() && theme.get_template_file_name() == file_name =>
+                {
+                    Some(theme.name.clone())
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| get_theme_name_from_template_file_name(file_name))
+    }
+
+    /// Get the variables available in the template of a theme hook, paired with their origin.
+    ///
+    /// Variables are collected from the hook_theme() definition and from all preprocess functions
+    /// of the theme hook, falling back to the base hook for suggestions like `node__article`.
+    pub fn get_theme_variables(&self, theme_name: &str) -> Vec<(&String, &str)> {
+        let mut variables: Vec<(&String, &str)> = vec![];
+        let mut theme_name = Some(theme_name);
+
+        while let Some(name) = theme_name {
+            self.get_documents_by_file_type(FileType::Php)
+                .iter()
+                .flat_map(|document| document.tokens.iter())
+                .for_each(|token| match &token.data {
+                    TokenData::DrupalThemeDefinition(theme) if theme.name == name => {
+                        variables.extend(
+                            theme
+                                .variables
+                                .iter()
+                                .map(|variable| (variable, theme.name.as_str())),
+                        );
+                    }
+                    TokenData::DrupalPreprocessDefinition(preprocess)
+                        if preprocess.theme == name =>
+                    {
+                        variables.extend(
+                            preprocess
+                                .variables
+                                .iter()
+                                .map(|variable| (variable, preprocess.function_name.as_str())),
+                        );
+                    }
+                    _ => (),
+                });
+            theme_name = name.rsplit_once("__").map(|(base_hook, _)| base_hook);
+        }
+
+        let mut seen: Vec<&String> = vec![];
+        variables.retain(|(variable, _)| {
+            if seen.contains(variable) {
+                return false;
+            }
+            seen.push(variable);
+            true
+        });
+        variables
+    }
+
+    /// Get the translations of a string from the indexed gettext files, sorted by language.
+    pub fn get_translations(&self, string: &str) -> Vec<(&Document, &Token, &DrupalTranslation)> {
+        let mut translations: Vec<(&Document, &Token, &DrupalTranslation)> = self
+            .get_documents_by_file_type(FileType::Gettext)
+            .into_iter()
+            .flat_map(|document| {
+                document
+                    .tokens
+                    .iter()
+                    .filter_map(move |token| match &token.data {
+                        TokenData::DrupalTranslationDefinition(translation)
+                            if translation.string == string
+                                && !translation.translation.is_empty() =>
+                        {
+                            Some((document, token, translation))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect();
+        translations.sort_by(|(_, _, a), (_, _, b)| a.langcode.cmp(&b.langcode));
+        translations
+    }
+
+    /// Get the languages that the indexed gettext files translate to.
+    pub fn get_translation_langcodes(&self) -> Vec<&str> {
+        let mut langcodes: Vec<&str> = self
+            .get_documents_by_file_type(FileType::Gettext)
+            .into_iter()
+            .flat_map(|document| document.tokens.iter())
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalTranslationDefinition(translation) => {
+                    Some(translation.langcode.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        langcodes.sort();
+        langcodes.dedup();
+        langcodes
+    }
+
+    /// Get the templates for a theme hook suggestion, including the more specific suggestions
+    /// starting with it, f.e `node--article.html.twig` and `node--article--teaser.html.twig` for
+    /// `node__article`.
+    ///
+    /// Falls back to the template of the base hook, when there is no template for the suggestion.
+    pub fn get_theme_suggestion_templates(&self, suggestion: &str) -> Vec<&Document> {
+        let mut templates: Vec<&Document> = self
+            .get_documents_by_file_type(FileType::Twig)
+            .into_iter()
+            .filter(|document| {
+                document
+                    .uri
+                    .rsplit('/')
+                    .next()
+                    .filter(|file_name| file_name.ends_with(".html.twig"))
+                    .is_some_and(|file_name| {
+                        is_theme_suggestion_match(
+                            &get_theme_name_from_template_file_name(file_name),
+                            suggestion,
+                        )
+                    })
+            })
+            .collect();
+        if templates.is_empty() {
+            return self
+                .get_theme_template(suggestion.trim_end_matches('_'))
+                .into_iter()
+                .collect();
+        }
+        templates.sort_by_key(|document| &document.uri);
+        templates
+    }
+
+    /// Resolve a template name like `node.html.twig` or `@node/node.html.twig` to its document.
+    pub fn get_template_definition(&self, template_name: &str) -> Option<&Document> {
+        let (namespace, file_name) = match template_name.rsplit_once('/') {
+            Some((namespace, file_name)) => (Some(namespace.trim_start_matches('@')), file_name),
+            None => (None, template_name),
+        };
+        let mut templates: Vec<&Document> = self
+            .get_documents_by_file_type(FileType::Twig)
+            .into_iter()
+            .filter(|document| document.uri.ends_with(&format!("/{}", file_name)))
+            .collect();
+        // Templates of themes override the ones of modules, which override the ones of core.
+        templates.sort_by_key(|document| (get_template_priority(&document.uri), &document.uri));
+
+        // Prefer the template provided by the namespaced module or theme.
+        namespace
+            .and_then(|namespace| {
+                templates
+                    .iter()
+                    .find(|document| document.uri.contains(&format!("/{}/", namespace)))
+            })
+            .or(templates.first())
+            .copied()
+    }
+
     fn get_documents_by_file_type(&self, file_type: FileType) -> Vec<&Document> {
         self.documents
             .values()
@@ -265,12 +1289,99 @@ impl DocumentStore {
     }
 }
 
+/// Get the priority of a template when several extensions provide one with the same name, where
+/// the template of a custom theme has the highest priority and the one of core the lowest.
+fn get_template_priority(uri: &str) -> u8 {
+    if uri.contains("/core/") {
+        4
+    } else if uri.contains("/themes/custom/") {
+        0
+    } else if uri.contains("/themes/") {
+        1
+    } else if uri.contains("/custom/") {
+        2
+    } else {
+        3
+    }
+}
+
+/// Get the snippet of a form or render element plugin with a usage example.
+fn get_element_snippet(token: &Token) -> Option<(String, String)> {
+    let TokenData::PhpClassDefinition(class) = &token.data else {
+        return None;
+    };
+    let Some(ClassAttribute::Plugin(plugin)) = &class.attribute else {
+        return None;
+    };
+    let prefix = match plugin.plugin_type {
+        DrupalPluginType::RenderElement => "render",
+        DrupalPluginType::FormElement => "form",
+        _ => return None,
+    };
+    Some((
+        format!("{}-{}", prefix, plugin.plugin_id),
+        plugin.usage_example.as_ref()?.replace("$", "\\$"),
+    ))
+}
+
+fn has_element_snippet(document: &Document) -> bool {
+    document
+        .tokens
+        .iter()
+        .any(|token| get_element_snippet(token).is_some())
+}
+
+/// Get the hooks that a document defines or invokes, which are part of the known hooks.
+fn get_known_hook_names(document: &Document) -> Vec<&str> {
+    let mut hook_names: Vec<&str> = document
+        .tokens
+        .iter()
+        .flat_map(|token| match &token.data {
+            TokenData::DrupalHookDefinition(hook) => vec![hook.name.as_str()],
+            TokenData::DrupalHookInvocation(hook_names) => {
+                hook_names.iter().map(String::as_str).collect()
+            }
+            _ => vec![],
+        })
+        .collect();
+    hook_names.sort();
+    hook_names.dedup();
+    hook_names
+}
+
+/// Get the names of the PHP classes that a document defines.
+fn get_class_names(document: &Document) -> Vec<&PhpClassName> {
+    document
+        .tokens
+        .iter()
+        .filter_map(|token| match &token.data {
+            TokenData::PhpClassDefinition(class) => Some(&class.name),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether a theme hook is the suggestion or a more specific suggestion starting with it, f.e
+/// `node__article__teaser` for `node__article`, but not `node__articles`.
+fn is_theme_suggestion_match(theme_name: &str, suggestion: &str) -> bool {
+    match theme_name.strip_prefix(suggestion) {
+        Some(rest) => rest.is_empty() || suggestion.ends_with("__") || rest.starts_with("__"),
+        None => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+    use std::sync::Mutex;
+
     use lsp_types::TextDocumentContentChangeEvent;
 
     use crate::document_store::document::FileType;
-    use crate::document_store::DocumentStore;
+    use crate::document_store::{
+        get_index_overrides, index_documents_with_progress, is_indexed_path, DocumentStore,
+        IndexProgress,
+    };
 
     #[test]
     fn add_document_to_store() {
@@ -310,4 +1421,350 @@ mod tests {
             store.get_document(&test_uri).unwrap().content
         );
     }
+
+    #[test]
+    fn remove_document_from_store() {
+        let mut store = DocumentStore::new();
+        let test_uri = String::from("file:///tmp/test.services.yml");
+        store.add_document(&test_uri, String::from("services:\n"));
+        assert!(store.get_document(&test_uri).is_some());
+
+        store.remove_document(&test_uri);
+        assert!(store.get_document(&test_uri).is_none());
+    }
+
+    #[test]
+    fn normalized_document_uris() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///c%3A/www/my%20site/test.php",
+            String::from("<?php\n"),
+        );
+        assert!(store
+            .get_document("file:///C:/www/my site/test.php")
+            .is_some());
+
+        store.remove_document("file:///C:/www/my%20site/test.php");
+        assert!(store.get_documents().is_empty());
+    }
+
+    #[test]
+    fn indexed_paths() {
+        let overrides = get_index_overrides("/var/www");
+        for (path, expected) in [
+            ("/var/www/web/modules/custom/foo/foo.services.yml", true),
+            ("/var/www/web/modules/custom/foo/src/Form/FooForm.php", true),
+            (
+                "/var/www/web/modules/custom/foo/src/FooInterface.php",
+                false,
+            ),
+            (
+                "/var/www/web/modules/custom/foo/tests/src/Unit/FooTest.php",
+                true,
+            ),
+            (
+                "/var/www/web/modules/contrib/bar/tests/src/Unit/BarTest.php",
+                false,
+            ),
+            ("/var/www/web/modules/custom/foo/foo.install", true),
+            ("/var/www/web/modules/custom/foo/foo.views.inc", true),
+            ("/var/www/web/core/includes/theme.inc", true),
+            ("/var/www/web/modules/custom/foo/README.md", false),
+            ("/var/www/vendor/foo/modules/bar/src/Bar.php", false),
+        ] {
+            assert_eq!(
+                expected,
+                is_indexed_path(&overrides, Path::new(path)),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn index_progress() {
+        let directory =
+            std::env::temp_dir().join(format!("drupal_ls_index_progress_{}", std::process::id()));
+        let module_directory = directory.join("web/modules/custom/foo");
+        std::fs::create_dir_all(&module_directory).unwrap();
+        std::fs::write(module_directory.join("foo.info.yml"), "name: Foo\n").unwrap();
+        std::fs::write(module_directory.join("README.md"), "# Foo\n").unwrap();
+        // Files that aren't valid UTF-8 can't be read.
+        std::fs::write(module_directory.join("foo.services.yml"), [0xff, 0xfe]).unwrap();
+
+        let reported = Mutex::new(vec![]);
+        let (documents, progress) =
+            index_documents_with_progress(directory.to_str().unwrap(), |progress| {
+                reported.lock().unwrap().push(progress.files_parsed)
+            });
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(1, documents.len());
+        assert_eq!(
+            IndexProgress {
+                files_parsed: 2,
+                files_total: 2,
+                failures: 1,
+            },
+            progress
+        );
+        let mut reported = reported.into_inner().unwrap();
+        reported.sort();
+        assert_eq!(vec![1, 2], reported);
+    }
+
+    #[test]
+    fn element_snippets() {
+        let mut store = DocumentStore::new();
+        let test_uri = String::from("file:///core/lib/Drupal/Core/Render/Element/Link.php");
+        let element_source = |example: &str| {
+            format!(
+                "<?php\n\nnamespace Drupal\\Core\\Render\\Element;\n\n/**\n * Provides a link render element.\n *\n * @code\n * {}\n * @endcode\n */\n#[RenderElement('link')]\nclass Link extends RenderElementBase {{\n}}\n",
+                example
+            )
+        };
+
+        store.add_document(
+            &test_uri,
+            element_source("$build['link'] = ['#type' => 'link'];"),
+        );
+        assert_eq!(
+            vec![(
+                "render-link".to_string(),
+                "\\$build['link'] = ['#type' => 'link'];".to_string()
+            )],
+            store.get_element_snippets()
+        );
+
+        store.change_document(
+            &test_uri,
+            vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: element_source("$link = ['#type' => 'link'];"),
+            }],
+        );
+        assert_eq!(
+            vec![(
+                "render-link".to_string(),
+                "\\$link = ['#type' => 'link'];".to_string()
+            )],
+            store.get_element_snippets()
+        );
+
+        // Replacing the element with a class without usage example removes its snippet.
+        store.add_document(&test_uri, String::from("<?php\n\nclass Link {\n}\n"));
+        assert!(store.get_element_snippets().is_empty());
+
+        store.add_document(&test_uri, element_source("$link = [];"));
+        assert_eq!(1, store.get_element_snippets().len());
+        store.remove_document(&test_uri);
+        assert!(store.get_element_snippets().is_empty());
+    }
+
+    #[test]
+    fn hook_implementations() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/modules/system/system.api.php",
+            String::from(
+                "<?php\n\nfunction hook_cron() {\n}\n\nfunction hook_ENTITY_TYPE_load($entities) {\n}\n",
+            ),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/foo.module",
+            String::from(
+                "<?php\n\nfunction foo_cron() {\n}\n\n/**\n * Implements hook_ENTITY_TYPE_load().\n */\nfunction foo_node_load($nodes) {\n}\n\nfunction foo_item_load($id) {\n}\n",
+            ),
+        );
+
+        let implementations = |hook_name: &str| {
+            store
+                .get_hook_implementations(hook_name)
+                .into_iter()
+                .map(|(document, token)| {
+                    document.content[token.range.start_byte..token.range.end_byte].to_string()
+                })
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(vec!["foo_cron"], implementations("hook_cron"));
+        // Helpers matching a hook with placeholders need an "Implements" docblock.
+        assert_eq!(
+            vec!["foo_node_load"],
+            implementations("hook_ENTITY_TYPE_load")
+        );
+    }
+
+    #[test]
+    fn known_hooks() {
+        let mut store = DocumentStore::new();
+        let test_uri = "file:///var/www/web/modules/custom/foo/foo.module";
+        let source = |statement: &str| {
+            format!(
+                "<?php\n\nfunction foo_cron() {{\n  {}\n  \\Drupal::moduleHandler()->invokeAll('foo_info');\n}}\n",
+                statement
+            )
+        };
+        let change = |store: &mut DocumentStore, text: String| {
+            store.change_document(
+                test_uri,
+                vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text,
+                }],
+            )
+        };
+
+        store.add_document(test_uri, source(""));
+        assert!(store.get_known_hooks().contains("hook_foo_info"));
+
+        // Editing a document that invokes hooks keeps the cache when the hooks stay the same.
+        change(&mut store, source("$count = 1;"));
+        assert!(store.known_hooks.get().is_some());
+        store.add_document(test_uri, source("$count = 2;"));
+        assert!(store.known_hooks.get().is_some());
+
+        change(
+            &mut store,
+            source("\\Drupal::moduleHandler()->alter('foo_data', $data);"),
+        );
+        assert!(store.known_hooks.get().is_none());
+        assert!(store.get_known_hooks().contains("hook_foo_data_alter"));
+
+        store.remove_document(test_uri);
+        assert!(!store.get_known_hooks().contains("hook_foo_info"));
+    }
+
+    #[test]
+    fn class_names() {
+        let mut store = DocumentStore::new();
+        let test_uri = "file:///var/www/web/modules/custom/foo/src/Foo.php";
+        let source = |class: &str, body: &str| {
+            format!(
+                "<?php\n\nnamespace Drupal\\foo;\n\nclass {} {{\n  {}\n}}\n",
+                class, body
+            )
+        };
+        let change = |store: &mut DocumentStore, text: String| {
+            store.change_document(
+                test_uri,
+                vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text,
+                }],
+            )
+        };
+        let class_names = |store: &DocumentStore, short_name: &str| {
+            store
+                .get_class_names_by_short_name()
+                .get(short_name)
+                .map(|class_names| {
+                    class_names
+                        .iter()
+                        .map(|class_name| class_name.to_string())
+                        .collect::<Vec<String>>()
+                })
+        };
+
+        store.add_document(test_uri, source("Foo", ""));
+        assert_eq!(
+            Some(vec!["Drupal\\foo\\Foo".to_string()]),
+            class_names(&store, "Foo")
+        );
+
+        // Editing the body of a class keeps the cache.
+        change(&mut store, source("Foo", "protected $bar;"));
+        assert!(store.class_names.get().is_some());
+
+        change(&mut store, source("Bar", ""));
+        assert!(store.class_names.get().is_none());
+        assert_eq!(None, class_names(&store, "Foo"));
+        assert!(class_names(&store, "Bar").is_some());
+
+        store.remove_document(test_uri);
+        assert_eq!(None, class_names(&store, "Bar"));
+    }
+
+    #[test]
+    fn theme_suggestion_templates() {
+        let mut store = DocumentStore::new();
+        for uri in [
+            "file:///var/www/web/core/modules/node/templates/node.html.twig",
+            "file:///var/www/web/themes/custom/foo/templates/node--article.html.twig",
+            "file:///var/www/web/themes/custom/foo/templates/node--article--teaser.html.twig",
+            "file:///var/www/web/themes/custom/foo/templates/node--page.html.twig",
+            "file:///var/www/web/themes/custom/foo/templates/node--articles.html.twig",
+            "file:///var/www/web/themes/custom/foo/templates/node-extra.html.twig",
+        ] {
+            store.add_document(uri, String::new());
+        }
+
+        let templates = |suggestion: &str| {
+            store
+                .get_theme_suggestion_templates(suggestion)
+                .into_iter()
+                .map(|document| document.uri.rsplit('/').next().unwrap().to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            vec!["node--article--teaser.html.twig", "node--article.html.twig"],
+            templates("node__article")
+        );
+        assert_eq!(4, templates("node__").len());
+        // Theme hooks that only share a prefix with the suggestion aren't matched.
+        assert!(!templates("node").contains(&"node-extra.html.twig".to_string()));
+        // Suggestions without a template fall back to the template of the base hook.
+        assert_eq!(vec!["node.html.twig"], templates("node__event"));
+    }
+
+    #[test]
+    fn template_definition() {
+        let mut store = DocumentStore::new();
+        for uri in [
+            "file:///var/www/web/core/modules/node/templates/node.html.twig",
+            "file:///var/www/web/modules/contrib/bar/templates/node.html.twig",
+            "file:///var/www/web/themes/custom/foo/templates/node.html.twig",
+            "file:///var/www/web/core/modules/system/templates/page.html.twig",
+            "file:///var/www/web/core/themes/olivero/templates/page.html.twig",
+        ] {
+            store.add_document(uri, String::new());
+        }
+
+        let template = |name: &str| store.get_template_definition(name).unwrap().uri.as_str();
+        assert_eq!(
+            "file:///var/www/web/themes/custom/foo/templates/node.html.twig",
+            template("node.html.twig")
+        );
+        assert_eq!(
+            "file:///var/www/web/modules/contrib/bar/templates/node.html.twig",
+            template("@bar/node.html.twig")
+        );
+        // Templates of core are sorted by their uri.
+        assert_eq!(
+            "file:///var/www/web/core/modules/system/templates/page.html.twig",
+            template("page.html.twig")
+        );
+    }
+
+    #[test]
+    fn config_overrides() {
+        let mut store = DocumentStore::new();
+        let source = "<?php\n$config['system.site']['page']['front'] = '/node';\n";
+        store.add_document(
+            "file:///var/www/web/sites/default/settings.local.php",
+            source.to_string(),
+        );
+        // Only settings files override config.
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/foo.module",
+            source.to_string(),
+        );
+
+        let overrides = store.get_config_overrides("system.site");
+        assert_eq!(1, overrides.len());
+        assert!(overrides[0].0.uri.ends_with("settings.local.php"));
+        assert_eq!(Some("page.front"), overrides[0].1.key.as_deref());
+        assert!(store.get_config_overrides("system.performance").is_empty());
+    }
 }
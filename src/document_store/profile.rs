@@ -0,0 +1,223 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use tree_sitter::Range;
+
+use crate::parser::tokens::{PhpClassName, Token};
+
+use super::document::{Document, FileType};
+use super::{get_document_paths, get_index_overrides, parse_document};
+
+/// The number of path components, relative to the project root, that files are grouped by, f.e
+/// `web/modules/contrib`.
+const DIRECTORY_DEPTH: usize = 3;
+
+/// The statistics of parsing a single file.
+struct FileProfile {
+    path: PathBuf,
+    file_type: FileType,
+    duration: Duration,
+    tokens: usize,
+    memory: usize,
+}
+
+#[derive(Default)]
+struct GroupProfile {
+    files: usize,
+    duration: Duration,
+    tokens: usize,
+    memory: usize,
+}
+
+impl GroupProfile {
+    fn add(&mut self, file: &FileProfile) {
+        self.files += 1;
+        self.duration += file.duration;
+        self.tokens += file.tokens;
+        self.memory += file.memory;
+    }
+}
+
+/// Index a project the same way the server does on startup, and report how long parsing took, how
+/// many tokens were found and how much memory they take per directory and file type.
+///
+/// This helps tuning which files are indexed and tracking the performance on real projects.
+pub fn profile_index(root_dir: &str) -> String {
+    let now = Instant::now();
+    let files: Vec<FileProfile> = get_document_paths(root_dir, get_index_overrides(root_dir))
+        .into_par_iter()
+        .filter_map(|path| {
+            let now = Instant::now();
+            let (_, document) = parse_document(&path)?;
+            Some(FileProfile {
+                duration: now.elapsed(),
+                file_type: document.file_type,
+                tokens: document.tokens.len(),
+                memory: get_memory_estimate(&document),
+                path,
+            })
+        })
+        .collect();
+
+    get_report(Path::new(root_dir), &files, now.elapsed())
+}
+
+/// Estimate the memory a document takes, i.e. its content and the tokens without the strings
+/// they own.
+fn get_memory_estimate(document: &Document) -> usize {
+    document.content.len()
+        + document.tokens.len() * size_of::<Token>()
+        + document.class_references.len() * size_of::<(PhpClassName, Range)>()
+}
+
+fn get_report(root_dir: &Path, files: &[FileProfile], elapsed: Duration) -> String {
+    let mut total = GroupProfile::default();
+    let mut file_types: HashMap<String, GroupProfile> = HashMap::new();
+    let mut directories: HashMap<String, GroupProfile> = HashMap::new();
+    for file in files {
+        total.add(file);
+        file_types
+            .entry(format!("{:?}", file.file_type))
+            .or_default()
+            .add(file);
+
+        let relative_path = file.path.strip_prefix(root_dir).unwrap_or(&file.path);
+        let directory = relative_path
+            .parent()
+            .map(|parent| {
+                parent
+                    .components()
+                    .take(DIRECTORY_DEPTH)
+                    .map(|component| component.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .filter(|directory| !directory.is_empty())
+            .unwrap_or(".".to_string());
+        directories.entry(directory).or_default().add(file);
+    }
+
+    let mut slowest_files: Vec<&FileProfile> = files.iter().collect();
+    slowest_files.sort_by_key(|file| Reverse(file.duration));
+
+    let mut report = format!(
+        "Parsed {} files in {:.2} seconds ({:.2} seconds of parsing), {} tokens, ~{}\n",
+        total.files,
+        elapsed.as_secs_f64(),
+        total.duration.as_secs_f64(),
+        total.tokens,
+        format_bytes(total.memory)
+    );
+    report.push_str(&format_groups("File type", file_types));
+    report.push_str(&format_groups("Directory", directories));
+
+    report.push_str("\nSlowest files\n");
+    for file in slowest_files.iter().take(10) {
+        report.push_str(&format!(
+            "{:>10.2}ms  {:>8}  {}\n",
+            file.duration.as_secs_f64() * 1000.0,
+            file.tokens,
+            file.path
+                .strip_prefix(root_dir)
+                .unwrap_or(&file.path)
+                .display()
+        ));
+    }
+    report
+}
+
+/// Format the groups as a table, sorted by the time spent parsing them.
+fn format_groups(title: &str, groups: HashMap<String, GroupProfile>) -> String {
+    let mut groups: Vec<(String, GroupProfile)> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, group)| Reverse(group.duration));
+
+    let mut table = format!(
+        "\n{:<40}  {:>7}  {:>10}  {:>8}  {:>10}\n",
+        title, "Files", "Time", "Tokens", "Memory"
+    );
+    for (name, group) in groups {
+        table.push_str(&format!(
+            "{:<40}  {:>7}  {:>8.2}ms  {:>8}  {:>10}\n",
+            name,
+            group.files,
+            group.duration.as_secs_f64() * 1000.0,
+            group.tokens,
+            format_bytes(group.memory)
+        ));
+    }
+    table
+}
+
+fn format_bytes(bytes: usize) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1048576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1048576.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report() {
+        let file = |path: &str, file_type, milliseconds, tokens| FileProfile {
+            path: PathBuf::from(path),
+            file_type,
+            duration: Duration::from_millis(milliseconds),
+            tokens,
+            memory: tokens * 100,
+        };
+        let files = vec![
+            file(
+                "/var/www/web/core/modules/node/node.routing.yml",
+                FileType::Yaml,
+                3,
+                20,
+            ),
+            file(
+                "/var/www/web/core/modules/node/src/Entity/Node.php",
+                FileType::Php,
+                10,
+                5,
+            ),
+            file(
+                "/var/www/web/modules/custom/foo/foo.module",
+                FileType::Php,
+                2,
+                3,
+            ),
+            file("/var/www/composer.php", FileType::Php, 1, 0),
+        ];
+        let report = get_report(Path::new("/var/www"), &files, Duration::from_millis(5));
+
+        assert!(report.starts_with(
+            "Parsed 4 files in 0.01 seconds (0.02 seconds of parsing), 28 tokens, ~2.7 KB\n"
+        ));
+        let rows = report
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+            .collect::<Vec<_>>();
+        for expected in [
+            vec!["Php", "3", "13.00ms", "8", "800", "B"],
+            vec!["web/core/modules", "2", "13.00ms", "25", "2.4", "KB"],
+            vec!["web/modules/custom", "1", "2.00ms", "3", "300", "B"],
+            vec![".", "1", "1.00ms", "0", "0", "B"],
+            vec!["10.00ms", "5", "web/core/modules/node/src/Entity/Node.php"],
+        ] {
+            assert!(rows.contains(&expected), "{:?} in {}", expected, report);
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!("512 B", format_bytes(512));
+        assert_eq!("1.5 KB", format_bytes(1536));
+        assert_eq!("2.0 MB", format_bytes(2 * 1048576));
+    }
+}
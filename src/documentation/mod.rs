@@ -1,112 +1,330 @@
-use crate::document_store::DOCUMENT_STORE;
+mod template;
+
+use crate::document_store::{DocumentStore, DOCUMENT_STORE};
+use crate::integrations::drush::get_drush_data;
 use crate::parser::tokens::*;
 
-const CLASS_REFERENCE: &str = r#"
-# Class reference
+use self::template::Template;
+
+const ROLE_REFERENCE: Template = Template {
+    name: "role_reference",
+    default: include_str!("templates/role_reference.md"),
+};
+
+const FIELD_REFERENCE: Template = Template {
+    name: "field_reference",
+    default: include_str!("templates/field_reference.md"),
+};
+
+const ENTITY_FIELD_REFERENCE: Template = Template {
+    name: "entity_field_reference",
+    default: include_str!("templates/entity_field_reference.md"),
+};
+
+const CONFIG_REFERENCE: Template = Template {
+    name: "config_reference",
+    default: include_str!("templates/config_reference.md"),
+};
+
+const CONFIG_KEY_REFERENCE: Template = Template {
+    name: "config_key_reference",
+    default: include_str!("templates/config_key_reference.md"),
+};
+
+const MODULE_REFERENCE: Template = Template {
+    name: "module_reference",
+    default: include_str!("templates/module_reference.md"),
+};
+
+const TRANSLATION_STRING: Template = Template {
+    name: "translation_string",
+    default: include_str!("templates/translation_string.md"),
+};
 
-@see [@class_name](@class_name)
-"#;
+const TRANSLATION_DEFINITION: Template = Template {
+    name: "translation_definition",
+    default: include_str!("templates/translation_definition.md"),
+};
 
-const SERVICE_REFERENCE: &str = r#"
-# Service reference: @name
+const ENTITY_TYPE_DEFINITION: Template = Template {
+    name: "entity_type_definition",
+    default: include_str!("templates/entity_type_definition.md"),
+};
 
-*Implementation:*
-```yaml
-@definition
-```
+const PLUGIN_DEFINITION: Template = Template {
+    name: "plugin_definition",
+    default: include_str!("templates/plugin_definition.md"),
+};
 
-@see [@uri](@uri)
-"#;
+const CLASS_REFERENCE: Template = Template {
+    name: "class_reference",
+    default: include_str!("templates/class_reference.md"),
+};
 
-const SERVICE_DEFINITION: &str = r#"
-# Service: @name
+const SERVICE_REFERENCE: Template = Template {
+    name: "service_reference",
+    default: include_str!("templates/service_reference.md"),
+};
 
-*Class:* @class
-"#;
+const SERVICE_DEFINITION: Template = Template {
+    name: "service_definition",
+    default: include_str!("templates/service_definition.md"),
+};
 
-const ROUTE_REFERENCE: &str = r#"
-# Route reference: @name
+const ROUTE_REFERENCE: Template = Template {
+    name: "route_reference",
+    default: include_str!("templates/route_reference.md"),
+};
 
-*Implementation:*
-```yaml
-@definition
-```
+const ROUTE_DEFINITION: Template = Template {
+    name: "route_definition",
+    default: include_str!("templates/route_definition.md"),
+};
 
-@see [@uri](@uri)
-"#;
+const HOOK_REFERENCE: Template = Template {
+    name: "hook_reference",
+    default: include_str!("templates/hook_reference.md"),
+};
 
-const ROUTE_DEFINITION: &str = r#"
-# Route: @name
+const METHOD_REFERENCE: Template = Template {
+    name: "method_reference",
+    default: include_str!("templates/method_reference.md"),
+};
 
-*Path:* @path
-"#;
+const HOOK_DEFINITION: Template = Template {
+    name: "hook_definition",
+    default: include_str!("templates/hook_definition.md"),
+};
 
-const HOOK_REFERENCE: &str = r#"
-# Hook reference: @name
+const PERMISSION_REFERENCE: Template = Template {
+    name: "permission_reference",
+    default: include_str!("templates/permission_reference.md"),
+};
 
-*Implementation:*
-```yaml
-@definition
-```
+const PERMISSION_DEFINITION: Template = Template {
+    name: "permission_definition",
+    default: include_str!("templates/permission_definition.md"),
+};
 
-@see [@uri](@uri)
-"#;
+const MENU_LINK_REFERENCE: Template = Template {
+    name: "menu_link_reference",
+    default: include_str!("templates/menu_link_reference.md"),
+};
 
-const HOOK_DEFINITION: &str = r#"
-# Hook: @name
+const THEME_REFERENCE: Template = Template {
+    name: "theme_reference",
+    default: include_str!("templates/theme_reference.md"),
+};
 
-```php
-<?php function @name(@parameters) {}
-```
-"#;
+const THEME_DEFINITION: Template = Template {
+    name: "theme_definition",
+    default: include_str!("templates/theme_definition.md"),
+};
 
-const PERMISSION_REFERENCE: &str = r#"
-# Permission reference: @name
+const LIBRARY_REFERENCE: Template = Template {
+    name: "library_reference",
+    default: include_str!("templates/library_reference.md"),
+};
 
-*Implementation:*
-```yaml
-@definition
-```
+const LIBRARY_DEFINITION: Template = Template {
+    name: "library_definition",
+    default: include_str!("templates/library_definition.md"),
+};
 
-@see [@uri](@uri)
-"#;
+const PLUGIN_REFERENCE: Template = Template {
+    name: "plugin_reference",
+    default: include_str!("templates/plugin_reference.md"),
+};
 
-const PERMISSION_DEFINITION: &str = r#"
-# Permission: @name
-Title: @title
-"#;
+const UNRESOLVED_METHOD_REFERENCE: Template = Template {
+    name: "unresolved_method_reference",
+    default: include_str!("templates/unresolved_method_reference.md"),
+};
+
+const SERVICE_CLASS_LINK: Template = Template {
+    name: "service_class_link",
+    default: include_str!("templates/service_class_link.md"),
+};
+
+const MENU_LINK_DEFINITION: Template = Template {
+    name: "menu_link_definition",
+    default: include_str!("templates/menu_link_definition.md"),
+};
+
+const OVERRIDDEN_CONFIG_REFERENCE: Template = Template {
+    name: "overridden_config_reference",
+    default: include_str!("templates/overridden_config_reference.md"),
+};
+
+const OVERRIDDEN_CONFIG_KEY_REFERENCE: Template = Template {
+    name: "overridden_config_key_reference",
+    default: include_str!("templates/overridden_config_key_reference.md"),
+};
+
+const CONFIG_OVERRIDES: Template = Template {
+    name: "config_overrides",
+    default: include_str!("templates/config_overrides.md"),
+};
+
+const EXTENSION_REFERENCE: Template = Template {
+    name: "extension_reference",
+    default: include_str!("templates/extension_reference.md"),
+};
 
 pub fn get_documentation_for_token(token: &Token) -> Option<String> {
     match &token.data {
         TokenData::PhpClassReference(class) => {
-            Some(CLASS_REFERENCE.replace("@class_name", &class.to_string()))
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let (source_document, token) = store.get_class_definition(class)?;
+            let TokenData::PhpClassDefinition(class_definition) = &token.data else {
+                return None;
+            };
+            let interfaces = class_definition
+                .interfaces
+                .iter()
+                .map(|interface| format!("`{}`", interface))
+                .collect::<Vec<String>>();
+            Some(
+                CLASS_REFERENCE.render(&[
+                    ("short_name", class.get_short_name()),
+                    (
+                        "summary",
+                        &class_definition
+                            .summary
+                            .as_ref()
+                            .map(|summary| format!("\n{}\n", summary))
+                            .unwrap_or_default(),
+                    ),
+                    ("namespace", class.get_namespace()),
+                    (
+                        "interfaces",
+                        &match interfaces.is_empty() {
+                            true => "None".to_string(),
+                            false => interfaces.join(", "),
+                        },
+                    ),
+                    ("uri", source_document.get_uri()?.as_str()),
+                ]),
+            )
+        }
+        TokenData::PhpClassDefinition(PhpClass {
+            name,
+            attribute: Some(ClassAttribute::EntityType(entity_type)),
+            ..
+        }) => Some(ENTITY_TYPE_DEFINITION.render(&[
+            ("group", &entity_type.group.to_string()),
+            ("id", &entity_type.id),
+            ("label", entity_type.label.as_deref().unwrap_or_default()),
+            ("class", &name.to_string()),
+            (
+                "bundle_entity_type",
+                entity_type.bundle_entity_type.as_deref().unwrap_or("None"),
+            ),
+        ])),
+        TokenData::PhpClassDefinition(PhpClass {
+            name,
+            attribute: Some(ClassAttribute::Plugin(plugin)),
+            ..
+        }) => Some(
+            PLUGIN_DEFINITION.render(&[
+                ("type", &plugin.plugin_type.to_string()),
+                ("id", &plugin.plugin_id),
+                ("label", plugin.label.as_deref().unwrap_or_default()),
+                ("class", &name.to_string()),
+                (
+                    "usage_example",
+                    &plugin
+                        .usage_example
+                        .as_ref()
+                        .map(|usage_example| {
+                            format!("\n*Usage:*\n```php\n{}\n```\n", usage_example)
+                        })
+                        .unwrap_or_default(),
+                ),
+            ]),
+        ),
+        TokenData::DrupalPluginReference(plugin_reference) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let (source_document, token) = store.get_plugin_definition(plugin_reference)?;
+            let uri = source_document.get_uri()?;
+            Some(PLUGIN_REFERENCE.render(&[
+                ("documentation", &get_documentation_for_token(token)?),
+                ("uri", uri.as_str()),
+            ]))
+        }
+        TokenData::PhpMethodReference(method) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let definition = store.get_method_definition(method).and_then(
+                |(source_document, token)| match &token.data {
+                    TokenData::PhpMethodDefinition(definition) => {
+                        Some((source_document, definition))
+                    }
+                    _ => None,
+                },
+            );
+            let Some((source_document, definition)) = definition else {
+                return Some(UNRESOLVED_METHOD_REFERENCE.render(&[
+                    ("class", &method.class_name.as_ref()?.to_string()),
+                    ("name", &method.name),
+                ]));
+            };
+            Some(
+                METHOD_REFERENCE.render(&[
+                    (
+                        "class",
+                        definition
+                            .class_name
+                            .as_ref()
+                            .map_or("", |class_name| class_name.get_short_name()),
+                    ),
+                    ("name", &definition.name),
+                    (
+                        "signature",
+                        definition.signature.as_deref().unwrap_or_default(),
+                    ),
+                    (
+                        "summary",
+                        &definition
+                            .summary
+                            .as_ref()
+                            .map(|summary| format!("\n{}\n", summary))
+                            .unwrap_or_default(),
+                    ),
+                    ("uri", source_document.get_uri()?.as_str()),
+                ]),
+            )
         }
-        TokenData::PhpMethodReference(method) => Some(format!(
-            "PHP Method reference\nclass: {}\nmethod: {}",
-            method.class_name.clone()?, method.name
-        )),
         TokenData::DrupalRouteReference(route_name) => {
             let store = DOCUMENT_STORE.lock().unwrap();
 
             let (source_document, token) = store.get_route_definition(route_name)?;
             if let TokenData::DrupalRouteDefinition(route) = &token.data {
-                let definition =
-                    &source_document.content[token.range.start_byte..token.range.end_byte];
+                let target_class = route
+                    ._defaults
+                    ._controller
+                    .as_ref()
+                    .map_or(route._defaults._form.clone(), |controller| {
+                        controller.class_name.clone()
+                    });
+                let target_uri = target_class
+                    .and_then(|class_name| store.get_class_definition(&class_name))
+                    .and_then(|(class_document, _)| class_document.get_uri());
 
-                return Some(
-                    ROUTE_REFERENCE
-                        .replace("@name", route.name.as_str())
-                        .replace("@uri", source_document.get_uri()?.as_str())
-                        .replace("@definition", definition),
-                );
+                return Some(ROUTE_REFERENCE.render(&[
+                    ("name", route.name.as_str()),
+                    (
+                        "details",
+                        &get_route_details(route, target_uri.as_ref().map(|uri| uri.as_str())),
+                    ),
+                    ("uri", source_document.get_uri()?.as_str()),
+                ]));
             }
             None
         }
-        TokenData::DrupalRouteDefinition(route) => Some(
-            ROUTE_DEFINITION
-                .replace("@name", &route.name)
-                .replace("@path", &route.path),
-        ),
+        TokenData::DrupalRouteDefinition(route) => Some(ROUTE_DEFINITION.render(&[
+            ("name", &route.name),
+            ("details", &get_route_details(route, None)),
+        ])),
         TokenData::DrupalServiceReference(service_name) => {
             let store = DOCUMENT_STORE.lock().unwrap();
 
@@ -115,43 +333,54 @@ pub fn get_documentation_for_token(token: &Token) -> Option<String> {
                 let definition =
                     &source_document.content[token.range.start_byte..token.range.end_byte];
 
-                return Some(
-                    SERVICE_REFERENCE
-                        .replace("@name", service.name.as_str())
-                        .replace("@uri", source_document.get_uri()?.as_str())
-                        .replace("@definition", definition),
-                );
+                let mut documentation = SERVICE_REFERENCE.render(&[
+                    ("name", service.name.as_str()),
+                    ("details", &get_service_details(service)),
+                    ("uri", source_document.get_uri()?.as_str()),
+                    ("definition", definition),
+                ]);
+                if let Some(class_uri) = service
+                    .class
+                    .as_ref()
+                    .and_then(|class_name| store.get_class_definition(class_name))
+                    .and_then(|(class_document, _)| class_document.get_uri())
+                {
+                    documentation
+                        .push_str(&SERVICE_CLASS_LINK.render(&[("uri", class_uri.as_str())]));
+                }
+                return Some(documentation);
             }
             None
         }
-        TokenData::DrupalServiceDefinition(service) => Some(
-            SERVICE_DEFINITION
-                .replace("@name", &service.name)
-                .replace("@class", &service.class.to_string()),
-        ),
+        TokenData::DrupalServiceDefinition(service) => Some(SERVICE_DEFINITION.render(&[
+            ("name", &service.name),
+            ("details", &get_service_details(service)),
+        ])),
         TokenData::DrupalHookReference(hook_name) => {
             let store = DOCUMENT_STORE.lock().unwrap();
 
             let (source_document, token) = store.get_hook_definition(hook_name)?;
             if let TokenData::DrupalHookDefinition(hook) = &token.data {
-                let definition =
-                    &source_document.content[token.range.start_byte..token.range.end_byte];
-
-                return Some(
-                    HOOK_REFERENCE
-                        .replace("@name", hook.name.as_str())
-                        .replace("@uri", source_document.get_uri()?.as_str())
-                        .replace("@definition", definition),
-                );
+                return Some(HOOK_REFERENCE.render(&[
+                    ("name", hook.name.as_str()),
+                    ("parameters", hook.parameters.as_deref().unwrap_or_default()),
+                    (
+                        "documentation",
+                        hook.documentation.as_deref().unwrap_or_default(),
+                    ),
+                    ("uri", source_document.get_uri()?.as_str()),
+                ]));
             }
             None
         }
-        TokenData::DrupalHookDefinition(hook) => {
-            Some(HOOK_DEFINITION.replace("@name", &hook.name).replace(
-                "@parameters",
-                &hook.parameters.clone().unwrap_or(String::default()),
-            ))
-        }
+        TokenData::DrupalHookDefinition(hook) => Some(HOOK_DEFINITION.render(&[
+            ("name", &hook.name),
+            ("parameters", hook.parameters.as_deref().unwrap_or_default()),
+            (
+                "documentation",
+                hook.documentation.as_deref().unwrap_or_default(),
+            ),
+        ])),
         TokenData::DrupalPermissionReference(permission_name) => {
             let store = DOCUMENT_STORE.lock().unwrap();
 
@@ -160,20 +389,527 @@ pub fn get_documentation_for_token(token: &Token) -> Option<String> {
                 let definition =
                     &source_document.content[token.range.start_byte..token.range.end_byte];
 
-                return Some(
-                    PERMISSION_REFERENCE
-                        .replace("@name", &permission.name)
-                        .replace("@uri", source_document.get_uri()?.as_str())
-                        .replace("@definition", definition),
-                );
+                return Some(PERMISSION_REFERENCE.render(&[
+                    ("name", &permission.name),
+                    ("warning", &get_permission_warning(permission)),
+                    ("uri", source_document.get_uri()?.as_str()),
+                    ("definition", definition),
+                ]));
             }
             None
         }
         TokenData::DrupalPermissionDefinition(permission) => Some(
-            PERMISSION_DEFINITION
-                .replace("@name", &permission.name)
-                .replace("@title", &permission.title),
+            PERMISSION_DEFINITION.render(&[
+                ("name", &permission.name),
+                ("warning", &get_permission_warning(permission)),
+                ("title", &permission.title),
+                (
+                    "description",
+                    &permission
+                        .description
+                        .as_ref()
+                        .map(|description| format!("\n{}\n", description))
+                        .unwrap_or_default(),
+                ),
+            ]),
         ),
+        TokenData::DrupalMenuLinkReference(menu_link_id) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+
+            let (source_document, token) = store.get_menu_link_definition(menu_link_id)?;
+            let definition = &source_document.content[token.range.start_byte..token.range.end_byte];
+            Some(MENU_LINK_REFERENCE.render(&[
+                ("id", menu_link_id),
+                ("uri", source_document.get_uri()?.as_str()),
+                ("definition", definition),
+            ]))
+        }
+        TokenData::DrupalMenuLinkDefinition(menu_link) => {
+            let mut details = vec![];
+            if let Some(title) = &menu_link.title {
+                details.push(format!("*Title:* {}", title));
+            }
+            if let Some(route_name) = &menu_link.route_name {
+                details.push(format!("*Route:* `{}`", route_name));
+            }
+            if let Some(parent) = &menu_link.parent {
+                details.push(format!("*Parent:* `{}`", parent));
+            }
+            Some(MENU_LINK_DEFINITION.render(&[
+                ("id", &menu_link.id),
+                ("details", &get_paragraphs(&details)),
+                ("title", menu_link.title.as_deref().unwrap_or_default()),
+                (
+                    "route_name",
+                    menu_link.route_name.as_deref().unwrap_or_default(),
+                ),
+                ("parent", menu_link.parent.as_deref().unwrap_or_default()),
+            ]))
+        }
+        TokenData::DrupalThemeReference(theme_name) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+
+            let (source_document, token) = store.get_theme_definition(theme_name)?;
+            if let TokenData::DrupalThemeDefinition(theme) = &token.data {
+                let definition =
+                    &source_document.content[token.range.start_byte..token.range.end_byte];
+
+                return Some(THEME_REFERENCE.render(&[
+                    ("name", &theme.name),
+                    ("uri", source_document.get_uri()?.as_str()),
+                    ("definition", definition),
+                ]));
+            }
+            None
+        }
+        TokenData::DrupalThemeDefinition(theme) => Some(THEME_DEFINITION.render(&[
+            ("name", &theme.name),
+            ("template", &theme.get_template_file_name()),
+            ("variables", &theme.variables.join(", ")),
+        ])),
+        TokenData::DrupalCacheContextReference(context) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let (_, token) = store.get_service_definition(&get_cache_context_service(context))?;
+            get_documentation_for_token(token)
+        }
+        TokenData::DrupalLibraryReference(library_name) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+
+            let (source_document, token) = store.get_library_definition(library_name)?;
+            if let TokenData::DrupalLibraryDefinition(library) = &token.data {
+                return Some(LIBRARY_REFERENCE.render(&[
+                    ("name", &library.name),
+                    ("details", &get_library_details(library)),
+                    ("uri", source_document.get_uri()?.as_str()),
+                ]));
+            }
+            None
+        }
+        TokenData::DrupalLibraryDefinition(library) => Some(LIBRARY_DEFINITION.render(&[
+            ("name", &library.name),
+            ("details", &get_library_details(library)),
+        ])),
+        TokenData::DrupalTranslationString(translation_string) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+
+            let translations = store.get_translations(&translation_string.string);
+            if translations.is_empty() {
+                return None;
+            }
+            Some(
+                TRANSLATION_STRING.render(&[
+                    ("string", &translation_string.string),
+                    (
+                        "translations",
+                        &translations
+                            .iter()
+                            .map(|(_, _, translation)| {
+                                format!(
+                                    "| {} | {} |",
+                                    translation.langcode,
+                                    translation.translation.replace('|', "\\|")
+                                )
+                            })
+                            .collect::<Vec<String>>()
+                            .join("\n"),
+                    ),
+                ]),
+            )
+        }
+        TokenData::DrupalTranslationDefinition(translation) => {
+            Some(TRANSLATION_DEFINITION.render(&[
+                ("langcode", &translation.langcode),
+                ("string", &translation.string),
+                ("translation", &translation.translation),
+            ]))
+        }
+        TokenData::DrupalConfigKeyReference(reference) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let documentation = store
+                .get_config_schema(&reference.config_name)
+                .and_then(|schema| schema.keys.iter().find(|key| key.key == reference.key))
+                .map(|key| {
+                    CONFIG_KEY_REFERENCE.render(&[
+                        (
+                            "name",
+                            &format!("{}:{}", reference.config_name, reference.key),
+                        ),
+                        ("type", key.type_name.as_deref().unwrap_or("undefined")),
+                        ("label", key.label.as_deref().unwrap_or_default()),
+                    ])
+                });
+            let overrides = get_config_overrides_documentation(
+                &store,
+                &reference.config_name,
+                Some(&reference.key),
+            );
+            join_documentation(
+                documentation,
+                overrides,
+                &OVERRIDDEN_CONFIG_KEY_REFERENCE.render(&[(
+                    "name",
+                    &format!("{}:{}", reference.config_name, reference.key),
+                )]),
+            )
+        }
+        TokenData::DrupalEntityFieldReference(reference) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let Some(entity_type) = store.get_entity_type(&reference.entity_type) else {
+                drop(store);
+                return get_documentation_for_token(&Token::new(
+                    TokenData::DrupalFieldReference(reference.field_name.clone()),
+                    token.range,
+                ));
+            };
+            // Entity queries can refer to a column, f.e `field_tags.target_id`, or to a field of
+            // the referenced entity, f.e `uid.entity.name` or `field_tags.entity:taxonomy_term.name`.
+            let (field_name, column) = match reference.field_name.split_once('.') {
+                Some((field_name, column)) => (field_name, Some(column)),
+                None => (reference.field_name.as_str(), None),
+            };
+            let field_storages = store.get_field_storages(&entity_type.id);
+            let field = entity_type
+                .base_fields
+                .iter()
+                .chain(
+                    field_storages
+                        .iter()
+                        .map(|field_storage| &field_storage.field),
+                )
+                .find(|field| field.name == field_name)?;
+
+            if let Some((entity, target_field_name)) =
+                column.and_then(|column| column.split_once('.'))
+            {
+                let target_type = match entity.strip_prefix("entity:") {
+                    Some(target_type) => Some(target_type.to_string()),
+                    None if entity == "entity" => field
+                        .settings
+                        .iter()
+                        .find(|(name, _)| name == "target_type")
+                        .map(|(_, target_type)| target_type.clone()),
+                    None => None,
+                };
+                if let Some(target_type) = target_type {
+                    drop(store);
+                    return get_documentation_for_token(&Token::new(
+                        TokenData::DrupalEntityFieldReference(DrupalEntityFieldReference {
+                            entity_type: DrupalEntityTypeHint::Id(target_type),
+                            field_name: target_field_name.to_string(),
+                        }),
+                        token.range,
+                    ));
+                }
+            }
+
+            let mut details = vec![];
+            if let Some(column) = column {
+                details.push(format!("*Column:* `{}`", column));
+            }
+            if !field.settings.is_empty() {
+                details.push(format!(
+                    "*Settings:*\n{}",
+                    field
+                        .settings
+                        .iter()
+                        .map(|(name, value)| format!("- `{}`: {}", name, value))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                ));
+            }
+            Some(ENTITY_FIELD_REFERENCE.render(&[
+                ("entity_type", &entity_type.id),
+                ("name", &field.name),
+                ("type", field.field_type.as_deref().unwrap_or("undefined")),
+                ("details", &get_paragraphs(&details)),
+            ]))
+        }
+        TokenData::DrupalRoleReference(role_id) => {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let label = match store.get_role_definition(role_id) {
+                Some((
+                    _,
+                    Token {
+                        data: TokenData::DrupalRoleDefinition(role),
+                        ..
+                    },
+                )) => &role.label,
+                _ => get_drush_data()?.roles.get(role_id)?,
+            };
+            Some(ROLE_REFERENCE.render(&[("name", role_id), ("label", label)]))
+        }
+        // These references can only be resolved with runtime data from drush.
+        TokenData::DrupalFieldReference(field_name) => Some(
+            FIELD_REFERENCE.render(&[
+                ("name", field_name),
+                (
+                    "bundles",
+                    &get_drush_data()?
+                        .get_field_names()
+                        .get(field_name.as_str())?
+                        .join(", "),
+                ),
+            ]),
+        ),
+        TokenData::DrupalConfigReference(config_name) => {
+            let documentation = get_drush_data()
+                .filter(|drush_data| drush_data.config.contains(config_name))
+                .map(|_| CONFIG_REFERENCE.render(&[("name", config_name)]));
+            let overrides = get_config_overrides_documentation(
+                &DOCUMENT_STORE.lock().unwrap(),
+                config_name,
+                None,
+            );
+            join_documentation(
+                documentation,
+                overrides,
+                &OVERRIDDEN_CONFIG_REFERENCE.render(&[("name", config_name)]),
+            )
+        }
+        TokenData::DrupalModuleReference(module_name) => {
+            let is_enabled =
+                get_drush_data().is_some_and(|drush_data| drush_data.modules.contains(module_name));
+            let store = DOCUMENT_STORE.lock().unwrap();
+            match store.get_extension_definition(module_name) {
+                Some((
+                    _,
+                    Token {
+                        data: TokenData::DrupalExtensionDefinition(extension),
+                        ..
+                    },
+                )) => Some(get_extension_documentation(extension, is_enabled)),
+                _ => is_enabled.then(|| MODULE_REFERENCE.render(&[("name", module_name)])),
+            }
+        }
         _ => None,
     }
 }
+
+/// Get a warning for permissions with `restrict access: true`, which should only be granted to
+/// trusted roles.
+fn get_permission_warning(permission: &DrupalPermission) -> String {
+    if !permission.restrict_access {
+        return String::new();
+    }
+    "\n> **Warning:** This permission has security implications, only grant it to trusted \
+     roles.\n"
+        .to_string()
+}
+
+/// Get the overrides of a config object, or of one of its keys, in settings files as Markdown.
+///
+/// Overrides of a parent or child of the key are included, since they override it as well.
+fn get_config_overrides_documentation(
+    store: &DocumentStore,
+    config_name: &str,
+    key: Option<&str>,
+) -> Option<String> {
+    let overrides: Vec<String> = store
+        .get_config_overrides(config_name)
+        .into_iter()
+        .filter(|(_, config_override)| match (key, &config_override.key) {
+            (Some(key), Some(override_key)) => {
+                key == override_key
+                    || key.starts_with(&format!("{}.", override_key))
+                    || override_key.starts_with(&format!("{}.", key))
+            }
+            _ => true,
+        })
+        .map(|(document, config_override)| {
+            let file_name = document.uri.rsplit('/').next().unwrap_or(&document.uri);
+            let value = config_override.value.lines().next().unwrap_or_default();
+            match &config_override.key {
+                Some(override_key) => format!("- `{}`: `{} = {}`", file_name, override_key, value),
+                None => format!("- `{}`: `{}`", file_name, value),
+            }
+        })
+        .collect();
+    if overrides.is_empty() {
+        return None;
+    }
+    Some(CONFIG_OVERRIDES.render(&[("overrides", &overrides.join("\n"))]))
+}
+
+/// Append extra documentation to the documentation of a token, with a heading when only the extra
+/// documentation is found.
+fn join_documentation(
+    documentation: Option<String>,
+    extra: Option<String>,
+    heading: &str,
+) -> Option<String> {
+    match (documentation, extra) {
+        (documentation, Some(extra)) => Some(format!(
+            "{}{}",
+            documentation.as_deref().unwrap_or(heading),
+            extra
+        )),
+        (documentation, None) => documentation,
+    }
+}
+
+/// Get the name, description and version constraints of a module, theme or profile as Markdown.
+fn get_extension_documentation(extension: &DrupalExtension, is_enabled: bool) -> String {
+    let mut details = vec![];
+    if is_enabled {
+        details.push("*Status:* Enabled".to_string());
+    }
+    if let Some(package) = &extension.package {
+        details.push(format!("*Package:* {}", package));
+    }
+    if let Some(version) = &extension.version {
+        details.push(format!("*Version:* {}", version));
+    }
+    if let Some(core_version_requirement) = &extension.core_version_requirement {
+        details.push(format!("*Core:* `{}`", core_version_requirement));
+    }
+
+    EXTENSION_REFERENCE.render(&[
+        (
+            "type",
+            match extension.extension_type.as_str() {
+                "theme" => "Theme",
+                "profile" => "Profile",
+                _ => "Module",
+            },
+        ),
+        ("name", &extension.name),
+        ("label", &extension.label),
+        (
+            "description",
+            &extension
+                .description
+                .as_ref()
+                .map(|description| format!("\n{}\n", description))
+                .unwrap_or_default(),
+        ),
+        ("details", &get_paragraphs(&details)),
+        ("package", extension.package.as_deref().unwrap_or_default()),
+        ("version", extension.version.as_deref().unwrap_or_default()),
+        (
+            "core_version_requirement",
+            extension
+                .core_version_requirement
+                .as_deref()
+                .unwrap_or_default(),
+        ),
+    ])
+}
+
+/// Get details as Markdown paragraphs, each surrounded by blank lines so templates can place them
+/// right after a heading.
+fn get_paragraphs(details: &[String]) -> String {
+    details
+        .iter()
+        .map(|detail| format!("\n{}\n", detail))
+        .collect()
+}
+
+/// Get the CSS files, JavaScript files and dependencies of a library as Markdown.
+fn get_library_details(library: &DrupalLibrary) -> String {
+    let get_list = |items: Vec<&String>| {
+        items
+            .iter()
+            .map(|item| format!("- `{}`", item))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let (css, js): (Vec<&String>, Vec<&String>) = library
+        .assets
+        .iter()
+        .partition(|asset| asset.ends_with(".css"));
+
+    let mut details = vec![];
+    if !css.is_empty() {
+        details.push(format!("*CSS:*\n{}", get_list(css)));
+    }
+    if !js.is_empty() {
+        details.push(format!("*JavaScript:*\n{}", get_list(js)));
+    }
+    if !library.dependencies.is_empty() {
+        details.push(format!(
+            "*Dependencies:*\n{}",
+            get_list(library.dependencies.iter().collect())
+        ));
+    }
+    get_paragraphs(&details)
+}
+
+/// Get the details of a route as Markdown, the controller or form links to `target_uri` when given.
+fn get_route_details(route: &DrupalRoute, target_uri: Option<&str>) -> String {
+    let mut details = vec![format!("*Path:* `{}`", route.path)];
+    if !route.methods.is_empty() {
+        details.push(format!("*Methods:* {}", route.methods.join(", ")));
+    }
+    if let Some(title) = &route._defaults._title {
+        details.push(format!("*Title:* {}", title.trim_matches(['\'', '"'])));
+    }
+    let target = if let Some(controller) = &route._defaults._controller {
+        controller
+            .class_name
+            .as_ref()
+            .map(|class_name| ("Controller", format!("{}::{}", class_name, controller.name)))
+    } else if let Some(form) = &route._defaults._form {
+        Some(("Form", form.to_string()))
+    } else {
+        route._defaults._entity_form.as_ref().map(|entity_form| {
+            (
+                "Entity form",
+                entity_form.trim_matches(['\'', '"']).to_string(),
+            )
+        })
+    };
+    if let Some((label, target)) = target {
+        details.push(match target_uri {
+            Some(uri) => format!("*{}:* [`{}`]({})", label, target, uri),
+            None => format!("*{}:* `{}`", label, target),
+        });
+    }
+    if !route.requirements.is_empty() {
+        details.push(format!(
+            "*Requirements:*\n{}",
+            route
+                .requirements
+                .iter()
+                .map(|(key, value)| format!("- `{}`: {}", key, value))
+                .collect::<Vec<String>>()
+                .join("\n")
+        ));
+    }
+    get_paragraphs(&details)
+}
+
+/// Get the details of a service as Markdown, one paragraph per detail that is set.
+fn get_service_details(service: &DrupalService) -> String {
+    let mut details = vec![format!(
+        "*Class:* {}",
+        service
+            .class
+            .as_ref()
+            .map(|class| format!("`{}`", class))
+            .unwrap_or_else(|| "-".to_string())
+    )];
+    if let Some(deprecated) = &service.deprecated {
+        details.push(format!("*Deprecated:* {}", deprecated));
+    }
+    if service.is_abstract {
+        details.push("*Abstract:* yes".to_string());
+    }
+    if let Some(parent) = &service.parent {
+        details.push(format!("*Parent:* `{}`", parent));
+    }
+    if !service.arguments.is_empty() {
+        details.push(format!(
+            "*Arguments:*\n{}",
+            service
+                .arguments
+                .iter()
+                .map(|argument| format!("- `{}`", argument))
+                .collect::<Vec<String>>()
+                .join("\n")
+        ));
+    }
+    if !service.tags.is_empty() {
+        details.push(format!("*Tags:* {}", service.tags.join(", ")));
+    }
+    get_paragraphs(&details)
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::server::get_root_dir;
+
+/// The directory in the project with templates overriding the default ones, by file name.
+pub const TEMPLATES_DIRECTORY: &str = ".drupal_ls/templates";
+
+static OVERRIDES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// A Markdown template with `@placeholder` values, f.e `# Route: @name`.
+///
+/// Placeholders without a value are left as is, so templates can use `@see` and the like.
+pub struct Template {
+    /// The file name of the template without extension, f.e `route_definition`.
+    pub name: &'static str,
+    pub default: &'static str,
+}
+
+impl Template {
+    /// Render the template, or the project's override of it, with the given placeholder values.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        let template = get_overrides()
+            .get(self.name)
+            .map_or(self.default, |template| template.as_str());
+        render(template, values)
+    }
+}
+
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    // Values are inserted in a single pass, so placeholders in values aren't replaced.
+    while let Some(index) = rest.find('@') {
+        rendered.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+        let length = after
+            .find(|character: char| !character.is_ascii_alphanumeric() && character != '_')
+            .unwrap_or(after.len());
+        match values.iter().find(|(name, _)| *name == &after[..length]) {
+            Some((_, value)) => rendered.push_str(value),
+            None => rendered.push_str(&rest[index..index + 1 + length]),
+        }
+        rest = &after[length..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Get the templates in the templates directory of the project, which are read once.
+fn get_overrides() -> &'static HashMap<String, String> {
+    OVERRIDES.get_or_init(|| match get_root_dir() {
+        Some(root_dir) => read_overrides(&Path::new(root_dir).join(TEMPLATES_DIRECTORY)),
+        None => HashMap::new(),
+    })
+}
+
+fn read_overrides(directory: &Path) -> HashMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return HashMap::new();
+    };
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "md" {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            match std::fs::read_to_string(&path) {
+                Ok(template) => {
+                    log::info!("Using documentation template {}", path.display());
+                    Some((name, template))
+                }
+                Err(err) => {
+                    log::error!("Unable to read template {}: {}", path.display(), err);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_placeholders() {
+        assert_eq!(
+            "# Class reference: Foo\n\n@see [file:///foo.php](file:///foo.php)\n",
+            render(
+                "# Class reference: @short_name\n\n@see [@uri](@uri)\n",
+                &[("short_name", "Foo"), ("uri", "file:///foo.php")]
+            )
+        );
+        assert_eq!(
+            "# Field reference: node.body",
+            render(
+                "# Field reference: @entity_type.@name",
+                &[("entity_type", "node"), ("name", "body")]
+            )
+        );
+        // Values containing placeholders are inserted as is.
+        assert_eq!(
+            "Title: @name @",
+            render("Title: @title @", &[("title", "@name"), ("name", "foo")])
+        );
+    }
+
+    #[test]
+    fn read_template_overrides() {
+        let directory =
+            std::env::temp_dir().join(format!("drupal_ls_templates_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("route_definition.md"), "Route @name").unwrap();
+        std::fs::write(directory.join("notes.txt"), "Not a template").unwrap();
+
+        let overrides = read_overrides(&directory);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            HashMap::from([("route_definition".to_string(), "Route @name".to_string())]),
+            overrides
+        );
+        assert!(read_overrides(&directory).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use tokio::process::Command;
+
+use crate::server::get_root_dir;
+use crate::settings::get_settings;
+
+use super::find_executable;
+
+/// Collects the runtime data in a single bootstrap, as bootstrapping Drupal is slow.
+const INTROSPECTION_SCRIPT: &str = r#"echo json_encode([
+  'modules' => array_keys(\Drupal::moduleHandler()->getModuleList()),
+  'roles' => (object) array_map(fn ($role) => (string) $role->label(), \Drupal::entityTypeManager()->getStorage('user_role')->loadMultiple()),
+  'config' => \Drupal::configFactory()->listAll(),
+  'bundles' => (object) array_map('array_keys', \Drupal::service('entity_type.bundle.info')->getAllBundleInfo()),
+  'fields' => array_keys(\Drupal::entityTypeManager()->getStorage('field_config')->loadMultiple()),
+]);"#;
+
+static DRUSH_DATA: OnceLock<DrushData> = OnceLock::new();
+
+/// Data about the site that can only be known at runtime.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DrushData {
+    /// The machine names of the enabled modules.
+    pub modules: Vec<String>,
+    /// The role labels, keyed by role id.
+    pub roles: HashMap<String, String>,
+    pub config: Vec<String>,
+    /// The bundles, keyed by entity type id.
+    pub bundles: HashMap<String, Vec<String>>,
+    /// The configurable fields, f.e `node.article.field_image`.
+    pub fields: Vec<String>,
+}
+
+impl DrushData {
+    /// Get the configurable field names, with the `entity_type.bundle` pairs they are used on.
+    pub fn get_field_names(&self) -> HashMap<&str, Vec<&str>> {
+        let mut field_names: HashMap<&str, Vec<&str>> = HashMap::new();
+        for field in &self.fields {
+            if let Some((bundle, field_name)) = field.rsplit_once('.') {
+                field_names.entry(field_name).or_default().push(bundle);
+            }
+        }
+        field_names
+    }
+}
+
+/// Fetch the runtime data from drush, when the integration is enabled.
+pub async fn initialize_drush() {
+    if !get_settings().drush.enabled {
+        return;
+    }
+
+    log::info!("Fetching site data from drush...");
+    match fetch_drush_data().await {
+        Ok(data) => {
+            log::info!(
+                "Fetched {} modules, {} roles, {} config objects and {} fields from drush",
+                data.modules.len(),
+                data.roles.len(),
+                data.config.len(),
+                data.fields.len()
+            );
+            let _ = DRUSH_DATA.set(data);
+        }
+        Err(err) => log::error!("Unable to fetch site data from drush: {}", err),
+    }
+}
+
+/// Get the data fetched from drush, if the integration is enabled and the data has been fetched.
+pub fn get_drush_data() -> Option<&'static DrushData> {
+    DRUSH_DATA.get()
+}
+
+async fn fetch_drush_data() -> Result<DrushData, String> {
+    let settings = &get_settings().drush;
+    let executable = find_executable(settings.executable.as_deref(), "drush")
+        .ok_or("drush executable not found")?;
+
+    let mut command = Command::new(executable);
+    if let Some(root_dir) = get_root_dir() {
+        command.current_dir(root_dir);
+    }
+    if let Some(alias) = &settings.alias {
+        command.arg(alias);
+    }
+    let output = command
+        .arg("php:eval")
+        .arg(INTROSPECTION_SCRIPT)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|err| err.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+    }
+    serde_json::from_slice::<DrushData>(&output.stdout).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_drush_data() {
+        let output = r#"{"modules":["system","node"],"roles":{"anonymous":"Anonymous user"},"config":["system.site"],"bundles":{"node":["article","page"]},"fields":["node.article.field_image","node.page.field_image","node.article.field_tags"]}"#;
+        let data = serde_json::from_str::<DrushData>(output).unwrap();
+
+        assert_eq!(vec!["system", "node"], data.modules);
+        assert_eq!(
+            Some(&"Anonymous user".to_string()),
+            data.roles.get("anonymous")
+        );
+        let field_names = data.get_field_names();
+        assert_eq!(
+            Some(&vec!["node.article", "node.page"]),
+            field_names.get("field_image")
+        );
+    }
+}
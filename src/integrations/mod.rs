@@ -0,0 +1,33 @@
+pub mod drush;
+pub mod phpcs;
+
+use std::path::{Path, PathBuf};
+
+use crate::server::get_root_dir;
+
+/// Find an executable, either the configured one or a composer installed one in the workspace.
+///
+/// Falls back to looking up the executable on the PATH.
+fn find_executable(configured: Option<&str>, name: &str) -> Option<PathBuf> {
+    let root_dir = get_root_dir().map(Path::new);
+    match configured {
+        // Relative paths are relative to the workspace.
+        Some(executable) if executable.contains(std::path::MAIN_SEPARATOR) => {
+            Some(match root_dir {
+                Some(root_dir) => root_dir.join(executable),
+                None => PathBuf::from(executable),
+            })
+        }
+        Some(executable) => find_executable_in_path(executable),
+        None => root_dir
+            .map(|root_dir| root_dir.join("vendor/bin").join(name))
+            .filter(|executable| executable.is_file())
+            .or_else(|| find_executable_in_path(name)),
+    }
+}
+
+fn find_executable_in_path(name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|directory| directory.join(name))
+        .find(|executable| executable.is_file())
+}
@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Uri};
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::document_store::document::FileType;
+use crate::document_store::DOCUMENT_STORE;
+use crate::server::publish_diagnostics;
+use crate::settings::get_settings;
+use crate::utils::{normalize_uri, uri_to_url};
+
+use super::find_executable;
+
+/// Wait for this long before running phpcs, so that a burst of events only triggers one run.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// The latest run that was scheduled for each document, keyed by the normalized uri like the
+/// document store.
+static GENERATIONS: LazyLock<Mutex<HashMap<String, u64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The diagnostics from the last completed run for each document.
+static DIAGNOSTICS: LazyLock<Mutex<HashMap<String, Vec<Diagnostic>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Deserialize)]
+struct Report {
+    files: HashMap<String, ReportFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportFile {
+    messages: Vec<ReportMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportMessage {
+    message: String,
+    source: String,
+    #[serde(rename = "type")]
+    message_type: String,
+    line: u32,
+    column: u32,
+}
+
+/// Run phpcs for a document in the background, and publish the result as diagnostics.
+pub fn schedule_phpcs(uri: Uri) {
+    if !get_settings().phpcs.enabled {
+        return;
+    }
+
+    let key = normalize_uri(uri.as_str());
+    let generation = next_generation(&key);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(DEBOUNCE).await;
+        if !is_current_generation(&key, generation) {
+            return;
+        }
+
+        let content = match DOCUMENT_STORE.lock().unwrap().get_document(&key) {
+            Some(document) if document.file_type == FileType::Php => document.content.clone(),
+            _ => return,
+        };
+        let Some(path) = uri_to_url(uri.clone()) else {
+            return;
+        };
+
+        match run_phpcs(&path, content).await {
+            Ok(diagnostics) => {
+                if set_phpcs_diagnostics(&key, generation, diagnostics) {
+                    publish_diagnostics(uri);
+                }
+            }
+            Err(err) => log::error!("Unable to run phpcs: {}", err),
+        }
+    });
+}
+
+/// Get the diagnostics from the last phpcs run for a document.
+pub fn get_phpcs_diagnostics(uri: &str) -> Vec<Diagnostic> {
+    DIAGNOSTICS
+        .lock()
+        .unwrap()
+        .get(&normalize_uri(uri))
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn clear_phpcs_diagnostics(uri: &str) {
+    let key = normalize_uri(uri);
+    GENERATIONS.lock().unwrap().remove(&key);
+    DIAGNOSTICS.lock().unwrap().remove(&key);
+}
+
+/// Start a new run for a document, which makes the runs that are in progress outdated.
+fn next_generation(uri: &str) -> u64 {
+    let mut generations = GENERATIONS.lock().unwrap();
+    let generation = generations.entry(normalize_uri(uri)).or_default();
+    *generation += 1;
+    *generation
+}
+
+fn is_current_generation(uri: &str, generation: u64) -> bool {
+    GENERATIONS.lock().unwrap().get(&normalize_uri(uri)) == Some(&generation)
+}
+
+/// Keep the diagnostics of a run, unless a newer run was scheduled in the meantime.
+fn set_phpcs_diagnostics(uri: &str, generation: u64, diagnostics: Vec<Diagnostic>) -> bool {
+    if !is_current_generation(uri, generation) {
+        return false;
+    }
+    DIAGNOSTICS
+        .lock()
+        .unwrap()
+        .insert(normalize_uri(uri), diagnostics);
+    true
+}
+
+async fn run_phpcs(path: &str, content: String) -> Result<Vec<Diagnostic>, String> {
+    let settings = &get_settings().phpcs;
+    let executable = find_executable(settings.executable.as_deref(), "phpcs")
+        .ok_or("phpcs executable not found")?;
+
+    let mut child = Command::new(executable)
+        .arg("--report=json")
+        .arg("-q")
+        .arg(format!("--standard={}", settings.standard))
+        .arg(format!("--stdin-path={}", path))
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content.as_bytes())
+            .await
+            .map_err(|err| err.to_string())?;
+    }
+
+    // phpcs exits with a non-zero status code when it finds violations.
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|err| err.to_string())?;
+    parse_report(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_report(output: &str) -> Result<Vec<Diagnostic>, String> {
+    let report =
+        serde_json::from_str::<Report>(output).map_err(|err| format!("{}: {}", err, output))?;
+
+    Ok(report
+        .files
+        .into_values()
+        .flat_map(|file| file.messages)
+        .map(|message| {
+            let position = Position {
+                line: message.line.saturating_sub(1),
+                character: message.column.saturating_sub(1),
+            };
+            Diagnostic {
+                range: Range {
+                    start: position,
+                    end: position,
+                },
+                severity: Some(match message.message_type.as_str() {
+                    "ERROR" => DiagnosticSeverity::ERROR,
+                    _ => DiagnosticSeverity::WARNING,
+                }),
+                code: Some(NumberOrString::String(message.source)),
+                source: Some("phpcs".to_string()),
+                message: message.message,
+                ..Diagnostic::default()
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_json_report() {
+        let output = r#"{"totals":{"errors":1,"warnings":0,"fixable":1},"files":{"STDIN":{"errors":1,"warnings":0,"messages":[{"message":"Missing file doc comment","source":"Drupal.Commenting.FileComment.Missing","severity":5,"fixable":false,"type":"ERROR","line":3,"column":1}]}}}"#;
+        let diagnostics = parse_report(output).unwrap();
+
+        assert_eq!(1, diagnostics.len());
+        assert_eq!(Position::new(2, 0), diagnostics[0].range.start);
+        assert_eq!(Some(DiagnosticSeverity::ERROR), diagnostics[0].severity);
+        assert_eq!(
+            Some(NumberOrString::String(
+                "Drupal.Commenting.FileComment.Missing".to_string()
+            )),
+            diagnostics[0].code
+        );
+    }
+
+    #[test]
+    fn diagnostics_of_encoded_uri() {
+        let encoded_uri = "file:///c%3A/www/web/modules/custom/foo/foo.module";
+        let normalized_uri = "file:///C:/www/web/modules/custom/foo/foo.module";
+        let diagnostic = Diagnostic {
+            message: "Missing file doc comment".to_string(),
+            ..Diagnostic::default()
+        };
+
+        let generation = next_generation(encoded_uri);
+        // Runs that were scheduled before the latest one are dropped.
+        assert!(next_generation(normalized_uri) > generation);
+        assert!(!set_phpcs_diagnostics(
+            encoded_uri,
+            generation,
+            vec![diagnostic.clone()]
+        ));
+
+        let generation = next_generation(encoded_uri);
+        assert!(set_phpcs_diagnostics(
+            encoded_uri,
+            generation,
+            vec![diagnostic.clone()]
+        ));
+        assert_eq!(vec![diagnostic], get_phpcs_diagnostics(normalized_uri));
+
+        clear_phpcs_diagnostics(encoded_uri);
+        assert!(get_phpcs_diagnostics(normalized_uri).is_empty());
+    }
+}
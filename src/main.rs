@@ -1,8 +1,11 @@
+mod diagnostics;
+mod document_store;
+mod documentation;
+mod integrations;
 mod opts;
 mod parser;
 mod server;
-mod document_store;
-mod documentation;
+mod settings;
 mod utils;
 
 use std::fs::File;
@@ -13,7 +16,10 @@ use clap::Parser;
 use structured_logger::json::new_writer;
 use structured_logger::Builder;
 
-use self::opts::DrupalLspConfig;
+use self::diagnostics::audit::audit_project;
+use self::diagnostics::check::check_project;
+use self::document_store::profile::profile_index;
+use self::opts::{Command, DrupalLspConfig};
 use self::server::start_lsp;
 
 #[tokio::main]
@@ -37,6 +43,30 @@ async fn main() -> Result<()> {
     builder.init();
     log::trace!("log options: {:?}", config);
 
+    if let Some(root_dir) = &config.profile_index {
+        print!("{}", profile_index(root_dir));
+        return Ok(());
+    }
+
+    if let Some(root_dir) = &config.audit {
+        print!("{}", audit_project(root_dir));
+        return Ok(());
+    }
+
+    if let Some(Command::Check {
+        root_dir,
+        paths,
+        format,
+    }) = &config.command
+    {
+        let (report, failed) = check_project(root_dir, paths, *format);
+        print!("{}", report);
+        if failed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     match start_lsp(config).await {
         Ok(_) => (),
         Err(error) => log::error!("An unexpected error happened: {:?}", error),
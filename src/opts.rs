@@ -1,4 +1,6 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::diagnostics::check::CheckFormat;
 
 #[derive(Parser, Debug)]
 #[clap(name = "drupal_ls")]
@@ -29,4 +31,34 @@ pub struct DrupalLspConfig {
     /// The port to use for the socket connection.
     #[clap(short, long)]
     pub port: Option<u16>,
+
+    /// Index the given project directory, print parse timings, token counts and memory estimates
+    /// per directory and file type, and exit.
+    #[clap(long)]
+    pub profile_index: Option<String>,
+
+    /// Index the given project directory, print the usages of deprecated APIs and exit.
+    #[clap(long)]
+    pub audit: Option<String>,
+
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Index the given project directory, print the diagnostics of its files and exit, with a
+    /// non-zero exit code when warnings or errors were found.
+    Check {
+        /// The project directory.
+        root_dir: String,
+
+        /// The directories or files to check, defaults to the custom modules, themes and
+        /// profiles.
+        paths: Vec<String>,
+
+        /// The output format.
+        #[clap(long, value_enum, default_value_t = CheckFormat::Text)]
+        format: CheckFormat,
+    },
 }
@@ -0,0 +1,203 @@
+use lsp_types::Position;
+
+use super::tokens::{DrupalTranslation, Token, TokenData};
+use super::{byte_range_to_range, position_to_byte};
+
+/// Parses gettext translation files, f.e `translations/nl.po`.
+pub struct GettextParser {
+    source: String,
+    uri: String,
+}
+
+#[derive(Default)]
+struct Entry {
+    msgid: String,
+    msgstr: String,
+    start_byte: usize,
+    end_byte: usize,
+}
+
+impl GettextParser {
+    pub fn new(source: &str, uri: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            uri: uri.to_string(),
+        }
+    }
+
+    pub fn get_tokens(&self) -> Vec<Token> {
+        let Some(langcode) = self.get_langcode() else {
+            return vec![];
+        };
+
+        self.get_entries()
+            .into_iter()
+            // The entry with an empty msgid is the header of the file.
+            .filter(|entry| !entry.msgid.is_empty())
+            .map(|entry| {
+                Token::new(
+                    TokenData::DrupalTranslationDefinition(DrupalTranslation {
+                        string: entry.msgid,
+                        translation: entry.msgstr,
+                        langcode: langcode.to_string(),
+                    }),
+                    byte_range_to_range(&self.source, entry.start_byte, entry.end_byte),
+                )
+            })
+            .collect()
+    }
+
+    pub fn get_token_at_position(&self, position: Position) -> Option<Token> {
+        let offset = position_to_byte(&self.source, position)?;
+        self.get_tokens()
+            .into_iter()
+            .find(|token| token.range.start_byte <= offset && offset <= token.range.end_byte)
+    }
+
+    /// Get the language of the file from its name, f.e `nl.po` or `drupal-10.3.0.nl.po`.
+    fn get_langcode(&self) -> Option<&str> {
+        let file_name = self.uri.rsplit('/').next()?.strip_suffix(".po")?;
+        file_name.rsplit('.').next()
+    }
+
+    fn get_entries(&self) -> Vec<Entry> {
+        let mut entries: Vec<Entry> = vec![];
+        let mut entry: Option<Entry> = None;
+        // The keyword that quoted continuation lines belong to.
+        let mut keyword = "";
+
+        let mut offset = 0;
+        for line in self.source.split_inclusive('\n') {
+            let start_byte = offset;
+            offset += line.len();
+            let line = line.trim();
+
+            let value = if let Some(value) = line.strip_prefix("msgctxt ") {
+                entries.extend(entry.replace(Entry {
+                    start_byte,
+                    ..Entry::default()
+                }));
+                keyword = "msgctxt";
+                value
+            } else if let Some(value) = line.strip_prefix("msgid ") {
+                // A msgid starts a new entry, unless it belongs to the preceding msgctxt.
+                if keyword != "msgctxt" {
+                    entries.extend(entry.replace(Entry {
+                        start_byte,
+                        ..Entry::default()
+                    }));
+                }
+                keyword = "msgid";
+                value
+            } else if let Some(value) = line.strip_prefix("msgstr ") {
+                keyword = "msgstr";
+                value
+            } else if let Some(value) = line.strip_prefix("msgstr[0] ") {
+                keyword = "msgstr";
+                value
+            } else if line.starts_with('"') {
+                line
+            } else {
+                // Comments, plural forms other than the first and empty lines.
+                keyword = "";
+                continue;
+            };
+
+            let Some(entry) = entry.as_mut() else {
+                continue;
+            };
+            let value = unescape(value.trim_matches('"'));
+            match keyword {
+                "msgid" => entry.msgid.push_str(&value),
+                "msgstr" => entry.msgstr.push_str(&value),
+                _ => continue,
+            }
+            entry.end_byte = start_byte + line.len();
+        }
+        entries.extend(entry);
+        entries
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(character) = chars.next() {
+        if character != '\\' {
+            result.push(character);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(character) => result.push(character),
+            None => (),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_po_file() {
+        let source = r#"# Dutch translation of Drupal
+msgid ""
+msgstr ""
+"Language: nl\n"
+
+msgid "Read more"
+msgstr "Lees meer"
+
+msgctxt "Long month name"
+msgid "May"
+msgstr "Mei"
+
+#: node.module
+msgid ""
+"A \"quoted\" string "
+"on multiple lines"
+msgstr ""
+
+msgid "1 comment"
+msgid_plural "@count comments"
+msgstr[0] "1 reactie"
+msgstr[1] "@count reacties"
+"#;
+        let parser = GettextParser::new(source, "file:///translations/drupal-10.3.0.nl.po");
+        let tokens = parser.get_tokens();
+        let translations: Vec<(&str, &str, &str)> = tokens
+            .iter()
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalTranslationDefinition(translation) => Some(translation),
+                _ => None,
+            })
+            .map(|translation| {
+                (
+                    translation.string.as_str(),
+                    translation.translation.as_str(),
+                    translation.langcode.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(4, translations.len());
+        assert_eq!(("Read more", "Lees meer", "nl"), translations[0]);
+        assert_eq!(("May", "Mei", "nl"), translations[1]);
+        assert_eq!(
+            ("A \"quoted\" string on multiple lines", "", "nl"),
+            translations[2]
+        );
+        assert_eq!(("1 comment", "1 reactie", "nl"), translations[3]);
+
+        let token = parser
+            .get_token_at_position(Position {
+                line: 5,
+                character: 10,
+            })
+            .unwrap();
+        assert_eq!(5, token.range.start_point.row);
+        assert_eq!(6, token.range.end_point.row);
+    }
+}
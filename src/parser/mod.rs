@@ -1,9 +1,12 @@
+pub mod gettext;
 pub mod php;
+pub mod symbol;
 pub mod tokens;
+pub mod twig;
 pub mod yaml;
 
 use lsp_types::Position;
-use tree_sitter::{Language, Node, Parser, Point, Tree};
+use tree_sitter::{Language, Node, Parser, Point, Range, Tree};
 
 pub fn get_closest_parent_by_kind<'a>(node: &'a Node, kind: &'a str) -> Option<Node<'a>> {
     let mut parent = node.parent();
@@ -19,7 +22,7 @@ pub fn get_tree(source: &str, language: &Language) -> Option<Tree> {
     parser.parse(source.as_bytes(), None)
 }
 
-pub fn get_node_at_position(tree: &Tree, position: Position) -> Option<Node> {
+pub fn get_node_at_position(tree: &Tree, position: Position) -> Option<Node<'_>> {
     let start = position_to_point(position);
     tree.root_node().descendant_for_point_range(start, start)
 }
@@ -27,3 +30,42 @@ pub fn get_node_at_position(tree: &Tree, position: Position) -> Option<Node> {
 pub fn position_to_point(position: Position) -> Point {
     Point::new(position.line as usize, position.character as usize)
 }
+
+/// Get the byte offset of a position, for parsers that don't use tree-sitter.
+pub fn position_to_byte(source: &str, position: Position) -> Option<usize> {
+    let mut offset = 0;
+    for (index, line) in source.split_inclusive('\n').enumerate() {
+        if index == position.line as usize {
+            return Some(offset + (position.character as usize).min(line.len()));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+pub fn byte_to_point(source: &str, byte: usize) -> Point {
+    let before = &source[..byte];
+    let row = before.matches('\n').count();
+    let column = byte - before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    Point::new(row, column)
+}
+
+pub fn byte_range_to_range(source: &str, start_byte: usize, end_byte: usize) -> Range {
+    Range {
+        start_byte,
+        end_byte,
+        start_point: byte_to_point(source, start_byte),
+        end_point: byte_to_point(source, end_byte),
+    }
+}
+
+/// Convert the range of a token or node to the range of a document in the protocol.
+pub fn token_range_to_range(range: &Range) -> lsp_types::Range {
+    lsp_types::Range::new(
+        Position::new(
+            range.start_point.row as u32,
+            range.start_point.column as u32,
+        ),
+        Position::new(range.end_point.row as u32, range.end_point.column as u32),
+    )
+}
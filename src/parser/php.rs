@@ -1,27 +1,176 @@
-use lsp_types::Position;
+use lsp_types::{Position, TextEdit};
 use regex::Regex;
-use std::collections::HashMap;
-use tree_sitter::{Node, Point};
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+use tree_sitter::{Node, Point, Range};
+
+use crate::settings::{get_settings, PluginTypeSettings};
 
 use super::tokens::{
-    ClassAttribute, DrupalHook, DrupalPlugin, DrupalPluginReference, DrupalPluginType, DrupalTranslationString, PhpClass, PhpClassName, PhpMethod, Token, TokenData
+    ClassAttribute, DrupalConfigKeyReference, DrupalConfigOverride, DrupalEntityBundleReference,
+    DrupalEntityField, DrupalEntityFieldReference, DrupalEntityType, DrupalEntityTypeGroup,
+    DrupalEntityTypeHint, DrupalHook, DrupalHookImplementation, DrupalPlugin,
+    DrupalPluginReference, DrupalPluginType, DrupalPreprocess, DrupalTheme,
+    DrupalTranslationString, PhpClass, PhpClassConstant, PhpClassName, PhpMethod, PhpParameter,
+    PhpVisibility, Token, TokenData,
+};
+use super::{
+    byte_range_to_range, get_closest_parent_by_kind, get_node_at_position, get_tree,
+    position_to_byte, position_to_point,
 };
-use super::{get_closest_parent_by_kind, get_node_at_position, get_tree, position_to_point};
+
+// Matches assignments like `$variables['name'] = ...` in preprocess functions.
+static PREPROCESS_VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\$variables\[['"](?<name>[^'"]+)['"]\]\s*=[^=]"#).unwrap());
+
+// Matches the words in a comment, which may be class names in f.e `@param` tags.
+static WORD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\w+").unwrap());
+
+// Matches a base field definition, f.e `$fields['title'] = BaseFieldDefinition::create('string')`.
+static BASE_FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\$fields\[['"](?<name>\w+)['"]\]\s*=\s*(?:BaseFieldDefinition::create\(\s*['"](?<type>\w+)['"])?"#)
+        .unwrap()
+});
+
+// Matches a setting of a base field, f.e `->setSetting('target_type', 'user')`.
+static BASE_FIELD_SETTING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"->setSetting\(\s*['"](?<name>\w+)['"]\s*,\s*(?<value>[^,)]+?)\s*\)"#).unwrap()
+});
+
+// Matches a fully qualified class name in an annotation, f.e `"storage" = "Drupal\node\NodeStorage"`.
+static ANNOTATION_CLASS_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""(?<name>\\?Drupal\\[\w\\]+)""#).unwrap());
+
+// Matches the PHPUnit tags of the code a test covers, f.e `@covers ::load` or
+// `@coversDefaultClass \Drupal\node\NodeStorage`.
+static PHPUNIT_COVERS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"@(?<tag>covers|coversDefaultClass)\s+(?<value>\\?\w[\w\\]*(?:::\w+)?|::\w+)")
+        .unwrap()
+});
+
+// Matches the annotation of a class, f.e `@ContentEntityType(` or `@Block(`.
+static ANNOTATION_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\*\s*@(?<type>.+)\("#).unwrap());
+
+// Matches the id of an annotation, f.e `id = "node"` or `@ViewsField("boolean")`.
+static ANNOTATION_ID: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"id\s*=\s*"(?<id>[^"]+)"|\*\s*@\w+\(\s*"(?<value_id>[^"]+)"\s*\)"#).unwrap()
+});
+
+// Matches a string value in an annotation, f.e `label = @Translation("User")`.
+static ANNOTATION_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*\*\s*(?<key>\w+)\s*=\s*(?:@Translation\()?"(?<value>[^"]*)""#).unwrap()
+});
+
+// Matches the "* " or "*" at the start of a docblock line.
+static DOCBLOCK_LINE_PREFIX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*\*\s?").unwrap());
+
+// Matches the assignment of a config object to a variable, f.e `$config = \Drupal::config('system.site');`.
+static CONFIG_ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?<variable>\$\w+)\s*=\s*[^;]*\b(?:config|getEditable)\(\s*['"](?<name>[^'"]+)['"]\s*\)\s*;"#)
+        .unwrap()
+});
+
+// Matches the last two keys of a render array property, f.e `$build['#attached']['library'][]`.
+static RENDER_ARRAY_PROPERTY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\[\s*['"](?<parent>[^'"]+)['"]\s*\]\s*\[\s*['"](?<key>[^'"]+)['"]\s*\]\s*(?<append>\[\s*\])?$"#)
+        .unwrap()
+});
+
+// Limits how many assignments are followed when inferring the type of an expression.
+const MAX_ASSIGNMENT_DEPTH: usize = 8;
+
+// The properties that entities are filtered by bundle with, f.e `vid` for taxonomy terms.
+const BUNDLE_KEYS: &[&str] = &["type", "bundle", "vid"];
+
+// The first update number of a module without updates, see hook_update_N().
+const DEFAULT_UPDATE_NUMBER: u32 = 10001;
 
 pub struct PhpParser {
     source: String,
+    /// The plugin types of contrib modules, from the settings.
+    plugin_types: &'static [PluginTypeSettings],
+}
+
+/// A `t()` call that passes an arguments array, f.e `t('Hello @name', ['@name' => $name])`.
+#[derive(Debug)]
+pub struct TranslationArguments {
+    pub string: String,
+    pub string_range: Range,
+    /// The range of the arguments array.
+    pub range: Range,
+    /// The indentation of the line the arguments array starts at.
+    pub indentation: String,
+    /// The keys of the arguments, with their range and the text of the whole array element.
+    pub elements: Vec<(String, Range, String)>,
 }
 
 impl PhpParser {
     pub fn new(source: &str) -> Self {
         Self {
             source: source.to_string(),
+            plugin_types: &get_settings().plugin_types,
         }
     }
 
     pub fn get_tokens(&self) -> Vec<Token> {
-        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into());
-        self.parse_nodes(vec![tree.unwrap().root_node()])
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()).unwrap();
+        let mut tokens = self.parse_nodes(vec![tree.root_node()]);
+        tokens.extend(self.get_hook_invocations(tree.root_node()));
+        tokens
+    }
+
+    /// Get the calls invoking hooks, which are mostly inside classes where other tokens aren't
+    /// looked for.
+    fn get_hook_invocations(&self, root_node: Node) -> Vec<Token> {
+        let mut tokens = vec![];
+        let mut nodes = vec![root_node];
+        while let Some(node) = nodes.pop() {
+            nodes.extend(node.children(&mut node.walk()));
+            if node.kind() != "member_call_expression" {
+                continue;
+            }
+            let Some(name_node) = node.child_by_field_name("name") else {
+                continue;
+            };
+            if let Some(hook_names) = self.get_invoked_hooks(&node, self.get_node_text(&name_node))
+            {
+                tokens.push(Token::new(
+                    TokenData::DrupalHookInvocation(hook_names),
+                    node.range(),
+                ));
+            }
+        }
+        tokens
+    }
+
+    /// Get the fully qualified classes referenced by `use` statements, type hints, `new` and
+    /// `::class`.
+    ///
+    /// Class references are kept apart from the tokens, as there are a lot of them.
+    pub fn get_class_references(&self) -> Vec<(PhpClassName, Range)> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+        let root_node = tree.root_node();
+        let imports = self.get_imported_class_names_from_root(root_node);
+        let namespace = root_node
+            .children(&mut root_node.walk())
+            .find(|child| child.kind() == "namespace_definition")
+            .and_then(|namespace_node| namespace_node.child_by_field_name("name"))
+            .map(|name_node| self.get_node_text(&name_node));
+
+        let mut references: Vec<(PhpClassName, Range)> = self
+            .get_use_clauses(root_node)
+            .into_iter()
+            .map(|(_, class_name, name_node)| (class_name, name_node.range()))
+            .collect();
+        references.extend(
+            self.get_class_name_usages_from_root(root_node)
+                .into_iter()
+                .map(|(name, range)| (self.resolve_class_name(&name, &imports, namespace), range)),
+        );
+        references
     }
 
     pub fn get_token_at_position(&self, position: Position) -> Option<Token> {
@@ -41,11 +190,624 @@ impl PhpParser {
         parsed_node
     }
 
+    /// Get the functions of a `.module`, `.theme`, `.install` or `.inc` file that implement a hook,
+    /// f.e `mymodule_cron()`, given the name of the module.
+    ///
+    /// The hook is taken from the `Implements hook_NAME()` docblock, or else derived from the
+    /// function name. Derived hooks are only matched against known hooks when looked up.
+    pub fn get_hook_implementations(&self, module: &str) -> Vec<Token> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+
+        let prefix = format!("{}_", module);
+        let mut tokens = vec![];
+        let mut nodes = vec![tree.root_node()];
+        while let Some(node) = nodes.pop() {
+            if node.kind() != "function_definition" {
+                nodes.extend(node.children(&mut node.walk()));
+                continue;
+            }
+            let Some(name_node) = node.child_by_field_name("name") else {
+                continue;
+            };
+            let function_name = self.get_node_text(&name_node);
+            let documented_hook_name = node
+                .prev_named_sibling()
+                .filter(|comment_node| comment_node.kind() == "comment")
+                .and_then(|comment_node| get_implemented_hook(self.get_node_text(&comment_node)));
+            let hook_name = match documented_hook_name {
+                Some(hook_name) => hook_name.to_string(),
+                None => match function_name.strip_prefix(&prefix) {
+                    Some(name) => format!("hook_{}", name),
+                    None => continue,
+                },
+            };
+
+            tokens.push(Token::new(
+                TokenData::DrupalHookImplementation(DrupalHookImplementation {
+                    hook_name,
+                    module: module.to_string(),
+                    documented: documented_hook_name.is_some(),
+                }),
+                name_node.range(),
+            ));
+        }
+        tokens.sort_by_key(|token| token.range.start_byte);
+        tokens
+    }
+
+    /// Get the classes covered by the tests of a test file, from their `@covers` and
+    /// `@coversDefaultClass` tags.
+    pub fn get_covered_classes(&self) -> Vec<Token> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+
+        let mut tokens = vec![];
+        let mut nodes = vec![tree.root_node()];
+        while let Some(node) = nodes.pop() {
+            nodes.extend(node.children(&mut node.walk()));
+            if node.kind() != "comment" {
+                continue;
+            }
+            let text = self.get_node_text(&node);
+            for value in PHPUNIT_COVERS
+                .captures_iter(text)
+                .filter_map(|captures| captures.name("value"))
+            {
+                let class_name = match value.as_str().split_once("::") {
+                    Some(("", _)) => self.get_covers_default_class(node),
+                    Some((class_name, _)) => Some(class_name),
+                    None => Some(value.as_str()),
+                };
+                if let Some(class_name) = class_name {
+                    tokens.push(Token::new(
+                        TokenData::PhpUnitCovers(PhpClassName::from(class_name)),
+                        byte_range_to_range(
+                            &self.source,
+                            node.start_byte() + value.start(),
+                            node.start_byte() + value.end(),
+                        ),
+                    ));
+                }
+            }
+        }
+        tokens.sort_by_key(|token| token.range.start_byte);
+        tokens
+    }
+
+    /// Get the `@coversDefaultClass` of the test class that a docblock is in, which `@covers ::method`
+    /// tags are relative to.
+    fn get_covers_default_class(&self, node: Node) -> Option<&str> {
+        let class_node = get_closest_parent_by_kind(&node, "class_declaration")?;
+        let comment_node = class_node
+            .prev_named_sibling()
+            .filter(|comment_node| comment_node.kind() == "comment")?;
+        PHPUNIT_COVERS
+            .captures_iter(self.get_node_text(&comment_node))
+            .filter(|captures| &captures["tag"] == "coversDefaultClass")
+            .find_map(|captures| Some(captures.name("value")?.as_str()))
+    }
+
+    /// Get the config overrides of a settings file, f.e `$config['system.site']['name'] = 'Foo';`.
+    pub fn get_config_overrides(&self) -> Vec<Token> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+
+        let mut tokens = vec![];
+        let mut nodes = vec![tree.root_node()];
+        while let Some(node) = nodes.pop() {
+            nodes.extend(node.children(&mut node.walk()));
+            if node.kind() != "assignment_expression" {
+                continue;
+            }
+            let (Some(left_node), Some(right_node)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                continue;
+            };
+
+            // The keys are collected from the innermost subscript, which is the config name.
+            let mut keys: Vec<&str> = vec![];
+            let mut subscript_node = left_node;
+            while subscript_node.kind() == "subscript_expression" {
+                let Some(key) = subscript_node
+                    .named_child(1)
+                    .and_then(|index_node| self.get_string_value(&index_node))
+                else {
+                    break;
+                };
+                keys.insert(0, key);
+                let Some(object_node) = subscript_node.named_child(0) else {
+                    break;
+                };
+                subscript_node = object_node;
+            }
+            if self.get_node_text(&subscript_node) != "$config" || keys.is_empty() {
+                continue;
+            }
+
+            tokens.push(Token::new(
+                TokenData::DrupalConfigOverride(DrupalConfigOverride {
+                    config_name: keys[0].to_string(),
+                    key: match keys.len() {
+                        1 => None,
+                        _ => Some(keys[1..].join(".")),
+                    },
+                    value: self.get_node_text(&right_node).to_string(),
+                }),
+                node.range(),
+            ));
+        }
+        tokens.sort_by_key(|token| token.range.start_byte);
+        tokens
+    }
+
+    /// Get the translatable strings in the whole file, including the ones in classes which aren't
+    /// part of the indexed tokens.
+    pub fn get_translation_strings(&self) -> Vec<Token> {
+        self.get_call_tokens()
+            .into_iter()
+            .filter(|token| matches!(token.data, TokenData::DrupalTranslationString(_)))
+            .collect()
+    }
+
+    /// Get the `t()` calls that pass an arguments array with literal keys.
+    pub fn get_translation_arguments(&self) -> Vec<TranslationArguments> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+        self.get_translation_strings()
+            .into_iter()
+            .filter_map(|token| {
+                let TokenData::DrupalTranslationString(translation_string) = token.data else {
+                    return None;
+                };
+                let node = tree
+                    .root_node()
+                    .descendant_for_byte_range(token.range.start_byte, token.range.end_byte)?;
+                let arguments_node = node.child_by_field_name("arguments")?;
+                let string_node = arguments_node.named_child(0)?.named_child(0)?;
+                let array_node = arguments_node.named_child(1)?.named_child(0)?;
+                if array_node.kind() != "array_creation_expression" {
+                    return None;
+                }
+
+                let mut elements = vec![];
+                for element in array_node.named_children(&mut array_node.walk()) {
+                    // Arrays with computed keys can't be checked.
+                    let (key, _) = self.get_array_element_key_value(element)?;
+                    elements.push((
+                        self.get_string_value(&key)?.to_string(),
+                        key.range(),
+                        self.get_node_text(&element).to_string(),
+                    ));
+                }
+
+                let line_start = self.source[..array_node.start_byte()]
+                    .rfind('\n')
+                    .map_or(0, |index| index + 1);
+                Some(TranslationArguments {
+                    string: translation_string.string,
+                    string_range: string_node.range(),
+                    range: array_node.range(),
+                    indentation: self.source[line_start..]
+                        .chars()
+                        .take_while(|char| *char == ' ' || *char == '\t')
+                        .collect(),
+                    elements,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_namespace(&self) -> Option<String> {
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into())?;
+        let root_node = tree.root_node();
+        let namespace_node = root_node
+            .children(&mut root_node.walk())
+            .find(|node| node.kind() == "namespace_definition")?;
+        Some(
+            self.get_node_text(&namespace_node.child_by_field_name("name")?)
+                .to_string(),
+        )
+    }
+
+    /// Get the classes imported by `use` statements, keyed by their (aliased) short name.
+    pub fn get_imported_class_names(&self) -> HashMap<String, PhpClassName> {
+        match get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) {
+            Some(tree) => self.get_imported_class_names_from_root(tree.root_node()),
+            None => HashMap::new(),
+        }
+    }
+
+    fn get_imported_class_names_from_root(&self, root_node: Node) -> HashMap<String, PhpClassName> {
+        self.get_use_clauses(root_node)
+            .into_iter()
+            .map(|(alias, class_name, _)| (alias, class_name))
+            .collect()
+    }
+
+    /// Get the (aliased) short name, the class name and the name node of every `use` clause.
+    fn get_use_clauses<'a>(&self, root_node: Node<'a>) -> Vec<(String, PhpClassName, Node<'a>)> {
+        let mut use_clauses = vec![];
+        for declaration in root_node
+            .children(&mut root_node.walk())
+            .filter(|node| node.kind() == "namespace_use_declaration")
+        {
+            // Group use declarations, f.e `use Drupal\Core\{Url, Link};`.
+            let prefix = declaration
+                .named_children(&mut declaration.walk())
+                .find(|node| node.kind() == "namespace_name")
+                .map(|node| format!("{}\\", self.get_node_text(&node)))
+                .unwrap_or_default();
+            let clauses: Vec<Node> = match declaration.child_by_field_name("body") {
+                Some(body) => body.named_children(&mut body.walk()).collect(),
+                None => declaration
+                    .named_children(&mut declaration.walk())
+                    .collect(),
+            };
+
+            for clause in clauses
+                .into_iter()
+                .filter(|node| node.kind() == "namespace_use_clause")
+            {
+                let Some(name_node) = clause.named_child(0) else {
+                    continue;
+                };
+                let class_name = format!("{}{}", prefix, self.get_node_text(&name_node));
+                let alias = match clause.child_by_field_name("alias") {
+                    Some(alias_node) => self.get_node_text(&alias_node).to_string(),
+                    None => class_name
+                        .rsplit('\\')
+                        .next()
+                        .unwrap_or(&class_name)
+                        .to_string(),
+                };
+                use_clauses.push((alias, PhpClassName::from(class_name.as_str()), name_node));
+            }
+        }
+        use_clauses
+    }
+
+    /// Get all unqualified class names used in the file, f.e in type hints and `new` expressions.
+    pub fn get_class_name_usages(&self) -> Vec<(String, Range)> {
+        match get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) {
+            Some(tree) => self.get_class_name_usages_from_root(tree.root_node()),
+            None => vec![],
+        }
+    }
+
+    fn get_class_name_usages_from_root(&self, root_node: Node) -> Vec<(String, Range)> {
+        let mut usages = vec![];
+        let mut nodes = vec![root_node];
+        while let Some(node) = nodes.pop() {
+            nodes.extend(node.children(&mut node.walk()));
+            if node.kind() != "name" {
+                continue;
+            }
+
+            let Some(parent) = node.parent() else {
+                continue;
+            };
+            let is_class_name = match parent.kind() {
+                "named_type" | "base_clause" | "class_interface_clause" | "attribute" => true,
+                "object_creation_expression" | "class_constant_access_expression" => {
+                    parent.named_child(0) == Some(node)
+                }
+                "scoped_call_expression" | "scoped_property_access_expression" => {
+                    parent.child_by_field_name("scope") == Some(node)
+                }
+                "binary_expression" => {
+                    parent
+                        .child_by_field_name("operator")
+                        .is_some_and(|operator| operator.kind() == "instanceof")
+                        && parent.child_by_field_name("right") == Some(node)
+                }
+                _ => false,
+            };
+
+            let name = self.get_node_text(&node);
+            if is_class_name && !["self", "static", "parent"].contains(&name) {
+                usages.push((name.to_string(), node.range()));
+            }
+        }
+
+        usages.sort_by_key(|(_, range)| range.start_byte);
+        usages
+    }
+
+    /// Get the names of the classes, interfaces and traits declared in the file.
+    pub fn get_declared_class_names(&self) -> Vec<String> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+        let root_node = tree.root_node();
+        let class_names = root_node
+            .children(&mut root_node.walk())
+            .filter(|node| {
+                [
+                    "class_declaration",
+                    "interface_declaration",
+                    "trait_declaration",
+                    "enum_declaration",
+                ]
+                .contains(&node.kind())
+            })
+            .filter_map(|node| {
+                Some(
+                    self.get_node_text(&node.child_by_field_name("name")?)
+                        .to_string(),
+                )
+            })
+            .collect();
+        class_names
+    }
+
+    /// Get the next available schema number for a hook_update_N() implementation of the module.
+    pub fn get_next_update_number(&self, module_name: &str) -> u32 {
+        let prefix = format!("{}_update_", module_name);
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return DEFAULT_UPDATE_NUMBER;
+        };
+
+        let root_node = tree.root_node();
+        let update_number = root_node
+            .children(&mut root_node.walk())
+            .filter(|node| node.kind() == "function_definition")
+            .filter_map(|node| {
+                self.get_node_text(&node.child_by_field_name("name")?)
+                    .strip_prefix(&prefix)?
+                    .parse::<u32>()
+                    .ok()
+            })
+            .max();
+
+        match update_number {
+            Some(update_number) => update_number + 1,
+            None => DEFAULT_UPDATE_NUMBER,
+        }
+    }
+
+    /// Create an edit inserting a `use` statement for the class, keeping the imports sorted.
+    pub fn get_use_statement_edit(&self, class_name: &PhpClassName) -> Option<TextEdit> {
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into())?;
+        let root_node = tree.root_node();
+        let use_statement = format!("use {};", class_name);
+
+        let declarations: Vec<Node> = root_node
+            .children(&mut root_node.walk())
+            .filter(|node| node.kind() == "namespace_use_declaration")
+            .collect();
+
+        let (line, new_text) = if let Some(next) = declarations
+            .iter()
+            .find(|node| self.get_node_text(node).to_lowercase() > use_statement.to_lowercase())
+        {
+            (next.start_position().row, format!("{}\n", use_statement))
+        } else if let Some(last) = declarations.last() {
+            (last.end_position().row + 1, format!("{}\n", use_statement))
+        } else {
+            let previous = root_node
+                .children(&mut root_node.walk())
+                .find(|node| node.kind() == "namespace_definition")
+                .or(root_node.child(0))?;
+            (
+                previous.end_position().row + 1,
+                format!("\n{}\n", use_statement),
+            )
+        };
+
+        let position = Position {
+            line: line as u32,
+            character: 0,
+        };
+        Some(TextEdit {
+            range: lsp_types::Range {
+                start: position,
+                end: position,
+            },
+            new_text,
+        })
+    }
+
+    /// Get the tokens of the calls in the whole file, including the ones in classes which aren't
+    /// part of the indexed tokens, f.e `$this->currentUser()->hasPermission('administer nodes')`.
+    pub fn get_call_tokens(&self) -> Vec<Token> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+            return vec![];
+        };
+
+        let mut tokens = vec![];
+        let mut nodes = vec![tree.root_node()];
+        while let Some(node) = nodes.pop() {
+            nodes.extend(node.children(&mut node.walk()));
+            if ![
+                "scoped_call_expression",
+                "member_call_expression",
+                "function_call_expression",
+            ]
+            .contains(&node.kind())
+            {
+                continue;
+            }
+            tokens.extend(self.parse_call_expression(node, None));
+        }
+        tokens.sort_by_key(|token| token.range.start_byte);
+        tokens
+    }
+
+    /// Get an edit that sorts the `use` statements and removes the unused ones.
+    ///
+    /// Class imports are sorted alphabetically, followed by the function and the constant imports
+    /// in their own blocks, like PSR-12 groups them.
+    pub fn get_organize_imports_edit(&self) -> Option<TextEdit> {
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into())?;
+        let root_node = tree.root_node();
+        let children: Vec<Node> = root_node.children(&mut root_node.walk()).collect();
+        let first = children
+            .iter()
+            .position(|node| node.kind() == "namespace_use_declaration")?;
+        let last = children
+            .iter()
+            .rposition(|node| node.kind() == "namespace_use_declaration")?;
+        // Don't lose comments or code in between the declarations.
+        if children[first..=last]
+            .iter()
+            .any(|node| node.kind() != "namespace_use_declaration")
+        {
+            return None;
+        }
+
+        let used_names = self.get_used_names(&children[last + 1..]);
+        // The statements with the block they belong to: classes, functions or constants.
+        let mut statements: Vec<(usize, String)> = vec![];
+        for declaration in &children[first..=last] {
+            let prefix = declaration
+                .named_children(&mut declaration.walk())
+                .find(|node| node.kind() == "namespace_name")
+                .map(|node| format!("{}\\", self.get_node_text(&node)))
+                .unwrap_or_default();
+            let clauses: Vec<Node> = match declaration.child_by_field_name("body") {
+                Some(body) => body.named_children(&mut body.walk()).collect(),
+                None => declaration
+                    .named_children(&mut declaration.walk())
+                    .collect(),
+            };
+
+            for clause in clauses
+                .into_iter()
+                .filter(|node| node.kind() == "namespace_use_clause")
+            {
+                let name = format!("{}{}", prefix, self.get_node_text(&clause.named_child(0)?));
+                let alias = clause
+                    .child_by_field_name("alias")
+                    .map(|alias| self.get_node_text(&alias));
+                let short_name = alias.unwrap_or(name.rsplit('\\').next().unwrap_or(&name));
+
+                // Functions and constants are kept, since we only track class name usages.
+                let import_type = clause
+                    .child_by_field_name("type")
+                    .or(declaration.child_by_field_name("type"))
+                    .map(|node| self.get_node_text(&node));
+                if import_type.is_none() && !used_names.contains(short_name) {
+                    continue;
+                }
+
+                statements.push((
+                    match import_type {
+                        None => 0,
+                        Some("function") => 1,
+                        Some(_) => 2,
+                    },
+                    format!(
+                        "use {}{}{};",
+                        import_type
+                            .map(|import_type| format!("{} ", import_type))
+                            .unwrap_or_default(),
+                        name,
+                        alias
+                            .map(|alias| format!(" as {}", alias))
+                            .unwrap_or_default()
+                    ),
+                ));
+            }
+        }
+        statements.sort_by_key(|(block, statement)| (*block, statement.to_lowercase()));
+        statements.dedup();
+
+        let range = Range {
+            start_byte: children[first].start_byte(),
+            end_byte: children[last].end_byte(),
+            start_point: children[first].start_position(),
+            end_point: children[last].end_position(),
+        };
+        let new_text = statements
+            .chunk_by(|(a, _), (b, _)| a == b)
+            .map(|block| {
+                block
+                    .iter()
+                    .map(|(_, statement)| statement.as_str())
+                    .collect::<Vec<&str>>()
+                    .join("\n")
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n");
+        if new_text == self.source[range.start_byte..range.end_byte] {
+            return None;
+        }
+
+        // Remove the line break after the declarations when all of them are unused.
+        let mut end = Position {
+            line: range.end_point.row as u32,
+            character: range.end_point.column as u32,
+        };
+        if new_text.is_empty() && self.source[range.end_byte..].starts_with('\n') {
+            end = Position {
+                line: end.line + 1,
+                character: 0,
+            };
+        }
+        Some(TextEdit {
+            range: lsp_types::Range {
+                start: Position {
+                    line: range.start_point.row as u32,
+                    character: range.start_point.column as u32,
+                },
+                end,
+            },
+            new_text,
+        })
+    }
+
+    /// Get the names that could refer to an import, including the ones in doc comments.
+    fn get_used_names(&self, nodes: &[Node]) -> HashSet<String> {
+        let mut used_names: HashSet<String> = self
+            .get_class_name_usages()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let mut nodes = nodes.to_vec();
+        while let Some(node) = nodes.pop() {
+            match node.kind() {
+                "comment" => used_names.extend(
+                    WORD.find_iter(self.get_node_text(&node))
+                        .map(|word| word.as_str().to_string()),
+                ),
+                // A trait used in a class body, f.e `use StringTranslationTrait;`.
+                "use_declaration" => {
+                    for child in node.named_children(&mut node.walk()) {
+                        if child.kind() == "name" {
+                            used_names.insert(self.get_node_text(&child).to_string());
+                        } else {
+                            nodes.push(child);
+                        }
+                    }
+                }
+                // A namespace import, f.e `Foo\Bar` when `Foo` is imported.
+                "qualified_name" => {
+                    let text = self.get_node_text(&node);
+                    if !text.starts_with('\\') {
+                        if let Some(name) = text.split('\\').next() {
+                            used_names.insert(name.to_string());
+                        }
+                    }
+                }
+                _ => nodes.extend(node.children(&mut node.walk())),
+            }
+        }
+        used_names
+    }
+
     fn parse_nodes(&self, nodes: Vec<Node>) -> Vec<Token> {
         let mut tokens: Vec<Token> = vec![];
 
         let mut current_nodes: Box<Vec<Node>> = Box::new(nodes);
-        while current_nodes.len() > 0 {
+        while !current_nodes.is_empty() {
             let mut new_nodes: Box<Vec<Node>> = Box::default();
             for node in current_nodes.into_iter() {
                 if node.is_error() {
@@ -70,13 +832,18 @@ impl PhpParser {
 
     fn parse_node(&self, node: Node, point: Option<Point>) -> Option<Token> {
         match node.kind() {
-            "class_declaration" => self.parse_class_declaration(node),
+            "class_declaration" | "trait_declaration" => self.parse_class_declaration(node),
             "method_declaration" => self.parse_method_declaration(node),
             "scoped_call_expression" | "member_call_expression" | "function_call_expression" => {
                 self.parse_call_expression(node, point)
             }
             "function_definition" => self.parse_function_definition(node),
-            "comment" => self.parse_comment(node),
+            "array_element_initializer" => self.parse_array_element_initializer(node, point),
+            "assignment_expression" => self.parse_assignment_expression(node, point),
+            "comment" => self.parse_comment(node, point),
+            "class_constant_access_expression" if point.is_some() => {
+                self.parse_entity_type_handler(node)
+            }
             _ => None,
         }
     }
@@ -94,22 +861,210 @@ impl PhpParser {
                 TokenData::DrupalHookDefinition(DrupalHook {
                     name: name.to_string(),
                     parameters: Some(parameters.to_string()),
+                    documentation: node
+                        .prev_named_sibling()
+                        .filter(|comment_node| comment_node.kind() == "comment")
+                        .and_then(|comment_node| {
+                            get_docblock_markdown(self.get_node_text(&comment_node))
+                        }),
+                }),
+                node.range(),
+            ));
+        } else if let Some((_, theme)) = name.split_once("_preprocess_") {
+            let body_node = node.child_by_field_name("body")?;
+            let variables = PREPROCESS_VARIABLE
+                .captures_iter(self.get_node_text(&body_node))
+                .filter_map(|captures| Some(captures.name("name")?.as_str().to_string()))
+                .fold(vec![], |mut variables: Vec<String>, variable| {
+                    if !variables.contains(&variable) {
+                        variables.push(variable);
+                    }
+                    variables
+                });
+
+            return Some(Token::new(
+                TokenData::DrupalPreprocessDefinition(DrupalPreprocess {
+                    function_name: name.to_string(),
+                    theme: theme.to_string(),
+                    variables,
+                }),
+                node.range(),
+            ));
+        }
+        None
+    }
+
+    fn parse_assignment_expression(&self, node: Node, point: Option<Point>) -> Option<Token> {
+        // $build['#attached']['library'][] = 'core/drupal.dialog';
+        let left_node = node.child_by_field_name("left")?;
+        let right_node = node.child_by_field_name("right")?;
+
+        // $suggestions[] = 'node__' . $node->bundle();
+        if point.is_some() && self.get_node_text(&left_node).replace(' ', "") == "$suggestions[]" {
+            let mut value_node = right_node;
+            while value_node.kind() == "binary_expression" {
+                value_node = value_node.child_by_field_name("left")?;
+            }
+            return Some(Token::new(
+                TokenData::DrupalThemeSuggestion(self.get_string_value(&value_node)?.to_string()),
+                right_node.range(),
+            ));
+        }
+
+        let property = self.get_render_array_property(&left_node, true)?;
+        self.get_render_array_list_item(property, &right_node)
+    }
+
+    fn parse_array_element_initializer(&self, node: Node, point: Option<Point>) -> Option<Token> {
+        // '#attached' => ['library' => ['core/drupal.dialog']]
+        if node.named_child_count() == 1 {
+            let value_node = node.named_child(0)?;
+            let array_node = node.parent()?;
+            let property = match array_node.parent()? {
+                element if element.kind() == "array_element_initializer" => {
+                    let (key_node, _) = self.get_array_element_key_value(element)?;
+                    let (parent_key_node, _) =
+                        self.get_array_element_key_value(element.parent()?.parent()?)?;
+                    (
+                        self.get_string_value(&parent_key_node)?,
+                        self.get_string_value(&key_node)?,
+                    )
+                }
+                assignment if assignment.kind() == "assignment_expression" => {
+                    self.get_render_array_property(&assignment.child_by_field_name("left")?, false)?
+                }
+                // $build->addCacheContexts(['user.permissions']);
+                argument if argument.kind() == "argument" => {
+                    let call_node = argument.parent()?.parent()?;
+                    match self.get_node_text(&call_node.child_by_field_name("name")?) {
+                        "addCacheContexts" => ("#cache", "contexts"),
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            };
+            return self.get_render_array_list_item(property, &value_node);
+        }
+
+        let (key_node, value_node) = self.get_array_element_key_value(node)?;
+        let key = self.get_string_value(&key_node)?;
+
+        if key == "#theme" && point.is_some() {
+            return Some(Token::new(
+                TokenData::DrupalThemeReference(self.get_string_value(&value_node)?.to_string()),
+                value_node.range(),
+            ));
+        } else if key == "#type" {
+            return Some(Token::new(
+                TokenData::DrupalPluginReference(DrupalPluginReference {
+                    plugin_type: DrupalPluginType::RenderElement,
+                    plugin_id: self.get_string_value(&value_node)?.to_string(),
+                }),
+                value_node.range(),
+            ));
+        } else if key == "target_type" {
+            return Some(Token::new(
+                TokenData::DrupalPluginReference(DrupalPluginReference {
+                    plugin_type: DrupalPluginType::EntityType,
+                    plugin_id: self.get_string_value(&value_node)?.to_string(),
+                }),
+                value_node.range(),
+            ));
+        }
+
+        // Theme hooks are defined by the array returned from hook_theme().
+        if value_node.kind() == "array_creation_expression"
+            && node.parent()?.parent()?.kind() == "return_statement"
+        {
+            let function_node = get_closest_parent_by_kind(&node, "function_definition")?;
+            let function_name = self.get_node_text(&function_node.child_by_field_name("name")?);
+            if !function_name.ends_with("_theme") || function_name.starts_with("hook_") {
+                return None;
+            }
+
+            let mut template = None;
+            let mut variables = vec![];
+            for child in value_node.named_children(&mut value_node.walk()) {
+                let Some((child_key_node, child_value_node)) =
+                    self.get_array_element_key_value(child)
+                else {
+                    continue;
+                };
+                match self.get_string_value(&child_key_node) {
+                    Some("template") => {
+                        template = self
+                            .get_string_value(&child_value_node)
+                            .map(|value| value.to_string());
+                    }
+                    Some("render element") => {
+                        if let Some(value) = self.get_string_value(&child_value_node) {
+                            variables.push(value.to_string());
+                        }
+                    }
+                    Some("variables") => {
+                        for variable in
+                            child_value_node.named_children(&mut child_value_node.walk())
+                        {
+                            if let Some(variable_name) = self
+                                .get_array_element_key_value(variable)
+                                .and_then(|(variable_key, _)| self.get_string_value(&variable_key))
+                            {
+                                variables.push(variable_name.to_string());
+                            }
+                        }
+                    }
+                    _ => (),
+                }
+            }
+
+            return Some(Token::new(
+                TokenData::DrupalThemeDefinition(DrupalTheme {
+                    name: key.to_string(),
+                    template,
+                    variables,
                 }),
                 node.range(),
             ));
         }
+
         None
     }
 
-    fn parse_comment(&self, node: Node) -> Option<Token> {
+    fn parse_comment(&self, node: Node, point: Option<Point>) -> Option<Token> {
         let text = self.get_node_text(&node);
 
-        // A comment with the text "Implements hook_NAME" is a reference to a Drupal hook.
-        if text.contains("Implements hook_") {
-            let start_bytes = text.find("hook_")?;
-            let end_bytes = text.find("()")?;
+        // Entity type annotations reference their handler classes as strings.
+        if let Some(point) = point {
+            if text.contains("@ContentEntityType(") || text.contains("@ConfigEntityType(") {
+                let offset = position_to_byte(
+                    &self.source,
+                    Position::new(point.row as u32, point.column as u32),
+                )? - node.start_byte();
+                let class_name = ANNOTATION_CLASS_NAME
+                    .captures_iter(text)
+                    .filter_map(|captures| captures.name("name"))
+                    .find(|name| name.start() <= offset && offset <= name.end())?;
+                return Some(Token::new(
+                    TokenData::PhpClassReference(PhpClassName::from(class_name.as_str())),
+                    byte_range_to_range(
+                        &self.source,
+                        node.start_byte() + class_name.start(),
+                        node.start_byte() + class_name.end(),
+                    ),
+                ));
+            }
+        }
+
+        // The code covered by a test, f.e `@covers ::load`.
+        if let Some(token) = point
+            .filter(|_| text.contains("@covers"))
+            .and_then(|point| self.parse_covers_tag(node, point))
+        {
+            return Some(token);
+        }
 
-            let hook_name = &text[start_bytes..end_bytes];
+        // A comment with the text "Implements hook_NAME" is a reference to a Drupal hook.
+        if let Some(hook_name) = get_implemented_hook(text) {
             return Some(Token::new(
                 TokenData::DrupalHookReference(hook_name.to_string()),
                 node.range(),
@@ -119,8 +1074,60 @@ impl PhpParser {
         None
     }
 
+    /// Parse the class or method of a `@covers` or `@coversDefaultClass` tag under the cursor.
+    fn parse_covers_tag(&self, node: Node, point: Point) -> Option<Token> {
+        let text = self.get_node_text(&node);
+        let offset = position_to_byte(
+            &self.source,
+            Position::new(point.row as u32, point.column as u32),
+        )? - node.start_byte();
+        let value = PHPUNIT_COVERS
+            .captures_iter(text)
+            .filter_map(|captures| captures.name("value"))
+            .find(|value| value.start() <= offset && offset <= value.end())?;
+        let (class_name, method_name) = match value.as_str().split_once("::") {
+            Some(("", method_name)) => (self.get_covers_default_class(node)?, Some(method_name)),
+            Some((class_name, method_name)) => (class_name, Some(method_name)),
+            None => (value.as_str(), None),
+        };
+        let data = match method_name {
+            Some(method_name) => TokenData::PhpMethodReference(
+                PhpMethod::try_from(format!("{}::{}", class_name, method_name).as_str()).ok()?,
+            ),
+            None => TokenData::PhpClassReference(PhpClassName::from(class_name)),
+        };
+        Some(Token::new(
+            data,
+            byte_range_to_range(
+                &self.source,
+                node.start_byte() + value.start(),
+                node.start_byte() + value.end(),
+            ),
+        ))
+    }
+
+    /// Parse a handler class in an entity type attribute, f.e `NodeStorage::class` in
+    /// `#[ContentEntityType(handlers: ['storage' => NodeStorage::class])]`.
+    fn parse_entity_type_handler(&self, node: Node) -> Option<Token> {
+        let attribute_node = get_closest_parent_by_kind(&node, "attribute")?;
+        DrupalEntityTypeGroup::try_from(self.get_node_text(&attribute_node.child(0)?)).ok()?;
+        if self.get_node_text(&node.named_child(1)?) != "class" {
+            return None;
+        }
+
+        let name = self.get_node_text(&node.named_child(0)?);
+        Some(Token::new(
+            TokenData::PhpClassReference(self.resolve_class_name_at(&node, name)),
+            node.range(),
+        ))
+    }
+
     fn parse_call_expression(&self, node: Node, point: Option<Point>) -> Option<Token> {
-        let string_content = node.descendant_for_point_range(point?, point?)?;
+        // When indexing the whole file, the first argument is the string of interest.
+        let string_content = match point {
+            Some(point) => node.descendant_for_point_range(point, point),
+            None => self.get_first_string_argument(&node),
+        };
         let name_node = match node.kind() {
             "function_call_expression" => node.child_by_field_name("function"),
             _ => node.child_by_field_name("name"),
@@ -140,44 +1147,82 @@ impl PhpParser {
                         name: name.to_string(),
                         class_name: None,
                         service_name: Some(service_name.to_string()),
+                        parameters: vec![],
+                        visibility: PhpVisibility::Public,
+                        is_static: false,
+                        signature: None,
+                        summary: None,
                     }),
                     node.range(),
                 ));
             }
         }
 
+        let string_content = string_content?;
         if string_content.kind() != "string_content" {
             return None;
         }
 
-        if name == "fromRoute" || name == "createFromRoute" || name == "setRedirect" {
+        if let Some(plugin_type) = self.get_custom_plugin_reference_type(node, name) {
+            return Some(Token::new(
+                TokenData::DrupalPluginReference(DrupalPluginReference {
+                    plugin_type,
+                    plugin_id: self.get_node_text(&string_content).to_string(),
+                }),
+                node.range(),
+            ));
+        } else if name == "fromRoute" || name == "createFromRoute" || name == "setRedirect" {
             return Some(Token::new(
-                TokenData::DrupalRouteReference(self.get_node_text(&string_content).to_string()),
+                TokenData::DrupalRouteReference(self.get_node_text(&string_content).into()),
                 node.range(),
             ));
         } else if name == "service" {
             return Some(Token::new(
-                TokenData::DrupalServiceReference(self.get_node_text(&string_content).to_string()),
+                TokenData::DrupalServiceReference(self.get_node_text(&string_content).into()),
                 node.range(),
             ));
         } else if name == "hasPermission" {
             return Some(Token::new(
-                TokenData::DrupalPermissionReference(
-                    self.get_node_text(&string_content).to_string(),
-                ),
+                TokenData::DrupalPermissionReference(self.get_node_text(&string_content).into()),
                 node.range(),
             ));
-        }
-        // TODO: This is a quite primitive way to detect ContainerInterface::get.
-        // Can we somehow get the interface of a given variable?
+        } else if ["hasRole", "addRole", "removeRole"].contains(&name) {
+            return Some(Token::new(
+                TokenData::DrupalRoleReference(self.get_node_text(&string_content).to_string()),
+                node.range(),
+            ));
+        } else if name == "moduleExists" {
+            return Some(Token::new(
+                TokenData::DrupalModuleReference(self.get_node_text(&string_content).to_string()),
+                node.range(),
+            ));
+        } else if name == "config" || name == "getEditable" {
+            return Some(Token::new(
+                TokenData::DrupalConfigReference(self.get_node_text(&string_content).to_string()),
+                node.range(),
+            ));
+        } else if name == "hasField" || name == "getFieldDefinition" {
+            return Some(Token::new(
+                TokenData::DrupalFieldReference(self.get_node_text(&string_content).to_string()),
+                node.range(),
+            ));
+        }
+        // TODO: This is a quite primitive way to detect ContainerInterface::get.
+        // Can we somehow get the interface of a given variable?
         else if name == "get" {
             let object_node = node.child_by_field_name("object")?;
             let object = self.get_node_text(&object_node);
-            if object == "$container" {
+            if let Some(config_name) = self.get_config_name(&object_node) {
                 return Some(Token::new(
-                    TokenData::DrupalServiceReference(
-                        self.get_node_text(&string_content).to_string(),
-                    ),
+                    TokenData::DrupalConfigKeyReference(DrupalConfigKeyReference {
+                        config_name,
+                        key: self.get_node_text(&string_content).to_string(),
+                    }),
+                    node.range(),
+                ));
+            } else if object == "$container" {
+                return Some(Token::new(
+                    TokenData::DrupalServiceReference(self.get_node_text(&string_content).into()),
                     node.range(),
                 ));
             } else if object.contains("queueFactory") {
@@ -188,6 +1233,21 @@ impl PhpParser {
                     }),
                     node.range(),
                 ));
+            } else if let Some(entity_type) = self.get_entity_type_hint(&object_node, 0) {
+                return Some(Token::new(
+                    TokenData::DrupalEntityFieldReference(DrupalEntityFieldReference {
+                        entity_type,
+                        field_name: self.get_node_text(&string_content).to_string(),
+                    }),
+                    node.range(),
+                ));
+            } else if self.get_node_text(&string_content).starts_with("field_") {
+                return Some(Token::new(
+                    TokenData::DrupalFieldReference(
+                        self.get_node_text(&string_content).to_string(),
+                    ),
+                    node.range(),
+                ));
             }
         } else if name == "getStorage" {
             let object_node = node.child_by_field_name("object")?;
@@ -223,6 +1283,50 @@ impl PhpParser {
                     node.range(),
                 ));
             }
+        } else if name == "set" || name == "condition" || name == "loadByProperties" {
+            let object_node = node.child_by_field_name("object")?;
+            let string_node = string_content.parent()?;
+            let entity_type = match name {
+                "set" => self.get_entity_type_hint(&object_node, 0)?,
+                "condition" => {
+                    let entity_type = self.get_query_entity_type(&object_node, 0)?;
+                    // Only the first argument is a field name.
+                    let argument_node = string_node.parent()?;
+                    if argument_node.kind() != "argument"
+                        || argument_node.prev_named_sibling().is_some()
+                    {
+                        return self.parse_entity_bundle_reference(node, string_node, entity_type);
+                    }
+                    DrupalEntityTypeHint::Id(entity_type)
+                }
+                _ => {
+                    let entity_type = self.get_storage_entity_type(&object_node, 0)?;
+                    // Only the keys of the properties are field names.
+                    let element_node = string_node.parent()?;
+                    if element_node.kind() != "array_element_initializer"
+                        || element_node.named_child(0)? != string_node
+                        || element_node.named_child_count() != 2
+                    {
+                        return self.parse_entity_bundle_reference(node, string_node, entity_type);
+                    }
+                    DrupalEntityTypeHint::Id(entity_type)
+                }
+            };
+            return Some(Token::new(
+                TokenData::DrupalEntityFieldReference(DrupalEntityFieldReference {
+                    entity_type,
+                    field_name: self.get_node_text(&string_content).to_string(),
+                }),
+                node.range(),
+            ));
+        } else if name == "entityQuery" {
+            return Some(Token::new(
+                TokenData::DrupalPluginReference(DrupalPluginReference {
+                    plugin_type: DrupalPluginType::EntityType,
+                    plugin_id: self.get_node_text(&string_content).to_string(),
+                }),
+                node.range(),
+            ));
         } else if name == "queue" {
             return Some(Token::new(
                 TokenData::DrupalPluginReference(DrupalPluginReference {
@@ -232,10 +1336,14 @@ impl PhpParser {
                 node.range(),
             ));
         } else if name == "t" {
+            let placeholders = node
+                .child_by_field_name("arguments")
+                .and_then(|arguments_node| arguments_node.named_child(1))
+                .map(|argument_node| self.get_node_text(&argument_node).to_string());
             return Some(Token::new(
                 TokenData::DrupalTranslationString(DrupalTranslationString {
                     string: self.get_node_text(&string_content).to_string(),
-                    placeholders: None,
+                    placeholders,
                 }),
                 node.range(),
             ));
@@ -244,6 +1352,95 @@ impl PhpParser {
         None
     }
 
+    /// Parse a bundle that entities are filtered by, f.e `'article'` in `condition('type', 'article')`
+    /// or `loadByProperties(['type' => 'article'])`, including bundles in a list of values.
+    fn parse_entity_bundle_reference(
+        &self,
+        node: Node,
+        string_node: Node,
+        entity_type: String,
+    ) -> Option<Token> {
+        let mut value_node = string_node;
+        let key_node = loop {
+            let parent = value_node.parent()?;
+            match parent.kind() {
+                // A list of values, f.e `['article', 'page']`.
+                "array_element_initializer" if parent.named_child_count() == 1 => {
+                    value_node = parent.parent()?;
+                }
+                "array_element_initializer" if parent.named_child(1)? == value_node => {
+                    break parent.named_child(0)?;
+                }
+                // The value argument of `condition()`, which follows the field name.
+                "argument" => {
+                    let field_argument = parent.prev_named_sibling()?;
+                    if field_argument.prev_named_sibling().is_some() {
+                        return None;
+                    }
+                    break field_argument.named_child(0)?;
+                }
+                _ => return None,
+            }
+        };
+        if !BUNDLE_KEYS.contains(&self.get_string_value(&key_node)?) {
+            return None;
+        }
+
+        Some(Token::new(
+            TokenData::DrupalEntityBundleReference(DrupalEntityBundleReference {
+                entity_type,
+                bundle: self.get_string_value(&string_node)?.to_string(),
+            }),
+            node.range(),
+        ))
+    }
+
+    /// Get the hooks invoked by a module handler or theme manager call, f.e `hook_form_alter` for
+    /// `$this->moduleHandler->alter('form', $form)`.
+    fn get_invoked_hooks(&self, node: &Node, name: &str) -> Option<Vec<String>> {
+        let (index, is_alter) = match name {
+            "invokeAll" | "invokeAllWith" => (0, false),
+            "invoke" | "invokeAllDeprecated" => (1, false),
+            "invokeDeprecated" => (2, false),
+            "alter" => (0, true),
+            "alterDeprecated" => (1, true),
+            _ => return None,
+        };
+        let object = self.get_node_text(&node.child_by_field_name("object")?);
+        if !["moduleHandler", "module_handler", "themeManager"]
+            .iter()
+            .any(|handler| object.contains(handler))
+        {
+            return None;
+        }
+
+        // Alter calls can invoke multiple hooks, f.e `alter(['form', 'form_node_form'])`.
+        let argument = node
+            .child_by_field_name("arguments")?
+            .named_child(index)?
+            .named_child(0)?;
+        let names = match argument.kind() {
+            "array_creation_expression" => argument
+                .named_children(&mut argument.walk())
+                .filter_map(|element| {
+                    Some(self.get_string_value(&element.named_child(0)?)?.to_string())
+                })
+                .collect(),
+            _ => vec![self.get_string_value(&argument)?.to_string()],
+        };
+        Some(
+            names
+                .into_iter()
+                // Skip names built from variables, f.e `"form_$form_id"`.
+                .filter(|name| !name.contains(['$', '{']))
+                .map(|name| match is_alter {
+                    true => format!("hook_{}_alter", name),
+                    false => format!("hook_{}", name),
+                })
+                .collect(),
+        )
+    }
+
     fn parse_class_declaration(&self, node: Node) -> Option<Token> {
         let mut methods: HashMap<String, Box<Token>> = HashMap::new();
         if let Some(body_node) = node.child_by_field_name("body") {
@@ -265,55 +1462,327 @@ impl PhpParser {
             if comment_node.kind() == "comment" {
                 let text = self.get_node_text(&comment_node);
 
-                let re = Regex::new(r#"\*\s*@(?<type>.+)\("#).unwrap();
                 let mut plugin_type: Option<DrupalPluginType> = None;
-                if let Some(captures) = re.captures(text) {
+                let mut entity_type_group: Option<DrupalEntityTypeGroup> = None;
+                if let Some(captures) = ANNOTATION_TYPE.captures(text) {
                     if let Some(str) = captures.name("type") {
-                        plugin_type = DrupalPluginType::try_from(str.as_str()).ok();
+                        plugin_type = self.get_plugin_type(str.as_str());
+                        entity_type_group = DrupalEntityTypeGroup::try_from(str.as_str()).ok();
                     }
                 }
 
-                let re = Regex::new(r#"id\s*=\s*"(?<id>[^"]+)""#).unwrap();
                 let mut plugin_id: Option<String> = None;
-                if let Some(captures) = re.captures(text) {
-                    if let Some(str) = captures.name("id") {
+                if let Some(captures) = ANNOTATION_ID.captures(text) {
+                    if let Some(str) = captures.name("id").or(captures.name("value_id")) {
                         plugin_id = Some(str.as_str().to_string());
                     }
                 }
 
-                if let (Some(plugin_type), Some(plugin_id)) = (plugin_type, plugin_id) {
+                if let (Some(group), Some(id)) = (entity_type_group, plugin_id.clone()) {
+                    class_attribute = Some(ClassAttribute::EntityType(DrupalEntityType {
+                        id,
+                        label: get_annotation_value(text, "label"),
+                        group,
+                        bundle_entity_type: get_annotation_value(text, "bundle_entity_type"),
+                        base_table: get_annotation_value(text, "base_table"),
+                        data_table: get_annotation_value(text, "data_table"),
+                        base_fields: vec![],
+                    }));
+                } else if let (Some(plugin_type), Some(plugin_id)) = (plugin_type, plugin_id) {
                     class_attribute = Some(ClassAttribute::Plugin(DrupalPlugin {
                         plugin_type,
                         plugin_id,
+                        label: get_annotation_value(text, "label")
+                            .or_else(|| get_annotation_value(text, "title")),
                         usage_example: self.extract_usage_example_from_comment(&comment_node),
                     }));
                 };
             }
         }
 
+        if let Some(ClassAttribute::EntityType(entity_type)) = &mut class_attribute {
+            if let Some(method) = methods.get("baseFieldDefinitions") {
+                let body = &self.source[method.range.start_byte..method.range.end_byte];
+                let captures: Vec<regex::Captures> = BASE_FIELD.captures_iter(body).collect();
+                entity_type.base_fields = captures
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, field_captures)| {
+                        // The settings are set in the statement defining the field.
+                        let start = field_captures.get(0)?.end();
+                        let end = captures
+                            .get(index + 1)
+                            .and_then(|captures| captures.get(0))
+                            .map_or(body.len(), |next_field| next_field.start());
+                        let statement = body[start..end].split(';').next().unwrap_or_default();
+                        Some(DrupalEntityField {
+                            name: field_captures.name("name")?.as_str().to_string(),
+                            field_type: field_captures
+                                .name("type")
+                                .map(|value| value.as_str().to_string()),
+                            settings: BASE_FIELD_SETTING
+                                .captures_iter(statement)
+                                .filter_map(|captures| {
+                                    Some((
+                                        captures.name("name")?.as_str().to_string(),
+                                        captures
+                                            .name("value")?
+                                            .as_str()
+                                            .trim_matches(['\'', '"'])
+                                            .to_string(),
+                                    ))
+                                })
+                                .collect(),
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        let mut root_node = node;
+        while let Some(parent) = root_node.parent() {
+            root_node = parent;
+        }
+        let imports = self.get_imported_class_names_from_root(root_node);
+        let namespace = self
+            .get_class_name_from_node(node)
+            .map(|class_name| class_name.get_namespace().to_string());
+        let resolve = |name_node: Node| {
+            self.resolve_class_name(
+                self.get_node_text(&name_node),
+                &imports,
+                namespace.as_deref(),
+            )
+        };
+
+        let mut interfaces = vec![];
+        let mut parent = None;
+        for clause in node.children(&mut node.walk()) {
+            match clause.kind() {
+                "class_interface_clause" => {
+                    interfaces.extend(clause.named_children(&mut clause.walk()).map(resolve))
+                }
+                "base_clause" => parent = clause.named_child(0).map(resolve),
+                _ => {}
+            }
+        }
+
+        let mut traits = vec![];
+        if let Some(body_node) = node.child_by_field_name("body") {
+            for use_node in body_node
+                .named_children(&mut body_node.walk())
+                .filter(|child| child.kind() == "use_declaration")
+            {
+                traits.extend(
+                    use_node
+                        .named_children(&mut use_node.walk())
+                        .filter(|child| child.kind() == "name" || child.kind() == "qualified_name")
+                        .map(resolve),
+                );
+            }
+        }
+
         Some(Token::new(
             TokenData::PhpClassDefinition(PhpClass {
                 name: self.get_class_name_from_node(node)?,
+                summary: node
+                    .prev_named_sibling()
+                    .filter(|comment_node| comment_node.kind() == "comment")
+                    .and_then(|comment_node| {
+                        get_docblock_summary(self.get_node_text(&comment_node))
+                    }),
                 attribute: class_attribute,
+                interfaces,
+                parent,
+                traits,
                 methods,
+                constants: self.get_class_constants(node),
             }),
             node.range(),
         ))
     }
 
+    fn get_class_constants(&self, node: Node) -> Vec<PhpClassConstant> {
+        let Some(body_node) = node.child_by_field_name("body") else {
+            return vec![];
+        };
+        body_node
+            .named_children(&mut body_node.walk())
+            .filter(|child| child.kind() == "const_declaration")
+            .flat_map(|declaration| {
+                declaration
+                    .named_children(&mut declaration.walk())
+                    .filter(|child| child.kind() == "const_element")
+                    .collect::<Vec<Node>>()
+            })
+            .filter_map(|element| {
+                Some(PhpClassConstant {
+                    name: self.get_node_text(&element.named_child(0)?).to_string(),
+                    value: element
+                        .named_child(1)
+                        .and_then(|value_node| self.get_string_value(&value_node))
+                        .map(|value| value.to_string()),
+                })
+            })
+            .collect()
+    }
+
+    /// Get the ranges of the arguments of the route call at a position, f.e in
+    /// `Url::fromRoute('entity.node.canonical', ['node' => 1])`: the route name inside the quotes,
+    /// and the parameters including the leading comma, which is empty without parameters.
+    pub fn get_route_call_ranges(
+        &self,
+        position: Position,
+    ) -> Option<(lsp_types::Range, lsp_types::Range)> {
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into())?;
+        let mut call_node = get_node_at_position(&tree, position)?;
+        while !matches!(
+            call_node.kind(),
+            "scoped_call_expression" | "member_call_expression" | "function_call_expression"
+        ) {
+            call_node = call_node.parent()?;
+        }
+        let name = self.get_node_text(&call_node.child_by_field_name("name")?);
+        if !["fromRoute", "createFromRoute", "setRedirect"].contains(&name) {
+            return None;
+        }
+
+        let arguments_node = call_node.child_by_field_name("arguments")?;
+        let mut arguments = arguments_node
+            .named_children(&mut arguments_node.walk())
+            .filter(|argument| argument.kind() == "argument")
+            .collect::<Vec<Node>>()
+            .into_iter();
+        let name_argument = arguments.next()?;
+        let string_node = name_argument.named_child(0)?;
+        if !matches!(string_node.kind(), "string" | "encapsed_string") {
+            return None;
+        }
+        let parameters_end = arguments.next().unwrap_or(name_argument).end_position();
+
+        let to_position = |point: Point| Position::new(point.row as u32, point.column as u32);
+        let (start, end) = (string_node.start_position(), string_node.end_position());
+        Some((
+            lsp_types::Range::new(
+                Position::new(start.row as u32, start.column as u32 + 1),
+                Position::new(end.row as u32, (end.column as u32).saturating_sub(1)),
+            ),
+            lsp_types::Range::new(
+                to_position(name_argument.end_position()),
+                to_position(parameters_end),
+            ),
+        ))
+    }
+
+    /// Get the name of the method that contains a position.
+    pub fn get_method_name_at_position(&self, position: Position) -> Option<String> {
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into())?;
+        let node = get_node_at_position(&tree, position)?;
+        let method_node = get_closest_parent_by_kind(&node, "method_declaration")?;
+        Some(
+            self.get_node_text(&method_node.child_by_field_name("name")?)
+                .to_string(),
+        )
+    }
+
+    /// Get the definition of the class that contains the position, f.e to complete the methods
+    /// that can be called on `$this`.
+    pub fn get_class_at_position(&self, position: Position) -> Option<Token> {
+        let tree = get_tree(&self.source, &tree_sitter_php::LANGUAGE_PHP.into())?;
+        let node = get_node_at_position(&tree, position)?;
+        let class_node = get_closest_parent_by_kind(&node, "class_declaration")?;
+        self.parse_class_declaration(class_node)
+    }
+
     fn parse_method_declaration(&self, node: Node) -> Option<Token> {
         if node.kind() != "method_declaration" {
             return None;
         }
 
-        let class_node = get_closest_parent_by_kind(&node, "class_declaration")?;
+        let class_node = get_closest_parent_by_kind(&node, "class_declaration")
+            .or_else(|| get_closest_parent_by_kind(&node, "trait_declaration"))?;
+
+        let class_name = self.get_class_name_from_node(class_node);
+
+        let mut parameters = vec![];
+        if let Some(parameters_node) = node.child_by_field_name("parameters") {
+            let mut root_node = node;
+            while let Some(parent) = root_node.parent() {
+                root_node = parent;
+            }
+            let imports = self.get_imported_class_names_from_root(root_node);
+            let namespace = class_name
+                .as_ref()
+                .map(|class_name| class_name.get_namespace().to_string());
+
+            for parameter_node in parameters_node.named_children(&mut parameters_node.walk()) {
+                let Some(name_node) = parameter_node.child_by_field_name("name") else {
+                    continue;
+                };
+                let type_node = parameter_node.child_by_field_name("type");
+                // Only resolve plain class types, f.e `EntityTypeManagerInterface` or `?Url`.
+                let class_type_node = type_node.and_then(|type_node| match type_node.kind() {
+                    "named_type" => Some(type_node),
+                    "optional_type" => type_node
+                        .named_child(0)
+                        .filter(|child| child.kind() == "named_type"),
+                    _ => None,
+                });
+
+                parameters.push(PhpParameter {
+                    name: self
+                        .get_node_text(&name_node)
+                        .trim_start_matches('$')
+                        .to_string(),
+                    type_hint: type_node
+                        .map(|type_node| self.get_node_text(&type_node).to_string()),
+                    class_name: class_type_node.map(|class_type_node| {
+                        self.resolve_class_name(
+                            self.get_node_text(&class_type_node),
+                            &imports,
+                            namespace.as_deref(),
+                        )
+                    }),
+                    is_optional: parameter_node
+                        .child_by_field_name("default_value")
+                        .is_some(),
+                    is_variadic: parameter_node.kind() == "variadic_parameter",
+                });
+            }
+        }
+
+        let visibility = node
+            .children(&mut node.walk())
+            .find(|child| child.kind() == "visibility_modifier")
+            .map(|visibility_node| PhpVisibility::from(self.get_node_text(&visibility_node)))
+            .unwrap_or_default();
+        let is_static = node
+            .children(&mut node.walk())
+            .any(|child| child.kind() == "static_modifier");
+
+        let signature_end = node
+            .child_by_field_name("body")
+            .map_or(node.end_byte(), |body_node| body_node.start_byte());
+        let signature = self.source[node.start_byte()..signature_end]
+            .trim_end_matches([' ', '\n', ';'])
+            .to_string();
 
         let name_node = node.child_by_field_name("name")?;
         Some(Token::new(
             TokenData::PhpMethodDefinition(PhpMethod {
                 name: self.get_node_text(&name_node).to_string(),
-                class_name: self.get_class_name_from_node(class_node),
+                class_name,
                 service_name: None,
+                parameters,
+                visibility,
+                is_static,
+                signature: Some(signature),
+                summary: node
+                    .prev_named_sibling()
+                    .filter(|comment_node| comment_node.kind() == "comment")
+                    .and_then(|comment_node| {
+                        get_docblock_summary(self.get_node_text(&comment_node))
+                    }),
             }),
             node.range(),
         ))
@@ -325,43 +1794,131 @@ impl PhpParser {
         }
 
         let mut plugin_id = String::default();
+        let mut label = None;
 
-        // TODO: Look into improving this if we want to extract more than plugin id.
         let parameters_node = node.child_by_field_name("parameters")?;
+        if let Ok(group) = DrupalEntityTypeGroup::try_from(self.get_node_text(&node.child(0)?)) {
+            return self.parse_entity_type_attribute(parameters_node, group);
+        }
+
+        // TODO: Look into improving this if we want to extract more than plugin id.
         for argument in parameters_node.named_children(&mut parameters_node.walk()) {
-            // In the case of f.e `#[FormElement('date')]` there is no `id` field.
-            if self.get_node_text(&argument).starts_with("'")
-                && self.get_node_text(&argument).ends_with("'")
-            {
-                plugin_id = self
-                    .get_node_text(&argument)
-                    .trim_matches(|c| c == '"' || c == '\'')
-                    .to_string();
-                break;
-            }
-            let argument_name = argument.child_by_field_name("name")?;
-            if self.get_node_text(&argument_name) == "id" {
-                plugin_id = self
-                    .get_node_text(&argument.named_child(1)?)
-                    .trim_matches(|c| c == '"' || c == '\'')
-                    .to_string()
+            // In the case of f.e `#[FormElement('date')]` or `#[ViewsField("boolean")]` there is
+            // no `id` field.
+            let Some(argument_name) = argument.child_by_field_name("name") else {
+                if let Some(value) = self.get_string_value(&argument.named_child(0)?) {
+                    plugin_id = value.to_string();
+                    break;
+                }
+                return None;
+            };
+            match self.get_node_text(&argument_name) {
+                "id" => {
+                    plugin_id = self
+                        .get_node_text(&argument.named_child(1)?)
+                        .trim_matches(|c| c == '"' || c == '\'')
+                        .to_string()
+                }
+                "label" | "title" if label.is_none() => {
+                    label = self.get_first_string_value(argument.named_child(1)?)
+                }
+                _ => {}
             }
         }
 
-        match DrupalPluginType::try_from(self.get_node_text(&node.child(0)?)) {
-            Ok(plugin_type) => Some(ClassAttribute::Plugin(DrupalPlugin {
+        match self.get_plugin_type(self.get_node_text(&node.child(0)?)) {
+            Some(plugin_type) => Some(ClassAttribute::Plugin(DrupalPlugin {
                 plugin_id,
                 plugin_type,
+                label,
                 usage_example: self.extract_usage_example_from_comment(
                     &node.parent()?.parent()?.parent()?.prev_named_sibling()?,
                 ),
             })),
-            Err(_) => None,
+            None => None,
+        }
+    }
+
+    /// Get the plugin type of an attribute or annotation, f.e `Block`, including the plugin types
+    /// of contrib modules.
+    fn get_plugin_type(&self, name: &str) -> Option<DrupalPluginType> {
+        DrupalPluginType::try_from(name).ok().or_else(|| {
+            self.plugin_types
+                .iter()
+                .find(|plugin_type| plugin_type.attribute == name)
+                .map(DrupalPluginType::Custom)
+        })
+    }
+
+    /// Get the plugin type of a contrib module whose plugin ids are passed to a call, based on the
+    /// reference patterns of the plugin type.
+    fn get_custom_plugin_reference_type(&self, node: Node, name: &str) -> Option<DrupalPluginType> {
+        let object = node
+            .child_by_field_name("object")
+            .or_else(|| node.child_by_field_name("scope"))
+            .map(|object_node| self.get_node_text(&object_node));
+        self.plugin_types
+            .iter()
+            .find(|plugin_type| {
+                plugin_type.references.iter().any(|pattern| {
+                    pattern.method == name
+                        && pattern.object.as_ref().is_none_or(|pattern_object| {
+                            object.is_some_and(|object| object.contains(pattern_object.as_str()))
+                        })
+                })
+            })
+            .map(DrupalPluginType::Custom)
+    }
+
+    /// Parse a `#[ContentEntityType(...)]` or `#[ConfigEntityType(...)]` attribute.
+    fn parse_entity_type_attribute(
+        &self,
+        parameters_node: Node,
+        group: DrupalEntityTypeGroup,
+    ) -> Option<ClassAttribute> {
+        let mut arguments: HashMap<&str, Node> = HashMap::new();
+        for argument in parameters_node.named_children(&mut parameters_node.walk()) {
+            if let (Some(name_node), Some(value_node)) = (
+                argument.child_by_field_name("name"),
+                argument.named_child(argument.named_child_count().checked_sub(1)?),
+            ) {
+                arguments.insert(self.get_node_text(&name_node), value_node);
+            }
+        }
+
+        let get_argument_string = |name: &str| self.get_first_string_value(*arguments.get(name)?);
+
+        Some(ClassAttribute::EntityType(DrupalEntityType {
+            id: get_argument_string("id")?,
+            label: get_argument_string("label"),
+            group,
+            bundle_entity_type: get_argument_string("bundle_entity_type"),
+            base_table: get_argument_string("base_table"),
+            data_table: get_argument_string("data_table"),
+            base_fields: vec![],
+        }))
+    }
+
+    /// Get the first string in an expression, since labels are usually wrapped, f.e
+    /// `new TranslatableMarkup('Content')`.
+    fn get_first_string_value(&self, node: Node) -> Option<String> {
+        let mut nodes = vec![node];
+        while let Some(node) = nodes.pop() {
+            if let Some(value) = self.get_string_value(&node) {
+                return Some(value.to_string());
+            }
+            nodes.extend(
+                node.named_children(&mut node.walk())
+                    .collect::<Vec<Node>>()
+                    .into_iter()
+                    .rev(),
+            );
         }
+        None
     }
 
     fn get_class_name_from_node(&self, node: Node) -> Option<PhpClassName> {
-        if node.kind() != "class_declaration" {
+        if node.kind() != "class_declaration" && node.kind() != "trait_declaration" {
             return None;
         }
         let mut prev = node.prev_sibling();
@@ -382,6 +1939,286 @@ impl PhpParser {
         ))
     }
 
+    /// Resolve a class name as written in the source to its fully qualified name.
+    fn resolve_class_name(
+        &self,
+        name: &str,
+        imports: &HashMap<String, PhpClassName>,
+        namespace: Option<&str>,
+    ) -> PhpClassName {
+        if name.starts_with('\\') {
+            return PhpClassName::from(name);
+        }
+
+        let (first, rest) = match name.split_once('\\') {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (name, None),
+        };
+        match (imports.get(first), rest, namespace) {
+            (Some(import), Some(rest), _) => {
+                PhpClassName::from(format!("{}\\{}", import, rest).as_str())
+            }
+            (Some(import), None, _) => import.clone(),
+            (None, _, Some(namespace)) => {
+                PhpClassName::from(format!("{}\\{}", namespace, name).as_str())
+            }
+            (None, _, None) => PhpClassName::from(name),
+        }
+    }
+
+    /// Get the name of the config object that a node evaluates to, f.e `system.site` for
+    /// `\Drupal::config('system.site')` or a variable that it was assigned to.
+    fn get_config_name(&self, node: &Node) -> Option<String> {
+        match node.kind() {
+            "member_call_expression" | "scoped_call_expression" => {
+                let name = self.get_node_text(&node.child_by_field_name("name")?);
+                if name != "config" && name != "getEditable" {
+                    return None;
+                }
+                let string_content = self.get_first_string_argument(node)?;
+                Some(self.get_node_text(&string_content).to_string())
+            }
+            "variable_name" => {
+                let function_node = get_closest_parent_by_kind(node, "method_declaration")
+                    .or_else(|| get_closest_parent_by_kind(node, "function_definition"))?;
+                let before_node = self
+                    .source
+                    .get(function_node.start_byte()..node.start_byte())?;
+                let variable = self.get_node_text(node);
+                let captures = CONFIG_ASSIGNMENT
+                    .captures_iter(before_node)
+                    .filter(|captures| {
+                        captures
+                            .name("variable")
+                            .is_some_and(|name| name.as_str() == variable)
+                    })
+                    .last()?;
+                Some(captures.name("name")?.as_str().to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the last two keys of a render array property, f.e `('#attached', 'library')` for
+    /// `$build['#attached']['library']`, which must end with `[]` when appending.
+    fn get_render_array_property(&self, node: &Node, append: bool) -> Option<(&str, &str)> {
+        let captures = RENDER_ARRAY_PROPERTY.captures(self.get_node_text(node))?;
+        if captures.name("append").is_some() != append {
+            return None;
+        }
+        Some((
+            captures.name("parent")?.as_str(),
+            captures.name("key")?.as_str(),
+        ))
+    }
+
+    /// Get the reference of an item in a list of a render array property, f.e a library in
+    /// `'#attached' => ['library' => [...]]`.
+    fn get_render_array_list_item(&self, property: (&str, &str), node: &Node) -> Option<Token> {
+        let value = self.get_string_value(node)?.to_string();
+        let data = match property {
+            ("#attached", "library") => TokenData::DrupalLibraryReference(value),
+            ("#cache", "contexts") => TokenData::DrupalCacheContextReference(value),
+            _ => return None,
+        };
+        Some(Token::new(data, node.range()))
+    }
+
+    /// Get the entity type of an expression that evaluates to an entity, f.e a `NodeInterface $node`
+    /// parameter or `$storage->load($id)`.
+    fn get_entity_type_hint(&self, node: &Node, depth: usize) -> Option<DrupalEntityTypeHint> {
+        match node.kind() {
+            "member_call_expression" => {
+                let name = self.get_node_text(&node.child_by_field_name("name")?);
+                if !["load", "loadUnchanged", "loadRevision", "create"].contains(&name) {
+                    return None;
+                }
+                let object_node = node.child_by_field_name("object")?;
+                Some(DrupalEntityTypeHint::Id(
+                    self.get_storage_entity_type(&object_node, depth)?,
+                ))
+            }
+            "scoped_call_expression" => {
+                let name = self.get_node_text(&node.child_by_field_name("name")?);
+                if name != "load" && name != "create" {
+                    return None;
+                }
+                let scope = self.get_node_text(&node.child_by_field_name("scope")?);
+                Some(DrupalEntityTypeHint::Class(
+                    self.resolve_class_name_at(node, scope),
+                ))
+            }
+            "variable_name" => match self.get_parameter_type(node) {
+                Some(class_name) => Some(DrupalEntityTypeHint::Class(class_name)),
+                None => {
+                    self.get_entity_type_hint(&self.get_assigned_value(node, depth)?, depth + 1)
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// Get the entity type of an entity storage, f.e `$this->entityTypeManager->getStorage('node')`.
+    fn get_storage_entity_type(&self, node: &Node, depth: usize) -> Option<String> {
+        match node.kind() {
+            "member_call_expression" => {
+                if self.get_node_text(&node.child_by_field_name("name")?) != "getStorage" {
+                    return None;
+                }
+                let string_content = self.get_first_string_argument(node)?;
+                Some(self.get_node_text(&string_content).to_string())
+            }
+            "variable_name" | "member_access_expression" => {
+                self.get_storage_entity_type(&self.get_assigned_value(node, depth)?, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the entity type of an entity query, f.e `\Drupal::entityQuery('node')`.
+    fn get_query_entity_type(&self, node: &Node, depth: usize) -> Option<String> {
+        match node.kind() {
+            "scoped_call_expression" => {
+                if self.get_node_text(&node.child_by_field_name("name")?) != "entityQuery" {
+                    return None;
+                }
+                let string_content = self.get_first_string_argument(node)?;
+                Some(self.get_node_text(&string_content).to_string())
+            }
+            "member_call_expression" => {
+                let object_node = node.child_by_field_name("object")?;
+                match self.get_node_text(&node.child_by_field_name("name")?) {
+                    "getQuery" => self.get_storage_entity_type(&object_node, depth),
+                    // Query methods return the query itself, f.e `->accessCheck(TRUE)`.
+                    _ => self.get_query_entity_type(&object_node, depth),
+                }
+            }
+            "variable_name" | "member_access_expression" => {
+                self.get_query_entity_type(&self.get_assigned_value(node, depth)?, depth + 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the value that was last assigned to a variable, or to a property of `$this` anywhere in
+    /// the class.
+    fn get_assigned_value<'a>(&self, node: &Node<'a>, depth: usize) -> Option<Node<'a>> {
+        if depth > MAX_ASSIGNMENT_DEPTH {
+            return None;
+        }
+        let scope_kinds: &[&str] = match node.kind() {
+            "variable_name" => &["method_declaration", "function_definition"],
+            "member_access_expression"
+                if self.get_node_text(&node.child_by_field_name("object")?) == "$this" =>
+            {
+                &["class_declaration"]
+            }
+            _ => return None,
+        };
+        let mut scope_node = node.parent()?;
+        while !scope_kinds.contains(&scope_node.kind()) {
+            scope_node = scope_node.parent()?;
+        }
+
+        let name = self.get_node_text(node);
+        let mut value: Option<Node<'a>> = None;
+        let mut nodes = vec![scope_node];
+        while let Some(current) = nodes.pop() {
+            if current.kind() == "assignment_expression"
+                && (node.kind() != "variable_name" || current.end_byte() <= node.start_byte())
+            {
+                if let (Some(left), Some(right)) = (
+                    current.child_by_field_name("left"),
+                    current.child_by_field_name("right"),
+                ) {
+                    if self.get_node_text(&left) == name
+                        && value.is_none_or(|value| value.start_byte() < right.start_byte())
+                    {
+                        value = Some(right);
+                    }
+                }
+            }
+            nodes.extend(current.named_children(&mut current.walk()));
+        }
+        value
+    }
+
+    /// Get the class that a variable is typed as, when it is a parameter of the function.
+    fn get_parameter_type(&self, node: &Node) -> Option<PhpClassName> {
+        let mut function_node = node.parent()?;
+        while !["method_declaration", "function_definition"].contains(&function_node.kind()) {
+            function_node = function_node.parent()?;
+        }
+        let parameters_node = function_node.child_by_field_name("parameters")?;
+        let parameter_node = parameters_node
+            .named_children(&mut parameters_node.walk())
+            .find(|parameter_node| {
+                parameter_node
+                    .child_by_field_name("name")
+                    .is_some_and(|name_node| {
+                        self.get_node_text(&name_node) == self.get_node_text(node)
+                    })
+            })?;
+
+        let type_node = parameter_node.child_by_field_name("type")?;
+        let type_node = match type_node.kind() {
+            "optional_type" => type_node.named_child(0)?,
+            _ => type_node,
+        };
+        if type_node.kind() != "named_type" {
+            return None;
+        }
+        Some(self.resolve_class_name_at(node, self.get_node_text(&type_node)))
+    }
+
+    /// Resolve a class name that is used in a node, taking imports and the namespace into account.
+    fn resolve_class_name_at(&self, node: &Node, name: &str) -> PhpClassName {
+        let mut root_node = *node;
+        while let Some(parent) = root_node.parent() {
+            root_node = parent;
+        }
+        let namespace = root_node
+            .children(&mut root_node.walk())
+            .find(|child| child.kind() == "namespace_definition")
+            .and_then(|namespace_node| namespace_node.child_by_field_name("name"))
+            .map(|name_node| self.get_node_text(&name_node).to_string());
+        self.resolve_class_name(
+            name,
+            &self.get_imported_class_names_from_root(root_node),
+            namespace.as_deref(),
+        )
+    }
+
+    fn get_first_string_argument<'a>(&self, node: &Node<'a>) -> Option<Node<'a>> {
+        let arguments_node = node.child_by_field_name("arguments")?;
+        let argument_node = arguments_node.named_child(0)?;
+        let string_node = argument_node.named_child(0)?;
+        if string_node.kind() != "string" && string_node.kind() != "encapsed_string" {
+            return None;
+        }
+        string_node
+            .named_children(&mut string_node.walk())
+            .find(|child| child.kind() == "string_content")
+    }
+
+    fn get_array_element_key_value<'a>(&self, node: Node<'a>) -> Option<(Node<'a>, Node<'a>)> {
+        if node.kind() != "array_element_initializer" || node.named_child_count() != 2 {
+            return None;
+        }
+        Some((node.named_child(0)?, node.named_child(1)?))
+    }
+
+    /// Get the content of a string literal node, without the surrounding quotes.
+    fn get_string_value(&self, node: &Node) -> Option<&str> {
+        match node.kind() {
+            "string" | "encapsed_string" => Some(
+                self.get_node_text(node)
+                    .trim_matches(|c| c == '"' || c == '\''),
+            ),
+            _ => None,
+        }
+    }
+
     fn get_node_text(&self, node: &Node) -> &str {
         node.utf8_text(self.source.as_bytes()).unwrap_or("")
     }
@@ -403,11 +2240,9 @@ impl PhpParser {
                 let code_start = start_index + start_tag.len();
                 let example = comment_text[code_start..end_index].trim();
 
-                // Regex to replace "* " or "*" from the beginning of a line.
-                let re = Regex::new(r"^\s*\*\s?").unwrap();
                 let cleaned_example = example
                     .lines()
-                    .map(|line| re.replace(line, "").to_string())
+                    .map(|line| DOCBLOCK_LINE_PREFIX.replace(line, "").to_string())
                     .collect::<Vec<String>>();
 
                 return Some(
@@ -421,3 +2256,1284 @@ impl PhpParser {
         None
     }
 }
+
+/// Get the value of a key in a Doctrine annotation, f.e `label = @Translation("User")`.
+fn get_annotation_value(text: &str, key: &str) -> Option<String> {
+    ANNOTATION_VALUE
+        .captures_iter(text)
+        .find(|captures| {
+            captures
+                .name("key")
+                .is_some_and(|name| name.as_str() == key)
+        })
+        .and_then(|captures| Some(captures.name("value")?.as_str().to_string()))
+}
+
+/// Get the summary of a docblock, which is its first paragraph.
+fn get_docblock_summary(docblock: &str) -> Option<String> {
+    if !docblock.starts_with("/**") {
+        return None;
+    }
+    let summary = docblock
+        .trim_start_matches("/**")
+        .trim_end_matches("*/")
+        .lines()
+        .map(|line| line.trim().trim_start_matches('*').trim())
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty() && !line.starts_with('@'))
+        .collect::<Vec<&str>>()
+        .join(" ");
+    (!summary.is_empty()).then_some(summary)
+}
+
+/// Get the text of a docblock as Markdown, f.e `@code` blocks become fenced code blocks.
+fn get_docblock_markdown(docblock: &str) -> Option<String> {
+    if !docblock.starts_with("/**") {
+        return None;
+    }
+    let markdown = docblock
+        .trim_start_matches("/**")
+        .trim_end_matches("*/")
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            let line = line.strip_prefix('*').unwrap_or(line);
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .map(|line| match line.trim() {
+            "@code" => "```php",
+            "@endcode" => "```",
+            _ => line,
+        })
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string();
+    (!markdown.is_empty()).then_some(markdown)
+}
+
+/// Get the hook that a docblock documents as implemented, f.e `hook_cron` for
+/// `Implements hook_cron().`
+fn get_implemented_hook(text: &str) -> Option<&str> {
+    if !text.contains("Implements hook_") {
+        return None;
+    }
+    let start_bytes = text.find("hook_")?;
+    let end_bytes = text.find("()")?;
+    text.get(start_bytes..end_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::settings::PluginReferencePattern;
+
+    use super::*;
+
+    #[test]
+    fn parse_theme_definitions() {
+        let source = r#"<?php
+function node_theme() {
+  return [
+    'node' => ['render element' => 'elements'],
+    'node_add_list' => ['variables' => ['content' => NULL], 'template' => 'node-add'],
+  ];
+}
+"#;
+        let tokens = PhpParser::new(source).get_tokens();
+        let themes: Vec<&DrupalTheme> = tokens
+            .iter()
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalThemeDefinition(theme) => Some(theme),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(2, themes.len());
+        assert_eq!("node", themes[0].name);
+        assert_eq!(vec!["elements"], themes[0].variables);
+        assert_eq!("node.html.twig", themes[0].get_template_file_name());
+        assert_eq!(vec!["content"], themes[1].variables);
+        assert_eq!("node-add.html.twig", themes[1].get_template_file_name());
+    }
+
+    #[test]
+    fn parse_preprocess_variables() {
+        let source = r#"<?php
+function mymodule_preprocess_node__article(&$variables) {
+  $variables['author'] = 'Admin';
+  $variables['label'] = $variables['author'];
+  if ($variables['author'] == 'Admin') {}
+}
+"#;
+        let tokens = PhpParser::new(source).get_tokens();
+        match &tokens[0].data {
+            TokenData::DrupalPreprocessDefinition(preprocess) => {
+                assert_eq!("node__article", preprocess.theme);
+                assert_eq!(vec!["author", "label"], preprocess.variables);
+            }
+            data => panic!("Unexpected token {:?}", data),
+        }
+    }
+
+    #[test]
+    fn get_imports_and_class_name_usages() {
+        let source = r#"<?php
+
+namespace Drupal\mymodule;
+
+use Drupal\Core\Url;
+use Drupal\node\NodeInterface as Node;
+use Drupal\Core\Entity\{EntityBase, EntityTypeInterface};
+
+class Example extends EntityBase {
+  public function build(Node $node): Url {
+    $term = Term::load(1);
+    return new Url('<front>');
+  }
+}
+"#;
+        let parser = PhpParser::new(source);
+        assert_eq!("Drupal\\mymodule", parser.get_namespace().unwrap());
+
+        let imports = parser.get_imported_class_names();
+        assert_eq!("Drupal\\node\\NodeInterface", imports["Node"].to_string());
+        assert_eq!(
+            "Drupal\\Core\\Entity\\EntityTypeInterface",
+            imports["EntityTypeInterface"].to_string()
+        );
+        assert_eq!(4, imports.len());
+
+        let usages: Vec<String> = parser
+            .get_class_name_usages()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(vec!["EntityBase", "Node", "Url", "Term", "Url"], usages);
+
+        let edit = parser
+            .get_use_statement_edit(&PhpClassName::from("Drupal\\taxonomy\\Entity\\Term"))
+            .unwrap();
+        assert_eq!(7, edit.range.start.line);
+        assert_eq!("use Drupal\\taxonomy\\Entity\\Term;\n", edit.new_text);
+    }
+
+    #[test]
+    fn get_next_update_number() {
+        let source = r#"<?php
+function mymodule_install() {}
+function mymodule_update_10003() {}
+function mymodule_update_10001() {}
+function other_update_10010() {}
+"#;
+        let parser = PhpParser::new(source);
+        assert_eq!(10004, parser.get_next_update_number("mymodule"));
+        assert_eq!(10001, parser.get_next_update_number("mymodule_extra"));
+    }
+
+    #[test]
+    fn parse_theme_reference() {
+        let source = "<?php\n$build = ['#theme' => 'node__article'];\n";
+        let token = PhpParser::new(source)
+            .get_token_at_position(Position {
+                line: 1,
+                character: 28,
+            })
+            .unwrap();
+        match token.data {
+            TokenData::DrupalThemeReference(name) => assert_eq!("node__article", name),
+            data => panic!("Unexpected token {:?}", data),
+        }
+    }
+
+    #[test]
+    fn parse_hook_implementations() {
+        let source = r#"<?php
+
+/**
+ * Implements hook_cron().
+ */
+function foo_cron() {
+}
+
+/**
+ * Implements hook_form_FORM_ID_alter() for node_form.
+ */
+function foo_form_node_form_alter(&$form) {
+}
+
+function foo_theme_suggestions_node_alter(array &$suggestions) {
+}
+
+function bar_helper() {
+}
+"#;
+        let parser = PhpParser::new(source);
+        let implementations: Vec<(String, String)> = parser
+            .get_hook_implementations("foo")
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalHookImplementation(implementation) => Some((
+                    source[token.range.start_byte..token.range.end_byte].to_string(),
+                    implementation.hook_name,
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("foo_cron".to_string(), "hook_cron".to_string()),
+                (
+                    "foo_form_node_form_alter".to_string(),
+                    "hook_form_FORM_ID_alter".to_string()
+                ),
+                (
+                    "foo_theme_suggestions_node_alter".to_string(),
+                    "hook_theme_suggestions_node_alter".to_string()
+                ),
+            ],
+            implementations
+        );
+    }
+
+    #[test]
+    fn parse_covers() {
+        let source = r#"<?php
+
+namespace Drupal\Tests\foo\Unit;
+
+/**
+ * @coversDefaultClass \Drupal\foo\FooManager
+ * @group foo
+ */
+class FooManagerTest extends UnitTestCase {
+
+  /**
+   * @covers ::load
+   * @covers \Drupal\foo\FooStorage::loadMultiple
+   */
+  public function testLoad() {
+  }
+
+}
+"#;
+        let parser = PhpParser::new(source);
+        let covered_classes: Vec<String> = parser
+            .get_covered_classes()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::PhpUnitCovers(class_name) => Some(class_name.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "Drupal\\foo\\FooManager",
+                "Drupal\\foo\\FooManager",
+                "Drupal\\foo\\FooStorage"
+            ],
+            covered_classes
+        );
+
+        let Some(TokenData::PhpMethodReference(method)) = parser
+            .get_token_at_position(Position::new(11, 16))
+            .map(|token| token.data)
+        else {
+            panic!("Expected a method reference");
+        };
+        assert_eq!("load", method.name);
+        assert_eq!(
+            Some("Drupal\\foo\\FooManager".to_string()),
+            method.class_name.map(|class_name| class_name.to_string())
+        );
+        let Some(TokenData::PhpClassReference(class_name)) = parser
+            .get_token_at_position(Position::new(5, 30))
+            .map(|token| token.data)
+        else {
+            panic!("Expected a class reference");
+        };
+        assert_eq!("Drupal\\foo\\FooManager", class_name.to_string());
+    }
+
+    #[test]
+    fn parse_config_overrides() {
+        let source = r#"<?php
+$config['system.site']['page']['front'] = '/node';
+if (getenv('ENVIRONMENT') === 'local') {
+  $config['system.logging']['error_level'] = 'verbose';
+}
+$config['system.performance'] = ['css' => ['preprocess' => FALSE]];
+$config[$name]['foo'] = 'bar';
+$settings['hash_salt'] = 'foo';
+"#;
+        let overrides: Vec<(String, Option<String>, String)> = PhpParser::new(source)
+            .get_config_overrides()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalConfigOverride(config_override) => Some((
+                    config_override.config_name,
+                    config_override.key,
+                    config_override.value,
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "system.site".to_string(),
+                    Some("page.front".to_string()),
+                    "'/node'".to_string()
+                ),
+                (
+                    "system.logging".to_string(),
+                    Some("error_level".to_string()),
+                    "'verbose'".to_string()
+                ),
+                (
+                    "system.performance".to_string(),
+                    None,
+                    "['css' => ['preprocess' => FALSE]]".to_string()
+                ),
+            ],
+            overrides
+        );
+    }
+
+    #[test]
+    fn parse_theme_suggestion() {
+        let source = r#"<?php
+function foo_theme_suggestions_node_alter(array &$suggestions, array $variables) {
+  $suggestions[] = 'node__' . $variables['elements']['#view_mode'];
+  $suggestions[] = 'node__foo';
+}
+"#;
+        let parser = PhpParser::new(source);
+        for (line, expected) in [(2, "node__"), (3, "node__foo")] {
+            match parser
+                .get_token_at_position(Position::new(line, 22))
+                .map(|token| token.data)
+            {
+                Some(TokenData::DrupalThemeSuggestion(suggestion)) => {
+                    assert_eq!(expected, suggestion)
+                }
+                data => panic!("Unexpected token {:?}", data),
+            }
+        }
+    }
+
+    #[test]
+    fn get_route_call_ranges() {
+        let source = r#"<?php
+$url = Url::fromRoute('entity.node.canonical', ['node' => 1]);
+$form_state->setRedirect('<front>');
+"#;
+        let parser = PhpParser::new(source);
+        let (name, parameters) = parser.get_route_call_ranges(Position::new(1, 30)).unwrap();
+        assert_eq!(
+            lsp_types::Range::new(Position::new(1, 23), Position::new(1, 44)),
+            name
+        );
+        assert_eq!(
+            lsp_types::Range::new(Position::new(1, 45), Position::new(1, 60)),
+            parameters
+        );
+
+        let (name, parameters) = parser.get_route_call_ranges(Position::new(2, 28)).unwrap();
+        assert_eq!(
+            lsp_types::Range::new(Position::new(2, 26), Position::new(2, 33)),
+            name
+        );
+        assert_eq!(
+            lsp_types::Range::new(Position::new(2, 34), Position::new(2, 34)),
+            parameters
+        );
+
+        assert!(parser.get_route_call_ranges(Position::new(0, 2)).is_none());
+    }
+
+    #[test]
+    fn parse_element_type_reference() {
+        let source = "<?php
+$form['name'] = ['#type' => 'textfield'];
+";
+        let token = PhpParser::new(source)
+            .get_token_at_position(Position {
+                line: 1,
+                character: 33,
+            })
+            .unwrap();
+        match token.data {
+            TokenData::DrupalPluginReference(DrupalPluginReference {
+                plugin_type: DrupalPluginType::RenderElement,
+                plugin_id,
+            }) => assert_eq!("textfield", plugin_id),
+            data => panic!("Unexpected token {:?}", data),
+        }
+    }
+
+    #[test]
+    fn parse_entity_types() {
+        let attribute_source = r#"<?php
+
+namespace Drupal\node\Entity;
+
+/**
+ * Defines the node entity class.
+ */
+#[ContentEntityType(
+  id: 'node',
+  label: new TranslatableMarkup('Content'),
+  bundle_label: new TranslatableMarkup('Content type'),
+  bundle_entity_type: 'node_type',
+)]
+class Node extends EditorialContentEntityBase implements NodeInterface {
+  public static function baseFieldDefinitions(EntityTypeInterface $entity_type) {
+    $fields = parent::baseFieldDefinitions($entity_type);
+    $fields['title'] = BaseFieldDefinition::create('string')
+      ->setRequired(TRUE)
+      ->setSetting('max_length', 255);
+    $fields['uid'] = clone $fields['revision_uid'];
+    return $fields;
+  }
+}
+"#;
+        let annotation_source = r#"<?php
+
+namespace Drupal\block\Entity;
+
+/**
+ * Defines a Block configuration entity class.
+ *
+ * @ConfigEntityType(
+ *   id = "block",
+ *   label = @Translation("Block"),
+ *   label_collection = @Translation("Blocks"),
+ * )
+ */
+class Block extends ConfigEntityBase implements BlockInterface {
+}
+"#;
+        let entity_types: Vec<(
+            String,
+            Option<String>,
+            DrupalEntityTypeGroup,
+            Option<String>,
+        )> = [attribute_source, annotation_source]
+            .iter()
+            .flat_map(|source| PhpParser::new(source).get_tokens())
+            .filter_map(|token| match token.data {
+                TokenData::PhpClassDefinition(PhpClass {
+                    attribute: Some(ClassAttribute::EntityType(entity_type)),
+                    ..
+                }) => Some((
+                    entity_type.id,
+                    entity_type.label,
+                    entity_type.group,
+                    entity_type.bundle_entity_type,
+                )),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                (
+                    "node".to_string(),
+                    Some("Content".to_string()),
+                    DrupalEntityTypeGroup::Content,
+                    Some("node_type".to_string())
+                ),
+                (
+                    "block".to_string(),
+                    Some("Block".to_string()),
+                    DrupalEntityTypeGroup::Config,
+                    None
+                ),
+            ],
+            entity_types
+        );
+
+        let base_fields = PhpParser::new(attribute_source)
+            .get_tokens()
+            .into_iter()
+            .find_map(|token| match token.data {
+                TokenData::PhpClassDefinition(PhpClass {
+                    attribute: Some(ClassAttribute::EntityType(entity_type)),
+                    ..
+                }) => Some(entity_type.base_fields),
+                _ => None,
+            });
+        assert_eq!(
+            Some(vec![
+                DrupalEntityField {
+                    name: "title".to_string(),
+                    field_type: Some("string".to_string()),
+                    settings: vec![("max_length".to_string(), "255".to_string())],
+                },
+                DrupalEntityField {
+                    name: "uid".to_string(),
+                    field_type: None,
+                    settings: vec![],
+                },
+            ]),
+            base_fields
+        );
+
+        let source = r#"<?php
+function mymodule_form() {
+  $ids = \Drupal::entityQuery('node')->execute();
+  $form['user'] = ['#type' => 'entity_autocomplete', '#target_type' => 'user', 'target_type' => 'user'];
+}
+"#;
+        let entity_type_ids: Vec<String> = PhpParser::new(source)
+            .get_tokens()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalPluginReference(DrupalPluginReference {
+                    plugin_type: DrupalPluginType::EntityType,
+                    plugin_id,
+                }) => Some(plugin_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["node", "user"], entity_type_ids);
+    }
+
+    #[test]
+    fn parse_entity_field_references() {
+        let source = r#"<?php
+
+namespace Drupal\mymodule;
+
+use Drupal\node\NodeInterface;
+
+class Foo {
+  public function __construct(EntityTypeManagerInterface $entity_type_manager) {
+    $this->nodeStorage = $entity_type_manager->getStorage('node');
+  }
+  public function bar(NodeInterface $node, $id) {
+    $node->get('title');
+    $loaded = $this->nodeStorage->load($id);
+    $loaded->set('field_tags', []);
+    $query = $this->nodeStorage->getQuery()->accessCheck(TRUE);
+    $query->condition('status', 1);
+    $query->condition('field_tags.entity:taxonomy_term.name', 'News');
+    $this->nodeStorage->loadByProperties(['type' => 'article']);
+    $query->condition('type', ['page', 'blog'], 'IN');
+    $this->nodeStorage->loadByProperties(['title' => 'Home']);
+  }
+  public function queue(QueueFactory $queueFactory) {
+    $queueFactory->get('mymodule_queue');
+  }
+}
+"#;
+        let parser = PhpParser::new(source);
+        let get_reference = |needle: &str| {
+            let (line, text) = source
+                .lines()
+                .enumerate()
+                .find(|(_, text)| text.contains(needle))
+                .unwrap();
+            let token = parser.get_token_at_position(Position {
+                line: line as u32,
+                character: (text.find(needle).unwrap() + 1) as u32,
+            });
+            match token.map(|token| token.data) {
+                Some(TokenData::DrupalEntityFieldReference(reference)) => {
+                    Some((reference.entity_type, reference.field_name))
+                }
+                _ => None,
+            }
+        };
+
+        assert_eq!(
+            Some((
+                DrupalEntityTypeHint::Class(PhpClassName::from("Drupal\\node\\NodeInterface")),
+                "title".to_string()
+            )),
+            get_reference("title")
+        );
+        for (needle, field_name) in [
+            ("field_tags", "field_tags"),
+            ("status", "status"),
+            ("field_tags.entity", "field_tags.entity:taxonomy_term.name"),
+            ("type'", "type"),
+        ] {
+            assert_eq!(
+                Some((
+                    DrupalEntityTypeHint::Id("node".to_string()),
+                    field_name.to_string()
+                )),
+                get_reference(needle)
+            );
+        }
+        assert_eq!(None, get_reference("article"));
+
+        let get_bundle = |needle: &str| {
+            let (line, text) = source
+                .lines()
+                .enumerate()
+                .find(|(_, text)| text.contains(needle))
+                .unwrap();
+            let token = parser.get_token_at_position(Position {
+                line: line as u32,
+                character: (text.find(needle).unwrap() + 1) as u32,
+            });
+            match token.map(|token| token.data) {
+                Some(TokenData::DrupalEntityBundleReference(reference)) => {
+                    Some((reference.entity_type, reference.bundle))
+                }
+                _ => None,
+            }
+        };
+        for bundle in ["article", "page", "blog"] {
+            assert_eq!(
+                Some(("node".to_string(), bundle.to_string())),
+                get_bundle(bundle)
+            );
+        }
+        assert_eq!(None, get_bundle("Home"));
+
+        // A typed parameter isn't assumed to be an entity when another reference matches.
+        let token = parser.get_token_at_position(Position::new(22, 25)).unwrap();
+        let TokenData::DrupalPluginReference(reference) = token.data else {
+            panic!("Expected a plugin reference");
+        };
+        assert_eq!(DrupalPluginType::QueueWorker, reference.plugin_type);
+        assert_eq!("mymodule_queue", reference.plugin_id);
+    }
+
+    #[test]
+    fn parse_plugins() {
+        let source = r#"<?php
+
+namespace Drupal\mymodule\Plugin\QueueWorker;
+
+/**
+ * @QueueWorker(
+ *   id = "mymodule_import",
+ *   title = @Translation("Import"),
+ * )
+ */
+class Import {}
+"#;
+        let attribute_source = r#"<?php
+
+namespace Drupal\mymodule\Element;
+
+#[FormElement(
+  id: 'mymodule_color',
+  label: new TranslatableMarkup('Color'),
+)]
+class Color {}
+"#;
+        for (source, expected_id, expected_label) in [
+            (source, "mymodule_import", "Import"),
+            (attribute_source, "mymodule_color", "Color"),
+        ] {
+            let tokens = PhpParser::new(source).get_tokens();
+            let Some(TokenData::PhpClassDefinition(PhpClass {
+                attribute: Some(ClassAttribute::Plugin(plugin)),
+                ..
+            })) = tokens.first().map(|token| &token.data)
+            else {
+                panic!("Expected a plugin definition");
+            };
+            assert_eq!(expected_id, plugin.plugin_id);
+            assert_eq!(Some(expected_label.to_string()), plugin.label);
+        }
+    }
+
+    #[test]
+    fn parse_views_plugins() {
+        let attribute_source = r#"<?php
+
+namespace Drupal\views\Plugin\views\field;
+
+#[ViewsField("boolean")]
+class Boolean {}
+"#;
+        let annotation_source = r#"<?php
+
+namespace Drupal\views\Plugin\views\filter;
+
+/**
+ * Simple filter to handle matching of boolean values.
+ *
+ * @ingroup views_filter_handlers
+ *
+ * @ViewsFilter("boolean")
+ */
+class BooleanOperator {}
+"#;
+        for (source, expected_type) in [
+            (attribute_source, DrupalPluginType::ViewsField),
+            (annotation_source, DrupalPluginType::ViewsFilter),
+        ] {
+            let tokens = PhpParser::new(source).get_tokens();
+            let Some(TokenData::PhpClassDefinition(PhpClass {
+                attribute: Some(ClassAttribute::Plugin(plugin)),
+                ..
+            })) = tokens.first().map(|token| &token.data)
+            else {
+                panic!("Expected a plugin definition");
+            };
+            assert_eq!(expected_type, plugin.plugin_type);
+            assert_eq!("boolean", plugin.plugin_id);
+        }
+
+        let entity_type_source = r#"<?php
+
+namespace Drupal\node\Entity;
+
+#[ContentEntityType(
+  id: 'node',
+  base_table: 'node',
+  data_table: 'node_field_data',
+)]
+class Node {}
+"#;
+        let tokens = PhpParser::new(entity_type_source).get_tokens();
+        let Some(TokenData::PhpClassDefinition(PhpClass {
+            attribute: Some(ClassAttribute::EntityType(entity_type)),
+            ..
+        })) = tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected an entity type definition");
+        };
+        assert_eq!(Some("node".to_string()), entity_type.base_table);
+        assert_eq!(Some("node_field_data".to_string()), entity_type.data_table);
+    }
+
+    #[test]
+    fn parse_custom_plugin_types() {
+        let plugin_types: &'static [PluginTypeSettings] = Box::leak(Box::new([
+            PluginTypeSettings {
+                attribute: "CommerceCheckoutPane".to_string(),
+                label: Some("Checkout pane".to_string()),
+                references: vec![PluginReferencePattern {
+                    method: "getPane".to_string(),
+                    object: Some("checkoutFlow".to_string()),
+                }],
+            },
+            PluginTypeSettings {
+                attribute: "Action".to_string(),
+                label: None,
+                references: vec![PluginReferencePattern {
+                    method: "createAction".to_string(),
+                    object: None,
+                }],
+            },
+        ]));
+        let parse = |source: &str| {
+            PhpParser {
+                source: source.to_string(),
+                plugin_types,
+            }
+            .get_tokens()
+        };
+
+        let attribute_source = r#"<?php
+
+namespace Drupal\commerce_checkout\Plugin\Commerce\CheckoutPane;
+
+#[CommerceCheckoutPane(
+  id: 'login',
+  label: new TranslatableMarkup('Login or continue as guest'),
+)]
+class Login {}
+"#;
+        let annotation_source = r#"<?php
+
+namespace Drupal\eca_content\Plugin\Action;
+
+/**
+ * @Action(
+ *   id = "eca_new_entity",
+ *   label = @Translation("Entity: create new")
+ * )
+ */
+class NewEntity {}
+"#;
+        for (source, expected_type, expected_id) in [
+            (attribute_source, "Checkout pane", "login"),
+            (annotation_source, "Action", "eca_new_entity"),
+        ] {
+            let tokens = parse(source);
+            let Some(TokenData::PhpClassDefinition(PhpClass {
+                attribute: Some(ClassAttribute::Plugin(plugin)),
+                ..
+            })) = tokens.first().map(|token| &token.data)
+            else {
+                panic!("Expected a plugin definition");
+            };
+            assert_eq!(expected_type, plugin.plugin_type.to_string());
+            assert_eq!(expected_id, plugin.plugin_id);
+        }
+
+        let references_source = r#"<?php
+
+namespace Drupal\mymodule;
+
+class Checkout {
+
+  public function build() {
+    $this->checkoutFlow->getPane('login');
+    $this->otherFlow->getPane('review');
+    $this->actionManager->createAction('eca_new_entity');
+  }
+
+}
+"#;
+        let parser = PhpParser {
+            source: references_source.to_string(),
+            plugin_types,
+        };
+        let references: Vec<Option<(String, String)>> = [(7, 35), (8, 32), (9, 42)]
+            .into_iter()
+            .map(|(line, character)| {
+                match parser
+                    .get_token_at_position(Position { line, character })?
+                    .data
+                {
+                    TokenData::DrupalPluginReference(reference) => {
+                        Some((reference.plugin_type.to_string(), reference.plugin_id))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                Some(("Checkout pane".to_string(), "login".to_string())),
+                None,
+                Some(("Action".to_string(), "eca_new_entity".to_string())),
+            ],
+            references
+        );
+    }
+
+    #[test]
+    fn parse_method_signature() {
+        let source = r#"<?php
+
+namespace Drupal\mymodule;
+
+class Foo {
+
+  /**
+   * Loads the bar.
+   */
+  protected function loadBar(int $id, ?Url $url = NULL): ?Bar {
+    return NULL;
+  }
+
+}
+"#;
+        let tokens = PhpParser::new(source).get_tokens();
+        let Some(TokenData::PhpClassDefinition(class)) = tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a class definition");
+        };
+        let Some(TokenData::PhpMethodDefinition(method)) =
+            class.methods.get("loadBar").map(|token| &token.data)
+        else {
+            panic!("Expected a method definition");
+        };
+        assert_eq!(
+            Some("protected function loadBar(int $id, ?Url $url = NULL): ?Bar".to_string()),
+            method.signature
+        );
+        assert_eq!(Some("Loads the bar.".to_string()), method.summary);
+        assert_eq!(PhpVisibility::Protected, method.visibility);
+        assert!(!method.is_static);
+    }
+
+    #[test]
+    fn parse_hook_documentation() {
+        let source = r#"<?php
+
+/**
+ * Perform alterations before a form is rendered.
+ *
+ * @code
+ *   $form['#title'] = t('Title');
+ * @endcode
+ *
+ * @param $form
+ *   Nested array of form elements.
+ */
+function hook_form_alter(&$form, $form_state, $form_id) {
+}
+"#;
+        let tokens = PhpParser::new(source).get_tokens();
+        let Some(TokenData::DrupalHookDefinition(hook)) = tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a hook definition");
+        };
+        assert_eq!(
+            Some(
+                "Perform alterations before a form is rendered.\n\n```php\n  $form['#title'] = t('Title');\n```\n\n@param $form\n  Nested array of form elements."
+                    .to_string()
+            ),
+            hook.documentation
+        );
+    }
+
+    #[test]
+    fn parse_entity_type_handlers() {
+        let attribute_source = r#"<?php
+
+namespace Drupal\node\Entity;
+
+use Drupal\node\NodeStorage;
+
+#[ContentEntityType(
+  id: 'node',
+  handlers: [
+    'storage' => NodeStorage::class,
+    'form' => ['default' => NodeForm::class],
+  ],
+)]
+class Node extends EditorialContentEntityBase {
+}
+"#;
+        let annotation_source = r#"<?php
+
+namespace Drupal\block\Entity;
+
+/**
+ * @ConfigEntityType(
+ *   id = "block",
+ *   handlers = {
+ *     "access" = "Drupal\block\BlockAccessControlHandler",
+ *   },
+ * )
+ */
+class Block extends ConfigEntityBase {
+}
+"#;
+        for (source, line, character, expected) in [
+            (attribute_source, 9, 20, "Drupal\\node\\NodeStorage"),
+            (attribute_source, 10, 30, "Drupal\\node\\Entity\\NodeForm"),
+            (
+                annotation_source,
+                8,
+                30,
+                "Drupal\\block\\BlockAccessControlHandler",
+            ),
+        ] {
+            let token = PhpParser::new(source).get_token_at_position(Position { line, character });
+            match token.map(|token| token.data) {
+                Some(TokenData::PhpClassReference(class_name)) => {
+                    assert_eq!(expected, class_name.to_string())
+                }
+                data => panic!("Unexpected token {:?}", data),
+            }
+        }
+    }
+
+    #[test]
+    fn get_class_references() {
+        let source = r#"<?php
+
+namespace Drupal\mymodule\Form;
+
+use Drupal\Core\Form\FormBase;
+use Drupal\Core\Form\FormStateInterface;
+
+class MyForm extends FormBase {
+  public function submitForm(array &$form, FormStateInterface $form_state) {
+    $form_state->setRedirectUrl(new Url('<front>'));
+    $form_state->set('form', MyOtherForm::class);
+  }
+}
+"#;
+        let references: Vec<(String, usize)> = PhpParser::new(source)
+            .get_class_references()
+            .into_iter()
+            .map(|(class_name, range)| (class_name.to_string(), range.start_point.row))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("Drupal\\Core\\Form\\FormBase".to_string(), 4),
+                ("Drupal\\Core\\Form\\FormStateInterface".to_string(), 5),
+                ("Drupal\\Core\\Form\\FormBase".to_string(), 7),
+                ("Drupal\\Core\\Form\\FormStateInterface".to_string(), 8),
+                ("Drupal\\mymodule\\Form\\Url".to_string(), 9),
+                ("Drupal\\mymodule\\Form\\MyOtherForm".to_string(), 10),
+            ],
+            references
+        );
+    }
+
+    #[test]
+    fn get_translation_strings() {
+        let source = r#"<?php
+
+function mymodule_help() {
+  return t('Some label');
+}
+
+class MyForm extends FormBase {
+  public function buildForm(array $form, FormStateInterface $form_state) {
+    $form['title'] = ['#title' => $this->t('Some label')];
+    $form['other'] = ['#title' => $this->t('Other label')];
+    $form['name'] = ['#title' => $this->t('Hello @name', ['@name' => $name])];
+    return $form;
+  }
+}
+"#;
+        let strings: Vec<(String, Option<String>, usize)> = PhpParser::new(source)
+            .get_translation_strings()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalTranslationString(translation_string) => Some((
+                    translation_string.string,
+                    translation_string.placeholders,
+                    token.range.start_point.row,
+                )),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("Some label".to_string(), None, 3),
+                ("Some label".to_string(), None, 8),
+                ("Other label".to_string(), None, 9),
+                (
+                    "Hello @name".to_string(),
+                    Some("['@name' => $name]".to_string()),
+                    10
+                )
+            ],
+            strings
+        );
+    }
+
+    #[test]
+    fn docblock_summary() {
+        for (docblock, expected) in [
+            (
+                "/**\n * Defines the node entity class.\n *\n * @ContentEntityType(\n */",
+                Some("Defines the node entity class."),
+            ),
+            (
+                "/**\n * Provides a form\n * for nodes.\n * @internal\n */",
+                Some("Provides a form for nodes."),
+            ),
+            ("/** Single line summary. */", Some("Single line summary.")),
+            ("/**\n * @internal\n */", None),
+            ("// Not a docblock.", None),
+        ] {
+            assert_eq!(
+                expected.map(|summary| summary.to_string()),
+                get_docblock_summary(docblock)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_class_constants() {
+        let source = r#"<?php
+
+namespace Drupal\Core\Config;
+
+final class ConfigEvents {
+  const SAVE = 'config.save';
+  public const DELETE = "config.delete", RENAME = 'config.rename';
+  const LIST = [];
+
+  public static function getSubscribedEvents(): array {
+    return [];
+  }
+}
+"#;
+        let parser = PhpParser::new(source);
+        let tokens = parser.get_tokens();
+        let Some(TokenData::PhpClassDefinition(class)) = tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a class definition");
+        };
+        assert_eq!(
+            vec![
+                ("SAVE", Some("config.save")),
+                ("DELETE", Some("config.delete")),
+                ("RENAME", Some("config.rename")),
+                ("LIST", None)
+            ],
+            class
+                .constants
+                .iter()
+                .map(|constant| (constant.name.as_str(), constant.value.as_deref()))
+                .collect::<Vec<(&str, Option<&str>)>>()
+        );
+
+        assert_eq!(
+            Some("getSubscribedEvents".to_string()),
+            parser.get_method_name_at_position(Position {
+                line: 10,
+                character: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_attached_libraries() {
+        let source = r#"<?php
+function mymodule_page() {
+  $build['#attached']['library'][] = 'core/drupal.dialog';
+  $build['#attached']['library'] = ['core/once'];
+  return [
+    '#attached' => [
+      'library' => ['mymodule/form', "mymodule/admin"],
+      'drupalSettings' => ['foo'],
+    ],
+    '#items' => ['bar'],
+  ];
+}
+"#;
+        let libraries: Vec<String> = PhpParser::new(source)
+            .get_tokens()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalLibraryReference(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                "core/drupal.dialog",
+                "core/once",
+                "mymodule/form",
+                "mymodule/admin"
+            ],
+            libraries
+        );
+    }
+
+    #[test]
+    fn parse_cache_contexts() {
+        let source = r#"<?php
+function mymodule_page() {
+  $build['#cache']['contexts'][] = 'user.permissions';
+  $metadata->addCacheContexts(['url.query_args', 'languages:language_interface']);
+  return [
+    '#cache' => ['contexts' => ['route'], 'tags' => ['node_list']],
+  ];
+}
+"#;
+        let contexts: Vec<String> = PhpParser::new(source)
+            .get_tokens()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalCacheContextReference(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            vec![
+                "user.permissions",
+                "url.query_args",
+                "languages:language_interface",
+                "route"
+            ],
+            contexts
+        );
+    }
+
+    #[test]
+    fn parse_config_key_references() {
+        let source = r#"<?php
+function mymodule_page() {
+  $front = \Drupal::config('system.site')->get('page.front');
+  $config = \Drupal::configFactory()->getEditable('system.mail');
+  $config->get('interface');
+  $other = $this->get('foo');
+}
+"#;
+        let tokens = PhpParser::new(source).get_tokens();
+        let mut references: Vec<(&str, &str)> = tokens
+            .iter()
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalConfigKeyReference(reference) => {
+                    Some((reference.config_name.as_str(), reference.key.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        references.sort();
+
+        assert_eq!(
+            vec![("system.mail", "interface"), ("system.site", "page.front")],
+            references
+        );
+    }
+
+    #[test]
+    fn organize_imports() {
+        let source = r#"<?php
+
+namespace Drupal\foo;
+
+use Drupal\node\NodeInterface;
+use Drupal\Core\{Url, Link};
+use Drupal\Core\Entity\EntityInterface as Entity;
+use function Drupal\foo\bar;
+use Drupal\Component\Utility;
+use const Drupal\foo\BAZ;
+use Drupal\Core\StringTranslation\StringTranslationTrait;
+use Drupal\foo\Traits\FooTrait;
+use Drupal\foo\Traits;
+use function Drupal\foo\assert;
+
+/**
+ * @param \Drupal\node\NodeTypeInterface $type
+ * @return Url
+ */
+function foo(Entity $entity) {
+  return Utility\Html::escape('');
+}
+
+class Foo {
+
+  use StringTranslationTrait;
+  use Traits\BarTrait, FooTrait {
+    FooTrait::foo insteadof Traits\BarTrait;
+  }
+
+}
+"#;
+        let edit = PhpParser::new(source).get_organize_imports_edit().unwrap();
+        assert_eq!(Position::new(4, 0), edit.range.start);
+        assert_eq!(Position::new(13, 31), edit.range.end);
+        assert_eq!(
+            r#"use Drupal\Component\Utility;
+use Drupal\Core\Entity\EntityInterface as Entity;
+use Drupal\Core\StringTranslation\StringTranslationTrait;
+use Drupal\Core\Url;
+use Drupal\foo\Traits;
+use Drupal\foo\Traits\FooTrait;
+
+use function Drupal\foo\assert;
+use function Drupal\foo\bar;
+
+use const Drupal\foo\BAZ;"#,
+            edit.new_text
+        );
+
+        // Organized imports don't need an edit.
+        let source = r#"<?php
+
+use Drupal\Core\Url;
+use Drupal\node\NodeInterface;
+
+function foo(NodeInterface $node): Url {}
+"#;
+        assert!(PhpParser::new(source).get_organize_imports_edit().is_none());
+    }
+}
@@ -0,0 +1,239 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, LazyLock, Mutex};
+
+// The interner is split in shards that are locked separately, so that the threads parsing files
+// in parallel rarely wait for each other.
+static INTERNER: LazyLock<[Mutex<Interner>; SHARD_COUNT]> =
+    LazyLock::new(|| std::array::from_fn(|_| Mutex::new(Interner::default())));
+
+const SHARD_COUNT: usize = 16;
+
+/// The number of strings in a shard after which unused strings are dropped for the first time.
+const INITIAL_COLLECT_AT: usize = 4096;
+
+/// Get the shard of the interner that holds a string.
+fn get_shard(value: &str) -> &'static Mutex<Interner> {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    &INTERNER[hasher.finish() as usize % SHARD_COUNT]
+}
+
+#[derive(Default)]
+struct Interner {
+    strings: HashSet<Arc<str>>,
+    collect_at: usize,
+}
+
+impl Interner {
+    fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(string) = self.strings.get(value) {
+            return string.clone();
+        }
+
+        // Drop strings that are no longer used by any symbol, f.e names that were only typed
+        // halfway, before the set grows any further.
+        if self.strings.len() >= self.collect_at.max(INITIAL_COLLECT_AT) {
+            self.strings.retain(|string| Arc::strong_count(string) > 1);
+            self.collect_at = self.strings.len() * 2;
+        }
+
+        let string: Arc<str> = Arc::from(value);
+        self.strings.insert(string.clone());
+        string
+    }
+}
+
+/// An interned string, f.e a route name, service ID or class name.
+///
+/// These names are repeated in many definition and reference tokens, so every distinct name is
+/// stored once and symbols are compared by pointer.
+#[derive(Clone)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    pub fn new(value: &str) -> Self {
+        Self(get_shard(value).lock().unwrap().intern(value))
+    }
+
+    /// Get the symbol of a string that was interned before, without interning it.
+    ///
+    /// Lookups of names that were never interned can't match any token.
+    pub fn get(value: &str) -> Option<Self> {
+        get_shard(value)
+            .lock()
+            .unwrap()
+            .strings
+            .get(value)
+            .map(|string| Self(string.clone()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<Symbol> for str {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == &*other.0
+    }
+}
+
+impl PartialEq<Symbol> for &str {
+    fn eq(&self, other: &Symbol) -> bool {
+        *self == &*other.0
+    }
+}
+
+impl PartialEq<Symbol> for String {
+    fn eq(&self, other: &Symbol) -> bool {
+        self == &*other.0
+    }
+}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(value: String) -> Self {
+        Self::new(&value)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(value: &String) -> Self {
+        Self::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern_symbols() {
+        let symbol = Symbol::new("entity.node.canonical");
+        let other = Symbol::from("entity.node.canonical".to_string());
+        assert!(Arc::ptr_eq(&symbol.0, &other.0));
+        assert_eq!(symbol, other);
+        assert_eq!(symbol, "entity.node.canonical");
+        assert_ne!(symbol, Symbol::new("entity.node.edit_form"));
+        assert_eq!("\"entity.node.canonical\"", format!("{:?}", symbol));
+        assert_eq!(Some(symbol), Symbol::get("entity.node.canonical"));
+        assert_eq!(None, Symbol::get("entity.node.never_interned"));
+    }
+
+    #[test]
+    fn intern_symbols_in_parallel() {
+        let symbols: Vec<Symbol> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| Symbol::new("system.admin_content")))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        });
+        assert!(symbols
+            .iter()
+            .all(|symbol| Arc::ptr_eq(&symbol.0, &symbols[0].0)));
+    }
+
+    #[test]
+    fn drop_unused_strings() {
+        let mut interner = Interner::default();
+        interner.intern("unused");
+        let used = (1..INITIAL_COLLECT_AT)
+            .map(|i| interner.intern(&format!("used.{}", i)))
+            .collect::<Vec<_>>();
+        assert!(interner.strings.contains("unused"));
+
+        // Reaching the limit drops the strings that aren't used anymore.
+        interner.intern("new");
+        assert!(!interner.strings.contains("unused"));
+        assert_eq!(used.len() + 1, interner.strings.len());
+    }
+}
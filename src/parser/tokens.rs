@@ -1,8 +1,14 @@
 use regex::Regex;
-use std::{collections::HashMap, fmt};
+use std::{collections::HashMap, fmt, sync::LazyLock};
 use tree_sitter::Range;
 
 use crate::document_store::DocumentStore;
+use crate::settings::PluginTypeSettings;
+
+use super::symbol::Symbol;
+
+// Matches the parameters in a route path, f.e `node` in `/node/{node}/edit`.
+static ROUTE_PARAMETER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([^{}]+)\}").unwrap());
 
 #[derive(Debug)]
 pub struct Token {
@@ -22,21 +28,54 @@ pub enum TokenData {
     PhpClassDefinition(PhpClass),
     PhpMethodReference(PhpMethod),
     PhpMethodDefinition(PhpMethod),
-    DrupalRouteReference(String),
+    /// A class covered by a PHPUnit test, from a `@covers` or `@coversDefaultClass` tag.
+    PhpUnitCovers(PhpClassName),
+    DrupalRouteReference(Symbol),
     DrupalRouteDefinition(DrupalRoute),
-    DrupalServiceReference(String),
+    DrupalServiceReference(Symbol),
     DrupalServiceDefinition(DrupalService),
     DrupalHookReference(String),
     DrupalHookDefinition(DrupalHook),
+    /// The hooks invoked by a module handler call, f.e `hook_cron` for `invokeAll('cron')`.
+    DrupalHookInvocation(Vec<String>),
+    DrupalHookImplementation(DrupalHookImplementation),
     DrupalPermissionDefinition(DrupalPermission),
-    DrupalPermissionReference(String),
+    DrupalPermissionReference(Symbol),
     DrupalPluginReference(DrupalPluginReference),
     DrupalTranslationString(DrupalTranslationString),
+    DrupalTranslationDefinition(DrupalTranslation),
+    DrupalLibraryReference(String),
+    DrupalLibraryDefinition(DrupalLibrary),
+    DrupalCacheContextReference(String),
+    DrupalThemeReference(String),
+    DrupalThemeDefinition(DrupalTheme),
+    /// A theme hook suggestion added in `hook_theme_suggestions_HOOK_alter()`, or the start of it
+    /// when it is concatenated, f.e `node__` for `'node__' . $bundle`.
+    DrupalThemeSuggestion(String),
+    DrupalPreprocessDefinition(DrupalPreprocess),
+    TwigTemplateReference(String),
+    DrupalConfigReference(String),
+    DrupalConfigSchemaDefinition(DrupalConfigSchema),
+    /// A reference to a config schema type, f.e `config_object` in `type: config_object`.
+    DrupalConfigSchemaReference(String),
+    DrupalConfigOverride(DrupalConfigOverride),
+    DrupalConfigKeyReference(DrupalConfigKeyReference),
+    DrupalModuleReference(String),
+    DrupalExtensionDefinition(DrupalExtension),
+    DrupalRoleReference(String),
+    DrupalRoleDefinition(DrupalRole),
+    DrupalEntityBundleReference(DrupalEntityBundleReference),
+    DrupalEntityBundleDefinition(DrupalEntityBundle),
+    DrupalFieldReference(String),
+    DrupalEntityFieldReference(DrupalEntityFieldReference),
+    DrupalFieldStorageDefinition(DrupalFieldStorage),
+    DrupalMenuLinkReference(String),
+    DrupalMenuLinkDefinition(DrupalMenuLink),
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct PhpClassName {
-    value: String,
+    value: Symbol,
 }
 
 impl fmt::Display for PhpClassName {
@@ -45,12 +84,26 @@ impl fmt::Display for PhpClassName {
     }
 }
 
+impl PhpClassName {
+    /// Get the class name without its namespace.
+    pub fn get_short_name(&self) -> &str {
+        self.value.rsplit('\\').next().unwrap_or(&self.value)
+    }
+
+    pub fn get_namespace(&self) -> &str {
+        self.value
+            .rsplit_once('\\')
+            .map(|(namespace, _)| namespace)
+            .unwrap_or_default()
+    }
+}
+
 impl From<&str> for PhpClassName {
     fn from(value: &str) -> Self {
         Self {
             // Trim single quotes and backslashes to ensure the most trimmed down version of a
             // fully qualified class name.
-            value: value.trim_matches(['\'', '\\']).to_string(),
+            value: value.trim_matches(['\'', '\\']).into(),
         }
     }
 }
@@ -58,13 +111,29 @@ impl From<&str> for PhpClassName {
 #[derive(Debug)]
 pub enum ClassAttribute {
     Plugin(DrupalPlugin),
+    EntityType(DrupalEntityType),
 }
 
 #[derive(Debug)]
 pub struct PhpClass {
     pub name: PhpClassName,
+    /// The summary line of the docblock.
+    pub summary: Option<String>,
     pub attribute: Option<ClassAttribute>,
+    pub interfaces: Vec<PhpClassName>,
+    /// The class that is extended, f.e `Drupal\Core\Form\FormBase`.
+    pub parent: Option<PhpClassName>,
+    /// The traits that are used in the class body.
+    pub traits: Vec<PhpClassName>,
     pub methods: HashMap<String, Box<Token>>,
+    pub constants: Vec<PhpClassConstant>,
+}
+
+#[derive(Debug)]
+pub struct PhpClassConstant {
+    pub name: String,
+    /// The value of string constants, f.e `kernel.request`.
+    pub value: Option<String>,
 }
 
 #[derive(Debug)]
@@ -72,6 +141,54 @@ pub struct PhpMethod {
     pub name: String,
     pub class_name: Option<PhpClassName>,
     pub service_name: Option<String>,
+    pub parameters: Vec<PhpParameter>,
+    /// Methods without a visibility modifier are public.
+    pub visibility: PhpVisibility,
+    pub is_static: bool,
+    /// The declaration up to the body, f.e `public function load($id): ?EntityInterface`.
+    pub signature: Option<String>,
+    /// The summary line of the docblock.
+    pub summary: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum PhpVisibility {
+    #[default]
+    Public,
+    Protected,
+    Private,
+}
+
+impl From<&str> for PhpVisibility {
+    fn from(value: &str) -> Self {
+        match value {
+            "protected" => Self::Protected,
+            "private" => Self::Private,
+            _ => Self::Public,
+        }
+    }
+}
+
+impl fmt::Display for PhpVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Public => write!(f, "public"),
+            Self::Protected => write!(f, "protected"),
+            Self::Private => write!(f, "private"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PhpParameter {
+    pub name: String,
+    /// The type as written in the source, f.e `?array`.
+    pub type_hint: Option<String>,
+    /// The fully qualified class name for class typed parameters.
+    pub class_name: Option<PhpClassName>,
+    /// Whether the parameter has a default value.
+    pub is_optional: bool,
+    pub is_variadic: bool,
 }
 
 impl PhpMethod {
@@ -79,11 +196,7 @@ impl PhpMethod {
         if let Some(class_name) = &self.class_name {
             return Some(class_name.clone());
         } else if let Some(service_name) = &self.service_name {
-            if let Some((_, token)) = store.get_service_definition(service_name) {
-                if let TokenData::DrupalServiceDefinition(service) = &token.data {
-                    return Some(service.class.clone());
-                }
-            }
+            return store.get_service_class(service_name).cloned();
         }
         None
     }
@@ -98,6 +211,11 @@ impl TryFrom<&str> for PhpMethod {
                 name: method.to_string(),
                 class_name: Some(PhpClassName::from(class)),
                 service_name: None,
+                parameters: vec![],
+                visibility: PhpVisibility::Public,
+                is_static: false,
+                signature: None,
+                summary: None,
             });
         }
 
@@ -107,21 +225,21 @@ impl TryFrom<&str> for PhpMethod {
 
 #[derive(Debug)]
 pub struct DrupalRoute {
-    pub name: String,
+    pub name: Symbol,
     pub path: String,
+    /// The allowed HTTP methods, empty when all methods are allowed.
+    pub methods: Vec<String>,
     pub _defaults: DrupalRouteDefaults,
+    /// The requirements in order of appearance, f.e `("_permission", "access content")`.
+    pub requirements: Vec<(String, String)>,
 }
 
 impl DrupalRoute {
     pub fn get_route_parameters(&self) -> Vec<&str> {
-        let re = Regex::new(r"\{([^{}]+)\}");
-        match re {
-            Ok(re) => re
-                .captures_iter(&self.path)
-                .map(|c| c.get(1).unwrap().as_str())
-                .collect(),
-            Err(_) => vec![],
-        }
+        ROUTE_PARAMETER
+            .captures_iter(&self.path)
+            .filter_map(|captures| Some(captures.get(1)?.as_str()))
+            .collect()
     }
 }
 
@@ -131,24 +249,192 @@ pub struct DrupalRouteDefaults {
     pub _form: Option<PhpClassName>,
     pub _entity_form: Option<String>,
     pub _title: Option<String>,
+    pub _title_callback: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct DrupalService {
-    pub name: String,
-    pub class: PhpClassName,
+    pub name: Symbol,
+    /// Abstract services are not required to have a class.
+    pub class: Option<PhpClassName>,
+    pub tags: Vec<String>,
+    /// The arguments as written, f.e `@entity_type.manager` or `%app.root%`.
+    pub arguments: Vec<String>,
+    pub is_abstract: bool,
+    pub parent: Option<String>,
+    /// The service this service is an alias for.
+    pub alias: Option<String>,
+    /// The deprecation message of deprecated services.
+    pub deprecated: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct DrupalHook {
     pub name: String,
     pub parameters: Option<String>,
+    /// The docblock from the `*.api.php` file, as Markdown.
+    pub documentation: Option<String>,
+}
+
+/// A function implementing a hook, f.e `mymodule_cron()` in `mymodule.module`.
+#[derive(Debug)]
+pub struct DrupalHookImplementation {
+    /// The implemented hook, from the `Implements hook_NAME()` docblock or else from the function
+    /// name, f.e `hook_cron`.
+    pub hook_name: String,
+    /// The module or theme that implements the hook, from the file name.
+    pub module: String,
+    /// Whether the hook is taken from the docblock rather than derived from the function name.
+    pub documented: bool,
+}
+
+impl DrupalHookImplementation {
+    /// Whether the function implements a hook, given the pattern of hooks with placeholders.
+    pub fn implements(&self, hook_name: &str, hook_pattern: Option<&Regex>) -> bool {
+        self.hook_name == hook_name
+            || hook_pattern.is_some_and(|pattern| pattern.is_match(&self.hook_name))
+    }
+}
+
+/// Get a pattern that matches the names of a hook with placeholders, f.e
+/// `hook_form_node_form_alter` for `hook_form_FORM_ID_alter`. Returns `None` for other hooks.
+pub fn get_hook_pattern(hook_name: &str) -> Option<Regex> {
+    if !hook_name.split('_').any(is_hook_placeholder) {
+        return None;
+    }
+    let pattern = hook_name
+        .split('_')
+        .map(|part| match is_hook_placeholder(part) {
+            true => r"\w+".to_string(),
+            false => regex::escape(part),
+        })
+        .collect::<Vec<String>>()
+        .join("_");
+    Regex::new(&format!("^{}$", pattern)).ok()
+}
+
+/// Whether a part of a hook name is a placeholder, f.e `FORM_ID` or `N`.
+fn is_hook_placeholder(part: &str) -> bool {
+    part.chars().any(|char| char.is_ascii_uppercase())
+        && part
+            .chars()
+            .all(|char| char.is_ascii_uppercase() || char.is_ascii_digit())
 }
 
 #[derive(Debug)]
 pub struct DrupalPermission {
-    pub name: String,
+    pub name: Symbol,
     pub title: String,
+    pub description: Option<String>,
+    /// Whether the permission has `restrict access: true`, i.e. it has security implications.
+    pub restrict_access: bool,
+}
+
+/// A menu link, defined in a `*.links.menu.yml` file.
+#[derive(Debug)]
+pub struct DrupalMenuLink {
+    pub id: String,
+    pub title: Option<String>,
+    pub route_name: Option<String>,
+    pub parent: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct DrupalRole {
+    pub id: String,
+    pub label: String,
+}
+
+/// A bundle of an entity type, defined by its config, f.e `node.type.article.yml`.
+#[derive(Debug)]
+pub struct DrupalEntityBundle {
+    /// The entity type id, f.e `node`.
+    pub entity_type: String,
+    /// The machine name, f.e `article`.
+    pub name: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A bundle that entities are filtered by, f.e `'article'` in
+/// `loadByProperties(['type' => 'article'])`.
+#[derive(Debug)]
+pub struct DrupalEntityBundleReference {
+    pub entity_type: String,
+    pub bundle: String,
+}
+
+/// A module, theme or profile, defined by its `*.info.yml` file.
+#[derive(Debug)]
+pub struct DrupalExtension {
+    /// The machine name, f.e `node`.
+    pub name: String,
+    pub label: String,
+    pub description: Option<String>,
+    /// `module`, `theme` or `profile`.
+    pub extension_type: String,
+    pub package: Option<String>,
+    /// The version of contrib extensions, f.e `8.x-1.12`.
+    pub version: Option<String>,
+    /// The supported core versions, f.e `^10.3 || ^11`.
+    pub core_version_requirement: Option<String>,
+    /// The project that provides the extension, f.e `drupal` for core and `webform` for
+    /// `webform_ui`, which prefixes it in dependencies.
+    pub project: String,
+}
+
+#[derive(Debug)]
+pub struct DrupalLibrary {
+    /// The fully qualified library name, f.e `core/drupal.dialog`.
+    pub name: String,
+    /// The paths of the JavaScript and CSS files.
+    pub assets: Vec<String>,
+    pub dependencies: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct DrupalTheme {
+    pub name: String,
+    pub template: Option<String>,
+    pub variables: Vec<String>,
+}
+
+impl DrupalTheme {
+    /// Get the file name of the template, f.e `node--article.html.twig` for `node__article`.
+    pub fn get_template_file_name(&self) -> String {
+        get_theme_template_file_name(&self.name, self.template.as_deref())
+    }
+}
+
+/// Get the theme hook rendered by a template file, f.e `node__article` for `node--article.html.twig`.
+pub fn get_theme_name_from_template_file_name(file_name: &str) -> String {
+    file_name
+        .trim_end_matches(".html.twig")
+        .trim_end_matches(".twig")
+        .replace('-', "_")
+}
+
+/// Get the service that provides a cache context, f.e `cache_context.user.roles` for
+/// `user.roles:authenticated`.
+pub fn get_cache_context_service(context: &str) -> String {
+    format!(
+        "cache_context.{}",
+        context.split_once(':').map_or(context, |(name, _)| name)
+    )
+}
+
+#[derive(Debug)]
+pub struct DrupalPreprocess {
+    pub function_name: String,
+    pub theme: String,
+    pub variables: Vec<String>,
+}
+
+pub fn get_theme_template_file_name(theme_name: &str, template: Option<&str>) -> String {
+    match template {
+        Some(template) => format!("{}.html.twig", template),
+        None => format!("{}.html.twig", theme_name.replace('_', "-")),
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -159,6 +445,17 @@ pub enum DrupalPluginType {
     DataType,
     FormElement,
     RenderElement,
+    ViewsDisplay,
+    ViewsRow,
+    ViewsStyle,
+    ViewsField,
+    ViewsFilter,
+    ViewsSort,
+    ViewsArgument,
+    ViewsRelationship,
+    ViewsArea,
+    /// A plugin type from the `pluginTypes` setting.
+    Custom(&'static PluginTypeSettings),
 }
 
 impl TryFrom<&str> for DrupalPluginType {
@@ -172,14 +469,40 @@ impl TryFrom<&str> for DrupalPluginType {
             "DataType" => Ok(DrupalPluginType::DataType),
             "FormElement" => Ok(DrupalPluginType::FormElement),
             "RenderElement" => Ok(DrupalPluginType::RenderElement),
+            "ViewsDisplay" => Ok(DrupalPluginType::ViewsDisplay),
+            "ViewsRow" => Ok(DrupalPluginType::ViewsRow),
+            "ViewsStyle" => Ok(DrupalPluginType::ViewsStyle),
+            "ViewsField" => Ok(DrupalPluginType::ViewsField),
+            "ViewsFilter" => Ok(DrupalPluginType::ViewsFilter),
+            "ViewsSort" => Ok(DrupalPluginType::ViewsSort),
+            "ViewsArgument" => Ok(DrupalPluginType::ViewsArgument),
+            "ViewsRelationship" => Ok(DrupalPluginType::ViewsRelationship),
+            "ViewsArea" => Ok(DrupalPluginType::ViewsArea),
             _ => Err("Unable to convert string to DrupalPluginType"),
         }
     }
 }
 
+impl DrupalPluginType {
+    /// Whether plugins of a type can be used where this type is expected, form elements are
+    /// render elements as well, f.e in `'#type'`.
+    pub fn includes(&self, plugin_type: &DrupalPluginType) -> bool {
+        self == plugin_type
+            || (*self == DrupalPluginType::RenderElement
+                && *plugin_type == DrupalPluginType::FormElement)
+    }
+}
+
 impl fmt::Display for DrupalPluginType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            DrupalPluginType::Custom(plugin_type) => write!(
+                f,
+                "{}",
+                plugin_type.label.as_ref().unwrap_or(&plugin_type.attribute)
+            ),
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
@@ -187,9 +510,82 @@ impl fmt::Display for DrupalPluginType {
 pub struct DrupalPlugin {
     pub plugin_type: DrupalPluginType,
     pub plugin_id: String,
+    /// The label or title of the plugin, f.e `Textfield`.
+    pub label: Option<String>,
     pub usage_example: Option<String>,
 }
 
+#[derive(Debug)]
+pub struct DrupalEntityType {
+    pub id: String,
+    pub label: Option<String>,
+    pub group: DrupalEntityTypeGroup,
+    /// The config entity type that holds the bundles, f.e `node_type`.
+    pub bundle_entity_type: Option<String>,
+    /// The database tables, f.e `node` and `node_field_data`, which views refer to.
+    pub base_table: Option<String>,
+    pub data_table: Option<String>,
+    /// The fields defined in `baseFieldDefinitions()`.
+    pub base_fields: Vec<DrupalEntityField>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DrupalEntityField {
+    pub name: String,
+    pub field_type: Option<String>,
+    /// The scalar settings as written, f.e `("target_type", "user")`.
+    pub settings: Vec<(String, String)>,
+}
+
+/// A configurable field, defined by f.e `field.storage.node.field_tags.yml`.
+#[derive(Debug)]
+pub struct DrupalFieldStorage {
+    pub entity_type: String,
+    pub field: DrupalEntityField,
+}
+
+/// What is known about the entity type of an expression.
+#[derive(Debug, PartialEq)]
+pub enum DrupalEntityTypeHint {
+    /// The entity type id, f.e from `getStorage('node')`.
+    Id(String),
+    /// The entity class or interface, f.e from a `NodeInterface $node` parameter.
+    Class(PhpClassName),
+}
+
+#[derive(Debug)]
+pub struct DrupalEntityFieldReference {
+    pub entity_type: DrupalEntityTypeHint,
+    pub field_name: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DrupalEntityTypeGroup {
+    Content,
+    Config,
+}
+
+impl TryFrom<&str> for DrupalEntityTypeGroup {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "ContentEntityType" => Ok(DrupalEntityTypeGroup::Content),
+            "ConfigEntityType" => Ok(DrupalEntityTypeGroup::Config),
+            _ => Err("Unable to convert string to DrupalEntityTypeGroup"),
+        }
+    }
+}
+
+impl fmt::Display for DrupalEntityTypeGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrupalEntityTypeGroup::Content => write!(f, "Content entity type"),
+            DrupalEntityTypeGroup::Config => write!(f, "Config entity type"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DrupalPluginReference {
     pub plugin_type: DrupalPluginType,
@@ -199,9 +595,70 @@ pub struct DrupalPluginReference {
 #[derive(Debug)]
 pub struct DrupalTranslationString {
     pub string: String,
+    /// The placeholder values that are passed along, f.e `['@name' => $name]`.
     pub placeholders: Option<String>,
 }
 
+/// A translation from a gettext file.
+#[derive(Debug)]
+pub struct DrupalTranslation {
+    pub string: String,
+    /// The translated string, which is empty for untranslated strings.
+    pub translation: String,
+    pub langcode: String,
+}
+
+#[derive(Debug)]
+pub struct DrupalConfigSchema {
+    /// The config name, which may contain wildcards, f.e `block.block.*`, or the name of a data
+    /// type, f.e `label`.
+    pub name: String,
+    /// The type it is based on, f.e `config_object`.
+    pub type_name: Option<String>,
+    pub label: Option<String>,
+    pub keys: Vec<DrupalConfigSchemaKey>,
+}
+
+impl DrupalConfigSchema {
+    /// Check whether this schema applies to a config name, taking wildcards into account.
+    pub fn matches(&self, config_name: &str) -> bool {
+        if self.name == config_name {
+            return true;
+        }
+        let parts: Vec<&str> = self.name.split('.').collect();
+        let config_parts: Vec<&str> = config_name.split('.').collect();
+        parts.len() == config_parts.len()
+            && parts
+                .iter()
+                .zip(config_parts)
+                .all(|(part, config_part)| *part == "*" || *part == config_part)
+    }
+}
+
+#[derive(Debug)]
+pub struct DrupalConfigSchemaKey {
+    /// The path to the key, f.e `page.front`.
+    pub key: String,
+    pub type_name: Option<String>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct DrupalConfigKeyReference {
+    pub config_name: String,
+    pub key: String,
+}
+
+/// A config override in a settings file, f.e `$config['system.site']['page']['front'] = '/node';`.
+#[derive(Debug)]
+pub struct DrupalConfigOverride {
+    pub config_name: String,
+    /// The overridden key, f.e `page.front`, or `None` when the whole config object is overridden.
+    pub key: Option<String>,
+    /// The value as written.
+    pub value: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,138 @@
+use std::sync::LazyLock;
+
+use lsp_types::Position;
+use regex::{Captures, Regex};
+
+use super::tokens::{DrupalTranslationString, Token, TokenData};
+use super::{byte_range_to_range, position_to_byte};
+
+// There is no maintained tree-sitter grammar for Twig, so the few constructs we care about are
+// extracted with regular expressions.
+static ROUTE_FUNCTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(?:url|path)\(\s*['"](?<value>[^'"]*)['"]"#).unwrap());
+static ATTACH_LIBRARY_FUNCTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\battach_library\(\s*['"](?<value>[^'"]*)['"]"#).unwrap());
+static TEMPLATE_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\{%-?\s*(?:include|extends|embed|use|import|from)\s+['"](?<value>[^'"]+)['"]"#)
+        .unwrap()
+});
+static TEMPLATE_FUNCTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(?:include|source)\(\s*['"](?<value>[^'"]+)['"]"#).unwrap());
+static TRANS_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)\{%-?\s*trans\s*-?%\}(?<value>.*?)\{%-?\s*endtrans\s*-?%\}"#).unwrap()
+});
+static TRANS_FILTER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"['"](?<value>[^'"]+)['"]\s*\|\s*(?:t|trans)\b"#).unwrap());
+
+pub struct TwigParser {
+    source: String,
+}
+
+impl TwigParser {
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+        }
+    }
+
+    pub fn get_tokens(&self) -> Vec<Token> {
+        let mut tokens: Vec<Token> = vec![];
+
+        self.captures_to_tokens(&ROUTE_FUNCTION, &mut tokens, |value| {
+            TokenData::DrupalRouteReference(value.into())
+        });
+        self.captures_to_tokens(&ATTACH_LIBRARY_FUNCTION, &mut tokens, |value| {
+            TokenData::DrupalLibraryReference(value.to_string())
+        });
+        self.captures_to_tokens(&TEMPLATE_TAG, &mut tokens, |value| {
+            TokenData::TwigTemplateReference(value.to_string())
+        });
+        self.captures_to_tokens(&TEMPLATE_FUNCTION, &mut tokens, |value| {
+            TokenData::TwigTemplateReference(value.to_string())
+        });
+        self.captures_to_tokens(&TRANS_TAG, &mut tokens, |value| {
+            TokenData::DrupalTranslationString(DrupalTranslationString {
+                string: value.trim().to_string(),
+                placeholders: None,
+            })
+        });
+        self.captures_to_tokens(&TRANS_FILTER, &mut tokens, |value| {
+            TokenData::DrupalTranslationString(DrupalTranslationString {
+                string: value.to_string(),
+                placeholders: None,
+            })
+        });
+
+        tokens.sort_by_key(|token| token.range.start_byte);
+        tokens
+    }
+
+    pub fn get_token_at_position(&self, position: Position) -> Option<Token> {
+        let offset = position_to_byte(&self.source, position)?;
+
+        // Include the opening quote, so an empty string like `path('')` still resolves.
+        self.get_tokens()
+            .into_iter()
+            .find(|token| token.range.start_byte <= offset + 1 && offset <= token.range.end_byte)
+    }
+
+    fn captures_to_tokens(
+        &self,
+        re: &Regex,
+        tokens: &mut Vec<Token>,
+        to_token_data: impl Fn(&str) -> TokenData,
+    ) {
+        re.captures_iter(&self.source)
+            .filter_map(|captures: Captures| captures.name("value"))
+            .for_each(|value| {
+                tokens.push(Token::new(
+                    to_token_data(value.as_str()),
+                    byte_range_to_range(&self.source, value.start(), value.end()),
+                ));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_twig_tokens() {
+        let source = r#"{% extends '@classy/node.html.twig' %}
+{{ attach_library('core/drupal.dialog') }}
+<a href="{{ path('entity.node.canonical', {'node': 1}) }}">{{ 'Read more'|t }}</a>
+{% trans %}Hello world{% endtrans %}
+"#;
+        let parser = TwigParser::new(source);
+        let tokens = parser.get_tokens();
+        assert_eq!(5, tokens.len());
+
+        match &tokens[0].data {
+            TokenData::TwigTemplateReference(name) => assert_eq!("@classy/node.html.twig", name),
+            data => panic!("Unexpected token {:?}", data),
+        }
+        match &tokens[1].data {
+            TokenData::DrupalLibraryReference(name) => assert_eq!("core/drupal.dialog", name),
+            data => panic!("Unexpected token {:?}", data),
+        }
+        match &tokens[4].data {
+            TokenData::DrupalTranslationString(translation) => {
+                assert_eq!("Hello world", translation.string)
+            }
+            data => panic!("Unexpected token {:?}", data),
+        }
+
+        let token = parser
+            .get_token_at_position(Position {
+                line: 2,
+                character: 22,
+            })
+            .unwrap();
+        match token.data {
+            TokenData::DrupalRouteReference(name) => assert_eq!("entity.node.canonical", name),
+            data => panic!("Unexpected token {:?}", data),
+        }
+        assert_eq!(2, token.range.start_point.row);
+    }
+}
@@ -1,18 +1,72 @@
-use lsp_types::Position;
+use lsp_types::{Position, Range, TextEdit};
+use regex::Regex;
 use std::collections::HashMap;
+use std::sync::LazyLock;
 use std::vec;
 use tree_sitter::{Node, Point};
 
-use super::{get_node_at_position, get_tree, position_to_point, tokens::{
-    DrupalPermission, DrupalRoute, DrupalRouteDefaults, DrupalService, PhpClassName, PhpMethod,
-    Token, TokenData,
-}};
+use crate::document_store::document::is_default_config_file;
+
+use super::{
+    get_closest_parent_by_kind, get_node_at_position, get_tree, position_to_point,
+    tokens::{
+        DrupalConfigKeyReference, DrupalConfigSchema, DrupalConfigSchemaKey, DrupalEntityBundle,
+        DrupalEntityField, DrupalEntityFieldReference, DrupalEntityTypeHint, DrupalExtension,
+        DrupalFieldStorage, DrupalLibrary, DrupalMenuLink, DrupalPermission, DrupalPluginReference,
+        DrupalPluginType, DrupalRole, DrupalRoute, DrupalRouteDefaults, DrupalService,
+        PhpClassName, PhpMethod, Token, TokenData,
+    },
+};
+
+// Matches the attributes of an external library asset, f.e `{ type: external, minified: true }`.
+static LIBRARY_EXTERNAL_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\btype:\s*['"]?external\b"#).unwrap());
+
+// Matches a dependency in a `*.info.yml` file, f.e `drupal:node (>=10.3)` or `'token'`.
+static INFO_DEPENDENCY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^['"]?(?:\w+:)?(?<module>\w+)"#).unwrap());
+
+// The config prefixes of the bundles of entity types, f.e `node.type.article.yml` defines the
+// `article` bundle of nodes.
+pub const ENTITY_BUNDLE_CONFIG: &[(&str, &str)] = &[
+    ("node.type.", "node"),
+    ("taxonomy.vocabulary.", "taxonomy_term"),
+    ("media.type.", "media"),
+    ("block_content.type.", "block_content"),
+    ("comment.type.", "comment"),
+];
 
 pub struct YamlParser {
     source: String,
     uri: String,
 }
 
+/// The constructor arguments of a service in a `*.services.yml` file.
+#[derive(Debug)]
+pub struct ServiceArguments {
+    pub service_name: String,
+    /// The range of the service key.
+    pub range: tree_sitter::Range,
+    /// The positional arguments as written, f.e `@entity_type.manager`.
+    pub arguments: Vec<(String, tree_sitter::Range)>,
+    /// Whether all constructor arguments are listed, and not partly provided by a parent service,
+    /// a factory, autowiring or named arguments.
+    pub is_complete: bool,
+}
+
+/// A key of a default config file, f.e `page.front` in `config/install/system.site.yml`.
+#[derive(Debug)]
+pub struct ConfigValue {
+    /// The keys from the root of the file, f.e `["page", "front"]`.
+    pub keys: Vec<String>,
+    /// The type of a scalar value, f.e `integer`, or `None` for mappings, sequences and `null`.
+    pub value_type: Option<&'static str>,
+    /// The range of the key.
+    pub range: tree_sitter::Range,
+    /// The range of the value, or of the key when there is no value.
+    pub value_range: tree_sitter::Range,
+}
+
 impl YamlParser {
     pub fn new(source: &str, uri: &str) -> Self {
         Self {
@@ -21,9 +75,261 @@ impl YamlParser {
         }
     }
 
+    /// Get an edit that adds a tag to the definition of a service.
+    pub fn get_service_tag_edit(&self, service_name: &str, tag: &str) -> Option<TextEdit> {
+        let tree = get_tree(&self.source, &tree_sitter_yaml::language())?;
+        let service_node = self.find_block_mapping_pair(tree.root_node(), service_name)?;
+        let value_node = service_node.child_by_field_name("value")?;
+        let map = self.get_block_node_map(&value_node)?;
+
+        let (point, new_text) = match map.get("tags") {
+            Some(tags) => match tags.named_child(0)? {
+                // tags: [{ name: foo }]
+                sequence if sequence.kind() == "flow_sequence" => {
+                    let closing_bracket = sequence.child(sequence.child_count() - 1)?;
+                    let separator = if sequence.named_child_count() > 0 {
+                        ", "
+                    } else {
+                        ""
+                    };
+                    (
+                        closing_bracket.start_position(),
+                        format!("{}{{ name: {} }}", separator, tag),
+                    )
+                }
+                sequence => (
+                    get_last_named_descendant_end(sequence),
+                    format!(
+                        "\n{}- {{ name: {} }}",
+                        " ".repeat(sequence.start_position().column),
+                        tag
+                    ),
+                ),
+            },
+            None => {
+                let indentation = " ".repeat(value_node.start_position().column);
+                (
+                    get_last_named_descendant_end(value_node),
+                    format!(
+                        "\n{}tags:\n{}  - {{ name: {} }}",
+                        indentation, indentation, tag
+                    ),
+                )
+            }
+        };
+
+        let position = Position {
+            line: point.row as u32,
+            character: point.column as u32,
+        };
+        Some(TextEdit {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            new_text,
+        })
+    }
+
     pub fn get_tokens(&self) -> Vec<Token> {
-        let tree = get_tree(&self.source, &tree_sitter_yaml::language());
-        self.parse_nodes(vec![tree.unwrap().root_node()])
+        let tree = get_tree(&self.source, &tree_sitter_yaml::language()).unwrap();
+        let file_name = self.uri.rsplit('/').next().unwrap_or_default();
+        if file_name.starts_with("field.storage.") {
+            return self
+                .parse_field_storage(tree.root_node())
+                .into_iter()
+                .collect();
+        } else if file_name.starts_with("user.role.") {
+            return self.parse_role(tree.root_node()).into_iter().collect();
+        } else if let Some(token) = self.parse_entity_bundle(tree.root_node()) {
+            return vec![token];
+        } else if self.is_view_config() {
+            return self.parse_view(tree.root_node());
+        } else if self.is_default_config() {
+            // Other default config is only indexed to go to its file.
+            return vec![];
+        } else if file_name.ends_with(".info.yml") {
+            let mut tokens = self.parse_nodes(vec![tree.root_node()]);
+            tokens.extend(self.parse_info_file(tree.root_node()));
+            return tokens;
+        }
+        self.parse_nodes(vec![tree.root_node()])
+    }
+
+    /// Get the references to the parents of the menu links in a `*.links.menu.yml` file.
+    pub fn get_menu_link_parent_references(&self) -> Vec<Token> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_yaml::language()) else {
+            return vec![];
+        };
+        let Some(node) = get_root_block_node(tree.root_node()) else {
+            return vec![];
+        };
+        self.get_block_mapping_pairs(&node)
+            .into_iter()
+            .filter_map(|pair| {
+                let parent =
+                    self.get_block_mapping_value(pair.child_by_field_name("value")?, &["parent"])?;
+                Some(Token::new(
+                    TokenData::DrupalMenuLinkReference(
+                        self.get_node_text(&parent)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    ),
+                    parent.range(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the paths and ranges of the local CSS and JavaScript files of the libraries in a
+    /// `*.libraries.yml` file. External files are skipped.
+    pub fn get_library_asset_paths(&self) -> Vec<(String, tree_sitter::Range)> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_yaml::language()) else {
+            return vec![];
+        };
+        let Some(node) = get_root_block_node(tree.root_node()) else {
+            return vec![];
+        };
+
+        let mut file_pairs = vec![];
+        for library in self.get_block_mapping_pairs(&node) {
+            let Some(library) = library.child_by_field_name("value") else {
+                continue;
+            };
+            if let Some(css) = self.get_block_mapping_value(library, &["css"]) {
+                for group in self.get_block_mapping_pairs(&css) {
+                    if let Some(files) = group.child_by_field_name("value") {
+                        file_pairs.extend(self.get_block_mapping_pairs(&files));
+                    }
+                }
+            }
+            if let Some(js) = self.get_block_mapping_value(library, &["js"]) {
+                file_pairs.extend(self.get_block_mapping_pairs(&js));
+            }
+        }
+
+        file_pairs
+            .into_iter()
+            .filter_map(|pair| {
+                let key = pair.child_by_field_name("key")?;
+                let path = self.get_node_text(&key).trim_matches(['\'', '"']);
+                let is_external = path.contains("://")
+                    || path.starts_with("//")
+                    || pair.child_by_field_name("value").is_some_and(|value| {
+                        LIBRARY_EXTERNAL_TYPE.is_match(self.get_node_text(&value))
+                    });
+                (!is_external).then(|| (path.to_string(), key.range()))
+            })
+            .collect()
+    }
+
+    /// Get the keys of a default config file with the types of their values, including the keys
+    /// of nested mappings. Sequences are skipped, since their items aren't keyed by the schema.
+    pub fn get_config_values(&self) -> Vec<ConfigValue> {
+        if !self.is_default_config() {
+            return vec![];
+        }
+        let Some(tree) = get_tree(&self.source, &tree_sitter_yaml::language()) else {
+            return vec![];
+        };
+        let Some(node) = get_root_block_node(tree.root_node()) else {
+            return vec![];
+        };
+
+        let mut values = vec![];
+        let mut mappings = vec![(node, vec![])];
+        while let Some((mapping, keys)) = mappings.pop() {
+            for pair in self.get_block_mapping_pairs(&mapping) {
+                let Some(key_node) = pair.child_by_field_name("key") else {
+                    continue;
+                };
+                let mut keys = keys.clone();
+                keys.push(
+                    self.get_node_text(&key_node)
+                        .trim_matches(['\'', '"'])
+                        .to_string(),
+                );
+                let value_node = pair.child_by_field_name("value");
+                if let Some(value_node) = value_node.filter(|value_node| {
+                    value_node
+                        .named_child(0)
+                        .is_some_and(|child| child.kind() == "block_mapping")
+                }) {
+                    mappings.push((value_node, keys.clone()));
+                }
+                values.push(ConfigValue {
+                    keys,
+                    value_type: value_node.and_then(get_scalar_type),
+                    range: key_node.range(),
+                    value_range: value_node.unwrap_or(key_node).range(),
+                });
+            }
+        }
+        values.sort_by_key(|value| value.range.start_byte);
+        values
+    }
+
+    /// Get the arguments of the services in a `*.services.yml` file.
+    pub fn get_service_arguments(&self) -> Vec<ServiceArguments> {
+        let Some(tree) = get_tree(&self.source, &tree_sitter_yaml::language()) else {
+            return vec![];
+        };
+        let Some(services) = get_root_block_node(tree.root_node())
+            .and_then(|node| self.get_block_mapping_value(node, &["services"]))
+        else {
+            return vec![];
+        };
+        let is_autowired = self
+            .get_block_mapping_value(services, &["_defaults", "autowire"])
+            .is_some_and(|autowire| self.get_node_text(&autowire) == "true");
+
+        self.get_block_mapping_pairs(&services)
+            .into_iter()
+            .filter_map(|pair| {
+                let key = pair.child_by_field_name("key")?;
+                let service_name = self.get_node_text(&key).trim_matches(['\'', '"']);
+                let value = pair.child_by_field_name("value")?;
+                let map = self.get_block_node_map(&value)?;
+                if service_name == "_defaults" || map.contains_key("alias") {
+                    return None;
+                }
+
+                let sequence = map
+                    .get("arguments")
+                    .and_then(|arguments| arguments.named_child(0))
+                    .filter(|sequence| sequence.kind().ends_with("_sequence"));
+                let arguments = sequence
+                    .map(|sequence| {
+                        sequence
+                            .named_children(&mut sequence.walk())
+                            .filter_map(|item| match item.kind() {
+                                "block_sequence_item" => item.named_child(0),
+                                _ => Some(item),
+                            })
+                            .map(|argument| {
+                                (
+                                    self.get_node_text(&argument)
+                                        .trim_matches(['\'', '"'])
+                                        .to_string(),
+                                    argument.range(),
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                Some(ServiceArguments {
+                    service_name: service_name.to_string(),
+                    range: key.range(),
+                    arguments,
+                    is_complete: !is_autowired
+                        && (sequence.is_some() || !map.contains_key("arguments"))
+                        && ["parent", "factory", "autowire"]
+                            .iter()
+                            .all(|key| !map.contains_key(key)),
+                })
+            })
+            .collect()
     }
 
     pub fn get_token_at_position(&self, position: Position) -> Option<Token> {
@@ -47,7 +353,7 @@ impl YamlParser {
         let mut tokens: Vec<Token> = vec![];
 
         let mut current_nodes: Box<Vec<Node>> = Box::new(nodes.clone());
-        while current_nodes.len() > 0 {
+        while !current_nodes.is_empty() {
             let mut new_nodes: Box<Vec<Node>> = Box::default();
             for node in current_nodes.into_iter() {
                 if node.is_error() {
@@ -73,6 +379,7 @@ impl YamlParser {
     fn parse_node(&self, node: Node, point: Option<Point>) -> Option<Token> {
         match node.kind() {
             "block_mapping_pair" => self.parse_block_mapping_pair(node, point),
+            "block_sequence_item" => self.parse_block_sequence_item(node),
             _ => None,
         }
     }
@@ -83,13 +390,87 @@ impl YamlParser {
         let value_node = node.child_by_field_name("value")?;
 
         if let Some(map) = self.get_block_node_map(&value_node) {
+            // Parse Drupal config schema. The top level keys define config objects and data types,
+            // which are only indexed, so that the types under the cursor are found.
+            if self.uri.ends_with(".schema.yml") {
+                if point.is_none() && node.parent()?.parent()?.parent()?.kind() == "document" {
+                    let get_value = |name: &str| {
+                        map.get(name).map(|node| {
+                            self.get_node_text(node)
+                                .trim_matches(['\'', '"'])
+                                .to_string()
+                        })
+                    };
+                    let mut keys = vec![];
+                    if let Some(mapping) = map.get("mapping") {
+                        self.get_config_schema_keys(mapping, "", &mut keys);
+                    }
+                    return Some(Token::new(
+                        TokenData::DrupalConfigSchemaDefinition(DrupalConfigSchema {
+                            name: key.trim_matches(['\'', '"']).to_string(),
+                            type_name: get_value("type"),
+                            label: get_value("label"),
+                            keys,
+                        }),
+                        node.range(),
+                    ));
+                }
+            }
+            // Parse Drupal Library.
+            else if self.uri.ends_with(".libraries.yml") {
+                if node.parent()?.parent()?.parent()?.kind() == "document" {
+                    return Some(Token::new(
+                        TokenData::DrupalLibraryDefinition(DrupalLibrary {
+                            name: format!("{}/{}", self.get_extension_name()?, key),
+                            assets: self.get_library_assets(&map),
+                            dependencies: map
+                                .get("dependencies")
+                                .map(|dependencies| self.get_scalar_values(dependencies))
+                                .unwrap_or_default(),
+                        }),
+                        node.range(),
+                    ));
+                }
+            }
             // Parse Drupal Permission.
-            if self.uri.ends_with(".permissions.yml") {
+            else if self.uri.ends_with(".permissions.yml") {
                 if let Some(title) = map.get("title") {
+                    let get_text = |node: &Node| {
+                        self.get_node_text(node)
+                            .trim_matches(['\'', '"'])
+                            .to_string()
+                    };
                     return Some(Token::new(
                         TokenData::DrupalPermissionDefinition(DrupalPermission {
-                            name: key.to_string(),
-                            title: self.get_node_text(title).to_string(),
+                            name: key.into(),
+                            title: get_text(title),
+                            description: map.get("description").map(get_text),
+                            restrict_access: map.get("restrict access").is_some_and(
+                                |restrict_access| {
+                                    get_text(restrict_access).eq_ignore_ascii_case("true")
+                                },
+                            ),
+                        }),
+                        node.range(),
+                    ));
+                }
+            }
+            // Parse Drupal menu links.
+            else if self.uri.ends_with(".links.menu.yml") {
+                if node.parent()?.parent()?.parent()?.kind() == "document" {
+                    let get_value = |key: &str| {
+                        map.get(key).map(|node| {
+                            self.get_node_text(node)
+                                .trim_matches(['\'', '"'])
+                                .to_string()
+                        })
+                    };
+                    return Some(Token::new(
+                        TokenData::DrupalMenuLinkDefinition(DrupalMenuLink {
+                            id: key.trim_matches(['\'', '"']).to_string(),
+                            title: get_value("title"),
+                            route_name: get_value("route_name"),
+                            parent: get_value("parent"),
                         }),
                         node.range(),
                     ));
@@ -99,19 +480,71 @@ impl YamlParser {
             else if let (Some(path), Some(defaults)) = (map.get("path"), map.get("defaults")) {
                 return Some(Token::new(
                     TokenData::DrupalRouteDefinition(DrupalRoute {
-                        name: key.to_string(),
-                        path: self.get_node_text(path).to_string(),
+                        name: key.into(),
+                        path: self
+                            .get_node_text(path)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                        methods: map
+                            .get("methods")
+                            .map(|methods| self.get_scalar_values(methods))
+                            .unwrap_or_default(),
                         _defaults: self.parse_route_defaults(defaults)?,
+                        requirements: map
+                            .get("requirements")
+                            .map(|requirements| self.get_route_requirements(requirements))
+                            .unwrap_or_default(),
                     }),
                     node.range(),
                 ));
             }
             // Parse Drupal Service.
-            else if let Some(class) = map.get("class") {
+            else if map.contains_key("class")
+                || (self.uri.ends_with(".services.yml")
+                    && (["abstract", "alias", "parent"]
+                        .iter()
+                        .any(|key| map.contains_key(key))
+                        || map
+                            .get("synthetic")
+                            .is_some_and(|synthetic| self.get_node_text(synthetic) == "true")))
+            {
                 return Some(Token::new(
                     TokenData::DrupalServiceDefinition(DrupalService {
-                        name: key.to_string(),
-                        class: PhpClassName::from(self.get_node_text(class)),
+                        name: key.into(),
+                        class: map
+                            .get("class")
+                            .map(|class| PhpClassName::from(self.get_node_text(class))),
+                        tags: map
+                            .get("tags")
+                            .map(|tags| self.get_service_tags(tags))
+                            .unwrap_or_default(),
+                        arguments: map
+                            .get("arguments")
+                            .map(|arguments| self.get_scalar_values(arguments))
+                            .unwrap_or_default(),
+                        is_abstract: map
+                            .get("abstract")
+                            .is_some_and(|value| self.get_node_text(value) == "true"),
+                        parent: map.get("parent").map(|parent| {
+                            self.get_node_text(parent)
+                                .trim_matches(['\'', '"'])
+                                .to_string()
+                        }),
+                        alias: map.get("alias").map(|alias| {
+                            self.get_node_text(alias)
+                                .trim_matches(['\'', '"'])
+                                .to_string()
+                        }),
+                        // The message is either the value or in a `message` key.
+                        deprecated: map.get("deprecated").map(|deprecated| {
+                            let message = self
+                                .get_block_node_map(deprecated)
+                                .and_then(|deprecated| deprecated.get("message").copied())
+                                .unwrap_or(*deprecated);
+                            self.get_node_text(&message)
+                                .trim_matches(['\'', '"'])
+                                .to_string()
+                        }),
                     }),
                     node.range(),
                 ));
@@ -119,6 +552,14 @@ impl YamlParser {
         }
 
         match key {
+            "type" if self.uri.ends_with(".schema.yml") => Some(Token::new(
+                TokenData::DrupalConfigSchemaReference(
+                    self.get_node_text(&value_node)
+                        .trim_matches(['\'', '"'])
+                        .to_string(),
+                ),
+                value_node.range(),
+            )),
             "_controller" => Some(Token::new(
                 TokenData::PhpMethodReference(
                     PhpMethod::try_from(self.get_node_text(&value_node)).ok()?,
@@ -131,37 +572,466 @@ impl YamlParser {
             )),
             "_permission" => Some(Token::new(
                 TokenData::DrupalPermissionReference(
+                    self.get_node_text(&value_node).replace("'", "").into(),
+                ),
+                value_node.range(),
+            )),
+            "_role" => Some(Token::new(
+                TokenData::DrupalRoleReference(
                     self.get_node_text(&value_node).to_string().replace("'", ""),
                 ),
                 value_node.range(),
             )),
+            "parent" if self.uri.ends_with(".links.menu.yml") => Some(Token::new(
+                TokenData::DrupalMenuLinkReference(
+                    self.get_node_text(&value_node)
+                        .trim_matches(['\'', '"'])
+                        .to_string(),
+                ),
+                value_node.range(),
+            )),
+            "parent" if self.uri.ends_with(".services.yml") => Some(Token::new(
+                TokenData::DrupalServiceReference(
+                    self.get_node_text(&value_node)
+                        .trim_matches(['\'', '"'])
+                        .into(),
+                ),
+                value_node.range(),
+            )),
             "route_name" => Some(Token::new(
                 TokenData::DrupalRouteReference(
-                    self.get_node_text(&value_node).to_string().replace("'", ""),
+                    self.get_node_text(&value_node).replace("'", "").into(),
                 ),
                 value_node.range(),
             )),
+            // Dependencies in a flow sequence, f.e `dependencies: [core/once]`. Items of block
+            // sequences are parsed on their own.
+            "dependencies" if self.uri.ends_with(".libraries.yml") => {
+                let dependency = value_node.descendant_for_point_range(point?, point?)?;
+                if !dependency.kind().ends_with("_scalar") {
+                    return None;
+                }
+
+                Some(Token::new(
+                    TokenData::DrupalLibraryReference(
+                        self.get_node_text(&dependency)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    ),
+                    dependency.range(),
+                ))
+            }
             "arguments" => {
                 let argument = value_node.descendant_for_point_range(point?, point?)?;
-                if argument.kind() != "single_quote_scalar" {
+                if argument.kind() != "single_quote_scalar"
+                    && argument.kind() != "double_quote_scalar"
+                {
                     return None;
                 }
 
+                // Optional services are prefixed with `@?`.
                 let argument_string = self
                     .get_node_text(&argument)
                     .to_string()
-                    .trim_matches(['\'', '@'])
+                    .trim_matches(['\'', '"', '@', '?'])
                     .to_string();
 
                 Some(Token::new(
-                    TokenData::DrupalServiceReference(argument_string),
+                    TokenData::DrupalServiceReference(argument_string.into()),
+                    value_node.range(),
+                ))
+            }
+            "entity_type" | "target_type"
+                if (self.is_default_config() || self.is_view_config())
+                    && value_node.kind() == "flow_node" =>
+            {
+                Some(Token::new(
+                    TokenData::DrupalPluginReference(DrupalPluginReference {
+                        plugin_type: DrupalPluginType::EntityType,
+                        plugin_id: self
+                            .get_node_text(&value_node)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    }),
+                    value_node.range(),
+                ))
+            }
+            // Views refer to the tables of entity types, f.e `base_table: node_field_data`.
+            "base_table" | "table" if self.is_view_config() && value_node.kind() == "flow_node" => {
+                Some(Token::new(
+                    TokenData::DrupalPluginReference(DrupalPluginReference {
+                        plugin_type: DrupalPluginType::EntityType,
+                        plugin_id: self
+                            .get_node_text(&value_node)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    }),
+                    value_node.range(),
+                ))
+            }
+            "display_plugin" | "plugin_id" | "type"
+                if self.is_view_config() && self.get_views_plugin_type(node).is_some() =>
+            {
+                Some(Token::new(
+                    TokenData::DrupalPluginReference(DrupalPluginReference {
+                        plugin_type: self.get_views_plugin_type(node)?,
+                        plugin_id: self
+                            .get_node_text(&value_node)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    }),
                     value_node.range(),
                 ))
             }
+            // The fields of views handlers, f.e `entity_field: title` next to `entity_type: node`.
+            "entity_field" if self.is_view_config() => {
+                let siblings = node.parent()?.parent()?;
+                let entity_type = self.get_block_mapping_value(siblings, &["entity_type"])?;
+                Some(Token::new(
+                    TokenData::DrupalEntityFieldReference(DrupalEntityFieldReference {
+                        entity_type: DrupalEntityTypeHint::Id(
+                            self.get_node_text(&entity_type)
+                                .trim_matches(['\'', '"'])
+                                .to_string(),
+                        ),
+                        field_name: self
+                            .get_node_text(&value_node)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    }),
+                    value_node.range(),
+                ))
+            }
+            // Keys in default config are described by the config schema.
+            _ if point.is_some() && self.is_default_config() => self.get_config_key_reference(node),
+            _ => None,
+        }
+    }
+
+    /// Check whether this file is default config, f.e `config/install/mymodule.settings.yml`.
+    pub fn is_default_config(&self) -> bool {
+        is_default_config_file(&self.uri)
+    }
+
+    /// Check whether this file is the config of a view, f.e `views.view.frontpage.yml`.
+    fn is_view_config(&self) -> bool {
+        self.uri
+            .rsplit('/')
+            .next()
+            .is_some_and(|file_name| file_name.starts_with("views.view."))
+    }
+
+    /// Get a reference to the config key of a mapping pair in default config, f.e
+    /// `mymodule.settings:page.front`.
+    fn get_config_key_reference(&self, node: Node) -> Option<Token> {
+        let config_name = self.uri.rsplit('/').next()?.strip_suffix(".yml")?;
+        let keys = self.get_keys(node)?;
+
+        Some(Token::new(
+            TokenData::DrupalConfigKeyReference(DrupalConfigKeyReference {
+                config_name: config_name.to_string(),
+                key: keys.join("."),
+            }),
+            node.child_by_field_name("key")?.range(),
+        ))
+    }
+
+    /// Get the keys from the root of the file up to a mapping pair, f.e
+    /// `["display", "default", "display_plugin"]`.
+    fn get_keys(&self, node: Node) -> Option<Vec<&str>> {
+        let mut keys: Vec<&str> = vec![];
+        let mut current = Some(node);
+        while let Some(pair) = current {
+            if pair.kind() == "block_mapping_pair" {
+                keys.push(self.get_node_text(&pair.child_by_field_name("key")?));
+            }
+            current = pair.parent();
+        }
+        keys.reverse();
+        Some(keys)
+    }
+
+    /// Get the type of views plugin a mapping pair refers to, based on where it is in the view,
+    /// f.e `display.default.display_options.fields.title.plugin_id` refers to a field plugin.
+    fn get_views_plugin_type(&self, node: Node) -> Option<DrupalPluginType> {
+        match self.get_keys(node)?.as_slice() {
+            ["display", _, "display_plugin"] => Some(DrupalPluginType::ViewsDisplay),
+            [.., "display_options", "row", "type"] => Some(DrupalPluginType::ViewsRow),
+            [.., "display_options", "style", "type"] => Some(DrupalPluginType::ViewsStyle),
+            [.., "display_options", handler_type, _, "plugin_id"] => match *handler_type {
+                "fields" => Some(DrupalPluginType::ViewsField),
+                "filters" => Some(DrupalPluginType::ViewsFilter),
+                "sorts" => Some(DrupalPluginType::ViewsSort),
+                "arguments" => Some(DrupalPluginType::ViewsArgument),
+                "relationships" => Some(DrupalPluginType::ViewsRelationship),
+                "header" | "footer" | "empty" => Some(DrupalPluginType::ViewsArea),
+                _ => None,
+            },
             _ => None,
         }
     }
 
+    /// Parse the routes of the displays of a view that have a path, f.e `view.frontpage.page_1`
+    /// in `views.view.frontpage.yml`.
+    fn parse_view<'a>(&self, root_node: Node<'a>) -> Vec<Token> {
+        let get_value = |node: Node| {
+            self.get_node_text(&node)
+                .trim_matches(['\'', '"'])
+                .to_string()
+        };
+        let Some(node) = get_root_block_node(root_node) else {
+            return vec![];
+        };
+        let (Some(view_id), Some(displays)) = (
+            self.get_block_mapping_value(node, &["id"]).map(get_value),
+            self.get_block_mapping_value(node, &["display"]),
+        ) else {
+            return vec![];
+        };
+
+        // Displays inherit the options they don't override from the default display.
+        let default_display = self.get_block_mapping_value(displays, &["default"]);
+        let get_option = |display: Node<'a>, keys: &[&str]| -> Option<Node<'a>> {
+            let keys = [&["display_options"], keys].concat();
+            self.get_block_mapping_value(display, &keys)
+                .or_else(|| self.get_block_mapping_value(default_display?, &keys))
+        };
+
+        self.get_block_mapping_pairs(&displays)
+            .into_iter()
+            .filter_map(|display_pair| {
+                let display_id = get_value(display_pair.child_by_field_name("key")?);
+                let display = display_pair.child_by_field_name("value")?;
+                let path = self.get_block_mapping_value(display, &["display_options", "path"])?;
+
+                let requirements = match get_option(display, &["access", "type"]).map(get_value) {
+                    Some(access) if access == "perm" => {
+                        get_option(display, &["access", "options", "perm"]).map(|permission| {
+                            vec![("_permission".to_string(), get_value(permission))]
+                        })
+                    }
+                    Some(access) if access == "role" => {
+                        get_option(display, &["access", "options", "role"]).map(|roles| {
+                            vec![(
+                                "_role".to_string(),
+                                self.get_block_mapping_keys(&roles).join("+"),
+                            )]
+                        })
+                    }
+                    _ => None,
+                };
+
+                Some(Token::new(
+                    TokenData::DrupalRouteDefinition(DrupalRoute {
+                        name: format!("view.{}.{}", view_id, display_id).into(),
+                        path: get_view_route_path(&get_value(path)),
+                        methods: vec![],
+                        _defaults: DrupalRouteDefaults {
+                            _controller: PhpMethod::try_from(
+                                "\\Drupal\\views\\Routing\\ViewPageController::handle",
+                            )
+                            .ok(),
+                            _form: None,
+                            _entity_form: None,
+                            _title: get_option(display, &["title"]).map(get_value),
+                            _title_callback: None,
+                        },
+                        requirements: requirements.unwrap_or_default(),
+                    }),
+                    display_pair.range(),
+                ))
+            })
+            .collect()
+    }
+
+    fn parse_block_sequence_item(&self, node: Node) -> Option<Token> {
+        let pair_node = get_closest_parent_by_kind(&node, "block_mapping_pair")?;
+        let key_node = pair_node.child_by_field_name("key")?;
+        let value_node = node.named_child(0)?;
+
+        match self.get_node_text(&key_node) {
+            // Permission callbacks provide dynamic permissions, f.e
+            // `\Drupal\node\NodePermissions::nodeTypePermissions`.
+            "permission_callbacks" if self.uri.ends_with(".permissions.yml") => Some(Token::new(
+                TokenData::PhpMethodReference(
+                    PhpMethod::try_from(self.get_node_text(&value_node)).ok()?,
+                ),
+                value_node.range(),
+            )),
+            // Config dependencies, f.e `dependencies: { config: [node.type.article] }`.
+            "config" if self.is_default_config() => Some(Token::new(
+                TokenData::DrupalConfigReference(
+                    self.get_node_text(&value_node)
+                        .trim_matches(['\'', '"'])
+                        .to_string(),
+                ),
+                value_node.range(),
+            )),
+            // Module dependencies, f.e `drupal:node (>=10.3)`. The token only covers the module name.
+            "dependencies" if self.uri.ends_with(".info.yml") => {
+                let captures = INFO_DEPENDENCY.captures(self.get_node_text(&value_node))?;
+                let module = captures.name("module")?;
+                let mut range = value_node.range();
+                range.start_byte += module.start();
+                range.end_byte = range.start_byte + module.len();
+                range.start_point.column += module.start();
+                range.end_point = Point {
+                    row: range.start_point.row,
+                    column: range.start_point.column + module.len(),
+                };
+                Some(Token::new(
+                    TokenData::DrupalModuleReference(module.as_str().to_string()),
+                    range,
+                ))
+            }
+            // Themes also attach libraries globally in their `*.info.yml` file.
+            key @ ("dependencies" | "libraries")
+                if self.uri.ends_with(match key {
+                    "dependencies" => ".libraries.yml",
+                    _ => ".info.yml",
+                }) =>
+            {
+                Some(Token::new(
+                    TokenData::DrupalLibraryReference(
+                        self.get_node_text(&value_node)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    ),
+                    value_node.range(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parse field storage config, which describes a single field.
+    fn parse_field_storage(&self, root_node: Node) -> Option<Token> {
+        let node = get_root_block_node(root_node)?;
+        let map = self.get_block_node_map(&node)?;
+        let get_value = |key: &str| {
+            map.get(key).map(|node| {
+                self.get_node_text(node)
+                    .trim_matches(['\'', '"'])
+                    .to_string()
+            })
+        };
+
+        Some(Token::new(
+            TokenData::DrupalFieldStorageDefinition(DrupalFieldStorage {
+                entity_type: get_value("entity_type")?,
+                field: DrupalEntityField {
+                    name: get_value("field_name")?,
+                    field_type: get_value("type"),
+                    settings: map
+                        .get("settings")
+                        .map(|settings| self.get_block_mapping_pairs(settings))
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|pair| {
+                            let value = pair.child_by_field_name("value")?;
+                            get_scalar_type(value)?;
+                            Some((
+                                self.get_node_text(&pair.child_by_field_name("key")?)
+                                    .to_string(),
+                                self.get_node_text(&value)
+                                    .trim_matches(['\'', '"'])
+                                    .to_string(),
+                            ))
+                        })
+                        .collect(),
+                },
+            }),
+            node.range(),
+        ))
+    }
+
+    /// Parse user role config, f.e `user.role.editor.yml`.
+    fn parse_role(&self, root_node: Node) -> Option<Token> {
+        let node = get_root_block_node(root_node)?;
+        let map = self.get_block_node_map(&node)?;
+        let get_value = |key: &str| {
+            map.get(key).map(|node| {
+                self.get_node_text(node)
+                    .trim_matches(['\'', '"'])
+                    .to_string()
+            })
+        };
+
+        Some(Token::new(
+            TokenData::DrupalRoleDefinition(DrupalRole {
+                id: get_value("id")?,
+                label: get_value("label")?,
+            }),
+            node.range(),
+        ))
+    }
+
+    /// Parse the config of an entity bundle, f.e `node.type.article.yml`.
+    fn parse_entity_bundle(&self, root_node: Node) -> Option<Token> {
+        let file_name = self.uri.rsplit('/').next()?;
+        let (name, entity_type) =
+            ENTITY_BUNDLE_CONFIG
+                .iter()
+                .find_map(|(prefix, entity_type)| {
+                    Some((
+                        file_name.strip_prefix(prefix)?.strip_suffix(".yml")?,
+                        entity_type,
+                    ))
+                })?;
+        let node = get_root_block_node(root_node)?;
+        let map = self.get_block_node_map(&node)?;
+        let get_value = |key: &str| {
+            map.get(key).map(|node| {
+                self.get_node_text(node)
+                    .trim_matches(['\'', '"'])
+                    .to_string()
+            })
+        };
+
+        Some(Token::new(
+            TokenData::DrupalEntityBundleDefinition(DrupalEntityBundle {
+                entity_type: entity_type.to_string(),
+                name: name.to_string(),
+                // Node types and vocabularies have a name instead of a label.
+                label: get_value("label").or_else(|| get_value("name")),
+                description: get_value("description").filter(|description| !description.is_empty()),
+            }),
+            node.range(),
+        ))
+    }
+
+    /// Parse the `*.info.yml` file of a module, theme or profile.
+    fn parse_info_file(&self, root_node: Node) -> Option<Token> {
+        let node = get_root_block_node(root_node)?;
+        let map = self.get_block_node_map(&node)?;
+        let get_value = |key: &str| {
+            map.get(key).map(|node| {
+                self.get_node_text(node)
+                    .trim_matches(['\'', '"'])
+                    .to_string()
+            })
+        };
+
+        let name = self.get_extension_name()?;
+        Some(Token::new(
+            TokenData::DrupalExtensionDefinition(DrupalExtension {
+                name: name.to_string(),
+                label: get_value("name")?,
+                description: get_value("description"),
+                extension_type: get_value("type")?,
+                package: get_value("package"),
+                // Core extensions have `VERSION` as their version.
+                version: get_value("version").filter(|version| version != "VERSION"),
+                core_version_requirement: get_value("core_version_requirement"),
+                // Packaged contrib extensions have their project in the info file.
+                project: get_value("project").unwrap_or_else(|| {
+                    get_extension_project(&self.uri).unwrap_or(name).to_string()
+                }),
+            }),
+            node.range(),
+        ))
+    }
+
     fn parse_route_defaults(&self, node: &Node) -> Option<DrupalRouteDefaults> {
         let map = self.get_block_node_map(node)?;
         Some(DrupalRouteDefaults {
@@ -172,14 +1042,199 @@ impl YamlParser {
                 .get("_form")
                 .map(|node| PhpClassName::from(self.get_node_text(node))),
             _entity_form: map
-                .get("_form")
+                .get("_entity_form")
                 .map(|node| self.get_node_text(node).to_string()),
             _title: map
                 .get("_title")
                 .map(|node| self.get_node_text(node).to_string()),
+            _title_callback: map.get("_title_callback").map(|node| {
+                self.get_node_text(node)
+                    .trim_matches(['\'', '"'])
+                    .to_string()
+            }),
         })
     }
 
+    /// Get the key/value pairs of route `requirements`, keeping their order.
+    fn get_route_requirements(&self, node: &Node) -> Vec<(String, String)> {
+        let Some(mapping_node) = node.named_child(0) else {
+            return vec![];
+        };
+        let mut cursor = mapping_node.walk();
+        mapping_node
+            .named_children(&mut cursor)
+            .filter_map(|pair| {
+                Some((
+                    self.get_node_text(&pair.child_by_field_name("key")?)
+                        .to_string(),
+                    self.get_node_text(&pair.child_by_field_name("value")?)
+                        .trim_matches(['\'', '"'])
+                        .to_string(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Get the names of the tags in a service `tags` sequence.
+    fn get_service_tags(&self, node: &Node) -> Vec<String> {
+        let mut tags: Vec<String> = vec![];
+        let mut nodes = vec![*node];
+        while let Some(node) = nodes.pop() {
+            if let (Some(key), Some(value)) = (
+                node.child_by_field_name("key"),
+                node.child_by_field_name("value"),
+            ) {
+                if self.get_node_text(&key) == "name" {
+                    tags.push(
+                        self.get_node_text(&value)
+                            .trim_matches(['\'', '"'])
+                            .to_string(),
+                    );
+                }
+                continue;
+            }
+
+            let mut cursor = node.walk();
+            nodes.extend(node.named_children(&mut cursor));
+        }
+        tags.reverse();
+        tags
+    }
+
+    /// Get the keys of a config schema `mapping`, including the keys of nested mappings.
+    fn get_config_schema_keys(
+        &self,
+        node: &Node,
+        prefix: &str,
+        keys: &mut Vec<DrupalConfigSchemaKey>,
+    ) {
+        let Some(mapping_node) = node.named_child(0) else {
+            return;
+        };
+        let mut cursor = mapping_node.walk();
+        for pair in mapping_node.named_children(&mut cursor) {
+            let (Some(key_node), Some(value_node)) = (
+                pair.child_by_field_name("key"),
+                pair.child_by_field_name("value"),
+            ) else {
+                continue;
+            };
+            let key = format!(
+                "{}{}",
+                prefix,
+                self.get_node_text(&key_node).trim_matches(['\'', '"'])
+            );
+            let map = self.get_block_node_map(&value_node).unwrap_or_default();
+            let get_value = |name: &str| {
+                map.get(name).map(|node| {
+                    self.get_node_text(node)
+                        .trim_matches(['\'', '"'])
+                        .to_string()
+                })
+            };
+            keys.push(DrupalConfigSchemaKey {
+                key: key.clone(),
+                type_name: get_value("type"),
+                label: get_value("label"),
+            });
+
+            if let Some(mapping) = map.get("mapping") {
+                self.get_config_schema_keys(mapping, &format!("{}.", key), keys);
+            }
+        }
+    }
+
+    /// Get the JavaScript files and the CSS files of all groups of a library.
+    fn get_library_assets(&self, map: &HashMap<&str, Node>) -> Vec<String> {
+        let mut assets: Vec<String> = vec![];
+        if let Some(css) = map.get("css") {
+            for group in self.get_block_mapping_pairs(css) {
+                if let Some(files) = group.child_by_field_name("value") {
+                    assets.extend(self.get_block_mapping_keys(&files));
+                }
+            }
+        }
+        if let Some(js) = map.get("js") {
+            assets.extend(self.get_block_mapping_keys(js));
+        }
+        assets
+    }
+
+    fn get_block_mapping_keys(&self, node: &Node) -> Vec<String> {
+        self.get_block_mapping_pairs(node)
+            .iter()
+            .filter_map(|pair| pair.child_by_field_name("key"))
+            .map(|key| {
+                self.get_node_text(&key)
+                    .trim_matches(['\'', '"'])
+                    .to_string()
+            })
+            .collect()
+    }
+
+    /// Get the value of a nested key of a block mapping, f.e `["display_options", "path"]`.
+    fn get_block_mapping_value<'a>(&self, node: Node<'a>, keys: &[&str]) -> Option<Node<'a>> {
+        keys.iter().try_fold(node, |node, key| {
+            self.get_block_mapping_pairs(&node)
+                .into_iter()
+                .find(|pair| {
+                    pair.child_by_field_name("key").is_some_and(|key_node| {
+                        self.get_node_text(&key_node).trim_matches(['\'', '"']) == *key
+                    })
+                })?
+                .child_by_field_name("value")
+        })
+    }
+
+    fn get_block_mapping_pairs<'a>(&self, node: &Node<'a>) -> Vec<Node<'a>> {
+        if node.kind() != "block_node" {
+            return vec![];
+        }
+        match node.named_child(0) {
+            Some(mapping) => mapping.named_children(&mut mapping.walk()).collect(),
+            None => vec![],
+        }
+    }
+
+    /// Get the values of a block or flow sequence.
+    fn get_scalar_values(&self, node: &Node) -> Vec<String> {
+        let mut values: Vec<String> = vec![];
+        let mut nodes = vec![*node];
+        while let Some(node) = nodes.pop() {
+            if node.kind().ends_with("_scalar") {
+                values.push(
+                    self.get_node_text(&node)
+                        .trim_matches(['\'', '"'])
+                        .to_string(),
+                );
+                continue;
+            }
+            nodes.extend(
+                node.named_children(&mut node.walk())
+                    .collect::<Vec<Node>>()
+                    .into_iter()
+                    .rev(),
+            );
+        }
+        values
+    }
+
+    fn find_block_mapping_pair<'a>(&self, node: Node<'a>, key: &str) -> Option<Node<'a>> {
+        if node.kind() == "block_mapping_pair"
+            && node
+                .child_by_field_name("key")
+                .is_some_and(|key_node| self.get_node_text(&key_node) == key)
+        {
+            return Some(node);
+        }
+
+        let mut cursor = node.walk();
+        let children: Vec<Node<'a>> = node.named_children(&mut cursor).collect();
+        children
+            .into_iter()
+            .find_map(|child| self.find_block_mapping_pair(child, key))
+    }
+
     fn get_block_node_map<'a>(&'a self, node: &'a Node) -> Option<HashMap<&'a str, Node<'a>>> {
         if node.kind() != "block_node" {
             return None;
@@ -198,7 +1253,981 @@ impl YamlParser {
         Some(result)
     }
 
+    /// Get the machine name of the module or theme this file belongs to, based on the file name.
+    fn get_extension_name(&self) -> Option<&str> {
+        let (name, _) = self.uri.rsplit('/').next()?.split_once('.')?;
+        Some(name)
+    }
+
     fn get_node_text(&self, node: &Node) -> &str {
         node.utf8_text(self.source.as_bytes()).unwrap_or("")
     }
 }
+
+/// Get the end of a node, without the trailing whitespace that belongs to block nodes.
+fn get_last_named_descendant_end(node: Node) -> Point {
+    let mut node = node;
+    while node.kind().starts_with("block_") {
+        match node.named_child(node.named_child_count().saturating_sub(1)) {
+            Some(child) => node = child,
+            None => break,
+        }
+    }
+    node.end_position()
+}
+
+/// Get the project of an extension from the path of its info file, f.e `drupal` for
+/// `core/modules/node/node.info.yml` and `webform` for
+/// `modules/contrib/webform/modules/webform_ui/webform_ui.info.yml`.
+fn get_extension_project(uri: &str) -> Option<&str> {
+    let segments: Vec<&str> = uri.split('/').collect();
+    let index = segments
+        .iter()
+        .position(|segment| ["modules", "themes", "profiles"].contains(segment))?;
+    if index > 0 && segments[index - 1] == "core" {
+        return Some("drupal");
+    }
+    let segments = &segments[index + 1..segments.len() - 1];
+    match segments {
+        [group, project, ..] if ["contrib", "custom"].contains(group) => Some(project),
+        [project, ..] if !["contrib", "custom"].contains(project) => Some(project),
+        _ => None,
+    }
+}
+
+/// Convert the path of a views display to a route path, f.e `taxonomy/term/%taxonomy_term/feed`
+/// to `/taxonomy/term/{taxonomy_term}/feed`. Unnamed arguments are named by their position, f.e
+/// `{arg_0}`.
+fn get_view_route_path(path: &str) -> String {
+    let mut argument = 0;
+    let components: Vec<String> = path
+        .trim_start_matches('/')
+        .split('/')
+        .map(|component| match component.strip_prefix('%') {
+            Some("") => {
+                argument += 1;
+                format!("{{arg_{}}}", argument - 1)
+            }
+            Some(name) => format!("{{{}}}", name),
+            None => component.to_string(),
+        })
+        .collect();
+    format!("/{}", components.join("/"))
+}
+
+/// Get the type of a scalar value, f.e `boolean` for `true`. Quoted and block scalars are strings.
+fn get_scalar_type(node: Node) -> Option<&'static str> {
+    match node.kind() {
+        "flow_node" | "block_node" | "plain_scalar" => {
+            get_scalar_type(node.named_child(node.named_child_count().checked_sub(1)?)?)
+        }
+        "string_scalar" | "single_quote_scalar" | "double_quote_scalar" | "block_scalar" => {
+            Some("string")
+        }
+        "integer_scalar" => Some("integer"),
+        "float_scalar" => Some("float"),
+        "boolean_scalar" => Some("boolean"),
+        _ => None,
+    }
+}
+
+/// Get the block node that holds the top level keys of a config file.
+fn get_root_block_node(root_node: Node) -> Option<Node> {
+    let mut node = root_node;
+    while node.kind() != "block_node" {
+        node = node.named_child(0)?;
+    }
+    Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_library() {
+        let source = r#"
+drupal.dialog:
+  version: VERSION
+  js:
+    misc/dialog/dialog.js: {}
+    misc/dialog/dialog.position.js: {}
+  css:
+    component:
+      misc/dialog/off-canvas.css: {}
+  dependencies:
+    - core/jquery
+    - core/drupal
+"#;
+        let parser = YamlParser::new(source, "file:///core/core.libraries.yml");
+        let tokens = parser.get_tokens();
+        let Some(TokenData::DrupalLibraryDefinition(library)) =
+            tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a library definition");
+        };
+
+        assert_eq!("core/drupal.dialog", library.name);
+        assert_eq!(
+            vec![
+                "misc/dialog/off-canvas.css",
+                "misc/dialog/dialog.js",
+                "misc/dialog/dialog.position.js"
+            ],
+            library.assets
+        );
+        assert_eq!(vec!["core/jquery", "core/drupal"], library.dependencies);
+
+        let Some(TokenData::DrupalLibraryReference(dependency)) = parser
+            .get_token_at_position(Position {
+                line: 10,
+                character: 8,
+            })
+            .map(|token| token.data)
+        else {
+            panic!("Expected a library reference");
+        };
+        assert_eq!("core/jquery", dependency);
+
+        let parser = YamlParser::new(
+            "form:\n  dependencies: [core/once, 'core/drupal']\n",
+            "file:///mymodule/mymodule.libraries.yml",
+        );
+        let Some(TokenData::DrupalLibraryReference(dependency)) = parser
+            .get_token_at_position(Position {
+                line: 1,
+                character: 31,
+            })
+            .map(|token| token.data)
+        else {
+            panic!("Expected a library reference");
+        };
+        assert_eq!("core/drupal", dependency);
+
+        let parser = YamlParser::new(
+            "name: Olivero\nlibraries:\n  - olivero/global-styling\n",
+            "file:///olivero/olivero.info.yml",
+        );
+        let Some(TokenData::DrupalLibraryReference(library)) = parser
+            .get_token_at_position(Position {
+                line: 2,
+                character: 8,
+            })
+            .map(|token| token.data)
+        else {
+            panic!("Expected a library reference");
+        };
+        assert_eq!("olivero/global-styling", library);
+    }
+
+    #[test]
+    fn parse_config_values() {
+        let source = r#"
+name: 'Foo'
+page:
+  front: /node
+  limit: 10
+ratio: 0.5
+enabled: true
+empty:
+roles:
+  - editor
+description: |
+  Multiple
+  lines
+"#;
+        let values: Vec<(String, Option<&str>)> =
+            YamlParser::new(source, "file:///foo/config/install/foo.settings.yml")
+                .get_config_values()
+                .into_iter()
+                .map(|value| (value.keys.join("."), value.value_type))
+                .collect();
+        assert_eq!(
+            vec![
+                ("name".to_string(), Some("string")),
+                ("page".to_string(), None),
+                ("page.front".to_string(), Some("string")),
+                ("page.limit".to_string(), Some("integer")),
+                ("ratio".to_string(), Some("float")),
+                ("enabled".to_string(), Some("boolean")),
+                ("empty".to_string(), None),
+                ("roles".to_string(), None),
+                ("description".to_string(), Some("string")),
+            ],
+            values
+        );
+        assert!(YamlParser::new(source, "file:///foo/foo.settings.yml")
+            .get_config_values()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_library_asset_paths() {
+        let source = r#"
+global-styling:
+  css:
+    theme:
+      css/style.css: {}
+      'css/print.css': { media: print }
+      https://cdn.example.com/font.css: { type: external }
+  js:
+    js/script.js: {}
+    //cdn.example.com/lib.js: {}
+    js/lib.min.js: { type: external, minified: true }
+"#;
+        let paths: Vec<String> = YamlParser::new(source, "file:///foo/foo.libraries.yml")
+            .get_library_asset_paths()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(
+            vec!["css/style.css", "css/print.css", "js/script.js"],
+            paths
+        );
+    }
+
+    #[test]
+    fn parse_info_dependencies() {
+        let source = r#"name: Foo
+type: module
+dependencies:
+  - drupal:node (>=10.3)
+  - 'pathauto:pathauto'
+  - token
+libraries:
+  - foo/global
+"#;
+        let modules: Vec<(String, usize, usize)> =
+            YamlParser::new(source, "file:///foo/foo.info.yml")
+                .get_tokens()
+                .into_iter()
+                .filter_map(|token| match token.data {
+                    TokenData::DrupalModuleReference(module) => Some((
+                        module,
+                        token.range.start_point.column,
+                        token.range.end_point.column,
+                    )),
+                    _ => None,
+                })
+                .collect();
+        assert_eq!(
+            vec![
+                ("node".to_string(), 11, 15),
+                ("pathauto".to_string(), 14, 22),
+                ("token".to_string(), 4, 9),
+            ],
+            modules
+        );
+    }
+
+    #[test]
+    fn parse_info_file() {
+        let source = r#"name: 'Webform UI'
+type: module
+description: 'Provides a user interface.'
+package: Webform
+core_version_requirement: ^10
+version: '6.2.9'
+"#;
+        let extension = |uri: &str| {
+            YamlParser::new(source, uri)
+                .get_tokens()
+                .into_iter()
+                .find_map(|token| match token.data {
+                    TokenData::DrupalExtensionDefinition(extension) => Some(extension),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let webform_ui =
+            extension("file:///web/modules/contrib/webform/modules/webform_ui/webform_ui.info.yml");
+        assert_eq!("webform_ui", webform_ui.name);
+        assert_eq!("Webform UI", webform_ui.label);
+        assert_eq!("module", webform_ui.extension_type);
+        assert_eq!(
+            Some("Provides a user interface.".to_string()),
+            webform_ui.description
+        );
+        assert_eq!(Some("Webform".to_string()), webform_ui.package);
+        assert_eq!(Some("6.2.9".to_string()), webform_ui.version);
+        assert_eq!(Some("^10".to_string()), webform_ui.core_version_requirement);
+        assert_eq!("webform", webform_ui.project);
+        assert_eq!(
+            "drupal",
+            extension("file:///web/core/modules/node/node.info.yml").project
+        );
+        assert_eq!(
+            "foo",
+            extension("file:///web/modules/custom/foo/foo.info.yml").project
+        );
+        assert_eq!(
+            "foo",
+            extension("file:///web/modules/foo/foo.info.yml").project
+        );
+    }
+
+    #[test]
+    fn parse_route() {
+        let source = r#"
+mymodule.node_edit:
+  path: '/node/{node}/mymodule'
+  methods: [GET, POST]
+  defaults:
+    _form: '\Drupal\mymodule\Form\NodeForm'
+    _title: 'Edit'
+  requirements:
+    _permission: 'administer nodes'
+    node: \d+
+"#;
+        let parser = YamlParser::new(source, "file:///mymodule/mymodule.routing.yml");
+        let tokens = parser.get_tokens();
+        let Some(TokenData::DrupalRouteDefinition(route)) = tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a route definition");
+        };
+
+        assert_eq!("/node/{node}/mymodule", route.path);
+        assert_eq!(vec!["GET", "POST"], route.methods);
+        assert_eq!(
+            Some("Drupal\\mymodule\\Form\\NodeForm".to_string()),
+            route._defaults._form.as_ref().map(|form| form.to_string())
+        );
+        assert_eq!(
+            vec![
+                ("_permission".to_string(), "administer nodes".to_string()),
+                ("node".to_string(), "\\d+".to_string())
+            ],
+            route.requirements
+        );
+    }
+
+    #[test]
+    fn parse_default_config_key() {
+        let source = r#"langcode: en
+page:
+  front: /node
+"#;
+        let parser = YamlParser::new(
+            source,
+            "file:///mymodule/config/install/mymodule.settings.yml",
+        );
+        let Some(TokenData::DrupalConfigKeyReference(reference)) = parser
+            .get_token_at_position(Position {
+                line: 2,
+                character: 4,
+            })
+            .map(|token| token.data)
+        else {
+            panic!("Expected a config key reference");
+        };
+
+        assert_eq!("mymodule.settings", reference.config_name);
+        assert_eq!("page.front", reference.key);
+        assert!(parser.get_tokens().is_empty());
+    }
+
+    #[test]
+    fn parse_config_entity_type() {
+        let source = r#"langcode: en
+entity_type: node
+settings:
+  target_type: taxonomy_term
+"#;
+        let parser = YamlParser::new(
+            source,
+            "file:///mymodule/config/install/field.storage.node.field_tags.yml",
+        );
+        for (line, expected) in [(1, "node"), (3, "taxonomy_term")] {
+            let Some(TokenData::DrupalPluginReference(reference)) = parser
+                .get_token_at_position(Position {
+                    line,
+                    character: 15,
+                })
+                .map(|token| token.data)
+            else {
+                panic!("Expected an entity type reference");
+            };
+            assert_eq!(DrupalPluginType::EntityType, reference.plugin_type);
+            assert_eq!(expected, reference.plugin_id);
+        }
+    }
+
+    #[test]
+    fn parse_config_dependency() {
+        let source = r#"langcode: en
+dependencies:
+  config:
+    - node.type.article
+id: node.article.body
+"#;
+        let parser = YamlParser::new(
+            source,
+            "file:///mymodule/config/optional/field.field.node.article.body.yml",
+        );
+        let Some(TokenData::DrupalConfigReference(config_name)) = parser
+            .get_token_at_position(Position {
+                line: 3,
+                character: 10,
+            })
+            .map(|token| token.data)
+        else {
+            panic!("Expected a config reference");
+        };
+
+        assert_eq!("node.type.article", config_name);
+    }
+
+    #[test]
+    fn parse_entity_bundle() {
+        let source = r#"langcode: en
+status: true
+name: 'Basic page'
+type: page
+description: "Use <em>basic pages</em> for your static content."
+"#;
+        let tokens = YamlParser::new(source, "file:///config/sync/node.type.page.yml").get_tokens();
+        let Some(TokenData::DrupalEntityBundleDefinition(bundle)) =
+            tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected an entity bundle definition");
+        };
+        assert_eq!("node", bundle.entity_type);
+        assert_eq!("page", bundle.name);
+        assert_eq!(Some("Basic page".to_string()), bundle.label);
+        assert!(
+            YamlParser::new(source, "file:///config/sync/node.settings.yml")
+                .get_tokens()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parse_role() {
+        let source = r#"langcode: en
+status: true
+id: content_editor
+label: 'Content editor'
+weight: 2
+permissions:
+  - 'access content overview'
+"#;
+        let parser = YamlParser::new(source, "file:///config/sync/user.role.content_editor.yml");
+        let tokens = parser.get_tokens();
+        let Some(TokenData::DrupalRoleDefinition(role)) = tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a role definition");
+        };
+
+        assert_eq!("content_editor", role.id);
+        assert_eq!("Content editor", role.label);
+    }
+
+    #[test]
+    fn parse_field_storage() {
+        let source = r#"langcode: en
+status: true
+dependencies:
+  module:
+    - node
+    - taxonomy
+id: node.field_tags
+field_name: field_tags
+entity_type: node
+type: entity_reference
+settings:
+  target_type: taxonomy_term
+"#;
+        let parser = YamlParser::new(
+            source,
+            "file:///config/sync/field.storage.node.field_tags.yml",
+        );
+        let tokens = parser.get_tokens();
+        let Some(TokenData::DrupalFieldStorageDefinition(storage)) =
+            tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a field storage definition");
+        };
+
+        assert_eq!("node", storage.entity_type);
+        assert_eq!("field_tags", storage.field.name);
+        assert_eq!(
+            Some("entity_reference".to_string()),
+            storage.field.field_type
+        );
+        assert_eq!(
+            vec![("target_type".to_string(), "taxonomy_term".to_string())],
+            storage.field.settings
+        );
+    }
+
+    #[test]
+    fn parse_config_schema() {
+        let source = r#"
+system.site:
+  type: config_object
+  label: 'Site information'
+  mapping:
+    name:
+      type: label
+      label: 'Site name'
+    page:
+      type: mapping
+      label: 'Pages'
+      mapping:
+        front:
+          type: path
+          label: 'Default front page'
+    mail_notification:
+      type: string
+"#;
+        let parser = YamlParser::new(source, "file://system.schema.yml");
+        let tokens = parser.get_tokens();
+        let Some(TokenData::DrupalConfigSchemaDefinition(schema)) =
+            tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a config schema definition");
+        };
+
+        assert_eq!("system.site", schema.name);
+        assert_eq!(Some("config_object".to_string()), schema.type_name);
+        assert_eq!(Some("Site information".to_string()), schema.label);
+        assert_eq!(
+            vec!["name", "page", "page.front", "mail_notification"],
+            schema
+                .keys
+                .iter()
+                .map(|key| key.key.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(Some("path".to_string()), schema.keys[2].type_name);
+        assert_eq!(Some("Default front page".to_string()), schema.keys[2].label);
+    }
+
+    #[test]
+    fn parse_config_schema_types() {
+        let source = r#"
+foo.list:
+  type: sequence
+  label: 'Foo list'
+  sequence:
+    type: mapping
+    mapping:
+      id:
+        type: string
+"#;
+        let parser = YamlParser::new(source, "file:///foo/config/schema/foo.schema.yml");
+        let schemas: Vec<(String, Option<String>)> = parser
+            .get_tokens()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalConfigSchemaDefinition(schema) => {
+                    Some((schema.name, schema.type_name))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![("foo.list".to_string(), Some("sequence".to_string()))],
+            schemas
+        );
+
+        let Some(TokenData::DrupalConfigSchemaReference(type_name)) = parser
+            .get_token_at_position(Position::new(5, 12))
+            .map(|token| token.data)
+        else {
+            panic!("Expected a config schema reference");
+        };
+        assert_eq!("mapping", type_name);
+    }
+
+    #[test]
+    fn parse_permission_definitions() {
+        let source = r#"
+administer nodes:
+  title: 'Administer content'
+  description: 'Promote, change ownership, edit revisions, and perform other tasks.'
+  restrict access: true
+
+access content:
+  title: 'View published content'
+
+administer users:
+  title: "Administer users"
+  restrict access: TRUE
+
+bypass node access:
+  title: Bypass node access
+  restrict access: 'true'
+"#;
+        let tokens = YamlParser::new(source, "file:///node.permissions.yml").get_tokens();
+        let permissions: Vec<(&str, Option<&str>, bool)> = tokens
+            .iter()
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalPermissionDefinition(permission) => Some((
+                    permission.title.as_str(),
+                    permission.description.as_deref(),
+                    permission.restrict_access,
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "Administer content",
+                    Some("Promote, change ownership, edit revisions, and perform other tasks."),
+                    true
+                ),
+                ("View published content", None, false),
+                ("Administer users", None, true),
+                ("Bypass node access", None, true),
+            ],
+            permissions
+        );
+    }
+
+    #[test]
+    fn parse_permission_callbacks() {
+        let source = r#"
+access content:
+  title: 'Access content'
+
+permission_callbacks:
+  - \Drupal\node\NodePermissions::nodeTypePermissions
+"#;
+        let parser = YamlParser::new(source, "file://node.permissions.yml");
+        let tokens = parser.get_tokens();
+
+        let method = tokens.iter().find_map(|token| match &token.data {
+            TokenData::PhpMethodReference(method) => Some(method),
+            _ => None,
+        });
+        assert_eq!("nodeTypePermissions", method.unwrap().name);
+        assert_eq!(
+            "Drupal\\node\\NodePermissions",
+            method.unwrap().class_name.clone().unwrap().to_string()
+        );
+
+        let token = parser
+            .get_token_at_position(Position {
+                line: 5,
+                character: 10,
+            })
+            .unwrap();
+        assert!(matches!(token.data, TokenData::PhpMethodReference(_)));
+    }
+
+    #[test]
+    fn parse_library_definitions() {
+        let source = r#"
+drupal.dialog:
+  version: VERSION
+  js:
+    misc/dialog/dialog.js: {}
+  dependencies:
+    - core/jquery
+"#;
+        let tokens = YamlParser::new(source, "file:///core/core.libraries.yml").get_tokens();
+        let libraries: Vec<&str> = tokens
+            .iter()
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalLibraryDefinition(library) => Some(library.name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["core/drupal.dialog"], libraries);
+    }
+
+    #[test]
+    fn parse_service_tags() {
+        let source = r#"
+services:
+  foo.subscriber:
+    class: Drupal\foo\EventSubscriber\FooSubscriber
+    tags:
+      - { name: event_subscriber }
+      - name: cache.context
+"#;
+        let tokens = YamlParser::new(source, "file:///foo/foo.services.yml").get_tokens();
+        let Some(TokenData::DrupalServiceDefinition(service)) =
+            tokens.first().map(|token| &token.data)
+        else {
+            panic!("Expected a service definition");
+        };
+        assert_eq!(vec!["event_subscriber", "cache.context"], service.tags);
+    }
+
+    #[test]
+    fn get_service_tag_edit() {
+        let source = r#"services:
+  foo.tagged:
+    class: Drupal\foo\Tagged
+    tags:
+      - { name: cache.context }
+  foo.flow:
+    class: Drupal\foo\Flow
+    tags: [{ name: cache.context }]
+  foo.untagged:
+    class: Drupal\foo\Untagged
+"#;
+        let parser = YamlParser::new(source, "file:///foo/foo.services.yml");
+
+        let edit = parser
+            .get_service_tag_edit("foo.tagged", "event_subscriber")
+            .unwrap();
+        assert_eq!(Position::new(4, 31), edit.range.start);
+        assert_eq!("\n      - { name: event_subscriber }", edit.new_text);
+
+        let edit = parser
+            .get_service_tag_edit("foo.flow", "event_subscriber")
+            .unwrap();
+        assert_eq!(Position::new(7, 34), edit.range.start);
+        assert_eq!(", { name: event_subscriber }", edit.new_text);
+
+        let edit = parser
+            .get_service_tag_edit("foo.untagged", "event_subscriber")
+            .unwrap();
+        assert_eq!(Position::new(9, 30), edit.range.start);
+        assert_eq!(
+            "\n    tags:\n      - { name: event_subscriber }",
+            edit.new_text
+        );
+    }
+
+    #[test]
+    fn parse_service_parent() {
+        let source = r#"services:
+  foo.base:
+    abstract: true
+    arguments: ['@entity_type.manager']
+  foo.bar:
+    class: Drupal\foo\Bar
+    parent: foo.base
+    arguments:
+      - '@?logger.factory'
+      - '%app.root%'
+    deprecated: The "%service_id%" service is deprecated.
+  logger.channel.foo:
+    parent: logger.channel_base
+    arguments: ['foo']
+"#;
+        let parser = YamlParser::new(source, "file:///foo/foo.services.yml");
+        let tokens = parser.get_tokens();
+        let services: Vec<&DrupalService> = tokens
+            .iter()
+            .filter_map(|token| match &token.data {
+                TokenData::DrupalServiceDefinition(service) => Some(service),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(3, services.len());
+        assert!(services[0].is_abstract && services[0].class.is_none());
+        assert_eq!(Some("logger.channel_base".to_string()), services[2].parent);
+        assert_eq!(Some("foo.base".to_string()), services[1].parent);
+        assert_eq!(
+            vec!["@?logger.factory", "%app.root%"],
+            services[1].arguments
+        );
+        assert_eq!(
+            Some("The \"%service_id%\" service is deprecated.".to_string()),
+            services[1].deprecated
+        );
+
+        match parser
+            .get_token_at_position(Position::new(6, 14))
+            .unwrap()
+            .data
+        {
+            TokenData::DrupalServiceReference(name) => assert_eq!("foo.base", name),
+            data => panic!("Unexpected token {:?}", data),
+        }
+    }
+
+    #[test]
+    fn parse_service_argument() {
+        let source = r#"services:
+  foo.bar:
+    class: Drupal\foo\Bar
+    arguments: ['@entity_type.manager', "@?logger.factory"]
+"#;
+        let parser = YamlParser::new(source, "file:///foo/foo.services.yml");
+        for (character, expected) in [(20, "entity_type.manager"), (45, "logger.factory")] {
+            let token = parser
+                .get_token_at_position(Position::new(3, character))
+                .unwrap();
+            match token.data {
+                TokenData::DrupalServiceReference(name) => assert_eq!(expected, name),
+                data => panic!("Unexpected token {:?}", data),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_view() {
+        let source = r#"langcode: en
+id: taxonomy_term
+base_table: taxonomy_index
+display:
+  default:
+    display_plugin: default
+    id: default
+    display_options:
+      title: Taxonomy term
+      access:
+        type: perm
+        options:
+          perm: 'access content'
+      row:
+        type: 'entity:node'
+      fields:
+        name:
+          id: name
+          table: taxonomy_term_field_data
+          entity_type: taxonomy_term
+          entity_field: name
+          plugin_id: field
+  page_1:
+    display_plugin: page
+    id: page_1
+    display_options:
+      path: taxonomy/term/%/feed
+  feed_1:
+    display_plugin: feed
+    display_options:
+      path: taxonomy/term/%taxonomy_term
+      access:
+        type: role
+        options:
+          role:
+            editor: editor
+            administrator: administrator
+"#;
+        let parser = YamlParser::new(
+            source,
+            "file:///core/modules/taxonomy/config/optional/views.view.taxonomy_term.yml",
+        );
+        let routes: Vec<DrupalRoute> = parser
+            .get_tokens()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalRouteDefinition(route) => Some(route),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, routes.len());
+        for (route, expected_name, expected_path, expected_requirement) in [
+            (
+                &routes[0],
+                "view.taxonomy_term.page_1",
+                "/taxonomy/term/{arg_0}/feed",
+                ("_permission", "access content"),
+            ),
+            (
+                &routes[1],
+                "view.taxonomy_term.feed_1",
+                "/taxonomy/term/{taxonomy_term}",
+                ("_role", "editor+administrator"),
+            ),
+        ] {
+            assert_eq!(expected_name, route.name);
+            assert_eq!(expected_path, route.path);
+            assert_eq!(Some("Taxonomy term".to_string()), route._defaults._title);
+            assert_eq!(
+                vec![(
+                    expected_requirement.0.to_string(),
+                    expected_requirement.1.to_string()
+                )],
+                route.requirements
+            );
+        }
+
+        for (line, expected_type, expected_id) in [
+            (2, DrupalPluginType::EntityType, "taxonomy_index"),
+            (5, DrupalPluginType::ViewsDisplay, "default"),
+            (14, DrupalPluginType::ViewsRow, "entity:node"),
+            (18, DrupalPluginType::EntityType, "taxonomy_term_field_data"),
+            (19, DrupalPluginType::EntityType, "taxonomy_term"),
+            (21, DrupalPluginType::ViewsField, "field"),
+        ] {
+            let character = source.lines().nth(line).unwrap().len() as u32 - 2;
+            let Some(TokenData::DrupalPluginReference(reference)) = parser
+                .get_token_at_position(Position::new(line as u32, character))
+                .map(|token| token.data)
+            else {
+                panic!("Expected a plugin reference on line {}", line);
+            };
+            assert_eq!(expected_type, reference.plugin_type);
+            assert_eq!(expected_id, reference.plugin_id);
+        }
+
+        let Some(TokenData::DrupalEntityFieldReference(reference)) = parser
+            .get_token_at_position(Position::new(20, 26))
+            .map(|token| token.data)
+        else {
+            panic!("Expected an entity field reference");
+        };
+        assert_eq!("name", reference.field_name);
+        assert!(
+            matches!(reference.entity_type, DrupalEntityTypeHint::Id(id) if id == "taxonomy_term")
+        );
+    }
+
+    #[test]
+    fn parse_menu_links() {
+        let source = r#"mymodule.admin:
+  title: 'My module'
+  route_name: mymodule.admin
+  parent: system.admin_config
+mymodule.settings:
+  title: Settings
+  parent: mymodule.admin
+"#;
+        let parser = YamlParser::new(source, "file:///mymodule/mymodule.links.menu.yml");
+        let menu_links: Vec<DrupalMenuLink> = parser
+            .get_tokens()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalMenuLinkDefinition(menu_link) => Some(menu_link),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(2, menu_links.len());
+        assert_eq!("mymodule.admin", menu_links[0].id);
+        assert_eq!(Some("My module".to_string()), menu_links[0].title);
+        assert_eq!(Some("mymodule.admin".to_string()), menu_links[0].route_name);
+        assert_eq!(
+            Some("system.admin_config".to_string()),
+            menu_links[0].parent
+        );
+        assert_eq!(None, menu_links[1].route_name);
+
+        match parser
+            .get_token_at_position(Position::new(6, 14))
+            .map(|token| token.data)
+        {
+            Some(TokenData::DrupalMenuLinkReference(parent)) => {
+                assert_eq!("mymodule.admin", parent)
+            }
+            data => panic!("Unexpected token {:?}", data),
+        }
+
+        let parents: Vec<(String, u32)> = parser
+            .get_menu_link_parent_references()
+            .into_iter()
+            .filter_map(|token| match token.data {
+                TokenData::DrupalMenuLinkReference(parent) => {
+                    Some((parent, token.range.start_point.row as u32))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("system.admin_config".to_string(), 3),
+                ("mymodule.admin".to_string(), 6)
+            ],
+            parents
+        );
+    }
+}
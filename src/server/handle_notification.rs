@@ -1,8 +1,24 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
 use lsp_server::Notification;
-use lsp_types::{DidChangeTextDocumentParams, DidOpenTextDocumentParams};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, FileChangeType, Uri,
+};
 use serde_json::Value;
 
 use crate::document_store::DOCUMENT_STORE;
+use crate::integrations::phpcs::{clear_phpcs_diagnostics, schedule_phpcs};
+use crate::utils::normalize_uri;
+
+use super::publish_diagnostics;
+
+// The documents that are open in the client, whose diagnostics are refreshed when another file is
+// saved. They are keyed by their normalized uri like the document store, and keep the uri of the
+// client to publish the diagnostics.
+static OPEN_DOCUMENTS: LazyLock<Mutex<HashMap<String, Uri>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
 
 pub fn handle_notification(notification: Notification) {
     log::trace!("Handling notification: {:?}", notification);
@@ -10,8 +26,11 @@ pub fn handle_notification(notification: Notification) {
     match notification.method.as_str() {
         "textDocument/didOpen" => handle_text_document_did_open(notification.params),
         "textDocument/didChange" => handle_text_document_did_change(notification.params),
-        "textDocument/didClose" => (),
-        "textDocument/didSave" => (),
+        "textDocument/didClose" => handle_text_document_did_close(notification.params),
+        "textDocument/didSave" => handle_text_document_did_save(notification.params),
+        "workspace/didChangeWatchedFiles" => {
+            handle_workspace_did_change_watched_files(notification.params)
+        }
         "exit" => (),
         _ => log::warn!("Unhandled notification {:?}", notification),
     };
@@ -25,6 +44,12 @@ fn handle_text_document_did_open(params: Value) {
                 .lock()
                 .unwrap()
                 .add_document(&uri, params.text_document.text);
+            OPEN_DOCUMENTS
+                .lock()
+                .unwrap()
+                .insert(normalize_uri(&uri), params.text_document.uri.clone());
+            publish_diagnostics(params.text_document.uri.clone());
+            schedule_phpcs(params.text_document.uri);
         }
         Err(err) => log::error!("Could not parse params: {:?}", err),
     }
@@ -38,6 +63,72 @@ fn handle_text_document_did_change(params: Value) {
                 .lock()
                 .unwrap()
                 .change_document(&uri, params.content_changes);
+            publish_diagnostics(params.text_document.uri);
+        }
+        Err(err) => log::error!("Could not parse params: {:?}", err),
+    }
+}
+
+fn handle_text_document_did_save(params: Value) {
+    match serde_json::from_value::<DidSaveTextDocumentParams>(params) {
+        Ok(params) => {
+            let uri = params.text_document.uri.to_string();
+            {
+                let mut store = DOCUMENT_STORE.lock().unwrap();
+                match params.text {
+                    Some(text) => store.add_document(&uri, text),
+                    None => store.reload_document(&uri),
+                }
+            }
+
+            // The saved file can define things other documents refer to, f.e permissions.
+            let saved_uri = normalize_uri(&uri);
+            let open_documents: Vec<Uri> = OPEN_DOCUMENTS
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(open_uri, _)| **open_uri != saved_uri)
+                .map(|(_, open_document)| open_document.clone())
+                .collect();
+            for open_document in open_documents {
+                publish_diagnostics(open_document);
+            }
+            publish_diagnostics(params.text_document.uri.clone());
+            schedule_phpcs(params.text_document.uri);
+        }
+        Err(err) => log::error!("Could not parse params: {:?}", err),
+    }
+}
+
+fn handle_workspace_did_change_watched_files(params: Value) {
+    match serde_json::from_value::<DidChangeWatchedFilesParams>(params) {
+        Ok(params) => {
+            // Open documents are kept in sync by the client, their content on disk can be stale.
+            let open_documents = OPEN_DOCUMENTS.lock().unwrap().clone();
+            let mut store = DOCUMENT_STORE.lock().unwrap();
+            for change in params.changes {
+                let uri = change.uri.to_string();
+                if open_documents.contains_key(&normalize_uri(&uri)) {
+                    continue;
+                }
+                match change.typ {
+                    FileChangeType::DELETED => store.remove_document(&uri),
+                    _ => store.reload_document(&uri),
+                }
+            }
+        }
+        Err(err) => log::error!("Could not parse params: {:?}", err),
+    }
+}
+
+fn handle_text_document_did_close(params: Value) {
+    match serde_json::from_value::<DidCloseTextDocumentParams>(params) {
+        Ok(params) => {
+            OPEN_DOCUMENTS
+                .lock()
+                .unwrap()
+                .remove(&normalize_uri(params.text_document.uri.as_str()));
+            clear_phpcs_diagnostics(&params.text_document.uri.to_string());
         }
         Err(err) => log::error!("Could not parse params: {:?}", err),
     }
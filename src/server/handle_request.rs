@@ -1,25 +1,39 @@
 use lsp_server::{ErrorCode, Request, RequestId, Response, ResponseError};
 
-use super::handlers::completion::handle_text_document_completion;
+use super::handlers::capabilities::handle_drupal_capabilities;
 use super::handlers::code_action::handle_text_document_code_action;
+use super::handlers::code_lens::handle_text_document_code_lens;
+use super::handlers::completion::handle_text_document_completion;
 use super::handlers::definition::handle_text_document_definition;
+use super::handlers::execute_command::handle_workspace_execute_command;
 use super::handlers::hover::handle_text_document_hover;
+use super::handlers::metrics::handle_drupal_metrics;
+use super::handlers::project_info::handle_drupal_project_info;
+use super::handlers::references::handle_text_document_references;
+use super::metrics::measure_request;
 
 pub fn handle_request(request: Request) -> Response {
     log::trace!("Handling request: {:?}", request);
 
     let request_id = request.id.clone();
-    let response = match request.method.as_str() {
+    let method = request.method.clone();
+    let response = measure_request(&method, || match request.method.as_str() {
         "textDocument/hover" => handle_text_document_hover(request),
         "textDocument/codeAction" => handle_text_document_code_action(request),
+        "textDocument/codeLens" => handle_text_document_code_lens(request),
         "textDocument/definition" => handle_text_document_definition(request),
+        "textDocument/references" => handle_text_document_references(request),
         "textDocument/completion" => handle_text_document_completion(request),
+        "workspace/executeCommand" => handle_workspace_execute_command(request),
+        "drupal/metrics" => handle_drupal_metrics(request),
+        "drupal/projectInfo" => handle_drupal_project_info(request),
+        "drupal/capabilities" => handle_drupal_capabilities(request),
         "shutdown" => None,
         _ => {
             log::warn!("Unhandled request {:?}", request);
             None
         }
-    };
+    });
 
     // The LSP spec requires the result to be null for empty responses.
     match response {
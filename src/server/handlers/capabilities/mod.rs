@@ -0,0 +1,122 @@
+use lsp_server::{Request, Response};
+use serde::Serialize;
+
+use crate::diagnostics::check::RULES;
+use crate::diagnostics::UNTRANSLATED_STRING;
+use crate::document_store::DOCUMENT_STORE;
+use crate::integrations::drush::get_drush_data;
+use crate::server::get_root_dir;
+use crate::settings::{get_settings, Settings};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Capabilities {
+    diagnostics: Vec<DiagnosticCategory>,
+    integrations: Integrations,
+    /// Where the server gets its knowledge of the project from.
+    index_backends: Vec<IndexBackend>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticCategory {
+    /// The code diagnostics of the category are reported with.
+    code: &'static str,
+    description: &'static str,
+    enabled: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Integrations {
+    phpcs: bool,
+    drush: bool,
+    /// Whether strings that aren't translated to every language are reported.
+    translations: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexBackend {
+    /// `workspace` for the indexed files, `drush` for the runtime data of the site.
+    name: &'static str,
+    /// Whether the backend has finished loading its data.
+    is_ready: bool,
+}
+
+/// Handle the custom `drupal/capabilities` request, which returns the Drupal specific features
+/// that are active with the current settings, f.e for editor extensions to hide unsupported
+/// commands.
+pub fn handle_drupal_capabilities(request: Request) -> Option<Response> {
+    let is_indexed = DOCUMENT_STORE.lock().unwrap().get_indexed_at().is_some();
+    let mut index_backends = vec![];
+    if get_root_dir().is_some() {
+        index_backends.push(IndexBackend {
+            name: "workspace",
+            is_ready: is_indexed,
+        });
+    }
+    if get_settings().drush.enabled {
+        index_backends.push(IndexBackend {
+            name: "drush",
+            is_ready: get_drush_data().is_some(),
+        });
+    }
+
+    Some(Response {
+        id: request.id,
+        result: serde_json::to_value(get_capabilities(get_settings(), index_backends)).ok(),
+        error: None,
+    })
+}
+
+fn get_capabilities(settings: &Settings, index_backends: Vec<IndexBackend>) -> Capabilities {
+    Capabilities {
+        diagnostics: RULES
+            .iter()
+            .map(|(code, description)| DiagnosticCategory {
+                code,
+                description,
+                enabled: *code != UNTRANSLATED_STRING || settings.translations.require_complete,
+            })
+            .collect(),
+        integrations: Integrations {
+            phpcs: settings.phpcs.enabled,
+            drush: settings.drush.enabled,
+            translations: settings.translations.require_complete,
+        },
+        index_backends,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities() {
+        let mut settings = Settings::default();
+        settings.drush.enabled = true;
+
+        let capabilities = get_capabilities(&settings, vec![]);
+        assert_eq!(RULES.len(), capabilities.diagnostics.len());
+        assert_eq!(
+            vec![UNTRANSLATED_STRING],
+            capabilities
+                .diagnostics
+                .iter()
+                .filter(|category| !category.enabled)
+                .map(|category| category.code)
+                .collect::<Vec<&str>>()
+        );
+        assert!(capabilities.integrations.drush);
+        assert!(!capabilities.integrations.phpcs);
+
+        settings.translations.require_complete = true;
+        let capabilities = get_capabilities(&settings, vec![]);
+        assert!(capabilities
+            .diagnostics
+            .iter()
+            .all(|category| category.enabled));
+    }
+}
@@ -0,0 +1,1148 @@
+use lsp_types::{CodeAction, CodeActionKind, Position, Range, TextEdit, Uri};
+use tree_sitter::{Node, Point};
+
+use crate::document_store::document::Document;
+use crate::document_store::DocumentStore;
+use crate::parser::php::PhpParser;
+use crate::parser::tokens::{
+    ClassAttribute, DrupalService, PhpClass, PhpClassName, PhpMethod, TokenData,
+};
+use crate::parser::{
+    get_closest_parent_by_kind, get_node_at_position, get_tree, token_range_to_range,
+};
+
+use super::get_document_edit;
+
+const CONTAINER_INTERFACE: &str = "Symfony\\Component\\DependencyInjection\\ContainerInterface";
+
+const CONTAINER_FACTORY_PLUGIN_INTERFACE: &str =
+    "Drupal\\Core\\Plugin\\ContainerFactoryPluginInterface";
+
+const CONTAINER_INJECTION_INTERFACE: &str =
+    "Drupal\\Core\\DependencyInjection\\ContainerInjectionInterface";
+
+// Constructor parameters of plugins, which are passed on from the plugin manager.
+const PLUGIN_PARAMETERS: [&str; 3] = ["configuration", "plugin_id", "plugin_definition"];
+
+// The plugin parameters as they are declared in generated methods.
+const PLUGIN_CREATE_PARAMETERS: [&str; 3] =
+    ["array $configuration", "$plugin_id", "$plugin_definition"];
+
+// How many parent classes are followed, in case a class hierarchy loops.
+const MAX_PARENT_DEPTH: usize = 20;
+
+/// Create a refactoring replacing `\Drupal::service('name')` with an injected dependency.
+pub fn get_inject_service_action(
+    uri: &Uri,
+    content: &str,
+    position: Position,
+    service_name: &str,
+    class: &PhpClass,
+    store: &DocumentStore,
+) -> Option<CodeAction> {
+    let tree = get_tree(content, &tree_sitter_php::LANGUAGE_PHP.into())?;
+    let mut call_node = get_node_at_position(&tree, position)?;
+    while !is_static_service_call(&call_node, content) {
+        call_node = call_node.parent()?;
+    }
+    // Static methods can't access the injected service through `$this`.
+    if get_closest_parent_by_kind(&call_node, "method_declaration")
+        .is_some_and(|method_node| is_static_method(&method_node))
+    {
+        return None;
+    }
+    let class_node = get_closest_parent_by_kind(&call_node, "class_declaration")?;
+    let body_node = class_node.child_by_field_name("body")?;
+
+    let is_plugin = is_plugin_class(class);
+    let service_type = get_service_type(service_name, store);
+    let type_hint = service_type
+        .as_ref()
+        .map(|service_type| format!("{} ", service_type.get_short_name()))
+        // Services without a known class are injected without a type hint.
+        .unwrap_or_default();
+    let property_name = service_name_to_property_name(service_name);
+    // Properties that are already declared, f.e by `ControllerBase`, are assigned instead of
+    // promoted, since declaring them again is an error.
+    let is_declared_property = get_declared_property_names(content).contains(&property_name)
+        || is_inherited_property(class, &property_name, store);
+    let (parameter, assignments) = if is_declared_property {
+        let variable_name = service_name.replace('.', "_");
+        (
+            format!("{}${}", type_hint, variable_name),
+            vec![format!("$this->{} = ${};", property_name, variable_name)],
+        )
+    } else {
+        (format!("protected {}${}", type_hint, property_name), vec![])
+    };
+    let argument = format!("$container->get('{}')", service_name);
+
+    let mut text_edits = vec![TextEdit {
+        range: token_range_to_range(&call_node.range()),
+        new_text: format!("$this->{}", property_name),
+    }];
+    let mut class_names: Vec<PhpClassName> = service_type.into_iter().collect();
+
+    let constructor_node = get_method_node(&body_node, "__construct", content);
+    match get_method_node(&body_node, "create", content) {
+        Some(create_node) => match get_new_static_arguments_node(&create_node) {
+            Some(arguments_node) => {
+                let has_arguments = arguments_node
+                    .named_children(&mut arguments_node.walk())
+                    .any(|node| node.kind() != "comment");
+                text_edits.push(get_append_argument_edit(arguments_node, &argument)?);
+                match constructor_node {
+                    Some(constructor_node) => text_edits.extend(get_constructor_edits(
+                        &constructor_node,
+                        &parameter,
+                        &assignments,
+                    )?),
+                    // The existing arguments are passed to the parent constructor, which are
+                    // only known for plugins.
+                    None if has_arguments && !is_plugin => return None,
+                    None => text_edits.push(get_class_body_edit(
+                        &body_node,
+                        get_constructor_method(
+                            &class_node,
+                            is_plugin && has_arguments,
+                            &[parameter],
+                            &assignments,
+                            content,
+                        ),
+                    )),
+                }
+            }
+            // Instances created with `parent::create($container)` get the service assigned.
+            None => {
+                text_edits.push(get_create_instance_edit(
+                    &create_node,
+                    &property_name,
+                    &argument,
+                    content,
+                )?);
+                if is_declared_property {
+                    class_names.clear();
+                } else {
+                    text_edits.push(get_class_body_edit(
+                        &body_node,
+                        format!("  protected {}${};\n", type_hint, property_name),
+                    ));
+                }
+            }
+        },
+        None => {
+            let mut create_parameters = vec!["ContainerInterface $container".to_string()];
+            let mut arguments = vec![];
+            let mut members = String::new();
+            match (constructor_node, class.methods.get("__construct")) {
+                (Some(constructor_node), Some(constructor_token)) => {
+                    let TokenData::PhpMethodDefinition(constructor) = &constructor_token.data
+                    else {
+                        return None;
+                    };
+                    let (parameters, constructor_arguments, fallback_class_names) =
+                        get_create_arguments(constructor, is_plugin, store)?;
+                    create_parameters.extend(parameters);
+                    arguments.extend(constructor_arguments);
+                    class_names.extend(fallback_class_names);
+                    text_edits.extend(get_constructor_edits(
+                        &constructor_node,
+                        &parameter,
+                        &assignments,
+                    )?);
+                }
+                _ => {
+                    if is_plugin {
+                        create_parameters.push(PLUGIN_CREATE_PARAMETERS.join(", "));
+                        arguments.extend(
+                            PLUGIN_PARAMETERS
+                                .iter()
+                                .map(|parameter| format!("${}", parameter)),
+                        );
+                    }
+                    members.push_str(&get_constructor_method(
+                        &class_node,
+                        is_plugin,
+                        &[parameter],
+                        &assignments,
+                        content,
+                    ));
+                    members.push('\n');
+                }
+            }
+            arguments.push(argument);
+            members.push_str(&get_create_method(
+                &create_parameters.join(", "),
+                &arguments,
+            ));
+            text_edits.push(get_class_body_edit(&body_node, members));
+            class_names.push(PhpClassName::from(CONTAINER_INTERFACE));
+
+            let interface = PhpClassName::from(if is_plugin {
+                CONTAINER_FACTORY_PLUGIN_INTERFACE
+            } else {
+                CONTAINER_INJECTION_INTERFACE
+            });
+            if !implements_interface(class, &interface, store) {
+                text_edits.push(get_implements_edit(
+                    &class_node,
+                    interface.get_short_name(),
+                )?);
+                class_names.push(interface);
+            }
+        }
+    }
+
+    // Imports that are inserted at the same position are added in order.
+    class_names.sort_by_key(|class_name| class_name.to_string());
+    let parser = PhpParser::new(content);
+    text_edits.extend(get_use_statement_edits(&parser, &class_names));
+
+    Some(CodeAction {
+        title: format!("Inject service {}", service_name),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        diagnostics: None,
+        edit: Some(get_document_edit(uri.clone(), text_edits)),
+        command: None,
+        is_preferred: None,
+        disabled: None,
+        data: None,
+    })
+}
+
+/// Create an action generating the create() factory method from the class constructor.
+///
+/// Constructors with scalar parameters are skipped, since their value can't be inferred.
+pub fn get_create_factory_action(
+    uri: &Uri,
+    content: &str,
+    class: &PhpClass,
+    store: &DocumentStore,
+) -> Option<CodeAction> {
+    if class.methods.contains_key("create") {
+        return None;
+    }
+    let constructor_token = class.methods.get("__construct")?;
+    let TokenData::PhpMethodDefinition(constructor) = &constructor_token.data else {
+        return None;
+    };
+    if !constructor
+        .parameters
+        .iter()
+        .any(|parameter| parameter.class_name.is_some())
+    {
+        return None;
+    }
+
+    let mut create_parameters = vec!["ContainerInterface $container".to_string()];
+    let mut class_names = vec![PhpClassName::from(CONTAINER_INTERFACE)];
+    let (parameters, arguments, fallback_class_names) =
+        get_create_arguments(constructor, is_plugin_class(class), store)?;
+    create_parameters.extend(parameters);
+    class_names.extend(fallback_class_names);
+
+    let create_method = get_create_method(&create_parameters.join(", "), &arguments);
+    let mut text_edits = vec![insert_at(
+        Point::new(constructor_token.range.end_point.row + 1, 0),
+        format!("\n{}", create_method),
+    )];
+    text_edits.extend(get_use_statement_edits(
+        &PhpParser::new(content),
+        &class_names,
+    ));
+
+    Some(CodeAction {
+        title: String::from("Generate create() from the constructor"),
+        kind: Some(CodeActionKind::SOURCE),
+        diagnostics: None,
+        edit: Some(get_document_edit(uri.clone(), text_edits)),
+        command: None,
+        is_preferred: None,
+        disabled: None,
+        data: None,
+    })
+}
+
+/// Get the parameters of a create() method passing the values of a constructor, besides the
+/// container, the arguments for the constructor and the classes to import for them.
+///
+/// Constructors with scalar parameters are skipped, since their value can't be inferred.
+fn get_create_arguments(
+    constructor: &PhpMethod,
+    is_plugin: bool,
+    store: &DocumentStore,
+) -> Option<(Vec<String>, Vec<String>, Vec<PhpClassName>)> {
+    let mut parameters = vec![];
+    let mut arguments = vec![];
+    let mut class_names = vec![];
+    for parameter in &constructor.parameters {
+        if is_plugin && PLUGIN_PARAMETERS.contains(&parameter.name.as_str()) {
+            parameters.push(match &parameter.type_hint {
+                Some(type_hint) => format!("{} ${}", type_hint, parameter.name),
+                None => format!("${}", parameter.name),
+            });
+            arguments.push(format!("${}", parameter.name));
+        } else if let Some(class_name) = &parameter.class_name {
+            arguments.push(match store.get_service_name_for_class(class_name) {
+                Some(service_name) => format!("$container->get('{}')", service_name),
+                // Fall back to autowiring aliases, which use the interface as service name.
+                None => {
+                    class_names.push(class_name.clone());
+                    format!("$container->get({}::class)", class_name.get_short_name())
+                }
+            });
+        } else {
+            return None;
+        }
+    }
+    Some((parameters, arguments, class_names))
+}
+
+/// Whether a class is a plugin, which includes the classes in a `Plugin` namespace of plugin
+/// types that aren't known, f.e `Drupal\mymodule\Plugin\Block`.
+fn is_plugin_class(class: &PhpClass) -> bool {
+    matches!(class.attribute, Some(ClassAttribute::Plugin(_)))
+        || format!("{}\\", class.name.get_namespace()).contains("\\Plugin\\")
+        || class
+            .interfaces
+            .iter()
+            .any(|interface| interface == &PhpClassName::from(CONTAINER_FACTORY_PLUGIN_INTERFACE))
+}
+
+/// Get the parent classes of a class that are indexed, starting with the closest one.
+fn get_parent_classes<'a>(
+    class: &'a PhpClass,
+    store: &'a DocumentStore,
+) -> Vec<(&'a Document, &'a PhpClass)> {
+    let mut parent_classes = vec![];
+    let mut parent = class.parent.as_ref();
+    while let Some(parent_name) = parent.filter(|_| parent_classes.len() < MAX_PARENT_DEPTH) {
+        let Some((document, token)) = store.get_class_definition(parent_name) else {
+            break;
+        };
+        let TokenData::PhpClassDefinition(parent_class) = &token.data else {
+            break;
+        };
+        parent_classes.push((document, parent_class));
+        parent = parent_class.parent.as_ref();
+    }
+    parent_classes
+}
+
+/// Whether a property is declared by a parent class or a trait, f.e `$entityTypeManager` of
+/// `ControllerBase`.
+fn is_inherited_property(class: &PhpClass, property_name: &str, store: &DocumentStore) -> bool {
+    let parent_classes = get_parent_classes(class, store);
+    let trait_documents = std::iter::once(class)
+        .chain(parent_classes.iter().map(|(_, parent_class)| *parent_class))
+        .flat_map(|class| class.traits.iter())
+        .filter_map(|trait_name| store.get_class_definition(trait_name))
+        .map(|(document, _)| document);
+    parent_classes
+        .iter()
+        .map(|(document, _)| *document)
+        .chain(trait_documents)
+        .any(|document| {
+            get_declared_property_names(&document.content)
+                .iter()
+                .any(|name| name == property_name)
+        })
+}
+
+fn implements_interface(class: &PhpClass, interface: &PhpClassName, store: &DocumentStore) -> bool {
+    class.interfaces.contains(interface)
+        || get_parent_classes(class, store)
+            .iter()
+            .any(|(_, parent_class)| parent_class.interfaces.contains(interface))
+}
+
+/// Get the names of the properties that are declared in a file, including the promoted
+/// constructor parameters.
+fn get_declared_property_names(content: &str) -> Vec<String> {
+    let Some(tree) = get_tree(content, &tree_sitter_php::LANGUAGE_PHP.into()) else {
+        return vec![];
+    };
+
+    let mut property_names = vec![];
+    let mut nodes = vec![tree.root_node()];
+    while let Some(node) = nodes.pop() {
+        if ["property_element", "property_promotion_parameter"].contains(&node.kind()) {
+            property_names.extend(
+                node.named_children(&mut node.walk())
+                    .find(|child| child.kind() == "variable_name")
+                    .and_then(|child| child.utf8_text(content.as_bytes()).ok())
+                    .map(|name| name.trim_start_matches('$').to_string()),
+            );
+            continue;
+        }
+        nodes.extend(node.named_children(&mut node.walk()));
+    }
+    property_names
+}
+
+/// Get the type to use for an injected service, preferring the interface of the service class.
+fn get_service_type(service_name: &str, store: &DocumentStore) -> Option<PhpClassName> {
+    let (_, token) = store.get_service_definition(service_name)?;
+    let TokenData::DrupalServiceDefinition(DrupalService {
+        class: Some(class_name),
+        ..
+    }) = &token.data
+    else {
+        return None;
+    };
+
+    if let Some((_, token)) = store.get_class_definition(class_name) {
+        if let TokenData::PhpClassDefinition(class) = &token.data {
+            let preferred_interface = format!("{}Interface", class_name.get_short_name());
+            if let Some(interface) = class
+                .interfaces
+                .iter()
+                .find(|interface| interface.get_short_name() == preferred_interface)
+                .or(class.interfaces.first())
+            {
+                return Some(interface.clone());
+            }
+        }
+    }
+    Some(class_name.clone())
+}
+
+/// Convert a service name like `entity_type.manager` to a property name like `entityTypeManager`.
+pub fn service_name_to_property_name(service_name: &str) -> String {
+    service_name
+        .split(['.', '_'])
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(index, part)| {
+            let mut chars = part.chars();
+            match (index, chars.next()) {
+                (0, Some(first)) => first.to_lowercase().chain(chars).collect(),
+                (_, Some(first)) => first.to_uppercase().chain(chars).collect(),
+                _ => String::new(),
+            }
+        })
+        .collect()
+}
+
+pub fn get_use_statement_edits(parser: &PhpParser, class_names: &[PhpClassName]) -> Vec<TextEdit> {
+    let imports = parser.get_imported_class_names();
+    let namespace = parser.get_namespace().unwrap_or_default();
+    class_names
+        .iter()
+        .filter(|class_name| {
+            class_name.get_namespace() != namespace
+                && class_name.get_namespace() != ""
+                && !imports.values().any(|import| import == *class_name)
+        })
+        .filter_map(|class_name| parser.get_use_statement_edit(class_name))
+        .collect()
+}
+
+fn is_static_service_call(node: &Node, content: &str) -> bool {
+    if node.kind() != "scoped_call_expression" {
+        return false;
+    }
+    let node_text = |field: &str| {
+        node.child_by_field_name(field)
+            .and_then(|child| child.utf8_text(content.as_bytes()).ok())
+            .unwrap_or_default()
+    };
+    node_text("scope").trim_start_matches('\\') == "Drupal" && node_text("name") == "service"
+}
+
+fn is_static_method(method_node: &Node) -> bool {
+    method_node
+        .children(&mut method_node.walk())
+        .any(|child| child.kind() == "static_modifier")
+}
+
+pub fn get_method_node<'a>(body_node: &Node<'a>, name: &str, content: &str) -> Option<Node<'a>> {
+    body_node
+        .named_children(&mut body_node.walk())
+        .filter(|node| node.kind() == "method_declaration")
+        .find(|node| {
+            node.child_by_field_name("name")
+                .and_then(|name_node| name_node.utf8_text(content.as_bytes()).ok())
+                == Some(name)
+        })
+}
+
+/// Find the arguments of the `new static(...)` expression in a create() method.
+pub fn get_new_static_arguments_node<'a>(create_node: &Node<'a>) -> Option<Node<'a>> {
+    let mut nodes = vec![*create_node];
+    while let Some(node) = nodes.pop() {
+        if node.kind() == "object_creation_expression" {
+            return node
+                .named_children(&mut node.walk())
+                .find(|child| child.kind() == "arguments");
+        }
+        nodes.extend(node.named_children(&mut node.walk()));
+    }
+    None
+}
+
+/// Create an edit appending an argument or parameter to a list, following its formatting.
+pub fn get_append_argument_edit(list_node: Node, text: &str) -> Option<TextEdit> {
+    let closing_node = list_node.child(list_node.child_count().checked_sub(1)?)?;
+    let Some(last_node) = list_node
+        .named_children(&mut list_node.walk())
+        .filter(|node| node.kind() != "comment")
+        .last()
+    else {
+        return Some(insert_at(closing_node.start_position(), text.to_string()));
+    };
+
+    let is_multiline = list_node.start_position().row != closing_node.start_position().row;
+    let indent = " ".repeat(last_node.start_position().column);
+    let trailing_comma = last_node.next_sibling().filter(|node| node.kind() == ",");
+
+    Some(match (trailing_comma, is_multiline) {
+        (Some(comma), true) => insert_at(comma.end_position(), format!("\n{}{},", indent, text)),
+        (None, true) => insert_at(last_node.end_position(), format!(",\n{}{}", indent, text)),
+        (Some(comma), false) => insert_at(comma.end_position(), format!(" {}", text)),
+        (None, false) => insert_at(last_node.end_position(), format!(", {}", text)),
+    })
+}
+
+/// Create the edits adding a parameter to an existing constructor, and the statements assigning
+/// it to the end of its body.
+fn get_constructor_edits(
+    constructor_node: &Node,
+    parameter: &str,
+    statements: &[String],
+) -> Option<Vec<TextEdit>> {
+    let mut text_edits = vec![get_append_argument_edit(
+        constructor_node.child_by_field_name("parameters")?,
+        parameter,
+    )?];
+    if !statements.is_empty() {
+        text_edits.push(get_append_statements_edit(constructor_node, statements)?);
+    }
+    Some(text_edits)
+}
+
+/// Create an edit appending statements to the body of a method, f.e `) {}` becomes a body
+/// spanning multiple lines.
+fn get_append_statements_edit(method_node: &Node, statements: &[String]) -> Option<TextEdit> {
+    let body_node = method_node.child_by_field_name("body")?;
+    let closing_node = body_node.child(body_node.child_count().checked_sub(1)?)?;
+    let indent = " ".repeat(method_node.start_position().column);
+    let lines: String = statements
+        .iter()
+        .map(|statement| format!("{}  {}\n", indent, statement))
+        .collect();
+
+    let is_closing_on_own_line = closing_node
+        .prev_sibling()
+        .is_some_and(|node| node.end_position().row < closing_node.start_position().row);
+    Some(if is_closing_on_own_line {
+        insert_at(Point::new(closing_node.start_position().row, 0), lines)
+    } else {
+        insert_at(
+            closing_node.start_position(),
+            format!("\n{}{}", lines, indent),
+        )
+    })
+}
+
+/// Create an edit assigning a service to the instance that is returned by a create() method
+/// without `new static(...)`, f.e `$instance = parent::create($container);`.
+fn get_create_instance_edit(
+    create_node: &Node,
+    property_name: &str,
+    argument: &str,
+    content: &str,
+) -> Option<TextEdit> {
+    let body_node = create_node.child_by_field_name("body")?;
+    let return_node = body_node
+        .named_children(&mut body_node.walk())
+        .filter(|node| node.kind() == "return_statement")
+        .last()?;
+    let instance_node = return_node.named_child(0)?;
+    if instance_node.kind() != "variable_name" {
+        return None;
+    }
+    Some(insert_at(
+        Point::new(return_node.start_position().row, 0),
+        format!(
+            "{}{}->{} = {};\n",
+            " ".repeat(return_node.start_position().column),
+            instance_node.utf8_text(content.as_bytes()).ok()?,
+            property_name,
+            argument
+        ),
+    ))
+}
+
+/// Create an edit adding an interface to the ones that a class implements.
+fn get_implements_edit(class_node: &Node, interface: &str) -> Option<TextEdit> {
+    let children: Vec<Node> = class_node.named_children(&mut class_node.walk()).collect();
+    if let Some(interfaces_node) = children
+        .iter()
+        .find(|node| node.kind() == "class_interface_clause")
+    {
+        return Some(insert_at(
+            interfaces_node.end_position(),
+            format!(", {}", interface),
+        ));
+    }
+    let node = children
+        .iter()
+        .rfind(|node| ["name", "base_clause"].contains(&node.kind()))?;
+    Some(insert_at(
+        node.end_position(),
+        format!(" implements {}", interface),
+    ))
+}
+
+/// Create an edit inserting members at the start of the class body.
+pub fn get_class_body_edit(body_node: &Node, members: String) -> TextEdit {
+    insert_at(
+        Point::new(body_node.start_position().row + 1, 0),
+        format!("\n{}", members),
+    )
+}
+
+/// Get a constructor method for the parameters, which passes the plugin parameters on to the
+/// parent constructor for plugins.
+pub fn get_constructor_method(
+    class_node: &Node,
+    is_plugin: bool,
+    parameters: &[String],
+    statements: &[String],
+    content: &str,
+) -> String {
+    let class_name = class_node
+        .child_by_field_name("name")
+        .and_then(|node| node.utf8_text(content.as_bytes()).ok())
+        .unwrap_or("class");
+    let mut statements = statements.to_vec();
+    let mut parameters = parameters.to_vec();
+    if is_plugin {
+        parameters.splice(
+            0..0,
+            PLUGIN_CREATE_PARAMETERS
+                .iter()
+                .map(|parameter| parameter.to_string()),
+        );
+        if class_node
+            .named_children(&mut class_node.walk())
+            .any(|node| node.kind() == "base_clause")
+        {
+            statements.insert(
+                0,
+                format!(
+                    "parent::__construct({});",
+                    PLUGIN_PARAMETERS
+                        .iter()
+                        .map(|parameter| format!("${}", parameter))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            );
+        }
+    }
+    let body = if statements.is_empty() {
+        "{}".to_string()
+    } else {
+        format!(
+            "{{\n{}  }}",
+            statements
+                .iter()
+                .map(|statement| format!("    {}\n", statement))
+                .collect::<String>()
+        )
+    };
+    format!(
+        r#"  /**
+   * Constructs a new {} object.
+   */
+  public function __construct(
+{}
+  ) {}
+"#,
+        class_name,
+        parameters
+            .iter()
+            .map(|parameter| format!("    {},", parameter))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        body
+    )
+}
+
+pub fn get_create_method(parameters: &str, arguments: &[String]) -> String {
+    format!(
+        r#"  /**
+   * {{@inheritdoc}}
+   */
+  public static function create({}) {{
+    return new static(
+{}
+    );
+  }}
+"#,
+        parameters,
+        arguments
+            .iter()
+            .map(|argument| format!("      {},", argument))
+            .collect::<Vec<String>>()
+            .join("\n")
+    )
+}
+
+fn insert_at(point: Point, new_text: String) -> TextEdit {
+    let position = Position {
+        line: point.row as u32,
+        character: point.column as u32,
+    };
+    TextEdit {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        new_text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document_store::document::Document;
+
+    fn apply_text_edits(content: &str, text_edits: &[TextEdit]) -> String {
+        let mut lines: Vec<String> = content.split('\n').map(|line| line.to_string()).collect();
+        let mut text_edits = text_edits.to_vec();
+        text_edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+        for edit in text_edits.iter().rev() {
+            let (start, end) = (edit.range.start, edit.range.end);
+            let prefix = lines[start.line as usize][..start.character as usize].to_string();
+            let suffix = lines[end.line as usize][end.character as usize..].to_string();
+            let replaced = format!("{}{}{}", prefix, edit.new_text, suffix);
+            lines.splice(
+                start.line as usize..=end.line as usize,
+                replaced.split('\n').map(|line| line.to_string()),
+            );
+        }
+        lines.join("\n")
+    }
+
+    fn inject_service_into(
+        content: &str,
+        position: Position,
+        service_name: &str,
+        store: &DocumentStore,
+    ) -> Option<String> {
+        let uri = "file:///Example.php".parse::<Uri>().unwrap();
+        let mut document = Document::new(&uri.to_string(), content.to_string());
+        document.parse();
+        let class = document
+            .tokens
+            .iter()
+            .find_map(|token| match &token.data {
+                TokenData::PhpClassDefinition(class) => Some(class),
+                _ => None,
+            })
+            .unwrap();
+        let action =
+            get_inject_service_action(&uri, content, position, service_name, class, store)?;
+        Some(apply_text_edits(
+            content,
+            &action.edit.unwrap().changes.unwrap()[&uri],
+        ))
+    }
+
+    fn get_entity_type_manager_store() -> DocumentStore {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///core/core.services.yml",
+            "services:\n  entity_type.manager:\n    class: Drupal\\Core\\Entity\\EntityTypeManager\n"
+                .to_string(),
+        );
+        store.add_document(
+            "file:///core/EntityTypeManager.php",
+            "<?php\nnamespace Drupal\\Core\\Entity;\nclass EntityTypeManager implements EntityTypeManagerInterface {}\n"
+                .to_string(),
+        );
+        store
+    }
+
+    #[test]
+    fn inject_service() {
+        let store = get_entity_type_manager_store();
+        let content = r#"<?php
+
+namespace Drupal\mymodule;
+
+use Drupal\Core\Url;
+
+class Example {
+
+  public function build() {
+    return \Drupal::service('entity_type.manager')->getStorage('node');
+  }
+
+}
+"#;
+        let result = inject_service_into(
+            content,
+            Position {
+                line: 9,
+                character: 30,
+            },
+            "entity_type.manager",
+            &store,
+        );
+
+        assert_eq!(
+            r#"<?php
+
+namespace Drupal\mymodule;
+
+use Drupal\Core\DependencyInjection\ContainerInjectionInterface;
+use Drupal\Core\Entity\EntityTypeManagerInterface;
+use Drupal\Core\Url;
+use Symfony\Component\DependencyInjection\ContainerInterface;
+
+class Example implements ContainerInjectionInterface {
+
+  /**
+   * Constructs a new Example object.
+   */
+  public function __construct(
+    protected EntityTypeManagerInterface $entityTypeManager,
+  ) {}
+
+  /**
+   * {@inheritdoc}
+   */
+  public static function create(ContainerInterface $container) {
+    return new static(
+      $container->get('entity_type.manager'),
+    );
+  }
+
+  public function build() {
+    return $this->entityTypeManager->getStorage('node');
+  }
+
+}
+"#,
+            result.unwrap()
+        );
+    }
+
+    #[test]
+    fn inject_service_into_existing_constructor() {
+        let content = r#"<?php
+
+namespace Drupal\mymodule;
+
+use Symfony\Component\DependencyInjection\ContainerInterface;
+
+class Example {
+
+  public function __construct(protected $a) {}
+
+  public static function create(ContainerInterface $container) {
+    return new static(
+      $container->get('a'),
+    );
+  }
+
+  public function build() {
+    return \Drupal::service('logger');
+  }
+
+}
+"#;
+        let result = inject_service_into(
+            content,
+            Position {
+                line: 17,
+                character: 30,
+            },
+            "logger",
+            &DocumentStore::new(),
+        )
+        .unwrap();
+        assert!(result.contains("public function __construct(protected $a, protected $logger) {}"));
+        assert!(result.contains("      $container->get('a'),\n      $container->get('logger'),\n"));
+        assert!(result.contains("return $this->logger;"));
+    }
+
+    #[test]
+    fn inject_service_into_plugin() {
+        let store = get_entity_type_manager_store();
+        let content = r#"<?php
+
+namespace Drupal\mymodule\Plugin\Block;
+
+use Drupal\Core\Block\Attribute\Block;
+use Drupal\Core\Block\BlockBase;
+
+#[Block(id: 'example')]
+class Example extends BlockBase {
+
+  public function build() {
+    return \Drupal::service('entity_type.manager')->getStorage('node');
+  }
+
+}
+"#;
+        let result = inject_service_into(
+            content,
+            Position {
+                line: 11,
+                character: 30,
+            },
+            "entity_type.manager",
+            &store,
+        )
+        .unwrap();
+
+        assert!(result.contains(
+            "class Example extends BlockBase implements ContainerFactoryPluginInterface {"
+        ));
+        assert!(result.contains(
+            r#"  public function __construct(
+    array $configuration,
+    $plugin_id,
+    $plugin_definition,
+    protected EntityTypeManagerInterface $entityTypeManager,
+  ) {
+    parent::__construct($configuration, $plugin_id, $plugin_definition);
+  }
+
+  /**
+   * {@inheritdoc}
+   */
+  public static function create(ContainerInterface $container, array $configuration, $plugin_id, $plugin_definition) {
+    return new static(
+      $configuration,
+      $plugin_id,
+      $plugin_definition,
+      $container->get('entity_type.manager'),
+    );
+  }
+"#
+        ));
+        assert!(result.contains("use Drupal\\Core\\Plugin\\ContainerFactoryPluginInterface;"));
+
+        // Existing constructors keep passing the plugin parameters.
+        let content = content.replace(
+            "  public function build() {",
+            "  public function __construct(array $configuration, $plugin_id, $plugin_definition) {\n    parent::__construct($configuration, $plugin_id, $plugin_definition);\n  }\n\n  public function build() {",
+        );
+        let result = inject_service_into(
+            &content,
+            Position {
+                line: 15,
+                character: 30,
+            },
+            "entity_type.manager",
+            &store,
+        )
+        .unwrap();
+        assert!(result.contains("public function __construct(array $configuration, $plugin_id, $plugin_definition, protected EntityTypeManagerInterface $entityTypeManager) {"));
+        assert!(result.contains(
+            "  public static function create(ContainerInterface $container, array $configuration, $plugin_id, $plugin_definition) {\n    return new static(\n      $configuration,\n      $plugin_id,\n      $plugin_definition,\n      $container->get('entity_type.manager'),\n    );\n"
+        ));
+    }
+
+    #[test]
+    fn inject_service_into_controller() {
+        let mut store = get_entity_type_manager_store();
+        store.add_document(
+            "file:///core/lib/Drupal/Core/Controller/ControllerBase.php",
+            r#"<?php
+
+namespace Drupal\Core\Controller;
+
+use Drupal\Core\DependencyInjection\ContainerInjectionInterface;
+
+abstract class ControllerBase implements ContainerInjectionInterface {
+
+  /**
+   * The entity type manager.
+   *
+   * @var \Drupal\Core\Entity\EntityTypeManagerInterface
+   */
+  protected $entityTypeManager;
+
+}
+"#
+            .to_string(),
+        );
+        let content = r#"<?php
+
+namespace Drupal\mymodule\Controller;
+
+use Drupal\Core\Controller\ControllerBase;
+
+class ExampleController extends ControllerBase {
+
+  public function build() {
+    return \Drupal::service('entity_type.manager')->getStorage('node');
+  }
+
+}
+"#;
+        let result = inject_service_into(
+            content,
+            Position {
+                line: 9,
+                character: 30,
+            },
+            "entity_type.manager",
+            &store,
+        )
+        .unwrap();
+
+        // The property of the parent class is assigned instead of declared again.
+        assert!(result.contains("class ExampleController extends ControllerBase {"));
+        assert!(result.contains(
+            r#"  public function __construct(
+    EntityTypeManagerInterface $entity_type_manager,
+  ) {
+    $this->entityTypeManager = $entity_type_manager;
+  }
+"#
+        ));
+        assert!(!result.contains("ContainerInjectionInterface"));
+
+        // Controllers creating an instance of the parent get the service assigned.
+        let content = content.replace(
+            "  public function build() {",
+            "  public static function create(ContainerInterface $container) {\n    $instance = parent::create($container);\n    return $instance;\n  }\n\n  public function build() {",
+        );
+        let result = inject_service_into(
+            &content,
+            Position {
+                line: 14,
+                character: 30,
+            },
+            "entity_type.manager",
+            &store,
+        )
+        .unwrap();
+        assert!(result.contains(
+            "    $instance = parent::create($container);\n    $instance->entityTypeManager = $container->get('entity_type.manager');\n    return $instance;\n"
+        ));
+        assert!(!result.contains("__construct"));
+        assert!(!result.contains("protected"));
+        assert!(result.contains("return $this->entityTypeManager->getStorage('node');"));
+    }
+
+    #[test]
+    fn inject_service_into_static_method() {
+        let content = r#"<?php
+
+namespace Drupal\mymodule;
+
+class Example {
+
+  public static function build() {
+    return \Drupal::service('logger');
+  }
+
+}
+"#;
+        let result = inject_service_into(
+            content,
+            Position {
+                line: 7,
+                character: 30,
+            },
+            "logger",
+            &DocumentStore::new(),
+        );
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn generate_create_factory() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///core/core.services.yml",
+            "services:\n  entity_type.manager:\n    class: Drupal\\Core\\Entity\\EntityTypeManager\n"
+                .to_string(),
+        );
+        store.add_document(
+            "file:///core/EntityTypeManager.php",
+            "<?php\nnamespace Drupal\\Core\\Entity;\nclass EntityTypeManager implements EntityTypeManagerInterface {}\n"
+                .to_string(),
+        );
+
+        let content = r#"<?php
+
+namespace Drupal\mymodule\Plugin\Block;
+
+use Drupal\Core\Entity\EntityTypeManagerInterface;
+use Drupal\Core\Plugin\ContainerFactoryPluginInterface;
+
+class Example implements ContainerFactoryPluginInterface {
+
+  public function __construct(array $configuration, $plugin_id, $plugin_definition, protected EntityTypeManagerInterface $entityTypeManager, protected Foo $foo, protected \Drupal\foo\Bar $bar) {
+  }
+
+}
+"#;
+        let uri = "file:///Example.php".parse::<Uri>().unwrap();
+        let mut document = Document::new(&uri.to_string(), content.to_string());
+        document.parse();
+        let TokenData::PhpClassDefinition(class) = &document.tokens[0].data else {
+            panic!("Expected a class definition");
+        };
+
+        let action = get_create_factory_action(&uri, content, class, &store).unwrap();
+        let text_edits = &action.edit.unwrap().changes.unwrap()[&uri];
+        let result = apply_text_edits(content, text_edits);
+
+        assert!(result.contains(
+            r#"  }
+
+  /**
+   * {@inheritdoc}
+   */
+  public static function create(ContainerInterface $container, array $configuration, $plugin_id, $plugin_definition) {
+    return new static(
+      $configuration,
+      $plugin_id,
+      $plugin_definition,
+      $container->get('entity_type.manager'),
+      $container->get(Foo::class),
+      $container->get(Bar::class),
+    );
+  }
+"#
+        ));
+        assert!(result.contains("use Symfony\\Component\\DependencyInjection\\ContainerInterface;"));
+        assert!(result.contains("use Drupal\\foo\\Bar;"));
+        assert!(!result.contains("use Drupal\\mymodule\\Plugin\\Block\\Foo;"));
+
+        // The parameters of classes that aren't plugins can't be inferred.
+        let content = content
+            .replace(" implements ContainerFactoryPluginInterface", "")
+            .replace("\\Plugin\\Block;", ";");
+        let mut document = Document::new(&uri.to_string(), content.clone());
+        document.parse();
+        let TokenData::PhpClassDefinition(class) = &document.tokens[0].data else {
+            panic!("Expected a class definition");
+        };
+        assert!(get_create_factory_action(&uri, &content, class, &store).is_none());
+    }
+
+    #[test]
+    fn convert_service_name_to_property_name() {
+        assert_eq!(
+            "entityTypeManager",
+            service_name_to_property_name("entity_type.manager")
+        );
+        assert_eq!(
+            "configFactory",
+            service_name_to_property_name("config.factory")
+        );
+        assert_eq!("logger", service_name_to_property_name("logger"));
+    }
+}
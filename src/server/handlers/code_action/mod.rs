@@ -1,17 +1,34 @@
-use std::collections::HashMap;
+mod dependency_injection;
+
+use std::{borrow::Cow, collections::HashMap, fs, path::Path};
 
 use lsp_server::{ErrorCode, Request, Response};
 use lsp_types::{
-    CodeAction, CodeActionKind, CodeActionParams, Position, Range, TextEdit, Uri, WorkspaceEdit,
+    CodeAction, CodeActionKind, CodeActionParams, CreateFile, Diagnostic, DocumentChangeOperation,
+    DocumentChanges, NumberOrString, OneOf, OptionalVersionedTextDocumentIdentifier, Position,
+    Range, ResourceOp, TextDocumentEdit, TextEdit, Uri, WorkspaceEdit,
 };
-use regex::Regex;
 
 use crate::{
-    document_store::DOCUMENT_STORE,
-    parser::tokens::{Token, TokenData},
+    diagnostics::{
+        MISSING_USE_STATEMENT, TRANSLATION_PLACEHOLDER, TRANSLATION_PLACEHOLDERS,
+        UNKNOWN_PERMISSION,
+    },
+    document_store::{document::FileType, DocumentStore, DOCUMENT_STORE},
+    parser::{
+        php::PhpParser,
+        tokens::{DrupalTranslationString, PhpClass, PhpClassName, Token, TokenData},
+        yaml::YamlParser,
+    },
     server::handle_request::get_response_error,
+    utils::{find_info_file, get_extension_name, path_to_uri, uri_to_url},
 };
 
+use self::dependency_injection::{get_create_factory_action, get_inject_service_action};
+
+const EVENT_SUBSCRIBER_INTERFACE: &str =
+    "Symfony\\Component\\EventDispatcher\\EventSubscriberInterface";
+
 pub fn handle_text_document_code_action(request: Request) -> Option<Response> {
     let params = match serde_json::from_value::<CodeActionParams>(request.params) {
         Err(err) => {
@@ -25,54 +42,145 @@ pub fn handle_text_document_code_action(request: Request) -> Option<Response> {
     };
 
     let mut token: Option<Token> = None;
-    if let Some(document) = DOCUMENT_STORE
-        .lock()
-        .unwrap()
-        .get_document(&params.text_document.uri.to_string())
+    let mut code_actions_result: Vec<CodeAction> = vec![];
+    // The actions that need the store are created first, so that it isn't locked while the
+    // others are computed.
+    let mut document_content: Option<(String, String, FileType)> = None;
     {
-        token = document.get_token_under_cursor(params.range.start);
+        let store = DOCUMENT_STORE.lock().unwrap();
+        if let Some(document) = store.get_document(&params.text_document.uri.to_string()) {
+            // Twig translations don't take an arguments array.
+            if document.file_type == FileType::Php {
+                token = document.get_token_under_cursor(params.range.start);
+            }
+
+            let line = params.range.start.line as usize;
+            if let Some(class) = document.tokens.iter().find_map(|token| match &token.data {
+                TokenData::PhpClassDefinition(class)
+                    if token.range.start_point.row <= line && line <= token.range.end_point.row =>
+                {
+                    Some(class)
+                }
+                _ => None,
+            }) {
+                if let Some(TokenData::DrupalServiceReference(service_name)) =
+                    token.as_ref().map(|token| &token.data)
+                {
+                    code_actions_result.extend(get_inject_service_action(
+                        &params.text_document.uri,
+                        &document.content,
+                        params.range.start,
+                        service_name,
+                        class,
+                        &store,
+                    ));
+                }
+                code_actions_result.extend(get_create_factory_action(
+                    &params.text_document.uri,
+                    &document.content,
+                    class,
+                    &store,
+                ));
+                code_actions_result.extend(get_event_subscriber_action(
+                    &params.text_document.uri,
+                    class,
+                    &store,
+                ));
+            }
+
+            for diagnostic in &params.context.diagnostics {
+                if diagnostic.code == Some(NumberOrString::String(UNKNOWN_PERMISSION.to_string())) {
+                    code_actions_result.extend(get_define_permission_action(
+                        &params.text_document.uri,
+                        diagnostic,
+                        &store,
+                    ));
+                }
+            }
+
+            document_content = Some((
+                document.uri.clone(),
+                document.content.clone(),
+                document.file_type,
+            ));
+        }
+    }
+
+    if let Some((document_uri, content, file_type)) = document_content {
+        if file_type == FileType::Php {
+            code_actions_result.extend(get_organize_imports_action(
+                &params.text_document.uri,
+                &content,
+            ));
+        }
+
+        if document_uri.ends_with(".install") {
+            code_actions_result.extend(get_hook_update_n_action(
+                &params.text_document.uri,
+                &document_uri,
+                &content,
+            ));
+        }
+
+        for diagnostic in &params.context.diagnostics {
+            if diagnostic.code == Some(NumberOrString::String(MISSING_USE_STATEMENT.to_string())) {
+                code_actions_result.extend(get_missing_use_statement_actions(
+                    &params.text_document.uri,
+                    &content,
+                    diagnostic,
+                ));
+            } else if diagnostic.code
+                == Some(NumberOrString::String(TRANSLATION_PLACEHOLDERS.to_string()))
+            {
+                // All mismatches of a `t()` call are fixed by the same edit.
+                let action =
+                    get_sync_translation_placeholders_action(&params.text_document.uri, diagnostic);
+                if let Some(action) = action.filter(|action| {
+                    !code_actions_result
+                        .iter()
+                        .any(|existing| existing.edit == action.edit)
+                }) {
+                    code_actions_result.push(action);
+                }
+            }
+        }
     }
 
-    let mut code_actions_result: Vec<CodeAction> = vec![];
     if let Some(token) = token {
-        if let TokenData::DrupalTranslationString(token_data) = &token.data {
-            let re = Regex::new(r#"(?<placeholder>[@%:]\w*)"#).unwrap();
+        if let TokenData::DrupalTranslationString(DrupalTranslationString {
+            string,
+            placeholders: None,
+        }) = &token.data
+        {
             let arguments_string: String = format!(
                 ", [{}]",
-                re.captures_iter(&token_data.string)
+                TRANSLATION_PLACEHOLDER
+                    .captures_iter(string)
                     .map(|capture| capture.name("placeholder"))
                     .filter_map(|str| Some(format!("'{}' => ''", str?.as_str())))
                     .collect::<Vec<String>>()
                     .join(", ")
             );
 
-            let mut text_edits: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
-            text_edits.insert(
-                params.text_document.uri,
-                vec![TextEdit {
-                    range: Range {
-                        start: Position {
-                            line: token.range.end_point.row as u32,
-                            character: token.range.end_point.column as u32 - 1,
-                        },
-                        end: Position {
-                            line: token.range.end_point.row as u32,
-                            character: token.range.end_point.column as u32 - 1,
-                        },
+            let text_edit = TextEdit {
+                range: Range {
+                    start: Position {
+                        line: token.range.end_point.row as u32,
+                        character: token.range.end_point.column as u32 - 1,
                     },
-                    new_text: arguments_string,
-                }],
-            );
+                    end: Position {
+                        line: token.range.end_point.row as u32,
+                        character: token.range.end_point.column as u32 - 1,
+                    },
+                },
+                new_text: arguments_string,
+            };
 
             code_actions_result.push(CodeAction {
                 title: String::from("Add translations placeholders"),
                 kind: Some(CodeActionKind::REFACTOR_INLINE),
                 diagnostics: None,
-                edit: Some(WorkspaceEdit {
-                    changes: Some(text_edits),
-                    document_changes: None,
-                    change_annotations: None,
-                }),
+                edit: Some(get_document_edit(params.text_document.uri, vec![text_edit])),
                 command: None,
                 is_preferred: Some(true),
                 disabled: None,
@@ -94,3 +202,338 @@ pub fn handle_text_document_code_action(request: Request) -> Option<Response> {
         )),
     }
 }
+
+fn get_missing_use_statement_actions(
+    uri: &Uri,
+    content: &str,
+    diagnostic: &Diagnostic,
+) -> Vec<CodeAction> {
+    let Some(class_names) = diagnostic
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<Vec<String>>(data).ok())
+    else {
+        return vec![];
+    };
+
+    let parser = PhpParser::new(content);
+    class_names
+        .iter()
+        .filter_map(|class_name| {
+            let text_edit =
+                parser.get_use_statement_edit(&PhpClassName::from(class_name.as_str()))?;
+
+            Some(CodeAction {
+                title: format!("Import {}", class_name),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(get_document_edit(uri.clone(), vec![text_edit])),
+                command: None,
+                is_preferred: Some(class_names.len() == 1),
+                disabled: None,
+                data: None,
+            })
+        })
+        .collect()
+}
+
+fn get_organize_imports_action(uri: &Uri, content: &str) -> Option<CodeAction> {
+    let text_edit = PhpParser::new(content).get_organize_imports_edit()?;
+
+    Some(CodeAction {
+        title: String::from("Organize imports"),
+        kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+        diagnostics: None,
+        edit: Some(get_document_edit(uri.clone(), vec![text_edit])),
+        command: None,
+        is_preferred: None,
+        disabled: None,
+        data: None,
+    })
+}
+
+fn get_hook_update_n_action(uri: &Uri, document_uri: &str, content: &str) -> Option<CodeAction> {
+    let (module_name, _) = document_uri.rsplit('/').next()?.split_once('.')?;
+    let update_number = PhpParser::new(content).get_next_update_number(module_name);
+
+    // Append the update hook to the end of the file.
+    let last_line = content.rsplit('\n').next().unwrap_or_default();
+    let position = Position {
+        line: content.matches('\n').count() as u32,
+        character: last_line.len() as u32,
+    };
+    let new_text = format!(
+        "{}\n/**\n * Implements hook_update_N().\n */\nfunction {}_update_{}(&$sandbox) {{\n}}\n",
+        if last_line.is_empty() { "" } else { "\n" },
+        module_name,
+        update_number
+    );
+
+    let text_edit = TextEdit {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        new_text,
+    };
+
+    Some(CodeAction {
+        title: format!("Generate {}_update_{}()", module_name, update_number),
+        kind: Some(CodeActionKind::SOURCE),
+        diagnostics: None,
+        edit: Some(get_document_edit(uri.clone(), vec![text_edit])),
+        command: None,
+        is_preferred: None,
+        disabled: None,
+        data: None,
+    })
+}
+
+fn get_define_permission_action(
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+    store: &DocumentStore,
+) -> Option<CodeAction> {
+    let permission = diagnostic.data.as_ref()?.as_str()?;
+    let info_file = find_info_file(Path::new(&uri_to_url(uri.clone())?))?;
+    let module_name = get_extension_name(&info_file)?;
+    let permissions_file = info_file.with_file_name(format!("{}.permissions.yml", module_name));
+
+    let mut title = permission.to_string();
+    if let Some(first) = title.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    let definition = format!(
+        "{}:\n  title: '{}'\n",
+        permission,
+        title.replace('\'', "''")
+    );
+    let edit = get_append_to_file_edit(store, &permissions_file, &definition, "")?;
+
+    Some(CodeAction {
+        title: format!(
+            "Define permission '{}' in {}.permissions.yml",
+            permission, module_name
+        ),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(edit),
+        command: None,
+        is_preferred: Some(true),
+        disabled: None,
+        data: None,
+    })
+}
+
+fn get_sync_translation_placeholders_action(
+    uri: &Uri,
+    diagnostic: &Diagnostic,
+) -> Option<CodeAction> {
+    let edit = serde_json::from_value::<TextEdit>(diagnostic.data.clone()?).ok()?;
+
+    Some(CodeAction {
+        title: String::from("Sync translation placeholders"),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(get_document_edit(uri.clone(), vec![edit])),
+        command: None,
+        is_preferred: Some(true),
+        disabled: None,
+        data: None,
+    })
+}
+
+fn get_event_subscriber_action(
+    uri: &Uri,
+    class: &PhpClass,
+    store: &DocumentStore,
+) -> Option<CodeAction> {
+    if !class
+        .interfaces
+        .contains(&PhpClassName::from(EVENT_SUBSCRIBER_INTERFACE))
+    {
+        return None;
+    }
+
+    let services = store.get_service_definitions_for_class(&class.name);
+    if services
+        .iter()
+        .any(|(_, service)| service.tags.iter().any(|tag| tag == "event_subscriber"))
+    {
+        return None;
+    }
+
+    let (title, edit) = match services.first() {
+        Some((document, service)) => {
+            let text_edit = YamlParser::new(&document.content, &document.uri)
+                .get_service_tag_edit(&service.name, "event_subscriber")?;
+            (
+                format!("Tag service '{}' as an event subscriber", service.name),
+                get_document_edit(document.uri.parse().ok()?, vec![text_edit]),
+            )
+        }
+        None => {
+            let info_file = find_info_file(Path::new(&uri_to_url(uri.clone())?))?;
+            let module_name = get_extension_name(&info_file)?;
+            let service_name = format!(
+                "{}.{}",
+                module_name,
+                camel_case_to_snake_case(class.name.get_short_name())
+            );
+            let definition = format!(
+                "  {}:\n    class: {}\n    tags:\n      - {{ name: event_subscriber }}\n",
+                service_name, class.name
+            );
+            let services_file = info_file.with_file_name(format!("{}.services.yml", module_name));
+            (
+                format!(
+                    "Register '{}' as an event subscriber in {}.services.yml",
+                    service_name, module_name
+                ),
+                get_append_to_file_edit(store, &services_file, &definition, "services:\n")?,
+            )
+        }
+    };
+
+    Some(CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: None,
+        edit: Some(edit),
+        command: None,
+        is_preferred: None,
+        disabled: None,
+        data: None,
+    })
+}
+
+/// Get an edit appending text to the end of a file, creating the file with the given header when
+/// it doesn't exist yet.
+fn get_append_to_file_edit(
+    store: &DocumentStore,
+    path: &Path,
+    new_text: &str,
+    header: &str,
+) -> Option<WorkspaceEdit> {
+    let document_key = path_to_uri(path)?;
+    let uri: Uri = document_key.parse().ok()?;
+
+    let content: Option<Cow<str>> = match store.get_document(&document_key) {
+        Some(document) => Some(Cow::Borrowed(&document.content)),
+        // Files that exist but aren't indexed are appended to as well, instead of recreated.
+        None => fs::read_to_string(path).ok().map(Cow::Owned),
+    };
+    let edit = match content {
+        Some(content) => {
+            let last_line = content.rsplit('\n').next().unwrap_or_default();
+            let position = Position {
+                line: content.matches('\n').count() as u32,
+                character: last_line.len() as u32,
+            };
+            let text_edit = TextEdit {
+                range: Range {
+                    start: position,
+                    end: position,
+                },
+                new_text: format!(
+                    "{}\n{}",
+                    if last_line.is_empty() { "" } else { "\n" },
+                    new_text
+                ),
+            };
+            get_document_edit(uri, vec![text_edit])
+        }
+        None => WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Operations(vec![
+                DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                    uri: uri.clone(),
+                    options: None,
+                    annotation_id: None,
+                })),
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                    edits: vec![OneOf::Left(TextEdit {
+                        range: Range::default(),
+                        new_text: format!("{}{}", header, new_text),
+                    })],
+                }),
+            ])),
+            change_annotations: None,
+        },
+    };
+    Some(edit)
+}
+
+fn camel_case_to_snake_case(value: &str) -> String {
+    let mut result = String::new();
+    for (index, character) in value.chars().enumerate() {
+        if character.is_uppercase() && index > 0 {
+            result.push('_');
+        }
+        result.push(character.to_ascii_lowercase());
+    }
+    result
+}
+
+/// Create an edit changing a single document.
+// `lsp_types::Uri` has interior mutability but is the key type of `WorkspaceEdit::changes`.
+#[allow(clippy::mutable_key_type)]
+fn get_document_edit(uri: Uri, text_edits: Vec<TextEdit>) -> WorkspaceEdit {
+    let mut changes: HashMap<Uri, Vec<TextEdit>> = HashMap::new();
+    changes.insert(uri, text_edits);
+    WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+        change_annotations: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_to_file_that_is_not_indexed() {
+        let directory =
+            std::env::temp_dir().join(format!("drupal_ls_append_to_file_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("foo.permissions.yml");
+        std::fs::write(&path, "access foo:\n  title: 'Access foo'").unwrap();
+
+        let store = DocumentStore::new();
+        let edit = get_append_to_file_edit(&store, &path, "edit foo:\n", "");
+        let missing_edit =
+            get_append_to_file_edit(&store, &directory.join("bar.permissions.yml"), "", "");
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        let edits: Vec<TextEdit> = edit
+            .unwrap()
+            .changes
+            .unwrap()
+            .into_values()
+            .flatten()
+            .collect();
+        assert_eq!(
+            vec![TextEdit {
+                range: Range {
+                    start: Position {
+                        line: 1,
+                        character: 21,
+                    },
+                    end: Position {
+                        line: 1,
+                        character: 21,
+                    },
+                },
+                new_text: "\n\nedit foo:\n".to_string(),
+            }],
+            edits
+        );
+        assert!(matches!(
+            missing_edit.unwrap().document_changes,
+            Some(DocumentChanges::Operations(operations))
+                if matches!(operations[0], DocumentChangeOperation::Op(ResourceOp::Create(_)))
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+use lsp_server::{ErrorCode, Request, Response};
+use lsp_types::{CodeLens, CodeLensParams, Command, Location, Range};
+
+use crate::document_store::document::Document;
+use crate::document_store::{DocumentStore, DOCUMENT_STORE};
+use crate::parser::token_range_to_range;
+use crate::parser::tokens::TokenData;
+use crate::server::handle_request::get_response_error;
+
+/// The client side command that lists the implementations of a hook, with the uri and position
+/// of the code lens and the locations of the implementations as arguments.
+pub const SHOW_IMPLEMENTATIONS: &str = "drupal_ls.showImplementations";
+
+/// The client side command that lists the tests covering a class, with the same arguments as
+/// `drupal_ls.showImplementations`.
+pub const SHOW_TESTS: &str = "drupal_ls.showTests";
+
+// The maximum number of modules to list in the title of a code lens.
+const MAX_LISTED_MODULES: usize = 3;
+
+pub fn handle_text_document_code_lens(request: Request) -> Option<Response> {
+    let params = match serde_json::from_value::<CodeLensParams>(request.params) {
+        Err(err) => {
+            return Some(get_response_error(
+                request.id,
+                ErrorCode::InvalidParams,
+                format!("Could not parse code lens params: {:?}", err),
+            ));
+        }
+        Ok(value) => value,
+    };
+
+    let store = DOCUMENT_STORE.lock().unwrap();
+    let document = store.get_document(&params.text_document.uri.to_string())?;
+    let code_lenses = get_code_lenses(&store, document);
+
+    match serde_json::to_value(code_lenses) {
+        Ok(result) => Some(Response {
+            id: request.id,
+            result: Some(result),
+            error: None,
+        }),
+        Err(error) => Some(get_response_error(
+            request.id,
+            ErrorCode::InternalError,
+            format!("No code lenses found: {:?}", error),
+        )),
+    }
+}
+
+fn get_code_lenses(store: &DocumentStore, document: &Document) -> Vec<CodeLens> {
+    let mut code_lenses = get_hook_code_lenses(store, document);
+    code_lenses.extend(get_test_code_lenses(store, document));
+    code_lenses
+}
+
+/// Get the code lenses showing the number of implementations of the hooks defined in `*.api.php`
+/// files, and of the hooks invoked through the module handler.
+fn get_hook_code_lenses(store: &DocumentStore, document: &Document) -> Vec<CodeLens> {
+    let hooks: Vec<(&str, Range, bool)> = document
+        .tokens
+        .iter()
+        .flat_map(|token| {
+            let range = token_range_to_range(&token.range);
+            match &token.data {
+                TokenData::DrupalHookDefinition(hook) => vec![(hook.name.as_str(), range, false)],
+                // Calls invoking multiple hooks get a code lens per hook.
+                TokenData::DrupalHookInvocation(hook_names) => hook_names
+                    .iter()
+                    .map(|hook_name| (hook_name.as_str(), range, hook_names.len() > 1))
+                    .collect(),
+                _ => vec![],
+            }
+        })
+        .collect();
+    if hooks.is_empty() {
+        return vec![];
+    }
+
+    let hook_names: Vec<&str> = hooks.iter().map(|(hook_name, _, _)| *hook_name).collect();
+    let implementations = store.get_hooks_implementations(&hook_names);
+    hooks
+        .into_iter()
+        .map(|(hook_name, range, show_hook_name)| {
+            let hook_implementations = implementations.get(hook_name);
+            let locations: Vec<Location> = hook_implementations
+                .into_iter()
+                .flatten()
+                .filter_map(|(document, token)| {
+                    Some(Location {
+                        uri: document.get_uri()?,
+                        range: token_range_to_range(&token.range),
+                    })
+                })
+                .collect();
+            let mut modules: Vec<&str> = hook_implementations
+                .into_iter()
+                .flatten()
+                .filter_map(|(_, token)| match &token.data {
+                    TokenData::DrupalHookImplementation(implementation) => {
+                        Some(implementation.module.as_str())
+                    }
+                    _ => None,
+                })
+                .collect();
+            modules.sort();
+            modules.dedup();
+
+            let mut title = match locations.len() {
+                1 => "1 implementation".to_string(),
+                count => format!("{} implementations", count),
+            };
+            if show_hook_name {
+                title.push_str(&format!(" of {}", hook_name));
+            }
+            // Hooks with few implementations list the modules implementing them.
+            if !modules.is_empty() && modules.len() <= MAX_LISTED_MODULES {
+                title.push_str(&format!(" ({})", modules.join(", ")));
+            }
+
+            get_locations_code_lens(document, range, title, SHOW_IMPLEMENTATIONS, locations)
+        })
+        .collect()
+}
+
+/// Get the code lenses showing the number of tests covering the classes of a file, from their
+/// `@covers` and `@coversDefaultClass` tags.
+fn get_test_code_lenses(store: &DocumentStore, document: &Document) -> Vec<CodeLens> {
+    document
+        .tokens
+        .iter()
+        .filter_map(|token| {
+            let TokenData::PhpClassDefinition(class) = &token.data else {
+                return None;
+            };
+            let locations: Vec<Location> = store
+                .get_class_tests(&class.name)
+                .into_iter()
+                .filter_map(|(document, token)| {
+                    Some(Location {
+                        uri: document.get_uri()?,
+                        range: token_range_to_range(&token.range),
+                    })
+                })
+                .collect();
+            let title = match locations.len() {
+                0 => return None,
+                1 => "1 test".to_string(),
+                count => format!("{} tests", count),
+            };
+            Some(get_locations_code_lens(
+                document,
+                token_range_to_range(&token.range),
+                title,
+                SHOW_TESTS,
+                locations,
+            ))
+        })
+        .collect()
+}
+
+/// Get a code lens that runs a client side command listing locations.
+fn get_locations_code_lens(
+    document: &Document,
+    range: Range,
+    title: String,
+    command: &str,
+    locations: Vec<Location>,
+) -> CodeLens {
+    CodeLens {
+        range,
+        command: Some(Command {
+            title,
+            command: command.to_string(),
+            arguments: Some(vec![
+                serde_json::json!(document.uri),
+                serde_json::json!(range.start),
+                serde_json::json!(locations),
+            ]),
+        }),
+        data: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_implementation_counts() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/modules/system/system.api.php",
+            r#"<?php
+
+/**
+ * Perform periodic actions.
+ */
+function hook_cron() {
+}
+
+/**
+ * Alter the registry.
+ */
+function hook_registry_alter(&$registry) {
+}
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/foo.module",
+            r#"<?php
+
+/**
+ * Implements hook_cron().
+ */
+function foo_cron() {
+  \Drupal::moduleHandler()->alter(['registry', 'foo_registry'], $registry);
+}
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/bar/bar.module",
+            r#"<?php
+
+/**
+ * Implements hook_cron().
+ */
+function bar_cron() {
+}
+"#
+            .to_string(),
+        );
+
+        let titles = |uri: &str| {
+            get_code_lenses(&store, store.get_document(uri).unwrap())
+                .into_iter()
+                .map(|code_lens| (code_lens.range.start.line, code_lens.command.unwrap().title))
+                .collect::<Vec<(u32, String)>>()
+        };
+        assert_eq!(
+            vec![
+                (5, "2 implementations (bar, foo)".to_string()),
+                (11, "0 implementations".to_string()),
+            ],
+            titles("file:///var/www/web/core/modules/system/system.api.php")
+        );
+        assert_eq!(
+            vec![
+                (6, "0 implementations of hook_registry_alter".to_string()),
+                (
+                    6,
+                    "0 implementations of hook_foo_registry_alter".to_string()
+                ),
+            ],
+            titles("file:///var/www/web/modules/custom/foo/foo.module")
+        );
+        assert_eq!(
+            Vec::<(u32, String)>::new(),
+            titles("file:///var/www/web/modules/custom/bar/bar.module")
+        );
+    }
+
+    #[test]
+    fn class_test_counts() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/src/FooManager.php",
+            "<?php\n\nnamespace Drupal\\foo;\n\nclass FooManager {\n}\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/tests/src/Unit/FooManagerTest.php",
+            r#"<?php
+
+namespace Drupal\Tests\foo\Unit;
+
+/**
+ * @coversDefaultClass \Drupal\foo\FooManager
+ */
+class FooManagerTest extends UnitTestCase {
+
+  /**
+   * @covers ::load
+   */
+  public function testLoad() {
+  }
+
+}
+"#
+            .to_string(),
+        );
+
+        let code_lenses = get_code_lenses(
+            &store,
+            store
+                .get_document("file:///var/www/web/modules/custom/foo/src/FooManager.php")
+                .unwrap(),
+        );
+        assert_eq!(1, code_lenses.len());
+        let command = code_lenses[0].command.as_ref().unwrap();
+        assert_eq!("1 test", command.title);
+        assert_eq!(SHOW_TESTS, command.command);
+    }
+}
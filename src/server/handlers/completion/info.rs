@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionTextEdit, Position,
+    Range, TextEdit,
+};
+use regex::Regex;
+
+use crate::parser::tokens::DrupalExtension;
+
+// Matches a dependency that is being typed in a `*.info.yml` file, f.e `  - drupal:no`.
+static INFO_DEPENDENCY_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^(?<indentation> *)- +['"]?(?<value>[\w:]*)$"#).unwrap());
+
+/// Get the range of the dependency that is being typed in the `dependencies` of a `*.info.yml`
+/// file, including its project prefix.
+pub fn get_dependency_range(content: &str, position: Position) -> Option<Range> {
+    let lines: Vec<&str> = content.lines().collect();
+    let line_index = position.line as usize;
+    let before_cursor = lines.get(line_index)?.get(..position.character as usize)?;
+    let captures = INFO_DEPENDENCY_ITEM.captures(before_cursor)?;
+    let indentation = captures.name("indentation")?.len();
+
+    // Sequence items can have the same indentation as their key.
+    let parent = lines[..line_index].iter().rev().find(|line| {
+        let trimmed = line.trim_start();
+        !trimmed.is_empty() && !trimmed.starts_with('#') && !trimmed.starts_with('-')
+    })?;
+    if parent.len() - parent.trim_start().len() > indentation
+        || parent.trim_end() != "dependencies:"
+    {
+        return None;
+    }
+
+    Some(Range {
+        start: Position {
+            line: position.line,
+            character: position.character - captures.name("value")?.len() as u32,
+        },
+        end: position,
+    })
+}
+
+/// Get completion items for the modules that the extension in `extension_name` can depend on,
+/// prefixed with their project.
+pub fn get_dependency_completion_items(
+    extensions: &[&DrupalExtension],
+    range: Range,
+    extension_name: &str,
+) -> Vec<CompletionItem> {
+    let mut names = HashSet::new();
+    extensions
+        .iter()
+        .filter(|extension| {
+            extension.extension_type == "module"
+                && extension.name != extension_name
+                && names.insert(extension.name.as_str())
+        })
+        .map(|extension| {
+            let dependency = format!("{}:{}", extension.project, extension.name);
+            CompletionItem {
+                label: extension.name.clone(),
+                label_details: Some(CompletionItemLabelDetails {
+                    description: Some("Module".to_string()),
+                    detail: None,
+                }),
+                detail: Some(match &extension.package {
+                    Some(package) => format!("{} ({})", extension.label, package),
+                    None => extension.label.clone(),
+                }),
+                kind: Some(CompletionItemKind::MODULE),
+                filter_text: Some(dependency.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: dependency,
+                })),
+                deprecated: Some(false),
+                ..CompletionItem::default()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_range(content: &str) -> Option<(u32, u32)> {
+        let (line, column) = content
+            .lines()
+            .enumerate()
+            .find_map(|(line, text)| Some((line, text.find('|')?)))?;
+        let range = get_dependency_range(
+            &content.replace('|', ""),
+            Position {
+                line: line as u32,
+                character: column as u32,
+            },
+        )?;
+        Some((range.start.character, range.end.character))
+    }
+
+    #[test]
+    fn dependency_range() {
+        assert_eq!(
+            Some((4, 13)),
+            get_range("name: Foo\ndependencies:\n  - drupal:node\n  - drupal:no|\n")
+        );
+        assert_eq!(Some((2, 2)), get_range("dependencies:\n- |\n"));
+        assert_eq!(Some((5, 7)), get_range("dependencies:\n  - 'to|'\n"));
+        assert_eq!(None, get_range("libraries:\n  - foo/gl|\n"));
+        assert_eq!(
+            None,
+            get_range("dependencies:\n  - drupal:node\ntest_dependencies:\n  - |\n")
+        );
+    }
+
+    #[test]
+    fn dependency_completion_items() {
+        let extension = |name: &str, extension_type: &str, project: &str| DrupalExtension {
+            name: name.to_string(),
+            label: name.to_string(),
+            description: None,
+            extension_type: extension_type.to_string(),
+            package: Some("Core".to_string()),
+            version: None,
+            core_version_requirement: None,
+            project: project.to_string(),
+        };
+        let extensions = [
+            extension("node", "module", "drupal"),
+            extension("olivero", "theme", "drupal"),
+            extension("webform_ui", "module", "webform"),
+            extension("foo", "module", "foo"),
+        ];
+        let items = get_dependency_completion_items(
+            &extensions.iter().collect::<Vec<&DrupalExtension>>(),
+            Range::default(),
+            "foo",
+        );
+        assert_eq!(
+            vec!["drupal:node", "webform:webform_ui"],
+            items
+                .iter()
+                .filter_map(|item| match &item.text_edit {
+                    Some(CompletionTextEdit::Edit(edit)) => Some(edit.new_text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(Some("node (Core)".to_string()), items[0].detail);
+    }
+}
@@ -1,17 +1,174 @@
-use std::collections::HashMap;
+mod info;
+mod routing;
+mod services;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
 
 use lsp_server::{ErrorCode, Request, Response};
 use lsp_types::{
     CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionList,
-    CompletionParams, CompletionTextEdit, Documentation, InsertTextFormat, Position, Range,
-    TextEdit,
+    CompletionParams, CompletionTextEdit, CompletionTriggerKind, Documentation, InsertTextFormat,
+    MarkupContent, MarkupKind, Position, Range, TextEdit, Uri,
 };
 use regex::Regex;
 
-use crate::document_store::DOCUMENT_STORE;
+use crate::document_store::{document::FileType, DocumentStore, DOCUMENT_STORE};
 use crate::documentation::get_documentation_for_token;
-use crate::parser::tokens::{ClassAttribute, DrupalPluginType, Token, TokenData};
+use crate::integrations::drush::{get_drush_data, DrushData};
+use crate::parser::php::PhpParser;
+use crate::parser::tokens::{
+    get_theme_name_from_template_file_name, get_theme_template_file_name, ClassAttribute,
+    DrupalPluginReference, DrupalPluginType, PhpClass, PhpClassName, PhpMethod, PhpVisibility,
+    Token, TokenData,
+};
 use crate::server::handle_request::get_response_error;
+use crate::server::{client_supports_completion_markdown, client_supports_snippets};
+use crate::settings::get_settings;
+use crate::utils::{
+    find_info_file, get_extension_name, get_psr4_namespace, markdown_to_plain_text, uri_to_path,
+    uri_to_url,
+};
+
+const KERNEL_EVENTS_CLASS: &str = "Symfony\\Component\\HttpKernel\\KernelEvents";
+
+// Symfony's kernel events, which aren't indexed since they live in the vendor directory.
+const KERNEL_EVENTS: &[(&str, &str)] = &[
+    ("REQUEST", "kernel.request"),
+    ("EXCEPTION", "kernel.exception"),
+    ("CONTROLLER", "kernel.controller"),
+    ("CONTROLLER_ARGUMENTS", "kernel.controller_arguments"),
+    ("VIEW", "kernel.view"),
+    ("RESPONSE", "kernel.response"),
+    ("FINISH_REQUEST", "kernel.finish_request"),
+    ("TERMINATE", "kernel.terminate"),
+];
+
+// Common cache contexts of core, including ones with a parameter which can't be derived from the
+// `cache_context.*` services.
+const CACHE_CONTEXTS: &[&str] = &[
+    "cookies",
+    "headers",
+    "ip",
+    "languages",
+    "languages:language_content",
+    "languages:language_interface",
+    "languages:language_url",
+    "request_format",
+    "route",
+    "route.menu_active_trails",
+    "route.name",
+    "session",
+    "theme",
+    "timezone",
+    "url",
+    "url.path",
+    "url.path.is_front",
+    "url.path.parent",
+    "url.query_args",
+    "url.query_args:_wrapper_format",
+    "url.site",
+    "user",
+    "user.node_grants:view",
+    "user.permissions",
+    "user.roles",
+    "user.roles:authenticated",
+];
+
+// Trigger characters that are also typed outside of the contexts we complete, f.e `.` to
+// concatenate PHP strings, so they only trigger completion of specific items like route names.
+const CONTEXT_TRIGGER_CHARACTERS: &[&str] = &["'", "\"", ":", "."];
+
+// The data types defined by core in `core.data_types.schema.yml`, for when core isn't indexed.
+const CORE_SCHEMA_TYPES: &[&str] = &[
+    "undefined",
+    "ignore",
+    "boolean",
+    "email",
+    "integer",
+    "float",
+    "string",
+    "uri",
+    "mapping",
+    "sequence",
+    "label",
+    "required_label",
+    "plural_label",
+    "text",
+    "uuid",
+    "path",
+    "machine_name",
+    "color_hex",
+    "langcode",
+    "timestamp",
+    "config_object",
+    "config_entity",
+    "config_dependencies",
+];
+
+// Hooks with a `HOOK` placeholder for the name of a theme hook.
+const THEME_HOOK_HOOKS: &[&str] = &[
+    "hook_preprocess_HOOK",
+    "hook_theme_suggestions_HOOK",
+    "hook_theme_suggestions_HOOK_alter",
+];
+
+// Matches the placeholders in hook names, f.e `FORM_ID` in `hook_form_FORM_ID_alter`.
+static HOOK_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"([A-Z][A-Z_]+[A-Z])").unwrap());
+
+// Matches a method that is being typed on `$this`, the class itself or a service, f.e `$this->lo`,
+// `static::cre` or `\Drupal::service('entity_type.manager')->get`.
+static PHP_METHOD_CALL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\$this->|(?<scope>self|static)::|\\?Drupal::service\(\s*['"](?<service>[\w.\-]+)['"]\s*\)->)\w*$"#)
+        .unwrap()
+});
+
+// Matches a class name that is being typed where a type is expected, f.e `new Ur`,
+// `function foo(Entity`, `): Res` or `catch (Exc`.
+static PHP_CLASS_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:\b(?:new|instanceof|extends|implements|public|protected|private|readonly|static)\s+(?:[\w\\]+\s*[,|]\s*)*|\bcatch\s*\(\s*(?:[\w\\]+\s*\|\s*)*|\b(?:function|fn)\s*&?\w*\s*\((?:[^()]*,)?\s*|\)\s*:\s*)\??(?<name>[A-Z]\w*)$"#)
+        .unwrap()
+});
+
+// Matches a name that is being typed, f.e `Node` in `[Node`, which is a class before `::class`.
+static PHP_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w\\$>:])(?<name>[A-Z]\w*)$").unwrap());
+
+// Matches a route that is being typed in links files, f.e `route_name: entity.node.ca`.
+static YAML_ROUTE_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*(?:route_name|base_route):\s*['"]?(?<value>[\w.\-]*)$"#).unwrap()
+});
+
+// Matches the parent of a menu link that is being typed, f.e `parent: system.admin_con`.
+static YAML_MENU_LINK_PARENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*parent:\s*['"]?(?<value>[\w.\-:]*)$"#).unwrap());
+
+// Matches the controller of a route, f.e `_controller: '\Drupal\node\Controller\NodeController::a`.
+static YAML_CONTROLLER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^\s*_controller:\s*['"]?(?<value>\\?[\w\\]*(?:::\w*)?)$"#).unwrap()
+});
+
+// Matches the permission requirement of a route, f.e `_permission: 'access content+adm'`.
+static YAML_PERMISSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^(?<key>\s*_permission:\s*)(?<value>.*?)\s*$"#).unwrap());
+
+// Matches the parent of a service, f.e `parent: default_plugin_ma`.
+static YAML_SERVICE_PARENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*parent:\s*['"]?(?<value>[\w.]*)$"#).unwrap());
+
+// Matches the type of a config schema that is being typed, f.e `type: config_obj`.
+static YAML_SCHEMA_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*type:\s*['"]?(?<value>[\w.]*)$"#).unwrap());
+
+// Matches the role requirement of a route, f.e `_role: 'administrator+edi`.
+static YAML_ROLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"^\s*_role:\s*['"]?(?:\w+[+,])*(?<value>\w*)$"#).unwrap());
+
+// Matches a service argument that is being typed, f.e `- '@entity_type.ma` or `['@`.
+static YAML_SERVICE_ARGUMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:^\s*-\s*|[\[,]\s*)['"]?@\??(?<value>[\w.]*)$"#).unwrap());
 
 pub fn handle_text_document_completion(request: Request) -> Option<Response> {
     let params = match serde_json::from_value::<CompletionParams>(request.params) {
@@ -34,7 +191,11 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
     let uri = &params.text_document_position.text_document.uri.to_string();
     let mut token: Option<Token> = None;
     let mut current_line: String = String::default();
+    let mut file_type = FileType::Unknown;
+    let mut content = String::default();
     if let Some(document) = DOCUMENT_STORE.lock().unwrap().get_document(uri) {
+        file_type = document.file_type;
+        content = document.content.clone();
         current_line = document
             .content
             .lines()
@@ -44,30 +205,167 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
         token = document.get_token_under_cursor(position);
     }
 
-    let (file_name, extension) = uri.split('/').last()?.split_once('.')?;
-
-    let mut completion_items: Vec<CompletionItem> = get_global_snippets();
-    if let Some(token) = token {
-        if let TokenData::DrupalRouteReference(_) = token.data {
-            let re = Regex::new(r"(?<method>.*fromRoute\(')(?<name>[^']*)'(?<params>, \[.*\])?");
-            let mut method_len = 0;
-            let mut name_len = 0;
-            let mut params_len = 0;
-            if let Some(captures) = re.unwrap().captures(current_line.as_str()) {
-                method_len = match captures.name("method") {
-                    Some(str) => str.len() as u32,
-                    None => 0,
-                };
-                // TODO: name_len is sometimes incorrect if typing too fast.
-                name_len = match captures.name("name") {
-                    Some(str) => str.len() as u32,
-                    None => 0,
-                };
-                params_len = match captures.name("params") {
-                    Some(str) => str.len() as u32,
-                    None => 0,
-                };
-            }
+    let trigger_character = params
+        .context
+        .as_ref()
+        .filter(|context| context.trigger_kind == CompletionTriggerKind::TRIGGER_CHARACTER)
+        .and_then(|context| context.trigger_character.as_deref())
+        .filter(|character| CONTEXT_TRIGGER_CHARACTERS.contains(character));
+    let character = params.text_document_position.position.character as usize;
+    if trigger_character.is_some_and(|_| is_closing_quote(&current_line, character)) {
+        return Some(Response {
+            id: request.id,
+            result: None,
+            error: None,
+        });
+    }
+
+    let (file_name, extension) = uri.split('/').next_back()?.split_once('.')?;
+    let info_file = uri_to_url(params.text_document_position.text_document.uri.clone())
+        .and_then(|path| find_info_file(Path::new(&path)));
+    let module_name = info_file
+        .as_ref()
+        .and_then(|info_file| get_extension_name(info_file).map(|name| name.to_string()));
+
+    let mut completion_items: Vec<CompletionItem> = vec![];
+    // Whether the cursor is in a context that only expects specific items, f.e a route name.
+    let mut is_specific_context = false;
+    // Whether the methods of the object that a method is called on are completed.
+    let mut is_method_call = false;
+    if file_type == FileType::Php {
+        if let Some(item) = get_file_scaffold_item(
+            &params.text_document_position.text_document.uri,
+            &content,
+            &current_line,
+            params.text_document_position.position,
+        ) {
+            completion_items.push(item);
+        }
+
+        let parser = PhpParser::new(&content);
+        if parser
+            .get_method_name_at_position(params.text_document_position.position)
+            .is_some_and(|method_name| method_name == "getSubscribedEvents")
+        {
+            completion_items.extend(get_event_completion_items(
+                &parser,
+                &current_line,
+                params.text_document_position.position,
+            ));
+        } else if let Some(captures) = current_line
+            .get(..character)
+            .and_then(|before_cursor| PHP_METHOD_CALL.captures(before_cursor))
+        {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let method_items = match captures.name("service") {
+                Some(service_name) => match store
+                    .get_service_class(service_name.as_str())
+                    .and_then(|class_name| store.get_class_definition(class_name))
+                    .map(|(_, token)| &token.data)
+                {
+                    Some(TokenData::PhpClassDefinition(class)) => {
+                        get_method_completion_items(class, MethodCallContext::External, &store)
+                    }
+                    _ => vec![],
+                },
+                None => {
+                    let context = match captures.name("scope") {
+                        Some(_) => MethodCallContext::Static,
+                        None => MethodCallContext::Instance,
+                    };
+                    match parser
+                        .get_class_at_position(params.text_document_position.position)
+                        .map(|token| token.data)
+                    {
+                        Some(TokenData::PhpClassDefinition(class)) => {
+                            get_method_completion_items(&class, context, &store)
+                        }
+                        _ => vec![],
+                    }
+                }
+            };
+            // Other items are still offered when the class isn't known.
+            is_method_call = !method_items.is_empty();
+            is_specific_context = is_method_call;
+            completion_items.extend(method_items);
+        } else if let Some(name) = get_class_name_before_cursor(&current_line, character) {
+            is_specific_context = true;
+            completion_items.extend(get_class_completion_items(
+                &parser,
+                name,
+                &current_line,
+                params.text_document_position.position,
+            ));
+        }
+    }
+    let token_data = token.map(|token| token.data).or_else(|| match file_type {
+        FileType::Yaml => get_yaml_token_data(uri, &current_line, character),
+        _ => None,
+    });
+    // The keys of route definitions are completed from the routing schema, new services from the
+    // classes of the module and dependencies from the modules of the project, rather than the
+    // tokens under the cursor.
+    let snippet_items = match (trigger_character, uri.ends_with(".routing.yml")) {
+        (None, true) => routing::get_route_key_completion_items(
+            &content,
+            params.text_document_position.position,
+            file_name,
+        ),
+        (None, false) if uri.ends_with(".services.yml") => services::get_service_snippet_items(
+            &content,
+            params.text_document_position.position,
+            module_name.as_deref().unwrap_or(file_name),
+            || {
+                info_file
+                    .as_ref()
+                    .and_then(|info_file| info_file.parent())
+                    .map(|directory| get_module_class_names(&directory.join("src")))
+                    .unwrap_or_default()
+            },
+        ),
+        (_, false) if uri.ends_with(".info.yml") => {
+            info::get_dependency_range(&content, params.text_document_position.position).map(
+                |range| {
+                    info::get_dependency_completion_items(
+                        &DOCUMENT_STORE.lock().unwrap().get_extensions(),
+                        range,
+                        file_name,
+                    )
+                },
+            )
+        }
+        _ => None,
+    };
+    let is_string_reference = token_data.as_ref().is_some_and(is_string_reference);
+    is_specific_context |= snippet_items.is_some()
+        || is_string_reference
+        || matches!(
+            token_data,
+            Some(TokenData::PhpClassReference(_) | TokenData::PhpMethodReference(_))
+        );
+    if trigger_character.is_some() && !is_specific_context {
+        return Some(Response {
+            id: request.id,
+            result: None,
+            error: None,
+        });
+    }
+    if let Some(snippet_items) = snippet_items {
+        completion_items.extend(snippet_items);
+    } else if let Some(token_data) = token_data {
+        if let TokenData::DrupalRouteReference(_) = token_data {
+            let route_call_ranges = match file_type {
+                FileType::Php => PhpParser::new(&content).get_route_call_ranges(position),
+                _ => None,
+            };
+            let value_range = match file_type {
+                FileType::Yaml => get_value_range(
+                    &current_line,
+                    params.text_document_position.position,
+                    &[' ', '\'', '"'],
+                ),
+                _ => None,
+            };
 
             DOCUMENT_STORE
                 .lock()
@@ -84,21 +382,9 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
 
                             let mut text_edit = None;
                             let mut additional_text_edits = None;
-                            if method_len > 0 {
+                            if let Some((name_range, parameters_range)) = route_call_ranges {
                                 text_edit = Some(CompletionTextEdit::Edit(TextEdit {
-                                    range: Range {
-                                        start: Position {
-                                            line: params.text_document_position.position.line,
-                                            character: method_len,
-                                        },
-                                        end: Position {
-                                            line: params.text_document_position.position.line,
-                                            character: params
-                                                .text_document_position
-                                                .position
-                                                .character,
-                                        },
-                                    },
+                                    range: name_range,
                                     new_text: route.name.to_string(),
                                 }));
 
@@ -116,25 +402,22 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                                 }
 
                                 additional_text_edits = Some(vec![TextEdit {
-                                    range: Range {
-                                        start: Position {
-                                            line: params.text_document_position.position.line,
-                                            character: method_len + name_len + 1,
-                                        },
-                                        end: Position {
-                                            line: params.text_document_position.position.line,
-                                            character: method_len + name_len + 1 + params_len,
-                                        },
-                                    },
+                                    range: parameters_range,
                                     new_text: route_parameters_text,
                                 }]);
+                            } else if let Some(range) = value_range {
+                                text_edit = Some(CompletionTextEdit::Edit(TextEdit {
+                                    range,
+                                    new_text: route.name.to_string(),
+                                }));
                             }
                             completion_items.push(CompletionItem {
-                                label: route.name.clone(),
+                                label: route.name.to_string(),
                                 label_details: Some(CompletionItemLabelDetails {
                                     description: Some("Route".to_string()),
                                     detail: None,
                                 }),
+                                detail: Some(route.path.clone()),
                                 kind: Some(CompletionItemKind::REFERENCE),
                                 documentation,
                                 text_edit,
@@ -145,7 +428,20 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                         }
                     })
                 });
-        } else if let TokenData::DrupalServiceReference(_) = token.data {
+        } else if let TokenData::DrupalServiceReference(_) = token_data {
+            let value_range = match file_type {
+                FileType::Yaml => get_value_range(
+                    &current_line,
+                    params.text_document_position.position,
+                    &[' ', '\'', '"', '@', '?'],
+                ),
+                _ => None,
+            };
+            // Only abstract services can be used as the parent of a service.
+            let is_parent = file_type == FileType::Yaml
+                && current_line
+                    .get(..character)
+                    .is_some_and(|before_cursor| YAML_SERVICE_PARENT.is_match(before_cursor));
             DOCUMENT_STORE
                 .lock()
                 .unwrap()
@@ -154,12 +450,21 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                 .for_each(|document| {
                     document.tokens.iter().for_each(|token| {
                         if let TokenData::DrupalServiceDefinition(service) = &token.data {
+                            if is_parent && !service.is_abstract {
+                                return;
+                            }
                             let mut documentation = None;
                             if let Some(documentation_string) = get_documentation_for_token(token) {
                                 documentation = Some(Documentation::String(documentation_string));
                             }
                             completion_items.push(CompletionItem {
-                                label: service.name.clone(),
+                                text_edit: value_range.map(|range| {
+                                    CompletionTextEdit::Edit(TextEdit {
+                                        range,
+                                        new_text: service.name.to_string(),
+                                    })
+                                }),
+                                label: service.name.to_string(),
                                 label_details: Some(CompletionItemLabelDetails {
                                     description: Some("Service".to_string()),
                                     detail: None,
@@ -172,27 +477,317 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                         }
                     })
                 });
-        } else if let TokenData::PhpMethodReference(method) = token.data {
+        } else if let TokenData::PhpMethodReference(method) = &token_data {
+            // Controllers, callbacks and services, f.e in multi-line chains after
+            // `\Drupal::service()`, are called from outside of the class.
             let store = DOCUMENT_STORE.lock().unwrap();
-            // TODO: Don't suggest private/protected methods.
-            if let Some((_, class_token)) = store.get_class_definition(&method.get_class(&store)?) {
+            if let Some((_, class_token)) = method
+                .get_class(&store)
+                .and_then(|class_name| store.get_class_definition(&class_name))
+                .filter(|_| !is_method_call)
+            {
                 if let TokenData::PhpClassDefinition(class) = &class_token.data {
-                    class.methods.keys().for_each(|method_name| {
+                    completion_items.extend(get_method_completion_items(
+                        class,
+                        MethodCallContext::External,
+                        &store,
+                    ));
+                }
+            }
+        } else if let (TokenData::PhpClassReference(class_name), FileType::Yaml) =
+            (&token_data, file_type)
+        {
+            completion_items.extend(get_yaml_class_completion_items(
+                class_name,
+                &current_line,
+                params.text_document_position.position,
+            ));
+        } else if let TokenData::DrupalPermissionReference(_) = token_data {
+            DOCUMENT_STORE
+                .lock()
+                .unwrap()
+                .get_documents()
+                .values()
+                .for_each(|document| {
+                    document.tokens.iter().for_each(|token| {
+                        if let TokenData::DrupalPermissionDefinition(permission) = &token.data {
+                            let mut documentation = None;
+                            if let Some(documentation_string) = get_documentation_for_token(token) {
+                                documentation = Some(Documentation::String(documentation_string));
+                            }
+                            // Permissions contain spaces, so the whole value is replaced instead of
+                            // the word before the cursor.
+                            let text_edit = match file_type {
+                                FileType::Yaml => get_yaml_permission_edit(
+                                    &current_line,
+                                    params.text_document_position.position,
+                                    &permission.name,
+                                ),
+                                _ => get_string_edit(
+                                    &current_line,
+                                    params.text_document_position.position,
+                                    &permission.name,
+                                ),
+                            };
+                            completion_items.push(CompletionItem {
+                                label: permission.name.to_string(),
+                                label_details: Some(CompletionItemLabelDetails {
+                                    description: Some("Permission".to_string()),
+                                    detail: None,
+                                }),
+                                kind: Some(CompletionItemKind::REFERENCE),
+                                filter_text: text_edit.as_ref().map(|edit| edit.new_text.clone()),
+                                text_edit: text_edit.map(CompletionTextEdit::Edit),
+                                documentation,
+                                deprecated: Some(false),
+                                ..CompletionItem::default()
+                            });
+                        }
+                    })
+                });
+        } else if let (TokenData::DrupalConfigKeyReference(reference), FileType::Php) =
+            (&token_data, file_type)
+        {
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &['\'', '"'],
+            );
+            if let Some(schema) = DOCUMENT_STORE
+                .lock()
+                .unwrap()
+                .get_config_schema(&reference.config_name)
+            {
+                completion_items.extend(schema.keys.iter().map(|key| CompletionItem {
+                    label: key.key.clone(),
+                    label_details: Some(CompletionItemLabelDetails {
+                        description: Some("Config key".to_string()),
+                        detail: None,
+                    }),
+                    detail: key.type_name.clone(),
+                    documentation: key.label.clone().map(Documentation::String),
+                    kind: Some(CompletionItemKind::FIELD),
+                    text_edit: range.map(|range| {
+                        CompletionTextEdit::Edit(TextEdit {
+                            range,
+                            new_text: key.key.clone(),
+                        })
+                    }),
+                    deprecated: Some(false),
+                    ..CompletionItem::default()
+                }));
+            }
+        } else if let TokenData::DrupalEntityFieldReference(reference) = token_data {
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &['\'', '"'],
+            );
+            let store = DOCUMENT_STORE.lock().unwrap();
+            if let Some(entity_type) = store.get_entity_type(&reference.entity_type) {
+                let base_fields = entity_type
+                    .base_fields
+                    .iter()
+                    .map(|field| (field, "Base field"));
+                let field_storages = store
+                    .get_field_storages(&entity_type.id)
+                    .into_iter()
+                    .map(|field_storage| (&field_storage.field, "Field"));
+                completion_items.extend(base_fields.chain(field_storages).map(
+                    |(field, description)| CompletionItem {
+                        label: field.name.clone(),
+                        label_details: Some(CompletionItemLabelDetails {
+                            description: Some(description.to_string()),
+                            detail: None,
+                        }),
+                        detail: field.field_type.clone(),
+                        kind: Some(CompletionItemKind::FIELD),
+                        text_edit: range.map(|range| {
+                            CompletionTextEdit::Edit(TextEdit {
+                                range,
+                                new_text: field.name.clone(),
+                            })
+                        }),
+                        deprecated: Some(false),
+                        ..CompletionItem::default()
+                    },
+                ));
+            } else if let Some(data) = get_drush_data() {
+                // Fall back to all fields on the site, when the entity type is unknown.
+                completion_items.extend(get_drush_completion_items(
+                    &TokenData::DrupalFieldReference(reference.field_name),
+                    data,
+                ));
+            }
+        } else if let TokenData::DrupalEntityBundleReference(reference) = token_data {
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &['\'', '"'],
+            );
+            completion_items.extend(get_entity_bundle_completion_items(
+                &DOCUMENT_STORE.lock().unwrap(),
+                &reference.entity_type,
+                range,
+            ));
+        } else if let TokenData::DrupalThemeReference(_) = token_data {
+            let mut theme_names = HashSet::new();
+            DOCUMENT_STORE
+                .lock()
+                .unwrap()
+                .get_documents()
+                .values()
+                .for_each(|document| {
+                    document.tokens.iter().for_each(|token| {
+                        if let TokenData::DrupalThemeDefinition(theme) = &token.data {
+                            if !theme_names.insert(theme.name.clone()) {
+                                return;
+                            }
+                            let mut documentation = None;
+                            if let Some(documentation_string) = get_documentation_for_token(token) {
+                                documentation = Some(Documentation::String(documentation_string));
+                            }
+                            completion_items.push(CompletionItem {
+                                label: theme.name.clone(),
+                                label_details: Some(CompletionItemLabelDetails {
+                                    description: Some("Theme hook".to_string()),
+                                    detail: None,
+                                }),
+                                detail: Some(theme.get_template_file_name()),
+                                kind: Some(CompletionItemKind::REFERENCE),
+                                documentation,
+                                deprecated: Some(false),
+                                ..CompletionItem::default()
+                            });
+                        }
+                    })
+                });
+        } else if let TokenData::DrupalConfigSchemaReference(_) = token_data {
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &[' ', '\'', '"'],
+            );
+            completion_items.extend(get_schema_type_completion_items(
+                &DOCUMENT_STORE.lock().unwrap(),
+                range,
+            ));
+        } else if let TokenData::DrupalRoleReference(_) = token_data {
+            // Only replace the role under the cursor, since roles can be joined by `+` or `,`.
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &[' ', '\'', '"', '+', ','],
+            );
+            let store = DOCUMENT_STORE.lock().unwrap();
+            // Roles in config files are merged with the roles of the site from drush.
+            let mut roles: HashMap<&str, &str> = get_drush_data()
+                .map(|data| {
+                    data.roles
+                        .iter()
+                        .map(|(id, label)| (id.as_str(), label.as_str()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            store
+                .get_documents()
+                .values()
+                .flat_map(|document| document.tokens.iter())
+                .for_each(|token| {
+                    if let TokenData::DrupalRoleDefinition(role) = &token.data {
+                        roles.insert(&role.id, &role.label);
+                    }
+                });
+            completion_items.extend(roles.into_iter().map(|(id, label)| CompletionItem {
+                label: id.to_string(),
+                label_details: Some(CompletionItemLabelDetails {
+                    description: Some("Role".to_string()),
+                    detail: None,
+                }),
+                detail: Some(label.to_string()),
+                kind: Some(CompletionItemKind::REFERENCE),
+                text_edit: range.map(|range| {
+                    CompletionTextEdit::Edit(TextEdit {
+                        range,
+                        new_text: id.to_string(),
+                    })
+                }),
+                deprecated: Some(false),
+                ..CompletionItem::default()
+            }));
+        } else if let TokenData::DrupalMenuLinkReference(_) = token_data {
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &[' ', '\'', '"'],
+            );
+            let store = DOCUMENT_STORE.lock().unwrap();
+            store
+                .get_documents()
+                .values()
+                .flat_map(|document| document.tokens.iter())
+                .for_each(|token| {
+                    if let TokenData::DrupalMenuLinkDefinition(menu_link) = &token.data {
                         completion_items.push(CompletionItem {
-                            label: method_name.clone(),
+                            label: menu_link.id.clone(),
                             label_details: Some(CompletionItemLabelDetails {
-                                description: Some("Method".to_string()),
+                                description: Some("Menu link".to_string()),
                                 detail: None,
                             }),
+                            detail: menu_link.title.clone(),
                             kind: Some(CompletionItemKind::REFERENCE),
-                            documentation: None,
+                            documentation: get_documentation_for_token(token)
+                                .map(Documentation::String),
+                            text_edit: range.map(|range| {
+                                CompletionTextEdit::Edit(TextEdit {
+                                    range,
+                                    new_text: menu_link.id.clone(),
+                                })
+                            }),
                             deprecated: Some(false),
                             ..CompletionItem::default()
                         });
-                    });
+                    }
+                });
+        } else if let TokenData::DrupalCacheContextReference(_) = token_data {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            let mut contexts: HashMap<String, Option<&Token>> = CACHE_CONTEXTS
+                .iter()
+                .map(|context| (context.to_string(), None))
+                .collect();
+            store
+                .get_documents()
+                .values()
+                .flat_map(|document| document.tokens.iter())
+                .for_each(|token| {
+                    if let TokenData::DrupalServiceDefinition(service) = &token.data {
+                        if let Some(context) = service.name.strip_prefix("cache_context.") {
+                            contexts.insert(context.to_string(), Some(token));
+                        }
+                    }
+                });
+            completion_items.extend(contexts.into_iter().map(|(context, token)| {
+                CompletionItem {
+                    label: context,
+                    label_details: Some(CompletionItemLabelDetails {
+                        description: Some("Cache context".to_string()),
+                        detail: None,
+                    }),
+                    kind: Some(CompletionItemKind::REFERENCE),
+                    documentation: token
+                        .and_then(get_documentation_for_token)
+                        .map(Documentation::String),
+                    deprecated: Some(false),
+                    ..CompletionItem::default()
                 }
-            }
-        } else if let TokenData::DrupalPermissionReference(_) = token.data {
+            }));
+        } else if let TokenData::DrupalLibraryReference(_) = token_data {
+            // Replace the whole name, since the `/` is usually not part of a word.
+            let range = get_value_range(
+                &current_line,
+                params.text_document_position.position,
+                &['\'', '"'],
+            );
             DOCUMENT_STORE
                 .lock()
                 .unwrap()
@@ -200,20 +795,24 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                 .values()
                 .for_each(|document| {
                     document.tokens.iter().for_each(|token| {
-                        if let TokenData::DrupalPermissionDefinition(permission) = &token.data {
+                        if let TokenData::DrupalLibraryDefinition(library) = &token.data {
                             let mut documentation = None;
                             if let Some(documentation_string) = get_documentation_for_token(token) {
                                 documentation = Some(Documentation::String(documentation_string));
                             }
-                            // TODO: Figure out how to correctly deal with whitespaces in the
-                            // label.
                             completion_items.push(CompletionItem {
-                                label: permission.name.clone(),
+                                label: library.name.clone(),
                                 label_details: Some(CompletionItemLabelDetails {
-                                    description: Some("Permission".to_string()),
+                                    description: Some("Library".to_string()),
                                     detail: None,
                                 }),
                                 kind: Some(CompletionItemKind::REFERENCE),
+                                text_edit: range.map(|range| {
+                                    CompletionTextEdit::Edit(TextEdit {
+                                        range,
+                                        new_text: library.name.clone(),
+                                    })
+                                }),
                                 documentation,
                                 deprecated: Some(false),
                                 ..CompletionItem::default()
@@ -221,7 +820,37 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                         }
                     })
                 });
-        } else if let TokenData::DrupalPluginReference(plugin_reference) = token.data {
+        } else if let TokenData::DrupalPluginReference(DrupalPluginReference {
+            plugin_type: DrupalPluginType::EntityType,
+            ..
+        }) = &token_data
+        {
+            let store = DOCUMENT_STORE.lock().unwrap();
+            completion_items.extend(store.get_entity_types().into_iter().map(
+                |(_, token, entity_type)| {
+                    // Entity bundles are only known at runtime.
+                    let detail = get_drush_data()
+                        .and_then(|data| data.bundles.get(&entity_type.id))
+                        .map(|bundles| format!("Bundles: {}", bundles.join(", ")));
+                    CompletionItem {
+                        label: entity_type.id.clone(),
+                        label_details: Some(CompletionItemLabelDetails {
+                            description: Some(entity_type.group.to_string()),
+                            detail: entity_type
+                                .label
+                                .as_ref()
+                                .map(|label| format!(" {}", label)),
+                        }),
+                        detail,
+                        kind: Some(CompletionItemKind::REFERENCE),
+                        documentation: get_documentation_for_token(token)
+                            .map(Documentation::String),
+                        deprecated: Some(false),
+                        ..CompletionItem::default()
+                    }
+                },
+            ));
+        } else if let TokenData::DrupalPluginReference(plugin_reference) = &token_data {
             DOCUMENT_STORE
                 .lock()
                 .unwrap()
@@ -231,7 +860,11 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                     document.tokens.iter().for_each(|token| {
                         if let TokenData::PhpClassDefinition(class) = &token.data {
                             if let Some(ClassAttribute::Plugin(plugin)) = &class.attribute {
-                                if plugin_reference.plugin_type == plugin.plugin_type {
+                                if plugin_reference.plugin_type.includes(&plugin.plugin_type) {
+                                    // Entity bundles are only known at runtime.
+                                    let detail = get_drush_data()
+                                        .and_then(|data| data.bundles.get(&plugin.plugin_id))
+                                        .map(|bundles| format!("Bundles: {}", bundles.join(", ")));
                                     let mut documentation = None;
                                     if let Some(documentation_string) =
                                         get_documentation_for_token(token)
@@ -245,6 +878,7 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                                             description: Some(plugin.plugin_type.to_string()),
                                             detail: None,
                                         }),
+                                        detail,
                                         kind: Some(CompletionItemKind::REFERENCE),
                                         documentation,
                                         deprecated: Some(false),
@@ -255,49 +889,146 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
                         }
                     })
                 });
+        } else if let Some(data) = get_drush_data() {
+            completion_items.extend(get_drush_completion_items(&token_data, data));
         }
-    } else if extension == "module" || extension == "theme" {
-        DOCUMENT_STORE
-            .lock()
-            .unwrap()
-            .get_documents()
-            .values()
-            .for_each(|document| {
-                document.tokens.iter().for_each(|token| {
-                    if let TokenData::DrupalHookDefinition(hook) = &token.data {
-                        let mut documentation = None;
-                        if let Some(documentation_string) = get_documentation_for_token(token) {
-                            documentation = Some(Documentation::String(documentation_string));
+    } else if matches!(
+        extension.rsplit('.').next(),
+        Some("module" | "theme" | "install" | "inc")
+    ) {
+        // Includes like `mymodule.views.inc` can be in subfolders, so the name of the module is
+        // taken from its info file.
+        let module_name = module_name.as_deref().unwrap_or(file_name);
+        let store = DOCUMENT_STORE.lock().unwrap();
+        let update_number = store
+            .get_document(uri)
+            .map(|document| PhpParser::new(&document.content).get_next_update_number(module_name));
+        // Themes mostly implement hooks for specific theme hooks, f.e `hook_preprocess_node`.
+        let theme_hooks = match extension {
+            "theme" => get_theme_hook_names(&store),
+            _ => vec![],
+        };
+        store.get_documents().values().for_each(|document| {
+            document.tokens.iter().for_each(|token| {
+                if let TokenData::DrupalHookDefinition(hook) = &token.data {
+                    // Update hooks can only be implemented in .install files.
+                    let function_name = match (hook.name.as_str(), update_number) {
+                        ("hook_update_N", Some(update_number)) if extension == "install" => {
+                            format!("update_{}", update_number)
                         }
-                        // Regex to replace placeholders in hook names.
-                        let re = Regex::new(r"([A-Z][A-Z_]+[A-Z])").unwrap();
+                        ("hook_update_N", _) => return,
+                        (name, _) => HOOK_PLACEHOLDER
+                            .replace_all(name.replace("hook_", "").as_str(), r"$${$1}")
+                            .to_string(),
+                    };
+                    let documentation = get_documentation_for_token(token);
+                    // The label, function name and docblock of the implementations to offer.
+                    let mut implementations = vec![(
+                        hook.name.clone(),
+                        function_name,
+                        format!("Implements {}().", hook.name),
+                    )];
+                    if THEME_HOOK_HOOKS.contains(&hook.name.as_str()) {
+                        implementations.extend(theme_hooks.iter().map(|theme_hook| {
+                            (
+                                hook.name.replace("HOOK", theme_hook),
+                                hook.name.replace("hook_", "").replace("HOOK", theme_hook),
+                                format!(
+                                    "Implements {}() for {}.",
+                                    hook.name,
+                                    get_theme_template_file_name(theme_hook, None)
+                                ),
+                            )
+                        }));
+                    }
+                    for (label, function_name, summary) in implementations {
                         completion_items.push(CompletionItem {
-                            label: hook.name.clone(),
+                            label,
                             label_details: Some(CompletionItemLabelDetails {
                                 description: Some("hook".to_string()),
                                 detail: None,
                             }),
                             kind: Some(CompletionItemKind::SNIPPET),
                             insert_text_format: Some(InsertTextFormat::SNIPPET),
-                            insert_text: Some(
-                                format!(
-                                    "/**\n * Implements {}().\n */\nfunction {}_{}({}) {{\n  $0\n}}",
-                                    hook.name,
-                                    file_name,
-                                    re.replace_all(hook.name.replace("hook_", "").as_str(), r"$${$1}"),
-                                    hook.parameters.clone().unwrap_or("".to_string()).replace("$", "\\$")
-                                )
-                                .to_string(),
-                            ),
-                            documentation,
+                            insert_text: Some(format!(
+                                "/**\n * {}\n */\nfunction {}_{}({}) {{\n  $0\n}}",
+                                summary,
+                                module_name,
+                                function_name,
+                                hook.parameters
+                                    .clone()
+                                    .unwrap_or("".to_string())
+                                    .replace("$", "\\$")
+                            )),
+                            documentation: documentation.clone().map(Documentation::String),
                             deprecated: Some(false),
                             ..CompletionItem::default()
                         });
                     }
-                })
+                }
+            })
+        });
+    } else if extension.ends_with("twig")
+        && is_inside_twig_expression(
+            &current_line,
+            params.text_document_position.position.character as usize,
+        )
+    {
+        let store = DOCUMENT_STORE.lock().unwrap();
+        let theme_name = store.get_theme_name_for_template(&format!("{}.{}", file_name, extension));
+        store
+            .get_theme_variables(&theme_name)
+            .into_iter()
+            .for_each(|(variable, origin)| {
+                completion_items.push(CompletionItem {
+                    label: variable.clone(),
+                    label_details: Some(CompletionItemLabelDetails {
+                        description: Some("Variable".to_string()),
+                        detail: None,
+                    }),
+                    detail: Some(origin.to_string()),
+                    kind: Some(CompletionItemKind::VARIABLE),
+                    deprecated: Some(false),
+                    ..CompletionItem::default()
+                });
             });
     }
 
+    // The global snippets are PHP snippets.
+    if file_type == FileType::Php && !is_specific_context {
+        completion_items.extend(get_global_snippets());
+    }
+
+    // Replace the whole string that is being typed, since clients split words on f.e dots.
+    let value_range = match (is_string_reference, file_type) {
+        (false, _) => None,
+        (true, FileType::Yaml) => get_value_range(
+            &current_line,
+            params.text_document_position.position,
+            &[' ', '\'', '"'],
+        ),
+        (true, _) => get_value_range(
+            &current_line,
+            params.text_document_position.position,
+            &['\'', '"'],
+        ),
+    };
+    let snippet_support = client_supports_snippets();
+    let markdown_support = client_supports_completion_markdown();
+    completion_items.iter_mut().for_each(|item| {
+        adapt_to_client(item, snippet_support, markdown_support);
+        if let (None, None, Some(range)) = (&item.text_edit, &item.insert_text, value_range) {
+            item.text_edit = Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: item.label.clone(),
+            }));
+        }
+        if item.filter_text.is_none() {
+            item.filter_text = Some(item.label.clone());
+        }
+        item.sort_text = Some(get_sort_text(item, module_name.as_deref()));
+    });
+
     if completion_items.is_empty() {
         return Some(Response {
             id: request.id,
@@ -306,8 +1037,20 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
         });
     }
 
+    // Large lists, f.e all routes of a project, are narrowed down to the items matching the typed
+    // text, and marked as incomplete so that the client queries again as the user types.
+    let max_items = get_settings().completion.max_items;
+    let is_incomplete = completion_items.len() > max_items;
+    if is_incomplete {
+        limit_completion_items(
+            &mut completion_items,
+            get_typed_text(&current_line, character),
+            max_items,
+        );
+    }
+
     let completion_result = CompletionList {
-        is_incomplete: false,
+        is_incomplete,
         items: completion_items,
     };
 
@@ -325,53 +1068,513 @@ pub fn handle_text_document_completion(request: Request) -> Option<Response> {
     }
 }
 
-fn get_global_snippets() -> Vec<CompletionItem> {
-    let mut snippets: HashMap<String, String> = HashMap::new();
+/// Get the names of the theme hooks, from `hook_theme()` implementations and the templates of the
+/// project, f.e `field__field_name` for `field--field-name.html.twig`.
+fn get_theme_hook_names(store: &DocumentStore) -> Vec<String> {
+    let mut theme_hooks: Vec<String> = store
+        .get_documents()
+        .values()
+        .flat_map(|document| {
+            let template_theme_hook = document
+                .uri
+                .rsplit('/')
+                .next()
+                .filter(|file_name| file_name.ends_with(".html.twig"))
+                .map(get_theme_name_from_template_file_name);
+            document
+                .tokens
+                .iter()
+                .filter_map(|token| match &token.data {
+                    TokenData::DrupalThemeDefinition(theme) => Some(theme.name.clone()),
+                    _ => None,
+                })
+                .chain(template_theme_hook)
+        })
+        .collect::<HashSet<String>>()
+        .into_iter()
+        .collect();
+    theme_hooks.sort();
+    theme_hooks
+}
 
-    let mut add_snippet = |key: &str, value: &str| {
-        snippets.insert(key.into(), value.into());
+/// Get the names of the classes in a directory, f.e the `src` directory of a module.
+fn get_module_class_names(directory: &Path) -> Vec<String> {
+    let store = DOCUMENT_STORE.lock().unwrap();
+    let mut class_names: Vec<String> = store
+        .get_documents()
+        .values()
+        .filter(|document| {
+            uri_to_path(&document.uri).is_some_and(|path| path.starts_with(directory))
+        })
+        .flat_map(|document| document.tokens.iter())
+        .filter_map(|token| match &token.data {
+            TokenData::PhpClassDefinition(class) => Some(class.name.to_string()),
+            _ => None,
+        })
+        .collect();
+    class_names.sort();
+    class_names
+}
+
+/// Where a method is called from, which determines the methods that can be called.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum MethodCallContext {
+    /// On an object or as a callable from outside of the class, f.e a service or a controller.
+    External,
+    /// On `$this` inside the class.
+    Instance,
+    /// On `self` or `static` inside the class.
+    Static,
+}
+
+/// Get completion items for the methods of a class that can be called in the context, f.e only
+/// the public methods of a service, or also the protected and private methods on `$this`.
+///
+/// The methods of the traits and parents of the class are included, except for private methods
+/// of parents.
+fn get_method_completion_items(
+    class: &PhpClass,
+    context: MethodCallContext,
+    store: &DocumentStore,
+) -> Vec<CompletionItem> {
+    let mut methods: HashMap<&str, &PhpMethod> = HashMap::new();
+    let mut visited: Vec<&PhpClassName> = vec![];
+    let mut classes = VecDeque::from([(class, false)]);
+    while let Some((class, is_parent)) = classes.pop_front() {
+        // Guard against classes that extend themselves through their parents.
+        if visited.contains(&&class.name) {
+            continue;
+        }
+        visited.push(&class.name);
+        for method in class
+            .methods
+            .values()
+            .filter_map(|token| match &token.data {
+                TokenData::PhpMethodDefinition(method) => Some(method),
+                _ => None,
+            })
+        {
+            if !is_parent || method.visibility != PhpVisibility::Private {
+                // Methods of the class override the ones of its traits and parents.
+                methods.entry(method.name.as_str()).or_insert(method);
+            }
+        }
+
+        let get_class = |class_name: &PhpClassName| match store.get_class_definition(class_name) {
+            Some((_, token)) => match &token.data {
+                TokenData::PhpClassDefinition(class) => Some(class),
+                _ => None,
+            },
+            None => None,
+        };
+        classes.extend(
+            class
+                .traits
+                .iter()
+                .filter_map(get_class)
+                .map(|class| (class, is_parent)),
+        );
+        classes.extend(
+            class
+                .parent
+                .as_ref()
+                .and_then(get_class)
+                .map(|class| (class, true)),
+        );
+    }
+
+    methods
+        .into_values()
+        .filter(|method| match context {
+            MethodCallContext::External => method.visibility == PhpVisibility::Public,
+            MethodCallContext::Instance => !method.is_static,
+            MethodCallContext::Static => method.is_static,
+        })
+        .map(|method| CompletionItem {
+            label: method.name.clone(),
+            label_details: Some(CompletionItemLabelDetails {
+                description: Some(match method.is_static {
+                    true => format!("{} static method", method.visibility),
+                    false => format!("{} method", method.visibility),
+                }),
+                detail: None,
+            }),
+            detail: method.signature.clone(),
+            documentation: method.summary.clone().map(Documentation::String),
+            kind: Some(CompletionItemKind::METHOD),
+            deprecated: Some(false),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// Whether the token data is a reference to a name in a string, f.e a route or service name.
+fn is_string_reference(token_data: &TokenData) -> bool {
+    matches!(
+        token_data,
+        TokenData::DrupalRouteReference(_)
+            | TokenData::DrupalServiceReference(_)
+            | TokenData::DrupalPermissionReference(_)
+            | TokenData::DrupalPluginReference(_)
+            | TokenData::DrupalLibraryReference(_)
+            | TokenData::DrupalCacheContextReference(_)
+            | TokenData::DrupalThemeReference(_)
+            | TokenData::DrupalConfigReference(_)
+            | TokenData::DrupalConfigKeyReference(_)
+            | TokenData::DrupalConfigSchemaReference(_)
+            | TokenData::DrupalModuleReference(_)
+            | TokenData::DrupalRoleReference(_)
+            | TokenData::DrupalMenuLinkReference(_)
+            | TokenData::DrupalFieldReference(_)
+            | TokenData::DrupalEntityFieldReference(_)
+            | TokenData::DrupalEntityBundleReference(_)
+    )
+}
+
+/// Get the sort text of a completion item, ranking items from the current module first and
+/// snippets last.
+fn get_sort_text(item: &CompletionItem, module_name: Option<&str>) -> String {
+    let is_from_module = module_name.is_some_and(|module_name| {
+        item.label == module_name
+            || item
+                .label
+                .strip_prefix(module_name)
+                .is_some_and(|rest| rest.starts_with(['.', '/', '_']))
+            || item
+                .detail
+                .as_ref()
+                .is_some_and(|detail| detail.starts_with(&format!("Drupal\\{}\\", module_name)))
+    });
+    let rank = if item.kind == Some(CompletionItemKind::SNIPPET) {
+        2
+    } else if is_from_module {
+        0
+    } else {
+        1
     };
+    format!("{}_{}", rank, item.label)
+}
 
-    add_snippet(
-        "batch",
-        r#"
-\$storage = \\Drupal::entityTypeManager()->getStorage('$0');
-if (!isset(\$sandbox['ids'])) {
-  \$ids = \$storage->getQuery()
-    ->accessCheck(FALSE)
-    ->execute();
-  \$sandbox['ids'] = \$ids;
-  \$sandbox['total'] = count(\$sandbox['ids']);
+/// Get the name that is being typed before the cursor, f.e `entity.node.ca` in
+/// `Url::fromRoute('entity.node.ca`.
+fn get_typed_text(line: &str, character: usize) -> &str {
+    let before_cursor = line.get(..character).unwrap_or_default();
+    let start = before_cursor
+        .rfind(|character: char| {
+            !(character.is_alphanumeric()
+                || matches!(character, '_' | '.' | ':' | '-' | '/' | '\\'))
+        })
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    &before_cursor[start..]
 }
 
-\$ids = array_splice(\$sandbox['ids'], 0, 20);
-foreach (\$storage->loadMultiple(\$ids) as \$entity) {
-  \$entity->save();
+/// Whether the quote before the cursor closes a string, f.e in `fromRoute('entity.node.canonical'`.
+fn is_closing_quote(line: &str, character: usize) -> bool {
+    let Some(before_cursor) = line.get(..character) else {
+        return false;
+    };
+    let mut chars = before_cursor.chars().rev();
+    if !matches!(chars.next(), Some('\'' | '"')) {
+        return false;
+    }
+    chars
+        .next()
+        .is_some_and(|char| !char.is_whitespace() && !"([{,=>:@".contains(char))
 }
 
-if (\$sandbox['total'] > 0) {
-  \$sandbox['#finished'] = (\$sandbox['total'] - count(\$sandbox['ids'])) / \$sandbox['total'];
-}"#,
-    );
-    add_snippet(
-        "ihdoc",
-        r#"
-/**
- * {@inheritdoc}
- */"#,
-    );
-    add_snippet(
-        "ensure-instanceof",
-        "if (!($1 instanceof $2)) {\n  return$0;\n}",
-    );
-    add_snippet(
-        "entity-storage",
-        "\\$storage = \\$this->entityTypeManager->getStorage('$0');",
-    );
-    add_snippet(
-        "entity-load",
-        "\\$$1 = \\$this->entityTypeManager->getStorage('$1')->load($0);",
-    );
+/// Downgrade snippets to plain text and mark the documentation as Markdown or plain text,
+/// depending on what the client supports.
+fn adapt_to_client(item: &mut CompletionItem, snippet_support: bool, markdown_support: bool) {
+    if !snippet_support && item.insert_text_format == Some(InsertTextFormat::SNIPPET) {
+        item.insert_text_format = Some(InsertTextFormat::PLAIN_TEXT);
+        if let Some(insert_text) = &mut item.insert_text {
+            *insert_text = snippet_to_plain_text(insert_text);
+        }
+        if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+            edit.new_text = snippet_to_plain_text(&edit.new_text);
+        }
+    }
+
+    if let Some(Documentation::String(documentation)) = item.documentation.take() {
+        item.documentation = Some(match markdown_support {
+            true => Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: documentation,
+            }),
+            false => Documentation::String(markdown_to_plain_text(&documentation)),
+        });
+    }
+}
+
+/// Convert a snippet to the text it inserts, f.e `${1:name}: $0` becomes `name: `.
+fn snippet_to_plain_text(snippet: &str) -> String {
+    let mut text = String::new();
+    // The number of placeholders that were opened and not closed yet.
+    let mut placeholders = 0;
+    let mut characters = snippet.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => match characters.peek() {
+                Some(&escaped @ ('$' | '}' | '\\')) => {
+                    text.push(escaped);
+                    characters.next();
+                }
+                _ => text.push(character),
+            },
+            '$' => match characters.peek() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    while characters.next_if(char::is_ascii_digit).is_some() {}
+                }
+                Some('{') => {
+                    characters.next();
+                    while characters.next_if(char::is_ascii_digit).is_some() {}
+                    match characters.next() {
+                        Some(':') => placeholders += 1,
+                        // Insert the first option of a choice, f.e ${1|public,protected|}.
+                        Some('|') => {
+                            while let Some(character) = characters.next() {
+                                match character {
+                                    '\\' => text.extend(characters.next()),
+                                    ',' | '|' => break,
+                                    _ => text.push(character),
+                                }
+                            }
+                            for character in characters.by_ref() {
+                                if character == '}' {
+                                    break;
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ => text.push(character),
+            },
+            '}' if placeholders > 0 => placeholders -= 1,
+            _ => text.push(character),
+        }
+    }
+    text
+}
+
+/// Keep the items that contain the typed text, up to the maximum number of items, preferring
+/// items that start with it.
+fn limit_completion_items(items: &mut Vec<CompletionItem>, typed_text: &str, max_items: usize) {
+    let typed_text = typed_text.to_lowercase();
+    let get_filter_text = |item: &CompletionItem| {
+        item.filter_text
+            .as_ref()
+            .unwrap_or(&item.label)
+            .to_lowercase()
+    };
+    items.retain(|item| get_filter_text(item).contains(&typed_text));
+    items.sort_by_cached_key(|item| {
+        (
+            !get_filter_text(item).starts_with(&typed_text),
+            item.sort_text.clone(),
+        )
+    });
+    items.truncate(max_items);
+}
+
+/// Detect references in YAML that don't parse yet, f.e an unterminated `- '@` service argument.
+fn get_yaml_token_data(uri: &str, line: &str, character: usize) -> Option<TokenData> {
+    let before_cursor = line.get(..character)?;
+    if uri.ends_with(".services.yml") {
+        let captures = YAML_SERVICE_ARGUMENT
+            .captures(before_cursor)
+            .or_else(|| YAML_SERVICE_PARENT.captures(before_cursor))?;
+        return Some(TokenData::DrupalServiceReference(
+            captures.name("value")?.as_str().into(),
+        ));
+    } else if uri.ends_with(".schema.yml") {
+        let captures = YAML_SCHEMA_TYPE.captures(before_cursor)?;
+        return Some(TokenData::DrupalConfigSchemaReference(
+            captures.name("value")?.as_str().to_string(),
+        ));
+    } else if uri.ends_with(".routing.yml") {
+        if let Some(captures) = YAML_CONTROLLER.captures(before_cursor) {
+            let value = captures.name("value")?.as_str();
+            return match PhpMethod::try_from(value) {
+                Ok(method) => Some(TokenData::PhpMethodReference(method)),
+                Err(_) => Some(TokenData::PhpClassReference(PhpClassName::from(value))),
+            };
+        }
+        if let Some(captures) = YAML_ROLE.captures(before_cursor) {
+            return Some(TokenData::DrupalRoleReference(
+                captures.name("value")?.as_str().to_string(),
+            ));
+        }
+        let captures = YAML_PERMISSION.captures(before_cursor)?;
+        return Some(TokenData::DrupalPermissionReference(
+            captures.name("value")?.as_str().into(),
+        ));
+    } else if [
+        ".links.menu.yml",
+        ".links.task.yml",
+        ".links.action.yml",
+        ".links.contextual.yml",
+    ]
+    .iter()
+    .any(|suffix| uri.ends_with(suffix))
+    {
+        if let Some(captures) = YAML_MENU_LINK_PARENT.captures(before_cursor) {
+            if uri.ends_with(".links.menu.yml") {
+                return Some(TokenData::DrupalMenuLinkReference(
+                    captures.name("value")?.as_str().to_string(),
+                ));
+            }
+        }
+        let captures = YAML_ROUTE_NAME.captures(before_cursor)?;
+        return Some(TokenData::DrupalRouteReference(
+            captures.name("value")?.as_str().into(),
+        ));
+    }
+    None
+}
+
+/// Get the range of the value that is being typed, f.e `entity_type.ma` in `- '@entity_type.ma`,
+/// so that completion replaces it including the dots.
+fn get_value_range(line: &str, position: Position, delimiters: &[char]) -> Option<Range> {
+    let before_cursor = line.get(..position.character as usize)?;
+    let start = before_cursor
+        .rfind(delimiters)
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    Some(Range {
+        start: Position {
+            line: position.line,
+            character: start as u32,
+        },
+        end: position,
+    })
+}
+
+/// Get the edit that replaces the whole string under the cursor, f.e both `access co` and `ntent`
+/// in `'access co|ntent'`.
+fn get_string_edit(line: &str, position: Position, new_text: &str) -> Option<TextEdit> {
+    let range = get_value_range(line, position, &['\'', '"'])?;
+    // Only strings that were started with a quote are replaced.
+    if range.start.character == 0 {
+        return None;
+    }
+    let after_cursor = line.get(position.character as usize..)?;
+    let end = position.character as usize + after_cursor.find(['\'', '"']).unwrap_or(0);
+
+    Some(TextEdit {
+        range: Range {
+            start: range.start,
+            end: Position {
+                line: position.line,
+                character: end as u32,
+            },
+        },
+        new_text: new_text.to_string(),
+    })
+}
+
+/// Get the edit that completes the permission under the cursor in a `_permission:` value.
+///
+/// Other permissions that are joined by `+` or `,` are kept, and the value is quoted when it
+/// contains spaces.
+fn get_yaml_permission_edit(line: &str, position: Position, permission: &str) -> Option<TextEdit> {
+    let captures = YAML_PERMISSION.captures(line)?;
+    let value = captures.name("value")?;
+    let cursor = (position.character as usize).clamp(value.start(), value.end());
+
+    let mut before = line.get(value.start()..cursor)?;
+    let mut after = line.get(cursor..value.end())?;
+    before = before.trim_start_matches(['\'', '"']);
+    after = after.trim_end_matches(['\'', '"']);
+
+    let prefix = match before.rfind(['+', ',']) {
+        Some(index) => &before[..=index],
+        None => "",
+    };
+    let suffix = match after.find(['+', ',']) {
+        Some(index) => &after[index..],
+        None => "",
+    };
+    let mut new_text = format!("{}{}{}", prefix, permission, suffix).replace("''", "'");
+    if new_text.contains([' ', ':', '#']) {
+        new_text = format!("'{}'", new_text.replace('\'', "''"));
+    }
+
+    Some(TextEdit {
+        range: Range {
+            start: Position {
+                line: position.line,
+                character: value.start() as u32,
+            },
+            end: Position {
+                line: position.line,
+                character: value.end().max(position.character as usize) as u32,
+            },
+        },
+        new_text,
+    })
+}
+
+/// Check whether the cursor is inside a `{{ }}` or `{% %}` block.
+fn is_inside_twig_expression(line: &str, character: usize) -> bool {
+    let before_cursor = line.get(..character).unwrap_or(line);
+    let opening = before_cursor.rfind("{{").max(before_cursor.rfind("{%"));
+    let closing = before_cursor.rfind("}}").max(before_cursor.rfind("%}"));
+    match (opening, closing) {
+        (Some(opening), Some(closing)) => opening > closing,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+fn get_global_snippets() -> Vec<CompletionItem> {
+    let mut snippets: HashMap<String, String> = HashMap::new();
+
+    let mut add_snippet = |key: &str, value: &str| {
+        snippets.insert(key.into(), value.into());
+    };
+
+    add_snippet(
+        "batch",
+        r#"
+\$storage = \\Drupal::entityTypeManager()->getStorage('$0');
+if (!isset(\$sandbox['ids'])) {
+  \$ids = \$storage->getQuery()
+    ->accessCheck(FALSE)
+    ->execute();
+  \$sandbox['ids'] = \$ids;
+  \$sandbox['total'] = count(\$sandbox['ids']);
+}
+
+\$ids = array_splice(\$sandbox['ids'], 0, 20);
+foreach (\$storage->loadMultiple(\$ids) as \$entity) {
+  \$entity->save();
+}
+
+if (\$sandbox['total'] > 0) {
+  \$sandbox['#finished'] = (\$sandbox['total'] - count(\$sandbox['ids'])) / \$sandbox['total'];
+}"#,
+    );
+    add_snippet(
+        "ihdoc",
+        r#"
+/**
+ * {@inheritdoc}
+ */"#,
+    );
+    add_snippet(
+        "ensure-instanceof",
+        "if (!($1 instanceof $2)) {\n  return$0;\n}",
+    );
+    add_snippet(
+        "entity-storage",
+        "\\$storage = \\$this->entityTypeManager->getStorage('$0');",
+    );
+    add_snippet(
+        "entity-load",
+        "\\$$1 = \\$this->entityTypeManager->getStorage('$1')->load($0);",
+    );
     add_snippet(
         "entity-query",
         r#"
@@ -427,50 +1630,1092 @@ public static function create(ContainerInterface \$container, array \$configurat
 }"#,
     );
 
-    // Create pre-generated snippets.
+    // Snippets generated from the usage examples of form and render elements.
+    for (name, snippet) in DOCUMENT_STORE.lock().unwrap().get_element_snippets() {
+        add_snippet(name, snippet);
+    }
+
+    snippets
+        .iter()
+        .map(|(name, snippet)| CompletionItem {
+            label: name.clone(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            insert_text: Some(snippet.clone()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            deprecated: Some(false),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// Get a snippet that scaffolds the namespace and class of an empty file in a module.
+fn get_file_scaffold_item(
+    uri: &Uri,
+    content: &str,
+    line: &str,
+    position: Position,
+) -> Option<CompletionItem> {
+    // Only offer the snippet when nothing but the word that is being typed is in the file.
+    let rest = content.trim_start().trim_start_matches("<?php").trim();
+    if rest.contains(|character: char| !(character.is_alphanumeric() || character == '_')) {
+        return None;
+    }
+
+    let path = PathBuf::from(uri_to_url(uri.clone())?);
+    let class_name = path.file_stem()?.to_str()?;
+    let info_file = find_info_file(&path)?;
+    let namespace = get_psr4_namespace(
+        path.parent()?,
+        info_file.parent()?,
+        get_extension_name(&info_file)?,
+    )?;
+    let kind = if class_name.ends_with("Interface") {
+        "interface"
+    } else if class_name.ends_with("Trait") {
+        "trait"
+    } else if class_name.ends_with("Base") || class_name.starts_with("Abstract") {
+        "abstract class"
+    } else {
+        "final class"
+    };
+
+    let range = get_word_range(line, position)?;
+    Some(CompletionItem {
+        label: format!("{} {}", kind, class_name),
+        label_details: Some(CompletionItemLabelDetails {
+            description: Some("File".to_string()),
+            detail: None,
+        }),
+        detail: Some(format!("{}\\{}", namespace, class_name)),
+        kind: Some(CompletionItemKind::SNIPPET),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range,
+            new_text: format!(
+                "<?php\n\ndeclare(strict_types=1);\n\nnamespace {};\n\n/**\n * ${{1:Description}}.\n */\n{} {} {{\n\n  $0\n\n}}\n",
+                namespace, kind, class_name
+            ),
+        })),
+        // Remove everything before the word that is being typed, f.e `<?php`.
+        additional_text_edits: Some(vec![TextEdit {
+            range: Range {
+                start: Position::default(),
+                end: range.start,
+            },
+            new_text: String::new(),
+        }]),
+        deprecated: Some(false),
+        ..CompletionItem::default()
+    })
+}
+
+/// Get completion items for fully qualified class names in YAML, f.e `_controller` in routes.
+fn get_yaml_class_completion_items(
+    class_name: &PhpClassName,
+    line: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Some(range) = get_value_range(line, position, &[' ', '\'', '"']) else {
+        return vec![];
+    };
+    // Keep the leading backslash if the class name is being typed with one.
+    let prefix = match line.get(range.start.character as usize..position.character as usize) {
+        Some(value) if value.starts_with('\\') => "\\",
+        _ => "",
+    };
+
+    let name = class_name.to_string().to_lowercase();
+    let mut class_names: Vec<PhpClassName> = vec![];
     DOCUMENT_STORE
         .lock()
         .unwrap()
         .get_documents()
         .values()
-        .flat_map(|document| document.tokens.iter())
-        .filter_map(|token| match &token.data {
-            TokenData::PhpClassDefinition(class_def) => match &class_def.attribute {
-                Some(ClassAttribute::Plugin(plugin)) => Some(plugin),
-                _ => None,
-            },
-            _ => None,
+        .for_each(|document| {
+            document.tokens.iter().for_each(|token| {
+                if let TokenData::PhpClassDefinition(class) = &token.data {
+                    if class.name.to_string().to_lowercase().contains(&name)
+                        && !class_names.contains(&class.name)
+                    {
+                        class_names.push(class.name.clone());
+                    }
+                }
+            })
+        });
+
+    class_names
+        .into_iter()
+        .map(|class_name| {
+            let new_text = format!("{}{}", prefix, class_name);
+            CompletionItem {
+                label: class_name.get_short_name().to_string(),
+                label_details: Some(CompletionItemLabelDetails {
+                    description: Some(class_name.get_namespace().to_string()),
+                    detail: None,
+                }),
+                detail: Some(class_name.to_string()),
+                kind: Some(CompletionItemKind::CLASS),
+                filter_text: Some(new_text.clone()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text })),
+                deprecated: Some(false),
+                ..CompletionItem::default()
+            }
         })
-        .filter_map(|plugin| {
-            let snippet_key_prefix = match plugin.plugin_type {
-                DrupalPluginType::RenderElement => Some("render"),
-                DrupalPluginType::FormElement => Some("form"),
-                _ => None,
-            };
+        .collect()
+}
 
-            snippet_key_prefix.and_then(|prefix| {
-                plugin
-                    .usage_example
-                    .as_ref()
-                    .map(|usage_example| (prefix, &plugin.plugin_id, usage_example))
+/// Get completion items for the event name constants, f.e `KernelEvents::REQUEST`.
+fn get_event_completion_items(
+    parser: &PhpParser,
+    line: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Some(range) = get_word_range(line, position) else {
+        return vec![];
+    };
+    // Events are the keys of the returned array, the values are the listeners.
+    if !line
+        .get(..range.start.character as usize)
+        .is_some_and(is_array_key_position)
+    {
+        return vec![];
+    }
+
+    let mut events: Vec<(PhpClassName, String, Option<String>)> = KERNEL_EVENTS
+        .iter()
+        .map(|(name, value)| {
+            (
+                PhpClassName::from(KERNEL_EVENTS_CLASS),
+                name.to_string(),
+                Some(value.to_string()),
+            )
+        })
+        .collect();
+    DOCUMENT_STORE
+        .lock()
+        .unwrap()
+        .get_documents()
+        .values()
+        .for_each(|document| {
+            document.tokens.iter().for_each(|token| {
+                if let TokenData::PhpClassDefinition(class) = &token.data {
+                    if class.name.get_short_name().ends_with("Events") {
+                        events.extend(class.constants.iter().map(|constant| {
+                            (
+                                class.name.clone(),
+                                constant.name.clone(),
+                                constant.value.clone(),
+                            )
+                        }));
+                    }
+                }
             })
+        });
+
+    events
+        .into_iter()
+        .map(|(class_name, name, value)| {
+            let (class_reference, additional_text_edits) = get_class_reference(parser, &class_name);
+            let label = format!("{}::{}", class_name.get_short_name(), name);
+            CompletionItem {
+                label: label.clone(),
+                label_details: Some(CompletionItemLabelDetails {
+                    description: Some("Event".to_string()),
+                    detail: None,
+                }),
+                detail: value,
+                kind: Some(CompletionItemKind::CONSTANT),
+                filter_text: Some(label),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: format!("{}::{}", class_reference, name),
+                })),
+                additional_text_edits,
+                deprecated: Some(false),
+                ..CompletionItem::default()
+            }
         })
-        .for_each(|(snippet_key_prefix, plugin_id, usage_example)| {
-            snippets.insert(
-                format!("{}-{}", snippet_key_prefix, plugin_id),
-                usage_example.replace("$", "\\$"),
-            );
+        .collect()
+}
+
+/// Whether a name after the text is an array key, f.e in `$events[Kernel` or `[Kernel`, but not
+/// in `KernelEvents::REQUEST => [on` or `$events[KernelEvents::REQUEST][] = [on`.
+fn is_array_key_position(before: &str) -> bool {
+    let before = before.trim_end();
+    match before.strip_suffix('[') {
+        Some(before_bracket) => {
+            let before_bracket = before_bracket.trim_end();
+            match before_bracket.strip_suffix('=') {
+                Some(assigned) => !assigned.trim_end().ends_with(']'),
+                None => !before_bracket.ends_with("=>"),
+            }
+        }
+        // Keys on their own line of a multi-line array.
+        None => before.is_empty() || before.ends_with(','),
+    }
+}
+
+/// Get the class name that is being typed, where a type is expected or before `::class`.
+fn get_class_name_before_cursor(line: &str, character: usize) -> Option<&str> {
+    let before_cursor = line.get(..character)?;
+    let regex = match line[character..].starts_with("::class") {
+        true => &PHP_NAME,
+        false => &PHP_CLASS_NAME,
+    };
+    regex
+        .captures(before_cursor)?
+        .name("name")
+        .map(|name| name.as_str())
+}
+
+/// Get completion items for the indexed classes starting with the typed name.
+fn get_class_completion_items(
+    parser: &PhpParser,
+    name: &str,
+    line: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let Some(range) = get_word_range(line, position) else {
+        return vec![];
+    };
+
+    let name = name.to_lowercase();
+    let mut class_names: Vec<PhpClassName> = vec![];
+    DOCUMENT_STORE
+        .lock()
+        .unwrap()
+        .get_documents()
+        .values()
+        .for_each(|document| {
+            document.tokens.iter().for_each(|token| {
+                if let TokenData::PhpClassDefinition(class) = &token.data {
+                    if class
+                        .name
+                        .get_short_name()
+                        .to_lowercase()
+                        .starts_with(&name)
+                        && !class_names.contains(&class.name)
+                    {
+                        class_names.push(class.name.clone());
+                    }
+                }
+            })
         });
 
-    snippets
+    class_names
+        .into_iter()
+        .map(|class_name| {
+            let (class_reference, additional_text_edits) = get_class_reference(parser, &class_name);
+            CompletionItem {
+                label: class_name.get_short_name().to_string(),
+                label_details: Some(CompletionItemLabelDetails {
+                    description: Some(class_name.get_namespace().to_string()),
+                    detail: None,
+                }),
+                detail: Some(class_name.to_string()),
+                kind: Some(CompletionItemKind::CLASS),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: class_reference,
+                })),
+                additional_text_edits,
+                deprecated: Some(false),
+                ..CompletionItem::default()
+            }
+        })
+        .collect()
+}
+
+/// Get how a class can be referenced, and the edit that imports it when needed.
+///
+/// Classes that conflict with an existing import are referenced by their fully qualified name.
+fn get_class_reference(
+    parser: &PhpParser,
+    class_name: &PhpClassName,
+) -> (String, Option<Vec<TextEdit>>) {
+    let short_name = class_name.get_short_name().to_string();
+    match parser.get_imported_class_names().get(&short_name) {
+        Some(import) if import == class_name => (short_name, None),
+        Some(_) => (format!("\\{}", class_name), None),
+        None if parser.get_namespace().as_deref() == Some(class_name.get_namespace()) => {
+            (short_name, None)
+        }
+        None => (
+            short_name,
+            parser
+                .get_use_statement_edit(class_name)
+                .map(|edit| vec![edit]),
+        ),
+    }
+}
+
+/// Get the range of the (qualified) name before the cursor, f.e `KernelEvents::REQ`.
+fn get_word_range(line: &str, position: Position) -> Option<Range> {
+    let before_cursor = line.get(..position.character as usize)?;
+    let start = before_cursor
+        .rfind(|character: char| {
+            !(character.is_alphanumeric()
+                || character == '_'
+                || character == ':'
+                || character == '\\')
+        })
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    Some(Range {
+        start: Position {
+            line: position.line,
+            character: start as u32,
+        },
+        end: position,
+    })
+}
+
+/// Get completion items for the bundles of an entity type, from their config and drush.
+fn get_entity_bundle_completion_items(
+    store: &DocumentStore,
+    entity_type: &str,
+    range: Option<Range>,
+) -> Vec<CompletionItem> {
+    let mut bundles: HashMap<&str, (Option<&str>, Option<&str>)> = get_drush_data()
+        .and_then(|data| data.bundles.get(entity_type))
+        .map(|bundles| {
+            bundles
+                .iter()
+                .map(|bundle| (bundle.as_str(), (None, None)))
+                .collect()
+        })
+        .unwrap_or_default();
+    for bundle in store.get_entity_bundles(entity_type) {
+        bundles.insert(
+            &bundle.name,
+            (bundle.label.as_deref(), bundle.description.as_deref()),
+        );
+    }
+
+    bundles
+        .into_iter()
+        .map(|(name, (label, description))| CompletionItem {
+            label: name.to_string(),
+            label_details: Some(CompletionItemLabelDetails {
+                description: Some("Bundle".to_string()),
+                detail: None,
+            }),
+            detail: label.map(|label| label.to_string()),
+            documentation: description
+                .map(|description| Documentation::String(description.to_string())),
+            kind: Some(CompletionItemKind::REFERENCE),
+            text_edit: range.map(|range| {
+                CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: name.to_string(),
+                })
+            }),
+            deprecated: Some(false),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// Get completion items for the types of config schema, from the schema files of the project and
+/// the data types of core. Schema of config objects with wildcards, f.e `block.block.*`, are left
+/// out since they aren't used as a type.
+fn get_schema_type_completion_items(
+    store: &DocumentStore,
+    range: Option<Range>,
+) -> Vec<CompletionItem> {
+    let mut types: HashMap<&str, (Option<&str>, Option<&str>)> = CORE_SCHEMA_TYPES
         .iter()
-        .map(|(name, snippet)| CompletionItem {
-            label: name.clone(),
-            kind: Some(CompletionItemKind::SNIPPET),
-            insert_text: Some(snippet.clone()),
-            insert_text_format: Some(InsertTextFormat::SNIPPET),
+        .map(|type_name| (*type_name, (None, None)))
+        .collect();
+    store
+        .get_documents()
+        .values()
+        .filter(|document| document.uri.ends_with(".schema.yml"))
+        .flat_map(|document| document.tokens.iter())
+        .for_each(|token| {
+            if let TokenData::DrupalConfigSchemaDefinition(schema) = &token.data {
+                if !schema.name.contains(['*', '[']) {
+                    types.insert(
+                        &schema.name,
+                        (schema.type_name.as_deref(), schema.label.as_deref()),
+                    );
+                }
+            }
+        });
+
+    types
+        .into_iter()
+        .map(|(name, (type_name, label))| CompletionItem {
+            label: name.to_string(),
+            label_details: Some(CompletionItemLabelDetails {
+                description: Some("Schema type".to_string()),
+                detail: None,
+            }),
+            detail: type_name.map(|type_name| type_name.to_string()),
+            documentation: label.map(|label| Documentation::String(label.to_string())),
+            kind: Some(CompletionItemKind::CLASS),
+            text_edit: range.map(|range| {
+                CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: name.to_string(),
+                })
+            }),
             deprecated: Some(false),
             ..CompletionItem::default()
         })
         .collect()
 }
+
+/// Get completion items for references that can only be resolved with runtime data from drush.
+fn get_drush_completion_items(token_data: &TokenData, data: &DrushData) -> Vec<CompletionItem> {
+    let (description, items): (&str, Vec<(&str, Option<String>)>) = match token_data {
+        TokenData::DrupalConfigReference(_) => (
+            "Config",
+            data.config
+                .iter()
+                .map(|name| (name.as_str(), None))
+                .collect(),
+        ),
+        TokenData::DrupalModuleReference(_) => (
+            "Module",
+            data.modules
+                .iter()
+                .map(|name| (name.as_str(), None))
+                .collect(),
+        ),
+        TokenData::DrupalRoleReference(_) => (
+            "Role",
+            data.roles
+                .iter()
+                .map(|(id, label)| (id.as_str(), Some(label.to_string())))
+                .collect(),
+        ),
+        TokenData::DrupalFieldReference(_) => (
+            "Field",
+            data.get_field_names()
+                .into_iter()
+                .map(|(name, bundles)| (name, Some(bundles.join(", "))))
+                .collect(),
+        ),
+        _ => return vec![],
+    };
+
+    items
+        .into_iter()
+        .map(|(label, detail)| CompletionItem {
+            label: label.to_string(),
+            label_details: Some(CompletionItemLabelDetails {
+                description: Some(description.to_string()),
+                detail: None,
+            }),
+            detail,
+            kind: Some(CompletionItemKind::REFERENCE),
+            deprecated: Some(false),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theme_hook_names() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/modules/node/node.module",
+            "<?php\n\nfunction node_theme() {\n  return [\n    'node' => ['render element' => 'elements'],\n    'node_add_list' => ['variables' => ['content' => NULL]],\n  ];\n}\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/themes/custom/foo/templates/field--field-tags.html.twig",
+            "{{ items }}".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/themes/custom/foo/templates/node.html.twig",
+            "{{ content }}".to_string(),
+        );
+
+        assert_eq!(
+            vec!["field__field_tags", "node", "node_add_list"],
+            get_theme_hook_names(&store)
+        );
+    }
+
+    #[test]
+    fn entity_bundle_completion_items() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/profiles/standard/config/install/node.type.article.yml",
+            "type: article\nname: Article\ndescription: 'Use <em>articles</em> for time-sensitive content.'\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/config/sync/node.type.article.yml",
+            "type: article\nname: Article\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/config/sync/taxonomy.vocabulary.tags.yml",
+            "vid: tags\nname: Tags\n".to_string(),
+        );
+
+        let items = get_entity_bundle_completion_items(&store, "node", None);
+        assert_eq!(
+            vec![("article".to_string(), Some("Article".to_string()))],
+            items
+                .into_iter()
+                .map(|item| (item.label, item.detail))
+                .collect::<Vec<(String, Option<String>)>>()
+        );
+        assert_eq!(
+            vec!["tags".to_string()],
+            get_entity_bundle_completion_items(&store, "taxonomy_term", None)
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn schema_type_completion_items() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/config/schema/foo.schema.yml",
+            r#"foo.settings:
+  type: config_object
+  label: 'Foo settings'
+  mapping:
+    bar:
+      type: foo.bar
+
+foo.bar:
+  type: mapping
+  label: 'Bar'
+
+foo.item.*:
+  type: mapping
+"#
+            .to_string(),
+        );
+
+        let items = get_schema_type_completion_items(&store, None);
+        let item = |label: &str| items.iter().find(|item| item.label == label);
+        assert!(item("string").is_some());
+        assert!(item("foo.item.*").is_none());
+        let item = item("foo.bar").unwrap();
+        assert_eq!(Some("mapping".to_string()), item.detail);
+        assert_eq!(
+            Some(Documentation::String("Bar".to_string())),
+            item.documentation
+        );
+    }
+
+    #[test]
+    fn detect_yaml_service_argument() {
+        let uri = "file:///foo/foo.services.yml";
+        for line in [
+            "      - '@entity_type.ma",
+            "    arguments: ['@entity_type.ma",
+        ] {
+            match get_yaml_token_data(uri, line, line.len()) {
+                Some(TokenData::DrupalServiceReference(name)) => {
+                    assert_eq!("entity_type.ma", name)
+                }
+                data => panic!("Unexpected token data {:?}", data),
+            }
+            let range =
+                get_value_range(line, Position::new(4, line.len() as u32), &['@', '?']).unwrap();
+            assert_eq!(line.len() as u32 - 14, range.start.character);
+        }
+
+        match get_yaml_token_data(uri, "    parent: default_plugin", 26) {
+            Some(TokenData::DrupalServiceReference(name)) => assert_eq!("default_plugin", name),
+            data => panic!("Unexpected token data {:?}", data),
+        }
+
+        assert!(get_yaml_token_data(uri, "    class: Drupal", 17).is_none());
+    }
+
+    #[test]
+    fn closing_quotes() {
+        let line = "  $url = Url::fromRoute('entity.node.canonical'";
+        assert!(is_closing_quote(line, line.len()));
+        assert!(!is_closing_quote(line, 25));
+        assert!(!is_closing_quote(
+            "    arguments: ['@entity_type.manager', '",
+            42
+        ));
+        assert!(!is_closing_quote("  _permission: '", 16));
+        assert!(!is_closing_quote("  _permission: 'access content", 30));
+    }
+
+    #[test]
+    fn snippet_plain_text() {
+        for (snippet, expected) in [
+            (
+                "function ${1:name}() {\n  $0\n}",
+                "function name() {\n  \n}",
+            ),
+            (
+                "'#type' => '${1|textfield,select|}',",
+                "'#type' => 'textfield',",
+            ),
+            ("\\\\Drupal::${1:service}()", "\\Drupal::service()"),
+            (
+                "class: ${1|Drupal\\\\foo\\\\Bar,Drupal\\\\foo\\\\Baz|}",
+                "class: Drupal\\foo\\Bar",
+            ),
+            ("\\$variables['${1:key}']$2", "$variables['key']"),
+            ("${1:outer ${2:inner}}", "outer inner"),
+            ("$ 10 {}", "$ 10 {}"),
+        ] {
+            assert_eq!(expected, snippet_to_plain_text(snippet), "{}", snippet);
+        }
+    }
+
+    #[test]
+    fn adapt_items_to_client() {
+        let item = CompletionItem {
+            label: "hook".to_string(),
+            insert_text: Some("function ${1:name}() {}".to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            documentation: Some(Documentation::String("*Path:* `/node`".to_string())),
+            ..CompletionItem::default()
+        };
+
+        let mut plain = item.clone();
+        adapt_to_client(&mut plain, false, false);
+        assert_eq!(Some(InsertTextFormat::PLAIN_TEXT), plain.insert_text_format);
+        assert_eq!(Some("function name() {}".to_string()), plain.insert_text);
+        assert_eq!(
+            Some(Documentation::String("Path: /node".to_string())),
+            plain.documentation
+        );
+
+        let mut rich = item.clone();
+        adapt_to_client(&mut rich, true, true);
+        assert_eq!(item.insert_text, rich.insert_text);
+        assert_eq!(
+            Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "*Path:* `/node`".to_string(),
+            })),
+            rich.documentation
+        );
+    }
+
+    #[test]
+    fn sort_text() {
+        let item = |label: &str, detail: Option<&str>, kind| CompletionItem {
+            label: label.to_string(),
+            detail: detail.map(|detail| detail.to_string()),
+            kind: Some(kind),
+            ..CompletionItem::default()
+        };
+        for (expected, item) in [
+            (
+                "0_mymodule.settings",
+                item("mymodule.settings", None, CompletionItemKind::REFERENCE),
+            ),
+            (
+                "0_mymodule/global",
+                item("mymodule/global", None, CompletionItemKind::REFERENCE),
+            ),
+            (
+                "0_FooForm",
+                item(
+                    "FooForm",
+                    Some("Drupal\\mymodule\\Form\\FooForm"),
+                    CompletionItemKind::CLASS,
+                ),
+            ),
+            (
+                "1_mymodules.settings",
+                item("mymodules.settings", None, CompletionItemKind::REFERENCE),
+            ),
+            (
+                "1_node.add",
+                item("node.add", None, CompletionItemKind::REFERENCE),
+            ),
+            (
+                "2_mymodule.snippet",
+                item("mymodule.snippet", None, CompletionItemKind::SNIPPET),
+            ),
+        ] {
+            assert_eq!(expected, get_sort_text(&item, Some("mymodule")));
+        }
+        assert_eq!(
+            "1_node.add",
+            get_sort_text(&item("node.add", None, CompletionItemKind::REFERENCE), None)
+        );
+    }
+
+    #[test]
+    fn limit_items() {
+        let line = "    $url = Url::fromRoute('entity.node.ca";
+        assert_eq!("entity.node.ca", get_typed_text(line, line.len()));
+        assert_eq!("", get_typed_text("    - '@", 7));
+
+        let mut items = [
+            "node.add",
+            "entity.node.canonical",
+            "node.add_page",
+            "user.login",
+        ]
+        .into_iter()
+        .map(|label| CompletionItem {
+            label: label.to_string(),
+            sort_text: Some(format!("1_{}", label)),
+            ..CompletionItem::default()
+        })
+        .collect::<Vec<CompletionItem>>();
+        limit_completion_items(&mut items, "Node.", 2);
+        assert_eq!(
+            vec!["node.add", "node.add_page"],
+            items.iter().map(|item| &item.label).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn method_completion() {
+        for (line, expected) in [
+            ("    $this->lo", Some((None, None))),
+            ("    return static::cre", Some((Some("static"), None))),
+            (
+                "    \\Drupal::service('entity_type.manager')->get",
+                Some((None, Some("entity_type.manager"))),
+            ),
+            ("    $node->get", None),
+        ] {
+            let captures = PHP_METHOD_CALL.captures(line);
+            assert_eq!(
+                expected,
+                captures.map(|captures| (
+                    captures.name("scope").map(|scope| scope.as_str()),
+                    captures.name("service").map(|service| service.as_str())
+                )),
+                "{}",
+                line
+            );
+        }
+
+        let source = r#"<?php
+
+namespace Drupal\mymodule;
+
+class Foo {
+
+  public function load() {}
+
+  protected function build() {}
+
+  private function prepare() {}
+
+  public static function create() {}
+
+}
+"#;
+        let Some(TokenData::PhpClassDefinition(class)) = PhpParser::new(source)
+            .get_class_at_position(Position::new(6, 20))
+            .map(|token| token.data)
+        else {
+            panic!("Expected a class definition");
+        };
+        for (context, expected) in [
+            (MethodCallContext::External, vec!["create", "load"]),
+            (
+                MethodCallContext::Instance,
+                vec!["build", "load", "prepare"],
+            ),
+            (MethodCallContext::Static, vec!["create"]),
+        ] {
+            let mut labels = get_method_completion_items(&class, context, &DocumentStore::new())
+                .into_iter()
+                .map(|item| item.label)
+                .collect::<Vec<String>>();
+            labels.sort();
+            assert_eq!(expected, labels);
+        }
+
+        let item =
+            get_method_completion_items(&class, MethodCallContext::Static, &DocumentStore::new())
+                .remove(0);
+        assert_eq!(
+            Some("public static method".to_string()),
+            item.label_details.and_then(|details| details.description)
+        );
+    }
+
+    #[test]
+    fn inherited_method_completion() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/modules/custom/mymodule/src/Base.php",
+            r#"<?php
+
+namespace Drupal\mymodule;
+
+abstract class Base {
+
+  public function load() {}
+
+  protected function build() {}
+
+  private function prepare() {}
+
+}
+"#
+            .to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/mymodule/src/LoggerTrait.php",
+            r#"<?php
+
+namespace Drupal\mymodule;
+
+trait LoggerTrait {
+
+  protected function getLogger() {}
+
+}
+"#
+            .to_string(),
+        );
+
+        let source = r#"<?php
+
+namespace Drupal\mymodule;
+
+class Foo extends Base {
+
+  use LoggerTrait;
+
+  public function load() {}
+
+  public function save() {}
+
+}
+"#;
+        let Some(TokenData::PhpClassDefinition(class)) = PhpParser::new(source)
+            .get_class_at_position(Position::new(10, 20))
+            .map(|token| token.data)
+        else {
+            panic!("Expected a class definition");
+        };
+        let mut labels = get_method_completion_items(&class, MethodCallContext::Instance, &store)
+            .into_iter()
+            .map(|item| item.label)
+            .collect::<Vec<String>>();
+        labels.sort();
+        assert_eq!(vec!["build", "getLogger", "load", "save"], labels);
+    }
+
+    #[test]
+    fn detect_php_class_name() {
+        for (line, expected) in [
+            ("    $url = new Ur", Some("Ur")),
+            ("  public function foo(Entity", Some("Entity")),
+            ("  public function foo(?Ent", Some("Ent")),
+            ("  public function foo(array $bar, Ent", Some("Ent")),
+            ("  public function foo(): Res", Some("Res")),
+            ("  protected Ent", Some("Ent")),
+            ("class Foo implements Bar, Baz", Some("Baz")),
+            ("    catch (Exc", Some("Exc")),
+            ("    $classes = [Node::class", Some("Node")),
+            ("    $classes = [Node", None),
+            ("    return [Node", None),
+            ("    $foo = Bar", None),
+            ("    $foo = bar(Baz", None),
+            ("    $foo = Foo::BA", None),
+            ("    $foo->Bar::class", None),
+            ("    $foo = 'Bar", None),
+        ] {
+            let character = line.find("::class").unwrap_or(line.len());
+            assert_eq!(
+                expected,
+                get_class_name_before_cursor(line, character),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn array_key_position() {
+        for (before, expected) in [
+            ("    $events[", true),
+            ("    return [", true),
+            ("      ", true),
+            ("      ConfigEvents::SAVE => 'onSave', ", true),
+            ("      KernelEvents::REQUEST => ", false),
+            ("      KernelEvents::REQUEST => [", false),
+            ("    $events[KernelEvents::REQUEST][] = [", false),
+            ("    $events = [", true),
+            ("    $foo = ", false),
+        ] {
+            assert_eq!(expected, is_array_key_position(before), "{}", before);
+        }
+    }
+
+    #[test]
+    fn class_reference() {
+        let parser = PhpParser::new(
+            r#"<?php
+
+namespace Drupal\mymodule\EventSubscriber;
+
+use Drupal\mymodule\KernelEvents;
+use Drupal\Core\Config\ConfigEvents;
+"#,
+        );
+
+        let (reference, edits) = get_class_reference(
+            &parser,
+            &PhpClassName::from("Drupal\\Core\\Config\\ConfigEvents"),
+        );
+        assert_eq!(
+            ("ConfigEvents", true),
+            (reference.as_str(), edits.is_none())
+        );
+
+        let (reference, edits) =
+            get_class_reference(&parser, &PhpClassName::from(KERNEL_EVENTS_CLASS));
+        assert_eq!(
+            ("\\Symfony\\Component\\HttpKernel\\KernelEvents", true),
+            (reference.as_str(), edits.is_none())
+        );
+
+        let (reference, edits) = get_class_reference(
+            &parser,
+            &PhpClassName::from("Drupal\\mymodule\\EventSubscriber\\Events"),
+        );
+        assert_eq!(("Events", true), (reference.as_str(), edits.is_none()));
+
+        let (reference, edits) = get_class_reference(
+            &parser,
+            &PhpClassName::from("Drupal\\Core\\Entity\\EntityTypeEvents"),
+        );
+        assert_eq!("EntityTypeEvents", reference);
+        assert_eq!(
+            "use Drupal\\Core\\Entity\\EntityTypeEvents;\n",
+            edits.unwrap()[0].new_text
+        );
+    }
+
+    #[test]
+    fn string_edit() {
+        let cursor = |line: &str| Position::new(2, (line.find("access").unwrap() + 9) as u32);
+        let line = "    $account->hasPermission('access co');";
+        let edit = get_string_edit(line, cursor(line), "access content").unwrap();
+        assert_eq!(
+            Range::new(Position::new(2, 29), Position::new(2, 38)),
+            edit.range
+        );
+        assert_eq!("access content", edit.new_text);
+
+        // The rest of the string after the cursor is replaced as well.
+        let line = "    $account->hasPermission('access content');";
+        let edit = get_string_edit(line, cursor(line), "access content").unwrap();
+        assert_eq!(Position::new(2, 43), edit.range.end);
+
+        // Without a closing quote only the typed value is replaced.
+        let line = "    $account->hasPermission('access co";
+        let edit = get_string_edit(line, cursor(line), "access content").unwrap();
+        assert_eq!(cursor(line), edit.range.end);
+    }
+
+    #[test]
+    fn yaml_permission_edit() {
+        for (line, character, expected) in [
+            ("    _permission: ", 17, "'access content'"),
+            ("    _permission: acc", 20, "'access content'"),
+            (
+                "    _permission: 'administer nodes+acc'",
+                37,
+                "'administer nodes+access content'",
+            ),
+            ("    _permission: acc,foo", 20, "'access content,foo'"),
+        ] {
+            let position = Position { line: 0, character };
+            let edit = get_yaml_permission_edit(line, position, "access content").unwrap();
+            assert_eq!(expected, edit.new_text);
+            assert_eq!(17, edit.range.start.character);
+            assert_eq!(line.len() as u32, edit.range.end.character);
+        }
+
+        let position = Position {
+            line: 0,
+            character: 20,
+        };
+        let edit = get_yaml_permission_edit("    _permission: adm", position, "administer_nodes");
+        assert_eq!("administer_nodes", edit.unwrap().new_text);
+    }
+
+    #[test]
+    fn detect_yaml_route_name() {
+        let uri = "file:///foo/foo.links.task.yml";
+        for (line, expected) in [
+            ("  route_name: ", ""),
+            ("  base_route: 'entity.node.ca", "entity.node.ca"),
+        ] {
+            match get_yaml_token_data(uri, line, line.len()) {
+                Some(TokenData::DrupalRouteReference(name)) => assert_eq!(expected, name),
+                data => panic!("Unexpected token data {:?}", data),
+            }
+        }
+
+        assert!(get_yaml_token_data("file:///foo/foo.yml", "  route_name: ", 14).is_none());
+    }
+
+    #[test]
+    fn detect_yaml_menu_link_parent() {
+        for (line, expected) in [
+            ("  parent: ", ""),
+            ("  parent: 'system.admin_con", "system.admin_con"),
+        ] {
+            match get_yaml_token_data("file:///foo/foo.links.menu.yml", line, line.len()) {
+                Some(TokenData::DrupalMenuLinkReference(name)) => assert_eq!(expected, name),
+                data => panic!("Unexpected token data {:?}", data),
+            }
+        }
+
+        // Only menu links refer to their parent by a `parent` key.
+        assert!(get_yaml_token_data("file:///foo/foo.links.task.yml", "  parent: ", 10).is_none());
+    }
+
+    #[test]
+    fn detect_yaml_role() {
+        let uri = "file:///foo/foo.routing.yml";
+        for (line, expected) in [
+            ("    _role: ", ""),
+            ("    _role: 'administrator+edi", "edi"),
+        ] {
+            match get_yaml_token_data(uri, line, line.len()) {
+                Some(TokenData::DrupalRoleReference(name)) => assert_eq!(expected, name),
+                data => panic!("Unexpected token data {:?}", data),
+            }
+        }
+    }
+
+    #[test]
+    fn detect_yaml_controller() {
+        let uri = "file:///foo/foo.routing.yml";
+        match get_yaml_token_data(uri, "    _controller: '\\Drupal\\foo\\Con", 33) {
+            Some(TokenData::PhpClassReference(class_name)) => {
+                assert_eq!("Drupal\\foo\\Con", class_name.to_string())
+            }
+            data => panic!("Unexpected token data {:?}", data),
+        }
+
+        let line = "    _controller: Drupal\\foo\\Controller\\FooController::";
+        match get_yaml_token_data(uri, line, line.len()) {
+            Some(TokenData::PhpMethodReference(method)) => {
+                assert_eq!(
+                    Some("Drupal\\foo\\Controller\\FooController".to_string()),
+                    method.class_name.map(|class_name| class_name.to_string())
+                );
+                assert_eq!("", method.name);
+            }
+            data => panic!("Unexpected token data {:?}", data),
+        }
+    }
+}
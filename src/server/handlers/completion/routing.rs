@@ -0,0 +1,482 @@
+use std::sync::LazyLock;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionTextEdit,
+    Documentation, InsertTextFormat, Position, Range, TextEdit,
+};
+use regex::Regex;
+
+/// A key of a route definition, with the snippet that is inserted for it.
+struct RouteKey {
+    /// The key the key is nested under, or `None` for the keys of the route itself.
+    parent: Option<&'static str>,
+    key: &'static str,
+    /// The variant of the snippet, for keys with multiple common shapes.
+    variant: Option<&'static str>,
+    /// The snippet, indented by two spaces per level.
+    snippet: &'static str,
+    documentation: &'static str,
+}
+
+// The structure of a route definition in `*.routing.yml` files.
+const ROUTE_SCHEMA: &[RouteKey] = &[
+    RouteKey {
+        parent: None,
+        key: "path",
+        variant: None,
+        snippet: "path: '/${1}'",
+        documentation: "The path of the route, with `{parameters}` in curly braces.",
+    },
+    RouteKey {
+        parent: None,
+        key: "defaults",
+        variant: Some("controller"),
+        snippet: "defaults:\n  _controller: '${1}'\n  _title: '${2}'",
+        documentation: "The default values of the parameters, including the controller of the route.",
+    },
+    RouteKey {
+        parent: None,
+        key: "defaults",
+        variant: Some("form"),
+        snippet: "defaults:\n  _form: '${1}'\n  _title: '${2}'",
+        documentation: "The default values of the parameters, including the form of the route.",
+    },
+    RouteKey {
+        parent: None,
+        key: "requirements",
+        variant: None,
+        snippet: "requirements:\n  _permission: '${1:access content}'",
+        documentation: "The access checks and parameter requirements of the route.",
+    },
+    RouteKey {
+        parent: None,
+        key: "options",
+        variant: None,
+        snippet: "options:\n  ${1:_admin_route}: ${2:TRUE}",
+        documentation: "Additional options of the route, f.e `_admin_route` or `parameters`.",
+    },
+    RouteKey {
+        parent: None,
+        key: "methods",
+        variant: None,
+        snippet: "methods: [${1:GET}]",
+        documentation: "The HTTP methods the route responds to.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_controller",
+        variant: None,
+        snippet: "_controller: '${1}'",
+        documentation: "The controller method returning the response, f.e `\\Drupal\\mymodule\\Controller\\MyController::build`.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_form",
+        variant: None,
+        snippet: "_form: '${1}'",
+        documentation: "The form class, f.e `\\Drupal\\mymodule\\Form\\SettingsForm`.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_title",
+        variant: None,
+        snippet: "_title: '${1}'",
+        documentation: "The title of the page.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_title_callback",
+        variant: None,
+        snippet: "_title_callback: '${1}'",
+        documentation: "The method returning the title of the page.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_entity_form",
+        variant: None,
+        snippet: "_entity_form: '${1:node}.${2:default}'",
+        documentation: "The entity form, as the entity type and form mode.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_entity_list",
+        variant: None,
+        snippet: "_entity_list: '${1:node}'",
+        documentation: "The entity type to list.",
+    },
+    RouteKey {
+        parent: Some("defaults"),
+        key: "_entity_view",
+        variant: None,
+        snippet: "_entity_view: '${1:node}.${2:full}'",
+        documentation: "The entity to view, as the entity type and view mode.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_permission",
+        variant: None,
+        snippet: "_permission: '${1:access content}'",
+        documentation: "The permissions required, separated by `+` for any or `,` for all.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_role",
+        variant: None,
+        snippet: "_role: '${1:authenticated}'",
+        documentation: "The roles required, separated by `+` for any or `,` for all.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_access",
+        variant: None,
+        snippet: "_access: 'TRUE'",
+        documentation: "Allow access to everyone.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_custom_access",
+        variant: None,
+        snippet: "_custom_access: '${1}::access'",
+        documentation: "The method checking access.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_entity_access",
+        variant: None,
+        snippet: "_entity_access: '${1:node}.${2:view}'",
+        documentation: "The entity parameter and operation to check access for.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_entity_create_access",
+        variant: None,
+        snippet: "_entity_create_access: '${1:node}'",
+        documentation: "The entity type to check create access for.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_user_is_logged_in",
+        variant: None,
+        snippet: "_user_is_logged_in: '${1:TRUE}'",
+        documentation: "Whether the user must be logged in.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_csrf_token",
+        variant: None,
+        snippet: "_csrf_token: 'TRUE'",
+        documentation: "Require a CSRF token in the `token` query parameter.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_format",
+        variant: None,
+        snippet: "_format: '${1:json}'",
+        documentation: "The request formats the route responds to.",
+    },
+    RouteKey {
+        parent: Some("requirements"),
+        key: "_module_dependencies",
+        variant: None,
+        snippet: "_module_dependencies: '${1}'",
+        documentation: "The modules that must be enabled, separated by `+` for any or `,` for all.",
+    },
+    RouteKey {
+        parent: Some("options"),
+        key: "_admin_route",
+        variant: None,
+        snippet: "_admin_route: TRUE",
+        documentation: "Use the administration theme.",
+    },
+    RouteKey {
+        parent: Some("options"),
+        key: "no_cache",
+        variant: None,
+        snippet: "no_cache: TRUE",
+        documentation: "Don't cache the response in the page cache.",
+    },
+    RouteKey {
+        parent: Some("options"),
+        key: "parameters",
+        variant: None,
+        snippet: "parameters:\n  ${1:node}:\n    type: entity:${2:node}",
+        documentation: "The types of the parameters, to convert them f.e to entities.",
+    },
+    RouteKey {
+        parent: Some("options"),
+        key: "_auth",
+        variant: None,
+        snippet: "_auth: ['${1:basic_auth}']",
+        documentation: "The authentication providers of the route.",
+    },
+];
+
+// Snippets for whole route definitions, with `{module}` replaced by the name of the module.
+const ROUTE_SNIPPETS: &[(&str, &str)] = &[
+    (
+        "controller",
+        "{module}.${1:route_name}:\n  path: '/${2}'\n  defaults:\n    _controller: '${3}'\n    _title: '${4}'\n  requirements:\n    _permission: '${5:access content}'",
+    ),
+    (
+        "form",
+        "{module}.${1:route_name}:\n  path: '/${2}'\n  defaults:\n    _form: '${3}'\n    _title: '${4}'\n  requirements:\n    _permission: '${5:access content}'",
+    ),
+];
+
+// Matches a route name that is being typed at the top level, f.e `mymodule.sett`.
+static YAML_ROUTE_NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[\w.]*$").unwrap());
+
+// Matches a key that is being typed on its own line, f.e `    _cont`.
+static YAML_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?<indentation> +)(?<key>\w*)$").unwrap());
+
+// Matches the key of a line, f.e `defaults` in `  defaults:`.
+static YAML_LINE_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?<key>[\w.\-]+):").unwrap());
+
+/// Get completion items for the keys of a route definition that is being typed, based on the
+/// keys it is nested under, or snippets for a whole route at the top level.
+///
+/// Returns `None` when the cursor isn't at a key of a route definition.
+pub fn get_route_key_completion_items(
+    content: &str,
+    position: Position,
+    module_name: &str,
+) -> Option<Vec<CompletionItem>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let line_index = position.line as usize;
+    let before_cursor = lines
+        .get(line_index)
+        .unwrap_or(&"")
+        .get(..position.character as usize)?;
+    if YAML_ROUTE_NAME.is_match(before_cursor) {
+        return Some(get_route_snippet_items(&lines, position, module_name));
+    }
+    let captures = YAML_KEY.captures(before_cursor)?;
+    let indentation = captures.name("indentation")?.len();
+
+    let (parent_index, parent_indentation) = find_parent(&lines, line_index, indentation)?;
+    // Keys nested under the route are only known for the keys of the schema.
+    let parent = match parent_indentation {
+        0 => None,
+        _ => {
+            let (_, grandparent_indentation) =
+                find_parent(&lines, parent_index, parent_indentation)?;
+            if grandparent_indentation != 0 {
+                return None;
+            }
+            Some(
+                YAML_LINE_KEY
+                    .captures(lines[parent_index])?
+                    .name("key")?
+                    .as_str(),
+            )
+        }
+    };
+
+    let existing_keys: Vec<&str> = lines
+        .iter()
+        .enumerate()
+        .skip(parent_index + 1)
+        .take_while(|(_, line)| {
+            line.trim().is_empty() || get_indentation(line) > parent_indentation
+        })
+        .filter(|(index, line)| *index != line_index && get_indentation(line) == indentation)
+        .filter_map(|(_, line)| Some(YAML_LINE_KEY.captures(line)?.name("key")?.as_str()))
+        .collect();
+
+    let indentation_unit = " ".repeat(indentation - parent_indentation);
+    let range = Range {
+        start: Position {
+            line: position.line,
+            character: indentation as u32,
+        },
+        end: position,
+    };
+    Some(
+        ROUTE_SCHEMA
+            .iter()
+            .filter(|route_key| {
+                route_key.parent == parent && !existing_keys.contains(&route_key.key)
+            })
+            .map(|route_key| CompletionItem {
+                label: route_key.key.to_string(),
+                label_details: Some(CompletionItemLabelDetails {
+                    description: Some("Route key".to_string()),
+                    detail: route_key.variant.map(|variant| format!(" ({})", variant)),
+                }),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: Some(Documentation::String(route_key.documentation.to_string())),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    // Clients indent the following lines like the current line.
+                    new_text: route_key.snippet.replace("  ", &indentation_unit),
+                })),
+                deprecated: Some(false),
+                ..CompletionItem::default()
+            })
+            .collect(),
+    )
+}
+
+/// Get snippets for a whole route definition, indented like the other routes of the file.
+fn get_route_snippet_items(
+    lines: &[&str],
+    position: Position,
+    module_name: &str,
+) -> Vec<CompletionItem> {
+    let indentation_unit = lines
+        .iter()
+        .map(|line| get_indentation(line))
+        .find(|indentation| *indentation > 0)
+        .map(|indentation| " ".repeat(indentation))
+        .unwrap_or("  ".to_string());
+    let range = Range {
+        start: Position {
+            line: position.line,
+            character: 0,
+        },
+        end: position,
+    };
+    ROUTE_SNIPPETS
+        .iter()
+        .map(|(variant, snippet)| CompletionItem {
+            label: "route".to_string(),
+            label_details: Some(CompletionItemLabelDetails {
+                description: Some("Route".to_string()),
+                detail: Some(format!(" ({})", variant)),
+            }),
+            kind: Some(CompletionItemKind::SNIPPET),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: snippet
+                    .replace("{module}", module_name)
+                    .replace("  ", &indentation_unit),
+            })),
+            deprecated: Some(false),
+            ..CompletionItem::default()
+        })
+        .collect()
+}
+
+/// Get the index and indentation of the line that a line with the given indentation is nested
+/// under, skipping empty lines and comments.
+pub(super) fn find_parent(
+    lines: &[&str],
+    line_index: usize,
+    indentation: usize,
+) -> Option<(usize, usize)> {
+    lines[..line_index]
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| (index, get_indentation(line)))
+        .find(|(_, line_indentation)| *line_indentation < indentation)
+}
+
+fn get_indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_items(content: &str) -> Option<Vec<(String, String)>> {
+        let (line, column) = content
+            .lines()
+            .enumerate()
+            .find_map(|(line, text)| Some((line, text.find('|')?)))?;
+        let content = content.replace('|', "");
+        let items = get_route_key_completion_items(
+            &content,
+            Position {
+                line: line as u32,
+                character: column as u32,
+            },
+            "foo",
+        )?;
+        Some(
+            items
+                .into_iter()
+                .map(|item| match item.text_edit {
+                    Some(CompletionTextEdit::Edit(edit)) => (item.label, edit.new_text),
+                    _ => (item.label, String::new()),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn route_keys() {
+        let labels = |content: &str| {
+            get_items(content).map(|items| {
+                items
+                    .into_iter()
+                    .map(|(label, _)| label)
+                    .collect::<Vec<String>>()
+            })
+        };
+        assert_eq!(
+            Some(vec![
+                "defaults".to_string(),
+                "defaults".to_string(),
+                "requirements".to_string(),
+                "options".to_string(),
+                "methods".to_string(),
+            ]),
+            labels("foo.page:\n  path: '/foo'\n  |\n")
+        );
+        assert_eq!(
+            Some(vec![
+                "_controller".to_string(),
+                "_form".to_string(),
+                "_title_callback".to_string(),
+                "_entity_form".to_string(),
+                "_entity_list".to_string(),
+                "_entity_view".to_string(),
+            ]),
+            labels("foo.page:\n  path: '/foo'\n  defaults:\n    _|\n\n    # Comment\n    _title: 'Foo'\n  requirements:\n    _access: 'TRUE'\n")
+        );
+        assert!(
+            labels("foo.page:\n  path: '/foo'\n  requirements:\n    _access: 'TRUE'\n    |")
+                .is_some_and(|labels| labels.contains(&"_permission".to_string())
+                    && !labels.contains(&"_access".to_string()))
+        );
+
+        // Values aren't keys of the schema.
+        assert_eq!(None, labels("foo.page:\n  path: |"));
+        assert_eq!(
+            None,
+            labels("foo.page:\n  options:\n    parameters:\n      node:\n        |")
+        );
+    }
+
+    #[test]
+    fn route_key_snippets() {
+        let items = get_items("foo.page:\n    path: '/foo'\n    def|\n").unwrap();
+        assert_eq!(
+            (
+                "defaults".to_string(),
+                "defaults:\n    _controller: '${1}'\n    _title: '${2}'".to_string()
+            ),
+            items[0]
+        );
+    }
+
+    #[test]
+    fn route_snippets() {
+        let items = get_items("foo.page:\n    path: '/foo'\n\nfoo.ot|\n").unwrap();
+        assert_eq!(
+            (
+                "route".to_string(),
+                "foo.${1:route_name}:\n    path: '/${2}'\n    defaults:\n        _controller: '${3}'\n        _title: '${4}'\n    requirements:\n        _permission: '${5:access content}'".to_string()
+            ),
+            items[0]
+        );
+        assert_eq!(2, items.len());
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::LazyLock;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionTextEdit,
+    InsertTextFormat, Position, Range, TextEdit,
+};
+use regex::Regex;
+
+use super::routing::find_parent;
+
+// Matches a service name that is being typed under `services:`, f.e `  mymodule.fo`.
+static YAML_SERVICE_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?<indentation> +)[\w.]*$").unwrap());
+
+/// Get a snippet for a whole service definition, prefixed with the module name and with a choice
+/// of the classes of the module for its class.
+///
+/// The class names are only looked up when the cursor is at a service name. Returns `None` when it
+/// isn't.
+pub fn get_service_snippet_items(
+    content: &str,
+    position: Position,
+    module_name: &str,
+    get_class_names: impl FnOnce() -> Vec<String>,
+) -> Option<Vec<CompletionItem>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let line_index = position.line as usize;
+    let before_cursor = lines.get(line_index)?.get(..position.character as usize)?;
+    let indentation = YAML_SERVICE_NAME
+        .captures(before_cursor)?
+        .name("indentation")?
+        .len();
+    let (parent_index, parent_indentation) = find_parent(&lines, line_index, indentation)?;
+    if parent_indentation != 0 || lines[parent_index].trim_end() != "services:" {
+        return None;
+    }
+
+    let class = match get_class_names().as_slice() {
+        [] => format!("${{2:Drupal\\\\{}\\\\}}", module_name),
+        class_names => format!(
+            "${{2|{}|}}",
+            class_names
+                .iter()
+                .map(|class_name| escape_choice(class_name))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+    };
+    let unit = " ".repeat(indentation);
+    Some(vec![CompletionItem {
+        label: "service".to_string(),
+        label_details: Some(CompletionItemLabelDetails {
+            description: Some("Service".to_string()),
+            detail: None,
+        }),
+        kind: Some(CompletionItemKind::SNIPPET),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range {
+                start: Position {
+                    line: position.line,
+                    character: indentation as u32,
+                },
+                end: position,
+            },
+            // Clients indent the following lines like the current line.
+            new_text: format!(
+                "{}.${{1:service_name}}:\n{}class: {}\n{}arguments: [${{3}}]",
+                module_name, unit, class, unit
+            ),
+        })),
+        deprecated: Some(false),
+        ..CompletionItem::default()
+    }])
+}
+
+/// Escape the characters that have a meaning in a snippet choice, f.e the backslashes of a class
+/// name.
+fn escape_choice(text: &str) -> String {
+    text.chars()
+        .flat_map(|character| match character {
+            '\\' | '$' | '}' | ',' | '|' => vec!['\\', character],
+            _ => vec![character],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_snippet(content: &str, class_names: &[&str]) -> Option<String> {
+        let (line, column) = content
+            .lines()
+            .enumerate()
+            .find_map(|(line, text)| Some((line, text.find('|')?)))?;
+        let content = content.replace('|', "");
+        let items = get_service_snippet_items(
+            &content,
+            Position {
+                line: line as u32,
+                character: column as u32,
+            },
+            "foo",
+            || class_names.iter().map(|name| name.to_string()).collect(),
+        )?;
+        match &items[0].text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => Some(edit.new_text.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn service_snippet() {
+        assert_eq!(
+            Some(
+                "foo.${1:service_name}:\n  class: ${2|Drupal\\\\foo\\\\Bar,Drupal\\\\foo\\\\Baz|}\n  arguments: [${3}]"
+                    .to_string()
+            ),
+            get_snippet(
+                "services:\n  foo.bar:\n    class: Drupal\\foo\\Bar\n\n  foo.|\n",
+                &["Drupal\\foo\\Bar", "Drupal\\foo\\Baz"]
+            )
+        );
+        assert_eq!(
+            Some(
+                "foo.${1:service_name}:\n  class: ${2:Drupal\\\\foo\\\\}\n  arguments: [${3}]"
+                    .to_string()
+            ),
+            get_snippet("services:\n  |", &[])
+        );
+
+        // Only service names are completed, not the keys of services.
+        assert_eq!(
+            None,
+            get_snippet("services:\n  foo.bar:\n    cl|", &["Drupal\\foo\\Bar"])
+        );
+        assert_eq!(None, get_snippet("parameters:\n  |", &["Drupal\\foo\\Bar"]));
+    }
+}
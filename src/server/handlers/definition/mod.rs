@@ -1,9 +1,12 @@
 use lsp_server::{ErrorCode, Request, Response};
-use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Position, Range};
+use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse, Location, LocationLink, Range};
 
-use crate::document_store::DOCUMENT_STORE;
-use crate::parser::tokens::{Token, TokenData};
-use crate::server::handle_request::get_response_error;
+use crate::document_store::document::Document;
+use crate::document_store::{DocumentStore, DOCUMENT_STORE};
+use crate::parser::token_range_to_range;
+use crate::parser::tokens::{get_cache_context_service, Token, TokenData};
+use crate::server::{get_client_capabilities, handle_request::get_response_error};
+use crate::settings::{get_settings, ServiceDefinitionTarget};
 
 pub fn handle_text_document_definition(request: Request) -> Option<Response> {
     let params = match serde_json::from_value::<GotoDefinitionParams>(request.params) {
@@ -29,9 +32,18 @@ pub fn handle_text_document_definition(request: Request) -> Option<Response> {
     }
 
     let Some(token) = token else {
+        // Templates go to the definition of their theme hook, outside of any reference.
+        let result = get_template_theme_definition(
+            params
+                .text_document_position_params
+                .text_document
+                .uri
+                .as_str(),
+        )
+        .and_then(|location| serde_json::to_value(GotoDefinitionResponse::Scalar(location)).ok());
         return Some(Response {
             id: request.id,
-            result: Some(serde_json::Value::Null),
+            result: Some(result.unwrap_or(serde_json::Value::Null)),
             error: None,
         });
     };
@@ -52,30 +64,167 @@ pub fn handle_text_document_definition(request: Request) -> Option<Response> {
 }
 
 fn provide_definition_for_token(token: &Token) -> Option<GotoDefinitionResponse> {
+    let mut locations = get_definition_locations(token)?;
+    if locations.is_empty() {
+        return None;
+    }
+
+    // Links let the client highlight the whole reference, rather than the word under the cursor.
+    let link_support = get_client_capabilities()
+        .and_then(|capabilities| {
+            capabilities
+                .text_document
+                .as_ref()?
+                .definition?
+                .link_support
+        })
+        .unwrap_or(false);
+    if link_support {
+        let origin_selection_range = token_range_to_range(&token.range);
+        return Some(GotoDefinitionResponse::Link(
+            locations
+                .into_iter()
+                .map(|location| LocationLink {
+                    origin_selection_range: Some(origin_selection_range),
+                    target_uri: location.uri,
+                    target_range: location.range,
+                    target_selection_range: location.range,
+                })
+                .collect(),
+        ));
+    }
+
+    match locations.len() {
+        1 => locations.pop().map(GotoDefinitionResponse::Scalar),
+        _ => Some(GotoDefinitionResponse::Array(locations)),
+    }
+}
+
+/// Get the locations a token is defined at, most tokens have a single definition.
+fn get_definition_locations(token: &Token) -> Option<Vec<Location>> {
     let store = DOCUMENT_STORE.lock().unwrap();
+    let get_location = |(document, token): (&Document, &Token)| {
+        Some(Location {
+            uri: document.get_uri()?,
+            range: token_range_to_range(&token.range),
+        })
+    };
 
-    let (source_document, token) = match &token.data {
+    // Templates are whole documents rather than tokens.
+    let template = match &token.data {
+        TokenData::TwigTemplateReference(name) => store.get_template_definition(name),
+        TokenData::DrupalThemeReference(name) => store.get_theme_template(name),
+        TokenData::DrupalThemeDefinition(theme) => store.get_theme_template(&theme.name),
+        _ => None,
+    };
+    if let Some(template) = template {
+        return Some(vec![Location {
+            uri: template.get_uri()?,
+            range: Range::default(),
+        }]);
+    }
+    if let TokenData::DrupalThemeSuggestion(suggestion) = &token.data {
+        return Some(
+            store
+                .get_theme_suggestion_templates(suggestion)
+                .into_iter()
+                .filter_map(|template| {
+                    Some(Location {
+                        uri: template.get_uri()?,
+                        range: Range::default(),
+                    })
+                })
+                .collect(),
+        );
+    }
+
+    let definitions = match &token.data {
+        // Config goes to its default config file, or to its schema when no module ships it.
+        TokenData::DrupalConfigReference(config_name) => {
+            if let Some(document) = store.get_default_config(config_name) {
+                return Some(vec![Location {
+                    uri: document.get_uri()?,
+                    range: Range::default(),
+                }]);
+            }
+            store
+                .get_config_schema_definition(config_name)
+                .into_iter()
+                .collect()
+        }
+        // A string can be translated to multiple languages.
+        TokenData::DrupalTranslationString(translation_string) => store
+            .get_translations(&translation_string.string)
+            .into_iter()
+            .map(|(document, token, _)| (document, token))
+            .collect(),
+        // Services are defined in YAML, but implemented by a class.
+        TokenData::DrupalServiceReference(name) => {
+            let definition = store.get_service_definition(name)?;
+            let class_definition = store
+                .get_service_class(name)
+                .and_then(|class_name| store.get_class_definition(class_name));
+            match (&get_settings().definition.service, class_definition) {
+                (ServiceDefinitionTarget::Both, Some(class_definition)) => {
+                    vec![definition, class_definition]
+                }
+                (ServiceDefinitionTarget::Class, Some(class_definition)) => vec![class_definition],
+                _ => vec![definition],
+            }
+        }
+        TokenData::DrupalPermissionReference(name) => store.get_permission_definitions(name),
+        TokenData::DrupalHookDefinition(hook) => store.get_hook_implementations(&hook.name),
+        data => get_definition(&store, data).into_iter().collect(),
+    };
+
+    Some(definitions.into_iter().filter_map(get_location).collect())
+}
+
+/// Get the definition of the theme hook a template renders, falling back to the base hooks of
+/// suggestions, f.e `node` for `node--article--teaser.html.twig`.
+fn get_template_theme_definition(uri: &str) -> Option<Location> {
+    let file_name = uri
+        .rsplit('/')
+        .next()
+        .filter(|file_name| file_name.ends_with(".html.twig"))?;
+    let store = DOCUMENT_STORE.lock().unwrap();
+    let mut theme_name = store.get_theme_name_for_template(file_name);
+    loop {
+        if let Some((document, token)) = store.get_theme_definition(&theme_name) {
+            return Some(Location {
+                uri: document.get_uri()?,
+                range: token_range_to_range(&token.range),
+            });
+        }
+        theme_name = theme_name.rsplit_once("__")?.0.to_string();
+    }
+}
+
+/// Get the definition of tokens that have a single definition.
+fn get_definition<'a>(
+    store: &'a DocumentStore,
+    data: &TokenData,
+) -> Option<(&'a Document, &'a Token)> {
+    match data {
         TokenData::PhpClassReference(class) => store.get_class_definition(class),
         TokenData::PhpMethodReference(method) => store.get_method_definition(method),
-        TokenData::DrupalServiceReference(name) => store.get_service_definition(name),
         TokenData::DrupalRouteReference(name) => store.get_route_definition(name),
         TokenData::DrupalHookReference(name) => store.get_hook_definition(name),
-        TokenData::DrupalPermissionReference(name) => store.get_permission_definition(name),
         TokenData::DrupalPluginReference(plugin_id) => store.get_plugin_definition(plugin_id),
+        TokenData::DrupalThemeReference(name) => store.get_theme_definition(name),
+        TokenData::DrupalLibraryReference(name) => store.get_library_definition(name),
+        TokenData::DrupalRoleReference(role_id) => store.get_role_definition(role_id),
+        TokenData::DrupalModuleReference(name) => store.get_extension_definition(name),
+        TokenData::DrupalConfigSchemaReference(name) => store.get_config_schema_definition(name),
+        TokenData::DrupalEntityBundleReference(reference) => {
+            store.get_entity_bundle_definition(&reference.entity_type, &reference.bundle)
+        }
+        TokenData::DrupalMenuLinkReference(menu_link_id) => {
+            store.get_menu_link_definition(menu_link_id)
+        }
+        TokenData::DrupalCacheContextReference(context) => {
+            store.get_service_definition(&get_cache_context_service(context))
+        }
         _ => None,
-    }?;
-
-    Some(GotoDefinitionResponse::Scalar(lsp_types::Location {
-        uri: source_document.get_uri()?,
-        range: Range {
-            start: Position {
-                line: token.range.start_point.row as u32,
-                character: token.range.start_point.column as u32,
-            },
-            end: Position {
-                line: token.range.end_point.row as u32,
-                character: token.range.end_point.column as u32,
-            },
-        },
-    }))
+    }
 }
@@ -0,0 +1,333 @@
+mod module;
+mod plugin;
+mod rename;
+mod theme;
+
+use std::path::{Path, PathBuf};
+
+use lsp_server::{ErrorCode, Request, Response};
+use lsp_types::{
+    request::ApplyWorkspaceEdit, ApplyWorkspaceEditParams, CreateFile, DocumentChangeOperation,
+    DocumentChanges, ExecuteCommandParams, OneOf, OptionalVersionedTextDocumentIdentifier, Range,
+    ResourceOp, TextDocumentEdit, TextEdit, Uri, WorkspaceEdit,
+};
+use serde_json::Value;
+
+use crate::{
+    diagnostics::audit::get_deprecated_apis,
+    document_store::DOCUMENT_STORE,
+    server::{get_root_dir, handle_request::get_response_error, send_request},
+    utils::{find_info_file, get_extension_name, path_to_uri, uri_to_url},
+};
+
+use self::plugin::{get_class_name, get_label, PLUGIN_SCAFFOLDS};
+
+pub const GENERATE_PLUGIN: &str = "drupal_ls.generatePlugin";
+pub const GENERATE_MODULE: &str = "drupal_ls.generateModule";
+pub const GENERATE_THEME: &str = "drupal_ls.generateTheme";
+pub const AUDIT: &str = "drupal_ls.audit";
+pub const RENAME_MODULE: &str = "drupal_ls.renameModule";
+
+pub const COMMANDS: &[&str] = &[
+    GENERATE_PLUGIN,
+    GENERATE_MODULE,
+    GENERATE_THEME,
+    AUDIT,
+    RENAME_MODULE,
+];
+
+pub fn handle_workspace_execute_command(request: Request) -> Option<Response> {
+    let params = match serde_json::from_value::<ExecuteCommandParams>(request.params) {
+        Err(err) => {
+            return Some(get_response_error(
+                request.id,
+                ErrorCode::InvalidParams,
+                format!("Could not parse execute command params: {:?}", err),
+            ));
+        }
+        Ok(value) => value,
+    };
+
+    // The audit returns the deprecated APIs and their usages, rather than editing the workspace.
+    if params.command == AUDIT {
+        let apis = get_deprecated_apis(&DOCUMENT_STORE.lock().unwrap());
+        return Some(Response {
+            id: request.id,
+            result: serde_json::to_value(apis).ok(),
+            error: None,
+        });
+    }
+
+    let edit = match params.command.as_str() {
+        GENERATE_PLUGIN => get_generate_plugin_edit(&params.arguments),
+        GENERATE_MODULE => get_generate_module_edit(&params.arguments),
+        GENERATE_THEME => get_generate_theme_edit(&params.arguments),
+        RENAME_MODULE => get_rename_module_edit(&params.arguments),
+        command => Err(format!("Unknown command: {}", command)),
+    };
+
+    match edit {
+        Ok(edit) => {
+            send_request::<ApplyWorkspaceEdit>(ApplyWorkspaceEditParams {
+                label: Some(params.command),
+                edit,
+            });
+            None
+        }
+        Err(message) => Some(get_response_error(
+            request.id,
+            ErrorCode::InvalidParams,
+            message,
+        )),
+    }
+}
+
+/// Generate a plugin class.
+///
+/// Expects the plugin type, the plugin id and the uri of a file in the module to add it to.
+fn get_generate_plugin_edit(arguments: &[Value]) -> Result<WorkspaceEdit, String> {
+    let arguments: Vec<&str> = arguments.iter().filter_map(Value::as_str).collect();
+    let [plugin_type, plugin_id, uri] = arguments.as_slice() else {
+        return Err(format!(
+            "{} expects a plugin type, plugin id and uri",
+            GENERATE_PLUGIN
+        ));
+    };
+    if !is_valid_machine_name(plugin_id) {
+        return Err(format!("Invalid plugin id: {}", plugin_id));
+    }
+
+    let scaffold = PLUGIN_SCAFFOLDS
+        .iter()
+        .find(|scaffold| scaffold.plugin_type == *plugin_type)
+        .ok_or_else(|| format!("Unsupported plugin type: {}", plugin_type))?;
+    let (module_name, module_directory) = get_module(uri)?;
+
+    let path = module_directory
+        .join("src/Plugin")
+        .join(scaffold.directory)
+        .join(format!("{}.php", get_class_name(plugin_id)));
+    let content =
+        scaffold.get_class_content(&module_name, plugin_id, &DOCUMENT_STORE.lock().unwrap());
+
+    Ok(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(get_create_file_operations(
+            &path, content,
+        )?)),
+        change_annotations: None,
+    })
+}
+
+/// Generate a module in the custom modules directory.
+///
+/// Expects the machine name of the module, and optionally whether to add a `src` directory.
+fn get_generate_module_edit(arguments: &[Value]) -> Result<WorkspaceEdit, String> {
+    let Some(module_name) = arguments.first().and_then(|argument| argument.as_str()) else {
+        return Err(format!("{} expects a module name", GENERATE_MODULE));
+    };
+    if !is_valid_machine_name(module_name) {
+        return Err(format!("Invalid module name: {}", module_name));
+    }
+    let with_src = arguments
+        .get(1)
+        .and_then(|argument| argument.as_bool())
+        .unwrap_or(false);
+
+    let root_dir = get_root_dir().ok_or("The workspace has not been initialized")?;
+    let module_directory = get_docroot(Path::new(root_dir))
+        .join("modules/custom")
+        .join(module_name);
+    if module_directory.exists() {
+        return Err(format!("{} already exists", module_directory.display()));
+    }
+
+    let label = get_label(module_name);
+    let mut files = vec![
+        (
+            module_directory.join(format!("{}.info.yml", module_name)),
+            module::get_info_file_content(&label),
+        ),
+        (
+            module_directory.join(format!("{}.module", module_name)),
+            module::get_module_file_content(&label),
+        ),
+    ];
+    if with_src {
+        let class_name = format!("{}Hooks", get_class_name(module_name));
+        files.push((
+            module_directory.join(format!("src/Hook/{}.php", class_name)),
+            module::get_hooks_class_content(module_name, &class_name),
+        ));
+    }
+
+    let mut operations = vec![];
+    for (path, content) in files {
+        operations.extend(get_create_file_operations(&path, content)?);
+    }
+    Ok(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    })
+}
+
+/// Generate a theme in the custom themes directory.
+///
+/// Expects the machine name of the theme, and optionally its base theme (`false` by default) and
+/// the machine names of its regions.
+fn get_generate_theme_edit(arguments: &[Value]) -> Result<WorkspaceEdit, String> {
+    let Some(theme_name) = arguments.first().and_then(|argument| argument.as_str()) else {
+        return Err(format!("{} expects a theme name", GENERATE_THEME));
+    };
+    if !is_valid_machine_name(theme_name) {
+        return Err(format!("Invalid theme name: {}", theme_name));
+    }
+    let base_theme = arguments
+        .get(1)
+        .and_then(|argument| argument.as_str())
+        .unwrap_or("false");
+    if base_theme != "false" && !is_valid_machine_name(base_theme) {
+        return Err(format!("Invalid base theme: {}", base_theme));
+    }
+    let mut regions: Vec<&str> = match arguments.get(2).and_then(|argument| argument.as_array()) {
+        Some(regions) => regions.iter().filter_map(Value::as_str).collect(),
+        None => theme::DEFAULT_REGIONS.to_vec(),
+    };
+    if let Some(region) = regions.iter().find(|region| !is_valid_machine_name(region)) {
+        return Err(format!("Invalid region: {}", region));
+    }
+    // Drupal requires a content region to render the main content in.
+    if !regions.contains(&"content") {
+        regions.push("content");
+    }
+
+    let root_dir = get_root_dir().ok_or("The workspace has not been initialized")?;
+    let theme_directory = get_docroot(Path::new(root_dir))
+        .join("themes/custom")
+        .join(theme_name);
+    if theme_directory.exists() {
+        return Err(format!("{} already exists", theme_directory.display()));
+    }
+
+    let files = [
+        (
+            theme_directory.join(format!("{}.info.yml", theme_name)),
+            theme::get_info_file_content(theme_name, &get_label(theme_name), base_theme, &regions),
+        ),
+        (
+            theme_directory.join(format!("{}.libraries.yml", theme_name)),
+            theme::get_libraries_file_content(),
+        ),
+        (theme_directory.join("css/style.css"), String::new()),
+        // Workspace edits can't create empty directories.
+        (theme_directory.join("templates/.gitkeep"), String::new()),
+    ];
+
+    let mut operations = vec![];
+    for (path, content) in files {
+        operations.extend(get_create_file_operations(&path, content)?);
+    }
+    Ok(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    })
+}
+
+/// Rename the machine name of a module, including its files, hook implementations, services,
+/// routes, permissions and config, and every reference to them in the workspace.
+///
+/// Expects the uri of a file in the module and the new machine name. The edits are annotated, so
+/// that the client asks the user to review them before applying them.
+fn get_rename_module_edit(arguments: &[Value]) -> Result<WorkspaceEdit, String> {
+    let arguments: Vec<&str> = arguments.iter().filter_map(Value::as_str).collect();
+    let [uri, new_name] = arguments.as_slice() else {
+        return Err(format!("{} expects a uri and module name", RENAME_MODULE));
+    };
+    if !is_valid_machine_name(new_name) {
+        return Err(format!("Invalid module name: {}", new_name));
+    }
+
+    let (old_name, module_directory) = get_module(uri)?;
+    if old_name == *new_name {
+        return Err(format!("The module is already named {}", new_name));
+    }
+    if module_directory.with_file_name(new_name).exists() {
+        return Err(format!(
+            "{} already exists",
+            module_directory.with_file_name(new_name).display()
+        ));
+    }
+
+    let operations = rename::get_rename_module_operations(
+        &DOCUMENT_STORE.lock().unwrap(),
+        &old_name,
+        new_name,
+        &module_directory,
+    );
+    Ok(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: Some(rename::get_change_annotations(&old_name, new_name)),
+    })
+}
+
+/// Get the docroot of the project, taking the common docroot directories into account.
+fn get_docroot(root_dir: &Path) -> PathBuf {
+    ["web", "docroot", "html"]
+        .iter()
+        .map(|docroot| root_dir.join(docroot))
+        .find(|docroot| docroot.join("modules").is_dir())
+        .unwrap_or(root_dir.to_path_buf())
+}
+
+/// Whether a name is a valid machine name, f.e `my_module`.
+fn is_valid_machine_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(|character| {
+            character.is_ascii_lowercase() || character.is_ascii_digit() || character == '_'
+        })
+        && !name.starts_with(|character: char| character.is_ascii_digit())
+}
+
+/// Get the name and directory of the module that a file belongs to.
+fn get_module(uri: &str) -> Result<(String, PathBuf), String> {
+    let path = uri
+        .parse::<Uri>()
+        .ok()
+        .and_then(uri_to_url)
+        .ok_or_else(|| format!("Invalid uri: {}", uri))?;
+    let info_file = find_info_file(Path::new(&path))
+        .ok_or_else(|| format!("Unable to find the module for {}", uri))?;
+
+    match (get_extension_name(&info_file), info_file.parent()) {
+        (Some(name), Some(directory)) => Ok((name.to_string(), directory.to_path_buf())),
+        _ => Err(format!("Unable to find the module for {}", uri)),
+    }
+}
+
+fn get_create_file_operations(
+    path: &Path,
+    content: String,
+) -> Result<Vec<DocumentChangeOperation>, String> {
+    let uri: Uri = path_to_uri(path)
+        .and_then(|uri| uri.parse().ok())
+        .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+
+    Ok(vec![
+        DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+            uri: uri.clone(),
+            // Never overwrite existing files, the client rejects the whole edit instead.
+            options: None,
+            annotation_id: None,
+        })),
+        DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+            edits: vec![OneOf::Left(TextEdit {
+                range: Range::default(),
+                new_text: content,
+            })],
+        }),
+    ])
+}
@@ -0,0 +1,43 @@
+pub fn get_info_file_content(label: &str) -> String {
+    format!(
+        r#"name: '{}'
+type: module
+description: ''
+package: Custom
+core_version_requirement: ^10.3 || ^11
+"#,
+        label
+    )
+}
+
+pub fn get_module_file_content(label: &str) -> String {
+    format!(
+        r#"<?php
+
+/**
+ * @file
+ * Primary module hooks for {} module.
+ */
+"#,
+        label
+    )
+}
+
+pub fn get_hooks_class_content(module_name: &str, class_name: &str) -> String {
+    format!(
+        r#"<?php
+
+declare(strict_types=1);
+
+namespace Drupal\{}\Hook;
+
+/**
+ * Hook implementations for {}.
+ */
+final class {} {{
+
+}}
+"#,
+        module_name, module_name, class_name
+    )
+}
@@ -0,0 +1,338 @@
+use crate::{
+    document_store::DocumentStore,
+    parser::tokens::{PhpClassName, TokenData},
+};
+
+pub struct PluginScaffold {
+    pub plugin_type: &'static str,
+    /// The directory below `src/Plugin` where plugins of this type are discovered.
+    pub directory: &'static str,
+    pub attribute: &'static str,
+    /// The attribute arguments, where `@id` and `@label` are replaced.
+    pub attribute_arguments: &'static str,
+    pub base_class: &'static str,
+    /// The classes used by the attribute arguments and the methods.
+    pub imports: &'static [&'static str],
+    pub methods: &'static [PluginMethod],
+}
+
+pub struct PluginMethod {
+    pub name: &'static str,
+    pub body: &'static str,
+}
+
+const TRANSLATABLE_MARKUP: &str = "Drupal\\Core\\StringTranslation\\TranslatableMarkup";
+
+pub const PLUGIN_SCAFFOLDS: &[PluginScaffold] = &[
+    PluginScaffold {
+        plugin_type: "Block",
+        directory: "Block",
+        attribute: "Drupal\\Core\\Block\\Attribute\\Block",
+        attribute_arguments: "  id: '@id',\n  admin_label: new TranslatableMarkup('@label'),\n",
+        base_class: "Drupal\\Core\\Block\\BlockBase",
+        imports: &[TRANSLATABLE_MARKUP],
+        methods: &[PluginMethod {
+            name: "build",
+            body: r#"  public function build(): array {
+    return [];
+  }
+"#,
+        }],
+    },
+    PluginScaffold {
+        plugin_type: "QueueWorker",
+        directory: "QueueWorker",
+        attribute: "Drupal\\Core\\Queue\\Attribute\\QueueWorker",
+        attribute_arguments:
+            "  id: '@id',\n  title: new TranslatableMarkup('@label'),\n  cron: ['time' => 60],\n",
+        base_class: "Drupal\\Core\\Queue\\QueueWorkerBase",
+        imports: &[TRANSLATABLE_MARKUP],
+        methods: &[PluginMethod {
+            name: "processItem",
+            body: r#"  public function processItem($data): void {
+  }
+"#,
+        }],
+    },
+    PluginScaffold {
+        plugin_type: "Condition",
+        directory: "Condition",
+        attribute: "Drupal\\Core\\Condition\\Attribute\\Condition",
+        attribute_arguments: "  id: '@id',\n  label: new TranslatableMarkup('@label'),\n",
+        base_class: "Drupal\\Core\\Condition\\ConditionPluginBase",
+        imports: &[TRANSLATABLE_MARKUP],
+        methods: &[
+            PluginMethod {
+                name: "evaluate",
+                body: r#"  public function evaluate(): bool {
+    return TRUE;
+  }
+"#,
+            },
+            PluginMethod {
+                name: "summary",
+                body: r#"  public function summary(): string {
+    return '';
+  }
+"#,
+            },
+        ],
+    },
+    PluginScaffold {
+        plugin_type: "Action",
+        directory: "Action",
+        attribute: "Drupal\\Core\\Action\\Attribute\\Action",
+        attribute_arguments: "  id: '@id',\n  label: new TranslatableMarkup('@label'),\n",
+        base_class: "Drupal\\Core\\Action\\ActionBase",
+        imports: &[
+            TRANSLATABLE_MARKUP,
+            "Drupal\\Core\\Access\\AccessResultInterface",
+            "Drupal\\Core\\Session\\AccountInterface",
+        ],
+        methods: &[
+            PluginMethod {
+                name: "execute",
+                body: r#"  public function execute($entity = NULL): void {
+  }
+"#,
+            },
+            PluginMethod {
+                name: "access",
+                body: r#"  public function access($object, ?AccountInterface $account = NULL, $return_as_object = FALSE): bool|AccessResultInterface {
+    return $return_as_object ? AccessResult::allowed() : TRUE;
+  }
+"#,
+            },
+        ],
+    },
+    PluginScaffold {
+        plugin_type: "FieldFormatter",
+        directory: "Field/FieldFormatter",
+        attribute: "Drupal\\Core\\Field\\Attribute\\FieldFormatter",
+        attribute_arguments:
+            "  id: '@id',\n  label: new TranslatableMarkup('@label'),\n  field_types: [],\n",
+        base_class: "Drupal\\Core\\Field\\FormatterBase",
+        imports: &[
+            TRANSLATABLE_MARKUP,
+            "Drupal\\Core\\Field\\FieldItemListInterface",
+        ],
+        methods: &[PluginMethod {
+            name: "viewElements",
+            body: r#"  public function viewElements(FieldItemListInterface $items, $langcode): array {
+    $elements = [];
+    foreach ($items as $delta => $item) {
+      $elements[$delta] = [];
+    }
+    return $elements;
+  }
+"#,
+        }],
+    },
+    PluginScaffold {
+        plugin_type: "FieldWidget",
+        directory: "Field/FieldWidget",
+        attribute: "Drupal\\Core\\Field\\Attribute\\FieldWidget",
+        attribute_arguments:
+            "  id: '@id',\n  label: new TranslatableMarkup('@label'),\n  field_types: [],\n",
+        base_class: "Drupal\\Core\\Field\\WidgetBase",
+        imports: &[
+            TRANSLATABLE_MARKUP,
+            "Drupal\\Core\\Field\\FieldItemListInterface",
+            "Drupal\\Core\\Form\\FormStateInterface",
+        ],
+        methods: &[PluginMethod {
+            name: "formElement",
+            body: r#"  public function formElement(FieldItemListInterface $items, $delta, array $element, array &$form, FormStateInterface $form_state): array {
+    return $element;
+  }
+"#,
+        }],
+    },
+];
+
+impl PluginScaffold {
+    pub fn get_class_content(
+        &self,
+        module_name: &str,
+        plugin_id: &str,
+        store: &DocumentStore,
+    ) -> String {
+        let class_name = get_class_name(plugin_id);
+        let attribute = PhpClassName::from(self.attribute);
+        let base_class = PhpClassName::from(self.base_class);
+
+        let methods: Vec<&PluginMethod> = self.get_required_methods(&base_class, store);
+        // Classes that are only used by methods the base class already implements aren't imported.
+        let mut imports: Vec<&str> = self
+            .imports
+            .iter()
+            .copied()
+            .filter(|import| {
+                let short_name = PhpClassName::from(*import).get_short_name().to_string();
+                self.attribute_arguments.contains(&short_name)
+                    || methods
+                        .iter()
+                        .any(|method| method.body.contains(&short_name))
+            })
+            .collect();
+        imports.extend([self.attribute, self.base_class]);
+        if methods
+            .iter()
+            .any(|method| method.body.contains("AccessResult::"))
+        {
+            imports.push("Drupal\\Core\\Access\\AccessResult");
+        }
+        imports.sort_by_key(|import| import.to_lowercase());
+        imports.dedup();
+
+        format!(
+            r#"<?php
+
+declare(strict_types=1);
+
+namespace Drupal\{}\Plugin\{};
+
+{}
+
+/**
+ * Provides a {} plugin.
+ */
+#[{}(
+{})]
+final class {} extends {} {{
+{}
+}}
+"#,
+            module_name,
+            self.directory.replace('/', "\\"),
+            imports
+                .iter()
+                .map(|import| format!("use {};", import))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            plugin_id,
+            attribute.get_short_name(),
+            self.attribute_arguments
+                .replace("@id", plugin_id)
+                .replace("@label", &get_label(plugin_id)),
+            class_name,
+            base_class.get_short_name(),
+            methods
+                .iter()
+                .map(|method| format!("\n  /**\n   * {{@inheritdoc}}\n   */\n{}", method.body))
+                .collect::<Vec<String>>()
+                .join("")
+        )
+    }
+
+    /// Get the method stubs that the base class doesn't already implement.
+    fn get_required_methods(
+        &self,
+        base_class: &PhpClassName,
+        store: &DocumentStore,
+    ) -> Vec<&PluginMethod> {
+        let implemented_methods = match store.get_class_definition(base_class) {
+            Some((_, token)) => match &token.data {
+                TokenData::PhpClassDefinition(class) => class.methods.keys().collect(),
+                _ => vec![],
+            },
+            None => vec![],
+        };
+
+        self.methods
+            .iter()
+            .filter(|method| !implemented_methods.iter().any(|name| *name == method.name))
+            .collect()
+    }
+}
+
+/// Get the class name for a plugin id, f.e `my_block` becomes `MyBlock`.
+pub fn get_class_name(plugin_id: &str) -> String {
+    plugin_id
+        .split(['_', '-', '.', ':'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut part = part.to_string();
+            if let Some(first) = part.get_mut(0..1) {
+                first.make_ascii_uppercase();
+            }
+            part
+        })
+        .collect()
+}
+
+pub fn get_label(plugin_id: &str) -> String {
+    let mut label = plugin_id.replace(['_', '-', '.', ':'], " ");
+    if let Some(first) = label.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    label.replace('\'', "\\'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_block_plugin() {
+        let scaffold = PLUGIN_SCAFFOLDS
+            .iter()
+            .find(|scaffold| scaffold.plugin_type == "Block")
+            .unwrap();
+        let content = scaffold.get_class_content("my_module", "my_block", &DocumentStore::new());
+
+        assert_eq!(
+            r#"<?php
+
+declare(strict_types=1);
+
+namespace Drupal\my_module\Plugin\Block;
+
+use Drupal\Core\Block\Attribute\Block;
+use Drupal\Core\Block\BlockBase;
+use Drupal\Core\StringTranslation\TranslatableMarkup;
+
+/**
+ * Provides a my_block plugin.
+ */
+#[Block(
+  id: 'my_block',
+  admin_label: new TranslatableMarkup('My block'),
+)]
+final class MyBlock extends BlockBase {
+
+  /**
+   * {@inheritdoc}
+   */
+  public function build(): array {
+    return [];
+  }
+
+}
+"#,
+            content
+        );
+    }
+
+    #[test]
+    fn generate_action_plugin_without_implemented_methods() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/lib/Drupal/Core/Action/ActionBase.php",
+            String::from(
+                "<?php\n\nnamespace Drupal\\Core\\Action;\n\nabstract class ActionBase {\n  public function access($object, $account = NULL, $return_as_object = FALSE) {\n  }\n}\n",
+            ),
+        );
+        let scaffold = PLUGIN_SCAFFOLDS
+            .iter()
+            .find(|scaffold| scaffold.plugin_type == "Action")
+            .unwrap();
+        let content = scaffold.get_class_content("my_module", "my_action", &store);
+
+        assert!(content.contains("public function execute("));
+        assert!(!content.contains("public function access("));
+        assert!(!content.contains("AccessResult"));
+        assert!(!content.contains("AccountInterface"));
+        assert!(content.contains("use Drupal\\Core\\StringTranslation\\TranslatableMarkup;"));
+    }
+}
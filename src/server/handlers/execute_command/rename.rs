@@ -0,0 +1,572 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use ignore::WalkBuilder;
+
+use lsp_types::{
+    AnnotatedTextEdit, ChangeAnnotation, DocumentChangeOperation, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, RenameFile, ResourceOp,
+    TextDocumentEdit, TextEdit, Uri,
+};
+use regex::Regex;
+
+use crate::document_store::document::Document;
+use crate::document_store::DocumentStore;
+use crate::parser::byte_to_point;
+use crate::parser::tokens::TokenData;
+use crate::utils::{path_to_uri, uri_to_path};
+
+pub const RENAME_ANNOTATION: &str = "renameModule";
+
+// Matches the functions defined in a file, f.e `function mymodule_form_alter(`.
+static FUNCTION_DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^function\s+(?<name>\w+)\s*\(").unwrap());
+
+// Matches the config names in a config schema file, f.e `mymodule.settings:`.
+static SCHEMA_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^(?<name>[\w.*]+):").unwrap());
+
+// Matches a module name on its own in YAML, f.e in config dependencies or block providers.
+static YAML_MODULE_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)^\s*(?:-|provider:|module:)\s*['"]?(?<name>\w+)['"]?\s*$"#).unwrap()
+});
+
+/// A file in the module directory, with its document when it is part of the index.
+///
+/// Not all files of a module are indexed, f.e `*.install` files and interfaces, but they need to
+/// be renamed all the same.
+struct ModuleFile<'a> {
+    uri: String,
+    content: Cow<'a, str>,
+    document: Option<&'a Document>,
+}
+
+/// Get the renames of the identifiers a module owns, f.e `mymodule.settings` for the route
+/// `mymodule.settings`, based on the files in the module directory.
+pub fn get_identifier_renames(
+    store: &DocumentStore,
+    old_name: &str,
+    new_name: &str,
+    directory: &Path,
+) -> Vec<(String, String)> {
+    let rename_prefix = |identifier: &str, separator: char| {
+        let rest = identifier.strip_prefix(old_name)?;
+        if !rest.starts_with(separator) {
+            return None;
+        }
+        Some((identifier.to_string(), format!("{}{}", new_name, rest)))
+    };
+
+    let mut renames: Vec<(String, String)> = vec![
+        (
+            format!("Drupal\\{}\\", old_name),
+            format!("Drupal\\{}\\", new_name),
+        ),
+        (
+            format!("Drupal\\Tests\\{}\\", old_name),
+            format!("Drupal\\Tests\\{}\\", new_name),
+        ),
+        // The namespace of classes at the root of the module, f.e `namespace Drupal\mymodule;`.
+        (
+            format!("Drupal\\{}", old_name),
+            format!("Drupal\\{}", new_name),
+        ),
+        (
+            format!("{}:{}", old_name, old_name),
+            format!("{}:{}", new_name, new_name),
+        ),
+    ];
+    for file in get_module_files(store, directory) {
+        for token in file.document.iter().flat_map(|document| &document.tokens) {
+            renames.extend(match &token.data {
+                TokenData::DrupalServiceDefinition(service) => rename_prefix(&service.name, '.'),
+                TokenData::DrupalRouteDefinition(route) => rename_prefix(&route.name, '.'),
+                TokenData::DrupalLibraryDefinition(library) => rename_prefix(&library.name, '/'),
+                // Permissions usually contain the module name as a word, f.e `administer mymodule`.
+                TokenData::DrupalPermissionDefinition(permission) => {
+                    let words: Vec<&str> = permission.name.split(' ').collect();
+                    words.contains(&old_name).then(|| {
+                        (
+                            permission.name.to_string(),
+                            words
+                                .iter()
+                                .map(|word| if *word == old_name { new_name } else { word })
+                                .collect::<Vec<&str>>()
+                                .join(" "),
+                        )
+                    })
+                }
+                _ => None,
+            });
+        }
+
+        // Hook implementations and helpers are prefixed with the module name.
+        renames.extend(
+            FUNCTION_DEFINITION
+                .captures_iter(&file.content)
+                .filter_map(|captures| rename_prefix(captures.name("name")?.as_str(), '_')),
+        );
+
+        // The config of a module is named after it, f.e `config/install/mymodule.settings.yml`.
+        let Some(path) = uri_to_path(&file.uri) else {
+            continue;
+        };
+        if path
+            .components()
+            .any(|component| component.as_os_str() == "config")
+        {
+            if let Some(config_name) = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_suffix(".yml"))
+            {
+                renames.extend(rename_prefix(config_name, '.'));
+            }
+            if file.uri.ends_with(".schema.yml") {
+                renames.extend(
+                    SCHEMA_KEY
+                        .captures_iter(&file.content)
+                        .filter_map(|captures| rename_prefix(captures.name("name")?.as_str(), '.')),
+                );
+            }
+        }
+    }
+
+    // Longer identifiers first, so that f.e `mymodule.settings_form` isn't renamed as
+    // `mymodule.settings`.
+    renames.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+    renames.dedup();
+    renames
+}
+
+/// Get the edits renaming the identifiers in a document.
+pub fn get_text_edits(
+    content: &str,
+    uri: &str,
+    old_name: &str,
+    new_name: &str,
+    renames: &[(String, String)],
+) -> Vec<TextEdit> {
+    let pattern = renames
+        .iter()
+        .map(|(identifier, _)| regex::escape(identifier))
+        .collect::<Vec<String>>()
+        .join("|");
+    let Ok(identifier) = Regex::new(&pattern) else {
+        return vec![];
+    };
+    let is_identifier_char = |char: char| char.is_alphanumeric() || char == '_' || char == '.';
+
+    let mut edits: Vec<(usize, usize, String)> = identifier
+        .find_iter(content)
+        .filter(|found| {
+            // Only whole identifiers are renamed, f.e not `mymodule.page` in `mymodule.page_2`.
+            let before = content[..found.start()].chars().next_back();
+            let after = content[found.end()..].chars().next();
+            let is_cut_off = found.as_str().ends_with(is_identifier_char)
+                && after.is_some_and(is_identifier_char);
+            !before.is_some_and(is_identifier_char) && !is_cut_off
+        })
+        .filter_map(|found| {
+            let (_, new_identifier) = renames
+                .iter()
+                .find(|(identifier, _)| identifier == found.as_str())?;
+            Some((found.start(), found.end(), new_identifier.to_string()))
+        })
+        .collect();
+
+    if uri.ends_with(".yml") {
+        edits.extend(
+            YAML_MODULE_NAME
+                .captures_iter(content)
+                .filter_map(|captures| captures.name("name"))
+                .filter(|name| name.as_str() == old_name)
+                .map(|name| (name.start(), name.end(), new_name.to_string())),
+        );
+    }
+
+    edits.sort_by_key(|(start, _, _)| *start);
+    edits
+        .into_iter()
+        .map(|(start, end, new_text)| TextEdit {
+            range: Range {
+                start: byte_to_position(content, start),
+                end: byte_to_position(content, end),
+            },
+            new_text,
+        })
+        .collect()
+}
+
+/// Get the renames of the files of a module named after it, f.e `mymodule.routing.yml`, and of
+/// the module directory itself.
+pub fn get_file_renames(
+    store: &DocumentStore,
+    old_name: &str,
+    new_name: &str,
+    directory: &Path,
+) -> Vec<(String, String)> {
+    let mut renames: Vec<(String, String)> = get_module_files(store, directory)
+        .into_iter()
+        .filter_map(|file| {
+            let path = uri_to_path(&file.uri)?;
+            let file_name = path.file_name()?.to_str()?;
+            let rest = file_name.strip_prefix(old_name)?;
+            if !rest.starts_with('.') {
+                return None;
+            }
+            Some((
+                file.uri,
+                path_to_uri(&path.with_file_name(format!("{}{}", new_name, rest)))?,
+            ))
+        })
+        .collect();
+    renames.sort();
+
+    // The directory is renamed last, since the files are renamed at their current location.
+    if directory
+        .file_name()
+        .is_some_and(|directory_name| directory_name == old_name)
+    {
+        if let (Some(old_uri), Some(new_uri)) = (
+            path_to_uri(directory),
+            path_to_uri(&directory.with_file_name(new_name)),
+        ) {
+            renames.push((old_uri, new_uri));
+        }
+    }
+    renames
+}
+
+/// Get the operations renaming a module, which need to be confirmed by the user since they touch
+/// the whole workspace.
+pub fn get_rename_module_operations(
+    store: &DocumentStore,
+    old_name: &str,
+    new_name: &str,
+    directory: &Path,
+) -> Vec<DocumentChangeOperation> {
+    let renames = get_identifier_renames(store, old_name, new_name, directory);
+
+    // The files of the module that aren't indexed are read from disk.
+    let mut files: Vec<(String, Cow<str>)> = store
+        .get_documents()
+        .iter()
+        .map(|(uri, document)| (uri.clone(), document.get_content()))
+        .chain(
+            get_module_files(store, directory)
+                .into_iter()
+                .filter(|file| file.document.is_none())
+                .map(|file| (file.uri, file.content)),
+        )
+        .collect();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut operations: Vec<DocumentChangeOperation> = files
+        .into_iter()
+        .filter_map(|(uri, content)| {
+            let edits = get_text_edits(&content, &uri, old_name, new_name, &renames);
+            if edits.is_empty() {
+                return None;
+            }
+            Some(DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier {
+                    uri: uri.parse().ok()?,
+                    version: None,
+                },
+                edits: edits
+                    .into_iter()
+                    .map(|text_edit| {
+                        OneOf::Right(AnnotatedTextEdit {
+                            text_edit,
+                            annotation_id: RENAME_ANNOTATION.to_string(),
+                        })
+                    })
+                    .collect(),
+            }))
+        })
+        .collect();
+
+    operations.extend(
+        get_file_renames(store, old_name, new_name, directory)
+            .into_iter()
+            .filter_map(|(old_uri, new_uri)| {
+                Some(DocumentChangeOperation::Op(ResourceOp::Rename(
+                    RenameFile {
+                        old_uri: old_uri.parse::<Uri>().ok()?,
+                        new_uri: new_uri.parse::<Uri>().ok()?,
+                        options: None,
+                        annotation_id: Some(RENAME_ANNOTATION.to_string()),
+                    },
+                )))
+            }),
+    );
+    operations
+}
+
+pub fn get_change_annotations(old_name: &str, new_name: &str) -> HashMap<String, ChangeAnnotation> {
+    HashMap::from([(
+        RENAME_ANNOTATION.to_string(),
+        ChangeAnnotation {
+            label: format!("Rename module {} to {}", old_name, new_name),
+            needs_confirmation: Some(true),
+            description: None,
+        },
+    )])
+}
+
+/// Get the files in the module directory, both the indexed documents and the files on disk that
+/// aren't indexed.
+fn get_module_files<'a>(store: &'a DocumentStore, directory: &Path) -> Vec<ModuleFile<'a>> {
+    let mut files: Vec<ModuleFile> = store
+        .get_documents()
+        .values()
+        .filter(|document| {
+            uri_to_path(&document.uri).is_some_and(|path| path.starts_with(directory))
+        })
+        .map(|document| ModuleFile {
+            uri: document.uri.clone(),
+            content: document.get_content(),
+            document: Some(document),
+        })
+        .collect();
+
+    for entry in WalkBuilder::new(directory).build().flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(uri) = path_to_uri(path) else {
+            continue;
+        };
+        if store.get_document(&uri).is_some() {
+            continue;
+        }
+        // Binary files, f.e images, can't be read as text and aren't renamed.
+        if let Ok(content) = fs::read_to_string(path) {
+            files.push(ModuleFile {
+                uri,
+                content: Cow::Owned(content),
+                document: None,
+            });
+        }
+    }
+    files
+}
+
+fn byte_to_position(content: &str, byte: usize) -> Position {
+    let point = byte_to_point(content, byte);
+    Position {
+        line: point.row as u32,
+        character: point.column as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> DocumentStore {
+        let mut store = DocumentStore::new();
+        let files = [
+            (
+                "file:///var/www/modules/custom/old_name/old_name.routing.yml",
+                "old_name.settings:\n  path: '/admin/config/old'\n  defaults:\n    _form: '\\Drupal\\old_name\\Form\\SettingsForm'\n  requirements:\n    _permission: 'administer old_name'\n",
+            ),
+            (
+                "file:///var/www/modules/custom/old_name/old_name.permissions.yml",
+                "administer old_name:\n  title: 'Administer'\n",
+            ),
+            (
+                "file:///var/www/modules/custom/old_name/old_name.module",
+                "<?php\n\n/**\n * Implements hook_cron().\n */\nfunction old_name_cron() {\n  old_name_helper(\\Drupal::config('old_name.settings'));\n}\n\nfunction old_name_helper($config) {\n}\n",
+            ),
+            (
+                "file:///var/www/modules/custom/old_name/config/install/old_name.settings.yml",
+                "limit: 10\n",
+            ),
+            (
+                "file:///var/www/modules/custom/other/other.info.yml",
+                "name: Other\ntype: module\ndependencies:\n  - old_name:old_name\n",
+            ),
+            (
+                "file:///var/www/modules/custom/other/other.module",
+                "<?php\n\nfunction other_cron() {\n  $url = Url::fromRoute('old_name.settings_page');\n  old_name_helper(NULL);\n}\n",
+            ),
+        ];
+        for (uri, content) in files {
+            store.add_document(uri, content.to_string());
+        }
+        store
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn identifier_renames() {
+        let renames = get_identifier_renames(
+            &store(),
+            "old_name",
+            "new_name",
+            Path::new("/var/www/modules/custom/old_name"),
+        );
+        let rename = |identifier: &str| {
+            renames
+                .iter()
+                .find(|(old, _)| old == identifier)
+                .map(|(_, new)| new.as_str())
+        };
+        assert_eq!(Some("new_name.settings"), rename("old_name.settings"));
+        assert_eq!(Some("new_name_helper"), rename("old_name_helper"));
+        assert_eq!(Some("administer new_name"), rename("administer old_name"));
+        assert_eq!(Some("Drupal\\new_name\\"), rename("Drupal\\old_name\\"));
+        assert_eq!(None, rename("other_cron"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rename_module() {
+        let store = store();
+        let directory = Path::new("/var/www/modules/custom/old_name");
+        let renames = get_identifier_renames(&store, "old_name", "new_name", directory);
+
+        let apply = |uri: &str| {
+            let content = &store.get_document(uri).unwrap().content;
+            let mut lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
+            for edit in get_text_edits(content, uri, "old_name", "new_name", &renames)
+                .iter()
+                .rev()
+            {
+                let line = &mut lines[edit.range.start.line as usize];
+                line.replace_range(
+                    edit.range.start.character as usize..edit.range.end.character as usize,
+                    &edit.new_text,
+                );
+            }
+            lines.join("\n")
+        };
+        assert_eq!(
+            "new_name.settings:\n  path: '/admin/config/old'\n  defaults:\n    _form: '\\Drupal\\new_name\\Form\\SettingsForm'\n  requirements:\n    _permission: 'administer new_name'",
+            apply("file:///var/www/modules/custom/old_name/old_name.routing.yml")
+        );
+        assert_eq!(
+            "<?php\n\n/**\n * Implements hook_cron().\n */\nfunction new_name_cron() {\n  new_name_helper(\\Drupal::config('new_name.settings'));\n}\n\nfunction new_name_helper($config) {\n}",
+            apply("file:///var/www/modules/custom/old_name/old_name.module")
+        );
+        assert_eq!(
+            "name: Other\ntype: module\ndependencies:\n  - new_name:new_name",
+            apply("file:///var/www/modules/custom/other/other.info.yml")
+        );
+        // Routes that aren't defined by the module are kept.
+        assert_eq!(
+            "<?php\n\nfunction other_cron() {\n  $url = Url::fromRoute('old_name.settings_page');\n  new_name_helper(NULL);\n}",
+            apply("file:///var/www/modules/custom/other/other.module")
+        );
+
+        let file_renames = get_file_renames(&store, "old_name", "new_name", directory);
+        assert_eq!(
+            vec![
+                (
+                    "file:///var/www/modules/custom/old_name/config/install/old_name.settings.yml"
+                        .to_string(),
+                    "file:///var/www/modules/custom/old_name/config/install/new_name.settings.yml"
+                        .to_string(),
+                ),
+                (
+                    "file:///var/www/modules/custom/old_name/old_name.module".to_string(),
+                    "file:///var/www/modules/custom/old_name/new_name.module".to_string(),
+                ),
+                (
+                    "file:///var/www/modules/custom/old_name/old_name.permissions.yml".to_string(),
+                    "file:///var/www/modules/custom/old_name/new_name.permissions.yml".to_string(),
+                ),
+                (
+                    "file:///var/www/modules/custom/old_name/old_name.routing.yml".to_string(),
+                    "file:///var/www/modules/custom/old_name/new_name.routing.yml".to_string(),
+                ),
+                (
+                    "file:///var/www/modules/custom/old_name".to_string(),
+                    "file:///var/www/modules/custom/new_name".to_string(),
+                ),
+            ],
+            file_renames
+        );
+    }
+
+    #[test]
+    fn rename_unindexed_module_files() {
+        let root =
+            std::env::temp_dir().join(format!("drupal_ls_rename_unindexed_{}", std::process::id()));
+        let directory = root.join("old_name");
+        std::fs::create_dir_all(directory.join("src")).unwrap();
+        std::fs::create_dir_all(directory.join("config/install")).unwrap();
+        std::fs::write(
+            directory.join("old_name.install"),
+            "<?php\n\nfunction old_name_install() {\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            directory.join("src/FooInterface.php"),
+            "<?php\n\nnamespace Drupal\\old_name;\n\ninterface FooInterface {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            directory.join("old_name.links.task.yml"),
+            "old_name.settings_tab:\n  route_name: old_name.settings\n",
+        )
+        .unwrap();
+        let config_path = directory.join("config/install/old_name.settings.yml");
+        std::fs::write(&config_path, "dependencies:\n  module:\n    - old_name\n").unwrap();
+
+        let mut store = DocumentStore::new();
+        store.add_document(
+            &path_to_uri(&directory.join("old_name.routing.yml")).unwrap(),
+            "old_name.settings:\n  path: '/admin/config/old'\n  defaults:\n    _title: 'Old'\n"
+                .to_string(),
+        );
+        // Default config without tokens is indexed without its content.
+        store.add_document(&path_to_uri(&config_path).unwrap(), String::new());
+        let operations = get_rename_module_operations(&store, "old_name", "new_name", &directory);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let edited: Vec<String> = operations
+            .iter()
+            .filter_map(|operation| match operation {
+                DocumentChangeOperation::Edit(edit) => Some(edit.text_document.uri.to_string()),
+                _ => None,
+            })
+            .filter_map(|uri| Some(uri.rsplit_once("/old_name/")?.1.to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                "config/install/old_name.settings.yml",
+                "old_name.install",
+                "old_name.links.task.yml",
+                "old_name.routing.yml",
+                "src/FooInterface.php",
+            ],
+            edited
+        );
+        let renamed: Vec<String> = operations
+            .iter()
+            .filter_map(|operation| match operation {
+                DocumentChangeOperation::Op(ResourceOp::Rename(rename)) => {
+                    Some(rename.new_uri.to_string())
+                }
+                _ => None,
+            })
+            .filter_map(|uri| Some(uri.rsplit_once('/')?.1.to_string()))
+            .collect();
+        assert_eq!(
+            vec![
+                "new_name.settings.yml",
+                "new_name.install",
+                "new_name.links.task.yml",
+                "new_name.routing.yml",
+                "new_name",
+            ],
+            renamed
+        );
+    }
+}
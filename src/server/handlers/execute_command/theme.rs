@@ -0,0 +1,80 @@
+use super::plugin::get_label;
+
+// The regions of a new theme, when none are given.
+pub const DEFAULT_REGIONS: &[&str] = &[
+    "header",
+    "primary_menu",
+    "secondary_menu",
+    "highlighted",
+    "breadcrumb",
+    "help",
+    "content",
+    "sidebar_first",
+    "sidebar_second",
+    "footer",
+];
+
+pub fn get_info_file_content(
+    theme_name: &str,
+    label: &str,
+    base_theme: &str,
+    regions: &[&str],
+) -> String {
+    let regions = regions
+        .iter()
+        .map(|region| format!("  {}: '{}'\n", region, get_label(region)))
+        .collect::<String>();
+    format!(
+        r#"name: '{}'
+type: theme
+description: ''
+package: Custom
+core_version_requirement: ^10.3 || ^11
+base theme: {}
+libraries:
+  - {}/global-styling
+regions:
+{}"#,
+        label, base_theme, theme_name, regions
+    )
+}
+
+pub fn get_libraries_file_content() -> String {
+    r#"global-styling:
+  version: VERSION
+  css:
+    theme:
+      css/style.css: {}
+"#
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn info_file() {
+        assert_eq!(
+            r#"name: 'My theme'
+type: theme
+description: ''
+package: Custom
+core_version_requirement: ^10.3 || ^11
+base theme: false
+libraries:
+  - my_theme/global-styling
+regions:
+  header: 'Header'
+  content: 'Content'
+  sidebar_first: 'Sidebar first'
+"#,
+            get_info_file_content(
+                "my_theme",
+                "My theme",
+                "false",
+                &["header", "content", "sidebar_first"]
+            )
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use lsp_server::{ErrorCode, Request, Response};
-use lsp_types::{Hover, HoverContents, HoverParams};
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
 
 use crate::document_store::DOCUMENT_STORE;
 use crate::documentation::get_documentation_for_token;
 use crate::parser::tokens::Token;
+use crate::server::client_supports_hover_markdown;
 use crate::server::handle_request::get_response_error;
+use crate::utils::markdown_to_plain_text;
 
 pub fn handle_text_document_hover(request: Request) -> Option<Response> {
     let params = match serde_json::from_value::<HoverParams>(request.params) {
@@ -29,10 +31,19 @@ pub fn handle_text_document_hover(request: Request) -> Option<Response> {
         token = document.get_token_under_cursor(params.text_document_position_params.position);
     }
 
+    let documentation = get_documentation_for_token(&token?)?;
+    let contents = match client_supports_hover_markdown() {
+        true => MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: documentation,
+        },
+        false => MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: markdown_to_plain_text(&documentation),
+        },
+    };
     let hover_result = Hover {
-        contents: HoverContents::Scalar(lsp_types::MarkedString::String(
-            get_documentation_for_token(&token?)?,
-        )),
+        contents: HoverContents::Markup(contents),
         range: None,
     };
 
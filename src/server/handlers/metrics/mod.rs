@@ -0,0 +1,13 @@
+use lsp_server::{Request, Response};
+
+use crate::server::metrics::get_metrics;
+
+/// Handle the custom `drupal/metrics` request, which returns the request latencies and document
+/// store lock wait times, f.e to include in a report about slow completion.
+pub fn handle_drupal_metrics(request: Request) -> Option<Response> {
+    Some(Response {
+        id: request.id,
+        result: Some(get_metrics()),
+        error: None,
+    })
+}
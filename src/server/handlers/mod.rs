@@ -1,4 +1,10 @@
-pub mod completion;
+pub mod capabilities;
 pub mod code_action;
+pub mod code_lens;
+pub mod completion;
 pub mod definition;
+pub mod execute_command;
 pub mod hover;
+pub mod metrics;
+pub mod project_info;
+pub mod references;
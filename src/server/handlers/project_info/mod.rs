@@ -0,0 +1,161 @@
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lsp_server::{Request, Response};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::document_store::{DocumentStore, DOCUMENT_STORE};
+use crate::parser::tokens::{ClassAttribute, TokenData};
+use crate::server::get_root_dir;
+use crate::utils::uri_to_path;
+
+// Matches the version constant of the `\Drupal` class, f.e `const VERSION = '11.1.0';`.
+static CORE_VERSION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"const VERSION = ['"](?<version>[^'"]+)['"]"#).unwrap());
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectInfo {
+    /// The workspace folder the server was started in.
+    root_dir: Option<String>,
+    /// The directory containing `core`, f.e `/var/www/web`.
+    drupal_root: Option<PathBuf>,
+    /// The version of core, from `\Drupal::VERSION`.
+    core_version: Option<String>,
+    counts: ProjectCounts,
+    index: IndexInfo,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectCounts {
+    documents: usize,
+    routes: usize,
+    services: usize,
+    permissions: usize,
+    plugins: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexInfo {
+    /// Whether the initial indexing of the project has finished.
+    is_ready: bool,
+    /// When the project was indexed, in seconds since the Unix epoch.
+    indexed_at: Option<u64>,
+    /// When a document was last added, changed or removed, in seconds since the Unix epoch.
+    changed_at: Option<u64>,
+}
+
+/// Handle the custom `drupal/projectInfo` request, which returns what is known about the project,
+/// f.e for editor extensions to show in a status bar item.
+pub fn handle_drupal_project_info(request: Request) -> Option<Response> {
+    let store = DOCUMENT_STORE.lock().unwrap();
+    let project_info = get_project_info(&store, get_root_dir());
+    drop(store);
+
+    Some(Response {
+        id: request.id,
+        result: serde_json::to_value(project_info).ok(),
+        error: None,
+    })
+}
+
+fn get_project_info(store: &DocumentStore, root_dir: Option<&str>) -> ProjectInfo {
+    let mut counts = ProjectCounts {
+        documents: store.get_documents().len(),
+        ..ProjectCounts::default()
+    };
+    store
+        .get_documents()
+        .values()
+        .flat_map(|document| document.tokens.iter())
+        .for_each(|token| match &token.data {
+            TokenData::DrupalRouteDefinition(_) => counts.routes += 1,
+            TokenData::DrupalServiceDefinition(_) => counts.services += 1,
+            TokenData::DrupalPermissionDefinition(_) => counts.permissions += 1,
+            TokenData::PhpClassDefinition(class) => {
+                if let Some(ClassAttribute::Plugin(_)) = class.attribute {
+                    counts.plugins += 1;
+                }
+            }
+            _ => {}
+        });
+
+    ProjectInfo {
+        root_dir: root_dir.map(|root_dir| root_dir.to_string()),
+        drupal_root: get_drupal_root(store),
+        core_version: get_core_version(store),
+        counts,
+        index: IndexInfo {
+            is_ready: store.get_indexed_at().is_some(),
+            indexed_at: store.get_indexed_at().and_then(get_timestamp),
+            changed_at: store.get_changed_at().and_then(get_timestamp),
+        },
+    }
+}
+
+/// Get the directory containing `core`, based on where `core.services.yml` was indexed.
+fn get_drupal_root(store: &DocumentStore) -> Option<PathBuf> {
+    let uri = store
+        .get_documents()
+        .keys()
+        .find(|uri| uri.ends_with("/core/core.services.yml"))?;
+    Some(uri_to_path(uri)?.parent()?.parent()?.to_path_buf())
+}
+
+/// Get the version of core from the `VERSION` constant of the `\Drupal` class.
+fn get_core_version(store: &DocumentStore) -> Option<String> {
+    let document = store
+        .get_documents()
+        .values()
+        .find(|document| document.uri.ends_with("/core/lib/Drupal.php"))?;
+    Some(
+        CORE_VERSION
+            .captures(&document.content)?
+            .name("version")?
+            .as_str()
+            .to_string(),
+    )
+}
+
+fn get_timestamp(time: SystemTime) -> Option<u64> {
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_info() {
+        let mut store = DocumentStore::new();
+        store.add_document(
+            "file:///var/www/web/core/core.services.yml",
+            "services:\n  cache.backend.database:\n    class: Drupal\\Core\\Cache\\DatabaseBackendFactory\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/core/lib/Drupal.php",
+            "<?php\n\nclass Drupal {\n\n  const VERSION = '11.1.0';\n\n}\n".to_string(),
+        );
+        store.add_document(
+            "file:///var/www/web/modules/custom/foo/foo.permissions.yml",
+            "administer foo:\n  title: 'Administer foo'\n".to_string(),
+        );
+
+        let project_info = get_project_info(&store, Some("/var/www"));
+        assert_eq!(
+            Some(PathBuf::from("/var/www/web")),
+            project_info.drupal_root
+        );
+        assert_eq!(Some("11.1.0".to_string()), project_info.core_version);
+        assert_eq!(3, project_info.counts.documents);
+        assert_eq!(1, project_info.counts.services);
+        assert_eq!(1, project_info.counts.permissions);
+        assert_eq!(0, project_info.counts.routes);
+        assert!(!project_info.index.is_ready);
+        assert!(project_info.index.changed_at.is_some());
+    }
+}
@@ -0,0 +1,94 @@
+use lsp_server::{ErrorCode, Request, Response};
+use lsp_types::{Location, ReferenceParams};
+
+use crate::document_store::document::Document;
+use crate::document_store::DOCUMENT_STORE;
+use crate::parser::token_range_to_range;
+use crate::parser::tokens::{PhpClass, Token, TokenData};
+use crate::server::handle_request::get_response_error;
+
+pub fn handle_text_document_references(request: Request) -> Option<Response> {
+    let params = match serde_json::from_value::<ReferenceParams>(request.params) {
+        Err(err) => {
+            return Some(get_response_error(
+                request.id,
+                ErrorCode::InvalidParams,
+                format!("Could not parse references params: {:?}", err),
+            ));
+        }
+        Ok(value) => value,
+    };
+
+    let mut token: Option<Token> = None;
+    if let Some(document) = DOCUMENT_STORE
+        .lock()
+        .unwrap()
+        .get_document(&params.text_document_position.text_document.uri.to_string())
+    {
+        token = document.get_token_under_cursor(params.text_document_position.position);
+    }
+
+    let locations = provide_references_for_token(&token?, params.context.include_declaration);
+    match serde_json::to_value(locations) {
+        Ok(result) => Some(Response {
+            id: request.id,
+            result: Some(result),
+            error: None,
+        }),
+        Err(error) => Some(get_response_error(
+            request.id,
+            ErrorCode::InternalError,
+            format!("No references found: {:?}", error),
+        )),
+    }
+}
+
+fn provide_references_for_token(token: &Token, include_declaration: bool) -> Vec<Location> {
+    let store = DOCUMENT_STORE.lock().unwrap();
+    let get_location = |document: &Document, range: &tree_sitter::Range| {
+        Some(Location {
+            uri: document.get_uri()?,
+            range: token_range_to_range(range),
+        })
+    };
+
+    match &token.data {
+        TokenData::PhpClassDefinition(PhpClass {
+            name: class_name, ..
+        })
+        | TokenData::PhpClassReference(class_name) => {
+            let mut locations: Vec<Location> = vec![];
+            if include_declaration {
+                locations.extend(
+                    store
+                        .get_class_definition(class_name)
+                        .and_then(|(document, token)| get_location(document, &token.range)),
+                );
+            }
+            locations.extend(
+                store
+                    .get_class_references(class_name)
+                    .into_iter()
+                    .filter_map(|(document, range)| get_location(document, &range)),
+            );
+            locations
+        }
+        // The same string may be translated in PHP and Twig files.
+        TokenData::DrupalTranslationString(translation_string) => store
+            .get_documents()
+            .values()
+            .filter(|document| document.content.contains(&translation_string.string))
+            .flat_map(|document| {
+                document
+                    .get_translation_strings()
+                    .into_iter()
+                    .filter(|token| {
+                        matches!(&token.data, TokenData::DrupalTranslationString(other) if other.string == translation_string.string)
+                    })
+                    .filter_map(|token| get_location(document, &token.range))
+                    .collect::<Vec<Location>>()
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
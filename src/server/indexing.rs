@@ -0,0 +1,228 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lsp_types::notification::{Notification, Progress};
+use lsp_types::request::WorkDoneProgressCreate;
+use lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::document_store::{initialize_document_store, IndexProgress};
+
+use super::{get_client_capabilities, send_notification, send_request};
+
+/// The token of the work done progress of indexing the project.
+const PROGRESS_TOKEN: &str = "drupal_ls/indexing";
+
+/// How often progress is reported while files are parsed.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The custom `drupal/indexingStatus` notification, which tells clients how far indexing the
+/// project has come, f.e to show a status bar item.
+pub enum IndexingStatusNotification {}
+
+impl Notification for IndexingStatusNotification {
+    type Params = IndexingStatus;
+    const METHOD: &'static str = "drupal/indexingStatus";
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexingState {
+    Starting,
+    Progress,
+    Ready,
+    Error,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexingStatus {
+    pub state: IndexingState,
+    pub files_parsed: usize,
+    /// The number of files to parse, which is only known once the project has been walked.
+    pub files_total: Option<usize>,
+    /// The files that couldn't be read.
+    pub failures: usize,
+    pub elapsed_ms: u64,
+    /// What went wrong, for the `error` state.
+    pub message: Option<String>,
+}
+
+/// Reports the indexing of the project to the client, both as standard work done progress and
+/// with the custom `drupal/indexingStatus` notification.
+struct IndexingReporter {
+    started: Instant,
+    /// When progress was last reported, to not flood the client while files are parsed.
+    last_reported: Mutex<Instant>,
+    /// Whether the client supports work done progress.
+    has_work_done_progress: bool,
+}
+
+impl IndexingReporter {
+    fn start() -> Self {
+        let has_work_done_progress = get_client_capabilities()
+            .and_then(|capabilities| capabilities.window.as_ref()?.work_done_progress)
+            .unwrap_or(false);
+        let reporter = Self {
+            started: Instant::now(),
+            last_reported: Mutex::new(Instant::now()),
+            has_work_done_progress,
+        };
+
+        if reporter.has_work_done_progress {
+            send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: NumberOrString::String(PROGRESS_TOKEN.to_string()),
+            });
+            reporter.send_progress(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: "Indexing".to_string(),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            }));
+        }
+        reporter.send_status(IndexingState::Starting, IndexProgress::default(), None);
+        reporter
+    }
+
+    fn report(&self, progress: IndexProgress) {
+        // Other threads that are parsing files skip reporting, instead of waiting for the lock.
+        let Ok(mut last_reported) = self.last_reported.try_lock() else {
+            return;
+        };
+        if last_reported.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        *last_reported = Instant::now();
+        drop(last_reported);
+
+        if self.has_work_done_progress {
+            self.send_progress(WorkDoneProgress::Report(WorkDoneProgressReport {
+                cancellable: Some(false),
+                message: Some(format!(
+                    "{}/{} files",
+                    progress.files_parsed, progress.files_total
+                )),
+                percentage: (progress.files_total > 0)
+                    .then(|| (progress.files_parsed * 100 / progress.files_total) as u32),
+            }));
+        }
+        self.send_status(IndexingState::Progress, progress, None);
+    }
+
+    fn finish(&self, progress: IndexProgress) {
+        self.end_progress(format!("Indexed {} files", progress.files_parsed));
+        self.send_status(IndexingState::Ready, progress, None);
+    }
+
+    fn fail(&self, message: &str) {
+        log::error!("{}", message);
+        self.end_progress(message.to_string());
+        self.send_status(
+            IndexingState::Error,
+            IndexProgress::default(),
+            Some(message.to_string()),
+        );
+    }
+
+    fn end_progress(&self, message: String) {
+        if self.has_work_done_progress {
+            self.send_progress(WorkDoneProgress::End(WorkDoneProgressEnd {
+                message: Some(message),
+            }));
+        }
+    }
+
+    fn send_progress(&self, progress: WorkDoneProgress) {
+        send_notification::<Progress>(ProgressParams {
+            token: NumberOrString::String(PROGRESS_TOKEN.to_string()),
+            value: ProgressParamsValue::WorkDone(progress),
+        });
+    }
+
+    fn send_status(&self, state: IndexingState, progress: IndexProgress, message: Option<String>) {
+        send_notification::<IndexingStatusNotification>(get_indexing_status(
+            state,
+            progress,
+            self.started.elapsed(),
+            message,
+        ));
+    }
+}
+
+/// Index the project, reporting the progress to the client.
+pub fn index_project(root_dir: String) {
+    let reporter = IndexingReporter::start();
+    if !Path::new(&root_dir).is_dir() {
+        reporter.fail(&format!("The workspace folder {} doesn't exist", root_dir));
+        return;
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        initialize_document_store(root_dir, |progress| reporter.report(progress))
+    }));
+    match result {
+        Ok(progress) => reporter.finish(progress),
+        Err(_) => reporter.fail("Indexing the project failed, see the log for details"),
+    }
+}
+
+fn get_indexing_status(
+    state: IndexingState,
+    progress: IndexProgress,
+    elapsed: Duration,
+    message: Option<String>,
+) -> IndexingStatus {
+    IndexingStatus {
+        state,
+        files_parsed: progress.files_parsed,
+        // Nothing is known about the project before it has been walked.
+        files_total: (state != IndexingState::Starting && state != IndexingState::Error)
+            .then_some(progress.files_total),
+        failures: progress.failures,
+        elapsed_ms: elapsed.as_millis() as u64,
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexing_status() {
+        let status = get_indexing_status(
+            IndexingState::Progress,
+            IndexProgress {
+                files_parsed: 120,
+                files_total: 4000,
+                failures: 1,
+            },
+            Duration::from_millis(1500),
+            None,
+        );
+        assert_eq!(
+            serde_json::json!({
+                "state": "progress",
+                "filesParsed": 120,
+                "filesTotal": 4000,
+                "failures": 1,
+                "elapsedMs": 1500,
+                "message": null,
+            }),
+            serde_json::to_value(status).unwrap()
+        );
+
+        let status = get_indexing_status(
+            IndexingState::Starting,
+            IndexProgress::default(),
+            Duration::ZERO,
+            None,
+        );
+        assert_eq!(None, status.files_total);
+    }
+}
@@ -0,0 +1,127 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::settings::get_settings;
+
+/// How often a summary of the metrics is logged.
+const LOG_INTERVAL: Duration = Duration::from_secs(300);
+
+static METRICS: LazyLock<Mutex<Metrics>> = LazyLock::new(|| Mutex::new(Metrics::default()));
+
+thread_local! {
+    // The time spent waiting for the document store during the current request.
+    static REQUEST_LOCK_WAIT: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Timings {
+    count: u64,
+    total_ms: f64,
+    average_ms: f64,
+    max_ms: f64,
+}
+
+impl Timings {
+    fn add(&mut self, duration: Duration) {
+        let milliseconds = duration.as_secs_f64() * 1000.0;
+        self.count += 1;
+        self.total_ms += milliseconds;
+        self.average_ms = self.total_ms / self.count as f64;
+        self.max_ms = self.max_ms.max(milliseconds);
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Metrics {
+    requests: BTreeMap<String, Timings>,
+    document_store_lock_wait: Timings,
+    #[serde(skip)]
+    last_logged: Option<Instant>,
+}
+
+/// Record how long a request took to handle, and log it when it was slow.
+pub fn record_request(method: &str, duration: Duration, lock_wait: Duration) {
+    if duration.as_millis() >= get_settings().metrics.slow_request_ms as u128 {
+        log::warn!(
+            "Slow request {} took {:.2}ms, of which {:.2}ms waiting for the document store",
+            method,
+            duration.as_secs_f64() * 1000.0,
+            lock_wait.as_secs_f64() * 1000.0
+        );
+    }
+
+    let mut metrics = METRICS.lock().unwrap();
+    metrics
+        .requests
+        .entry(method.to_string())
+        .or_default()
+        .add(duration);
+
+    let last_logged = *metrics.last_logged.get_or_insert_with(Instant::now);
+    if last_logged.elapsed() >= LOG_INTERVAL {
+        metrics.last_logged = Some(Instant::now());
+        log::info!(
+            "Metrics: {}",
+            serde_json::to_string(&*metrics).unwrap_or_default()
+        );
+    }
+}
+
+/// Record how long it took to acquire the document store lock.
+pub fn record_lock_wait(duration: Duration) {
+    REQUEST_LOCK_WAIT.with(|lock_wait| lock_wait.set(lock_wait.get() + duration));
+    METRICS
+        .lock()
+        .unwrap()
+        .document_store_lock_wait
+        .add(duration);
+}
+
+/// Measure a request handler, including the time it waited for the document store.
+pub fn measure_request<T>(method: &str, handler: impl FnOnce() -> T) -> T {
+    REQUEST_LOCK_WAIT.with(|lock_wait| lock_wait.set(Duration::ZERO));
+    let now = Instant::now();
+    let result = handler();
+    record_request(method, now.elapsed(), REQUEST_LOCK_WAIT.with(Cell::get));
+    result
+}
+
+/// Get the latency of each request method and the document store lock wait times so far.
+pub fn get_metrics() -> serde_json::Value {
+    serde_json::to_value(&*METRICS.lock().unwrap()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings() {
+        let mut timings = Timings::default();
+        timings.add(Duration::from_millis(10));
+        timings.add(Duration::from_millis(30));
+        assert_eq!(2, timings.count);
+        assert_eq!(40.0, timings.total_ms);
+        assert_eq!(20.0, timings.average_ms);
+        assert_eq!(30.0, timings.max_ms);
+    }
+
+    #[test]
+    fn measure_requests() {
+        let result = measure_request("test/metrics", || {
+            record_lock_wait(Duration::from_millis(1));
+            REQUEST_LOCK_WAIT.with(Cell::get)
+        });
+        assert_eq!(Duration::from_millis(1), result);
+
+        let metrics = get_metrics();
+        assert_eq!(1, metrics["requests"]["test/metrics"]["count"]);
+        assert!(metrics["documentStoreLockWait"]["count"].as_u64().unwrap() >= 1);
+    }
+}
@@ -1,23 +1,200 @@
 mod handle_notification;
 mod handle_request;
 mod handlers;
+mod indexing;
+pub mod metrics;
 
 use std::net::{Ipv4Addr, SocketAddrV4};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::OnceLock;
 use std::vec;
 
 use anyhow::Result;
-use lsp_server::{Connection, Message};
+use crossbeam_channel::Sender;
+use lsp_server::{Connection, Message, Notification, Request};
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::RegisterCapability;
 use lsp_types::{
-    CompletionOptions, HoverProviderCapability, InitializeParams, ServerCapabilities,
-    TextDocumentSyncCapability, TextDocumentSyncKind,
+    ClientCapabilities, CodeLensOptions, CompletionOptions,
+    DidChangeWatchedFilesRegistrationOptions, ExecuteCommandOptions, FileSystemWatcher,
+    GlobPattern, HoverProviderCapability, InitializeParams, InitializeResult, MarkupKind,
+    PublishDiagnosticsParams, Registration, RegistrationParams, SaveOptions, ServerCapabilities,
+    ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, Uri,
 };
 
-use crate::document_store::initialize_document_store;
+use crate::diagnostics::get_diagnostics;
+use crate::document_store::DOCUMENT_STORE;
+use crate::integrations::drush::initialize_drush;
 use crate::opts::DrupalLspConfig;
+use crate::settings::{get_settings, initialize_settings};
 use crate::utils::uri_to_url;
 
 use self::handle_notification::handle_notification;
 use self::handle_request::handle_request;
+use self::handlers::execute_command::COMMANDS;
+use self::indexing::index_project;
+
+static SENDER: OnceLock<Sender<Message>> = OnceLock::new();
+static REQUEST_ID: AtomicI32 = AtomicI32::new(1);
+static ROOT_DIR: OnceLock<String> = OnceLock::new();
+static CLIENT_CAPABILITIES: OnceLock<ClientCapabilities> = OnceLock::new();
+
+/// Get the path of the workspace folder the server was started in.
+pub fn get_root_dir() -> Option<&'static str> {
+    ROOT_DIR.get().map(|root_dir| root_dir.as_str())
+}
+
+/// Get the capabilities the client announced when initializing.
+pub fn get_client_capabilities() -> Option<&'static ClientCapabilities> {
+    CLIENT_CAPABILITIES.get()
+}
+
+/// Whether the client can insert completion items with snippet placeholders.
+pub fn client_supports_snippets() -> bool {
+    get_client_capabilities()
+        .and_then(|capabilities| {
+            capabilities
+                .text_document
+                .as_ref()?
+                .completion
+                .as_ref()?
+                .completion_item
+                .as_ref()?
+                .snippet_support
+        })
+        .unwrap_or(false)
+}
+
+/// Whether the client renders Markdown in the documentation of completion items.
+pub fn client_supports_completion_markdown() -> bool {
+    get_client_capabilities()
+        .and_then(|capabilities| {
+            capabilities
+                .text_document
+                .as_ref()?
+                .completion
+                .as_ref()?
+                .completion_item
+                .as_ref()?
+                .documentation_format
+                .as_ref()
+                .map(|formats| formats.contains(&MarkupKind::Markdown))
+        })
+        .unwrap_or(false)
+}
+
+/// Whether the client renders Markdown in hovers.
+pub fn client_supports_hover_markdown() -> bool {
+    get_client_capabilities()
+        .and_then(|capabilities| {
+            capabilities
+                .text_document
+                .as_ref()?
+                .hover
+                .as_ref()?
+                .content_format
+                .as_ref()
+                .map(|formats| formats.contains(&MarkupKind::Markdown))
+        })
+        .unwrap_or(false)
+}
+
+/// Send a server initiated notification to the client.
+pub fn send_notification<N: lsp_types::notification::Notification>(params: N::Params) {
+    let Some(sender) = SENDER.get() else {
+        log::warn!(
+            "Unable to send {} before the server is initialized",
+            N::METHOD
+        );
+        return;
+    };
+
+    let notification = Notification::new(N::METHOD.to_string(), params);
+    if let Err(e) = sender.send(Message::Notification(notification)) {
+        log::error!("Failed to send notification: {:?}", e);
+    }
+}
+
+/// Send a server initiated request to the client.
+///
+/// The response is only logged, as none of our requests need to act on the result.
+pub fn send_request<R: lsp_types::request::Request>(params: R::Params) {
+    let Some(sender) = SENDER.get() else {
+        log::warn!(
+            "Unable to send {} before the server is initialized",
+            R::METHOD
+        );
+        return;
+    };
+
+    let id = REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    let request = Request::new(id.into(), R::METHOD.to_string(), params);
+    if let Err(e) = sender.send(Message::Request(request)) {
+        log::error!("Failed to send request: {:?}", e);
+    }
+}
+
+/// Compute the diagnostics for a document and publish them to the client.
+pub fn publish_diagnostics(uri: Uri) {
+    let diagnostics = {
+        let store = DOCUMENT_STORE.lock().unwrap();
+        match store.get_document(&uri.to_string()) {
+            Some(document) => get_diagnostics(&store, document),
+            None => return,
+        }
+    };
+
+    send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
+        uri,
+        diagnostics,
+        version: None,
+    });
+}
+
+/// Ask the client to notify us of changes to files on disk, f.e after switching branches, so that
+/// the index stays up to date.
+fn register_file_watchers(capabilities: &ClientCapabilities) {
+    let supports_watchers = capabilities
+        .workspace
+        .as_ref()
+        .and_then(|workspace| workspace.did_change_watched_files)
+        .and_then(|watched_files| watched_files.dynamic_registration)
+        .unwrap_or(false);
+    if !supports_watchers {
+        return;
+    }
+
+    let register_options = DidChangeWatchedFilesRegistrationOptions {
+        watchers: vec![FileSystemWatcher {
+            glob_pattern: GlobPattern::String(
+                "**/*.{php,module,theme,inc,install,yml,twig,po}".to_string(),
+            ),
+            kind: None,
+        }],
+    };
+    send_request::<RegisterCapability>(RegistrationParams {
+        registrations: vec![Registration {
+            id: "drupal_ls-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(register_options).ok(),
+        }],
+    });
+}
+
+/// Get the path of the workspace, falling back to the deprecated `rootUri` and `rootPath` for
+/// clients that don't send workspace folders.
+#[allow(deprecated)]
+fn get_workspace_root(params: &InitializeParams) -> Option<String> {
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| folder.uri.clone())
+        .or_else(|| params.root_uri.clone())
+        .and_then(uri_to_url)
+        .or_else(|| params.root_path.clone())
+}
 
 async fn main_loop(connection: Connection) {
     for msg in &connection.receiver {
@@ -29,7 +206,10 @@ async fn main_loop(connection: Connection) {
                     log::error!("Failed to send response: {:?}", e);
                 }
             }
-            _ => log::error!("Unable to process message: {:?}", msg),
+            Message::Response(response) => match response.error {
+                Some(error) => log::error!("Request {} failed: {:?}", response.id, error),
+                None => log::trace!("Received response: {:?}", response),
+            },
         };
     }
 }
@@ -48,35 +228,64 @@ pub async fn start_lsp(config: DrupalLspConfig) -> Result<()> {
         (connection, io_threads) = Connection::stdio();
     }
 
+    // The settings are read from the initialize request, as they affect the capabilities.
+    let (initialize_id, initialize_params) = connection.initialize_start()?;
+    let initialize_params: InitializeParams = serde_json::from_value(initialize_params)?;
+    initialize_settings(initialize_params.initialization_options.clone());
+
     // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
-    let server_capabilities = serde_json::to_value(&ServerCapabilities {
-        code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
-        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
-        hover_provider: Some(HoverProviderCapability::Simple(true)),
-        definition_provider: Some(lsp_types::OneOf::Left(true)),
-        completion_provider: Some(CompletionOptions {
-            trigger_characters: Some(vec!["@".to_string(), " ".to_string()]),
-            ..CompletionOptions::default()
+    let initialize_result = serde_json::to_value(&InitializeResult {
+        capabilities: ServerCapabilities {
+            code_action_provider: Some(lsp_types::CodeActionProviderCapability::Simple(true)),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::FULL),
+                    save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                        include_text: Some(false),
+                    })),
+                    ..TextDocumentSyncOptions::default()
+                },
+            )),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            definition_provider: Some(lsp_types::OneOf::Left(true)),
+            references_provider: Some(lsp_types::OneOf::Left(true)),
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: COMMANDS.iter().map(|command| command.to_string()).collect(),
+                ..ExecuteCommandOptions::default()
+            }),
+            completion_provider: Some(CompletionOptions {
+                trigger_characters: Some(get_settings().completion.trigger_characters.clone()),
+                ..CompletionOptions::default()
+            }),
+            ..Default::default()
+        },
+        server_info: Some(ServerInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
         }),
-        ..Default::default()
     })
     .unwrap();
 
-    let initialize_params = connection.initialize(server_capabilities)?;
-    let initialize_params: InitializeParams = serde_json::from_value(initialize_params).unwrap();
+    connection.initialize_finish(initialize_id, initialize_result)?;
+    let workspace_root = get_workspace_root(&initialize_params);
+    let _ = SENDER.set(connection.sender.clone());
+    register_file_watchers(&initialize_params.capabilities);
+    let _ = CLIENT_CAPABILITIES.set(initialize_params.capabilities);
 
-    if let Some(folders) = initialize_params.workspace_folders {
-        let uri = folders
-            .first()
-            .expect("Unable to initialize without a workspace folder")
-            .uri
-            .clone();
-        if let Some(url) = uri_to_url(uri) {
+    match workspace_root {
+        Some(root_dir) => {
+            let _ = ROOT_DIR.set(root_dir.clone());
             // Start non-blocking document store initialization.
             tokio::spawn(async move {
-                initialize_document_store(url);
+                index_project(root_dir);
             });
+            tokio::spawn(initialize_drush());
         }
+        None => log::warn!("No workspace folder was given, only opened documents are indexed"),
     }
 
     main_loop(connection).await;
@@ -87,3 +296,41 @@ pub async fn start_lsp(config: DrupalLspConfig) -> Result<()> {
     log::info!("Shutting down Drupal LSP server");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use lsp_types::WorkspaceFolder;
+
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn workspace_root() {
+        let uri = |path: &str| Uri::from_str(&format!("file://{}", path)).unwrap();
+        let mut params = InitializeParams::default();
+        assert_eq!(None, get_workspace_root(&params));
+
+        params.root_path = Some("/var/www/path".to_string());
+        assert_eq!(
+            Some("/var/www/path"),
+            get_workspace_root(&params).as_deref()
+        );
+
+        params.root_uri = Some(uri("/var/www/uri"));
+        assert_eq!(Some("/var/www/uri"), get_workspace_root(&params).as_deref());
+
+        params.workspace_folders = Some(vec![]);
+        assert_eq!(Some("/var/www/uri"), get_workspace_root(&params).as_deref());
+
+        params.workspace_folders = Some(vec![WorkspaceFolder {
+            uri: uri("/var/www/folder"),
+            name: "folder".to_string(),
+        }]);
+        assert_eq!(
+            Some("/var/www/folder"),
+            get_workspace_root(&params).as_deref()
+        );
+    }
+}
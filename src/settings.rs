@@ -0,0 +1,148 @@
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Settings passed by the client as `initializationOptions`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    pub phpcs: PhpcsSettings,
+    pub drush: DrushSettings,
+    pub translations: TranslationSettings,
+    pub definition: DefinitionSettings,
+    pub completion: CompletionSettings,
+    pub metrics: MetricsSettings,
+    pub plugin_types: Vec<PluginTypeSettings>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PhpcsSettings {
+    pub enabled: bool,
+    /// Path to the phpcs executable, defaults to `vendor/bin/phpcs` or phpcs on the PATH.
+    pub executable: Option<String>,
+    pub standard: String,
+}
+
+impl Default for PhpcsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            executable: None,
+            standard: "Drupal,DrupalPractice".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DrushSettings {
+    pub enabled: bool,
+    /// Path to the drush executable, defaults to `vendor/bin/drush` or drush on the PATH.
+    pub executable: Option<String>,
+    /// The site alias to run drush against, f.e `@self`.
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TranslationSettings {
+    /// Report strings that aren't translated to every language in the gettext files.
+    pub require_complete: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DefinitionSettings {
+    pub service: ServiceDefinitionTarget,
+}
+
+/// Where go to definition of a service reference leads.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ServiceDefinitionTarget {
+    /// Both the `*.services.yml` entry and the class, letting the client pick.
+    #[default]
+    Both,
+    /// The `*.services.yml` entry.
+    Definition,
+    /// The class implementing the service, or the `*.services.yml` entry for classless services.
+    Class,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CompletionSettings {
+    /// The maximum number of completion items to return, beyond which the items are filtered by
+    /// the typed text and the client is asked to query again as the user types.
+    pub max_items: usize,
+    /// The characters that trigger completion as they are typed.
+    pub trigger_characters: Vec<String>,
+}
+
+impl Default for CompletionSettings {
+    fn default() -> Self {
+        Self {
+            max_items: 200,
+            trigger_characters: ["@", " ", "'", "\"", ":", "."]
+                .iter()
+                .map(|character| character.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MetricsSettings {
+    /// Requests that take at least this many milliseconds are logged as slow.
+    pub slow_request_ms: u64,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            slow_request_ms: 500,
+        }
+    }
+}
+
+/// A plugin type of a contrib module, f.e the checkout panes of Commerce.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PluginTypeSettings {
+    /// The attribute or annotation of the plugins, f.e `CommerceCheckoutPane`.
+    pub attribute: String,
+    /// The label of the plugin type, defaults to the attribute.
+    pub label: Option<String>,
+    /// The calls that are passed the id of a plugin as their first argument.
+    pub references: Vec<PluginReferencePattern>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PluginReferencePattern {
+    /// The name of the method or function, f.e `getPane`.
+    pub method: String,
+    /// Only match calls on an object or class containing this text, f.e `checkoutFlow`.
+    pub object: Option<String>,
+}
+
+pub fn initialize_settings(initialization_options: Option<serde_json::Value>) {
+    let settings = match initialization_options.map(serde_json::from_value::<Settings>) {
+        Some(Ok(settings)) => settings,
+        Some(Err(err)) => {
+            log::error!("Could not parse initialization options: {:?}", err);
+            Settings::default()
+        }
+        None => Settings::default(),
+    };
+    log::trace!("Settings: {:?}", settings);
+    let _ = SETTINGS.set(settings);
+}
+
+pub fn get_settings() -> &'static Settings {
+    SETTINGS.get_or_init(Settings::default)
+}
@@ -1,13 +1,203 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
 use lsp_types::Uri;
+use regex::Regex;
 use url::Url;
 
+// Matches Markdown links, f.e [`NodeController`](file:///var/www/NodeController.php).
+static MARKDOWN_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[(?<text>[^\]]*)\]\((?<url>[^)]*)\)").unwrap());
+// Matches emphasized or inline code text, f.e *Path:* or `node.add`.
+static MARKDOWN_INLINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\*(?<emphasis>[^*\s][^*]*)\*|`(?<code>[^`]*)`").unwrap());
+
 pub fn uri_to_url(uri: Uri) -> Option<String> {
-    Some(
-        Url::parse(&uri.to_string())
-            .ok()?
-            .to_file_path()
+    Some(uri_to_path(uri.as_str())?.to_str()?.to_string())
+}
+
+/// Get the path of a file uri, f.e `/var/www/web/index.php` for `file:///var/www/web/index.php`
+/// or `C:\www\web\index.php` for `file:///c%3A/www/web/index.php` on Windows.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(&normalize_uri(uri)).ok()?.to_file_path().ok()
+}
+
+/// Get the uri of a file path, f.e `file:///var/www/my%20site/index.php`.
+pub fn path_to_uri(path: &Path) -> Option<String> {
+    Some(Url::from_file_path(path).ok()?.to_string())
+}
+
+/// Normalize a file uri, so that the uris of a file are the same regardless of how a client
+/// encodes them, f.e `file:///c%3A/www/index.php` becomes `file:///C:/www/index.php`.
+///
+/// Uris that aren't valid file uris are returned as is.
+pub fn normalize_uri(uri: &str) -> String {
+    let Ok(mut url) = Url::parse(uri) else {
+        return uri.to_string();
+    };
+    if url.scheme() != "file" || url.host().is_some() {
+        return uri.to_string();
+    }
+
+    // Windows drive letters are encoded as f.e `c%3A` by some clients.
+    let path = url.path();
+    let drive = path.get(1..2).filter(|letter| {
+        letter.chars().all(|letter| letter.is_ascii_alphabetic())
+            && (path[2..].starts_with(':') || path[2..].to_ascii_lowercase().starts_with("%3a"))
+    });
+    if let Some(drive) = drive {
+        let rest = path[2..]
+            .strip_prefix(':')
+            .unwrap_or_else(|| &path[2 + "%3a".len()..]);
+        if rest.is_empty() || rest.starts_with('/') {
+            url.set_path(&format!("/{}:{}", drive.to_ascii_uppercase(), rest));
+        }
+    }
+
+    // Round-trip through the path to get the canonical percent-encoding.
+    url.to_file_path()
+        .ok()
+        .and_then(|path| Url::from_file_path(path).ok())
+        .unwrap_or(url)
+        .to_string()
+}
+
+/// Find the `*.info.yml` file of the module or theme that a file belongs to.
+pub fn find_info_file(path: &Path) -> Option<PathBuf> {
+    path.ancestors().skip(1).find_map(|directory| {
+        fs::read_dir(directory)
             .ok()?
-            .to_str()?
-            .to_string(),
-    )
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| {
+                path.file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .is_some_and(|file_name| file_name.ends_with(".info.yml"))
+            })
+    })
+}
+
+/// Get the machine name of a module or theme from the path to its `*.info.yml` file.
+pub fn get_extension_name(info_file: &Path) -> Option<&str> {
+    info_file.file_name()?.to_str()?.strip_suffix(".info.yml")
+}
+
+/// Get the PSR-4 namespace of a directory in a module, f.e `Drupal\mymodule\Form` for
+/// `mymodule/src/Form`, or `Drupal\Tests\mymodule\Unit` for `mymodule/tests/src/Unit`.
+pub fn get_psr4_namespace(
+    directory: &Path,
+    module_directory: &Path,
+    module_name: &str,
+) -> Option<String> {
+    let relative_path = directory.strip_prefix(module_directory).ok()?;
+    let mut components = relative_path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str());
+
+    let mut namespace = match (components.next(), components.clone().next()) {
+        (Some("src"), _) => vec!["Drupal", module_name],
+        (Some("tests"), Some("src")) => {
+            components.next();
+            vec!["Drupal", "Tests", module_name]
+        }
+        _ => return None,
+    };
+    namespace.extend(components);
+    Some(namespace.join("\\"))
+}
+
+/// Convert Markdown documentation to plain text for clients that can't render Markdown.
+///
+/// Code fences are dropped, links are written as their text followed by the url, and emphasis
+/// and inline code markers are removed outside of code blocks.
+pub fn markdown_to_plain_text(markdown: &str) -> String {
+    let mut in_code_block = false;
+    markdown
+        .lines()
+        .filter_map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                return None;
+            }
+            if in_code_block {
+                return Some(line.to_string());
+            }
+            let line = MARKDOWN_LINK.replace_all(line, "$text ($url)");
+            Some(
+                MARKDOWN_INLINE
+                    .replace_all(&line, "$emphasis$code")
+                    .into_owned(),
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_uris() {
+        for (uri, expected) in [
+            ("file:///var/www/index.php", "file:///var/www/index.php"),
+            (
+                "file:///var/www/my%20site/a.php",
+                "file:///var/www/my%20site/a.php",
+            ),
+            (
+                "file:///var/www/my site/a.php",
+                "file:///var/www/my%20site/a.php",
+            ),
+            ("file:///c%3A/www/index.php", "file:///C:/www/index.php"),
+            ("file:///c%3a/www/index.php", "file:///C:/www/index.php"),
+            ("file:///C:/www/index.php", "file:///C:/www/index.php"),
+            ("file:///cd/www/index.php", "file:///cd/www/index.php"),
+            ("file://test.php", "file://test.php"),
+            ("untitled:Untitled-1", "untitled:Untitled-1"),
+        ] {
+            assert_eq!(expected, normalize_uri(uri), "{}", uri);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn uri_paths() {
+        let path = Path::new("/var/www/my site/a.php");
+        let uri = path_to_uri(path).unwrap();
+        assert_eq!("file:///var/www/my%20site/a.php", uri);
+        assert_eq!(Some(path.to_path_buf()), uri_to_path(&uri));
+        assert_eq!(None, uri_to_path("untitled:Untitled-1"));
+    }
+
+    #[test]
+    fn markdown_plain_text() {
+        let markdown = "*Path:* `/node/add`\n*Controller:* [`NodeController`](file:///a.php)\n\n```php\n/** @var *node */\n$node = `x`;\n```\n- 2 * 3";
+        assert_eq!(
+            "Path: /node/add\nController: NodeController (file:///a.php)\n\n/** @var *node */\n$node = `x`;\n- 2 * 3",
+            markdown_to_plain_text(markdown)
+        );
+    }
+
+    #[test]
+    fn psr4_namespace() {
+        let module_directory = Path::new("/var/www/web/modules/custom/mymodule");
+        for (directory, expected) in [
+            ("src", Some("Drupal\\mymodule")),
+            ("src/Form/Element", Some("Drupal\\mymodule\\Form\\Element")),
+            ("tests/src/Unit", Some("Drupal\\Tests\\mymodule\\Unit")),
+            ("tests", None),
+            ("config", None),
+        ] {
+            assert_eq!(
+                expected.map(|namespace| namespace.to_string()),
+                get_psr4_namespace(
+                    &module_directory.join(directory),
+                    module_directory,
+                    "mymodule"
+                )
+            );
+        }
+    }
 }
@@ -1,9 +1,11 @@
 import path = require("path");
-import { workspace, ExtensionContext } from "vscode";
+import { commands, workspace, ExtensionContext, Uri } from "vscode";
 
 import {
   LanguageClient,
   LanguageClientOptions,
+  Location,
+  Position,
   ServerOptions,
   TransportKind,
 } from "vscode-languageclient/node";
@@ -41,6 +43,23 @@ export function activate(context: ExtensionContext) {
     clientOptions,
   );
 
+  // The code lenses on hooks and classes list their implementations and tests in the references
+  // view.
+  for (const command of ["drupal_ls.showImplementations", "drupal_ls.showTests"]) {
+    context.subscriptions.push(
+      commands.registerCommand(
+        command,
+        (uri: string, position: Position, locations: Location[]) =>
+          commands.executeCommand(
+            "editor.action.showReferences",
+            Uri.parse(uri),
+            client.protocol2CodeConverter.asPosition(position),
+            locations.map(client.protocol2CodeConverter.asLocation),
+          ),
+      ),
+    );
+  }
+
   // Start the client. This will also launch the server
   client.start();
 }